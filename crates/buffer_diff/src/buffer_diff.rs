@@ -8,7 +8,7 @@ use std::{
     future::Future,
     iter,
     ops::Range,
-    sync::{Arc, LazyLock},
+    sync::{Arc, LazyLock, OnceLock},
 };
 use sum_tree::SumTree;
 use text::{Anchor, Bias, BufferId, OffsetRangeExt, Point, ToOffset as _};
@@ -16,6 +16,11 @@ use util::ResultExt;
 
 pub static CALCULATE_DIFF_TASK: LazyLock<TaskLabel> = LazyLock::new(TaskLabel::new);
 
+/// Hunks that replace more lines than this on either side are not diffed word by word, as so many
+/// changed words would obscure the hunk rather than help reading it.
+const MAX_WORD_DIFF_LINE_COUNT: u32 = 8;
+const MAX_WORD_DIFF_LEN: usize = 4096;
+
 pub struct BufferDiff {
     pub buffer_id: BufferId,
     inner: BufferDiffInner,
@@ -59,7 +64,7 @@ pub enum DiffHunkSecondaryStatus {
 }
 
 /// A diff hunk resolved to rows in the buffer.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub struct DiffHunk {
     /// The buffer range as points.
     pub range: Range<Point>,
@@ -68,15 +73,64 @@ pub struct DiffHunk {
     /// The range in the buffer's diff base text to which this hunk corresponds.
     pub diff_base_byte_range: Range<usize>,
     pub secondary_status: DiffHunkSecondaryStatus,
+    /// The words that changed within the hunk.
+    pub word_diffs: LazyWordDiffs,
 }
 
+/// The words that changed within a hunk that modifies a few lines.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct WordDiffs {
+    /// The ranges of the buffer that were inserted.
+    pub buffer_ranges: Vec<Range<Anchor>>,
+    /// The byte ranges of the base text that were deleted.
+    pub base_byte_ranges: Vec<Range<usize>>,
+}
+
+/// The [`WordDiffs`] of a hunk, which are only computed once they are needed, such as when the
+/// hunk is displayed. They are shared by every copy of the hunk.
+#[derive(Clone, Default)]
+pub struct LazyWordDiffs(Arc<OnceLock<WordDiffs>>);
+
 /// We store [`InternalDiffHunk`]s internally so we don't need to store the additional row range.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 struct InternalDiffHunk {
     buffer_range: Range<Anchor>,
     diff_base_byte_range: Range<usize>,
+    word_diffs: LazyWordDiffs,
 }
 
+// The word diffs are derived from the rest of a hunk, so they are left out when comparing hunks.
+impl PartialEq for DiffHunk {
+    fn eq(&self, other: &Self) -> bool {
+        let DiffHunk {
+            range,
+            buffer_range,
+            diff_base_byte_range,
+            secondary_status,
+            word_diffs: _,
+        } = self;
+        *range == other.range
+            && *buffer_range == other.buffer_range
+            && *diff_base_byte_range == other.diff_base_byte_range
+            && *secondary_status == other.secondary_status
+    }
+}
+
+impl Eq for DiffHunk {}
+
+impl PartialEq for InternalDiffHunk {
+    fn eq(&self, other: &Self) -> bool {
+        let InternalDiffHunk {
+            buffer_range,
+            diff_base_byte_range,
+            word_diffs: _,
+        } = self;
+        *buffer_range == other.buffer_range && *diff_base_byte_range == other.diff_base_byte_range
+    }
+}
+
+impl Eq for InternalDiffHunk {}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct PendingHunk {
     buffer_range: Range<Anchor>,
@@ -534,11 +588,19 @@ impl BufferDiffInner {
             [
                 (
                     &hunk.buffer_range.start,
-                    (hunk.buffer_range.start, hunk.diff_base_byte_range.start),
+                    (
+                        hunk.buffer_range.start,
+                        hunk.diff_base_byte_range.start,
+                        &hunk.word_diffs,
+                    ),
                 ),
                 (
                     &hunk.buffer_range.end,
-                    (hunk.buffer_range.end, hunk.diff_base_byte_range.end),
+                    (
+                        hunk.buffer_range.end,
+                        hunk.diff_base_byte_range.end,
+                        &hunk.word_diffs,
+                    ),
                 ),
             ]
         });
@@ -557,8 +619,8 @@ impl BufferDiffInner {
         let mut summaries = buffer.summaries_for_anchors_with_payload::<Point, _, _>(anchor_iter);
         iter::from_fn(move || {
             loop {
-                let (start_point, (start_anchor, start_base)) = summaries.next()?;
-                let (mut end_point, (mut end_anchor, end_base)) = summaries.next()?;
+                let (start_point, (start_anchor, start_base, word_diffs)) = summaries.next()?;
+                let (mut end_point, (mut end_anchor, end_base, _)) = summaries.next()?;
 
                 if !start_anchor.is_valid(buffer) {
                     continue;
@@ -629,6 +691,7 @@ impl BufferDiffInner {
                     diff_base_byte_range: start_base..end_base,
                     buffer_range: start_anchor..end_anchor,
                     secondary_status,
+                    word_diffs: word_diffs.clone(),
                 });
             }
         })
@@ -659,6 +722,7 @@ impl BufferDiffInner {
                 buffer_range: hunk.buffer_range.clone(),
                 // The secondary status is not used by callers of this method.
                 secondary_status: DiffHunkSecondaryStatus::NoSecondaryHunk,
+                word_diffs: hunk.word_diffs.clone(),
             })
         })
     }
@@ -755,6 +819,7 @@ fn compute_hunks(
                 InternalDiffHunk {
                     buffer_range: buffer.anchor_before(0)..buffer.anchor_before(0),
                     diff_base_byte_range: 0..diff_base.len() - 1,
+                    word_diffs: LazyWordDiffs::default(),
                 },
                 &buffer,
             );
@@ -779,6 +844,7 @@ fn compute_hunks(
             InternalDiffHunk {
                 buffer_range: Anchor::MIN..Anchor::MAX,
                 diff_base_byte_range: 0..0,
+                word_diffs: LazyWordDiffs::default(),
             },
             &buffer,
         );
@@ -860,6 +926,7 @@ fn process_patch_hunk(
     InternalDiffHunk {
         buffer_range,
         diff_base_byte_range,
+        word_diffs: LazyWordDiffs::default(),
     }
 }
 
@@ -1247,6 +1314,70 @@ impl DiffHunk {
     }
 }
 
+impl LazyWordDiffs {
+    /// Returns the word diffs of the hunk with the given ranges, computing them on first use.
+    pub fn get(
+        &self,
+        buffer_range: &Range<Anchor>,
+        diff_base_byte_range: &Range<usize>,
+        diff: &BufferDiffSnapshot,
+        buffer: &text::BufferSnapshot,
+    ) -> &WordDiffs {
+        self.0.get_or_init(|| {
+            compute_word_diffs(buffer_range, diff_base_byte_range, diff.base_text(), buffer)
+        })
+    }
+}
+
+impl std::fmt::Debug for LazyWordDiffs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("LazyWordDiffs").field(&self.0.get()).finish()
+    }
+}
+
+fn compute_word_diffs(
+    buffer_range: &Range<Anchor>,
+    diff_base_byte_range: &Range<usize>,
+    base_text: &language::BufferSnapshot,
+    buffer: &text::BufferSnapshot,
+) -> WordDiffs {
+    let buffer_range = buffer_range.to_point(buffer);
+    let base_range = diff_base_byte_range.to_point(base_text);
+    if buffer_range.is_empty()
+        || base_range.is_empty()
+        || buffer_range.end.row - buffer_range.start.row > MAX_WORD_DIFF_LINE_COUNT
+        || base_range.end.row - base_range.start.row > MAX_WORD_DIFF_LINE_COUNT
+    {
+        return WordDiffs::default();
+    }
+    let buffer_offset = buffer.point_to_offset(buffer_range.start);
+    let buffer_text = buffer.text_for_range(buffer_range).collect::<String>();
+    let base_offset = diff_base_byte_range.start;
+    let base_text_in_hunk = base_text
+        .text_for_range(diff_base_byte_range.clone())
+        .collect::<String>();
+    if buffer_text.len() > MAX_WORD_DIFF_LEN || base_text_in_hunk.len() > MAX_WORD_DIFF_LEN {
+        return WordDiffs::default();
+    }
+
+    let language_scope = base_text.language_scope_at(base_offset);
+    let (base_ranges, buffer_ranges) =
+        language::word_diff(&base_text_in_hunk, &buffer_text, language_scope);
+    WordDiffs {
+        buffer_ranges: buffer_ranges
+            .into_iter()
+            .map(|range| {
+                buffer.anchor_after(buffer_offset + range.start)
+                    ..buffer.anchor_before(buffer_offset + range.end)
+            })
+            .collect(),
+        base_byte_ranges: base_ranges
+            .into_iter()
+            .map(|range| base_offset + range.start..base_offset + range.end)
+            .collect(),
+    }
+}
+
 impl DiffHunkStatus {
     pub fn has_secondary_hunk(&self) -> bool {
         matches!(
@@ -1423,6 +1554,89 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_word_diffs(cx: &mut gpui::TestAppContext) {
+        let diff_base = "
+            fn main() {
+                let value = compute(one, two);
+            }
+            one
+            two
+            three
+            four
+            five
+            six
+            seven
+            eight
+            nine
+        "
+        .unindent();
+
+        let buffer_text = "
+            fn main() {
+                let result = compute(one, three);
+            }
+            ONE
+            TWO
+            THREE
+            FOUR
+            FIVE
+            SIX
+            SEVEN
+            EIGHT
+            NINE
+        "
+        .unindent();
+
+        let buffer = Buffer::new(0, BufferId::new(1).unwrap(), buffer_text);
+        let diff = BufferDiffSnapshot::new_sync(buffer.clone(), diff_base.clone(), cx);
+        let hunks = diff
+            .hunks_intersecting_range(Anchor::MIN..Anchor::MAX, &buffer)
+            .collect::<Vec<_>>();
+        assert_eq!(hunks.len(), 2);
+
+        let hunk = &hunks[0];
+        let word_diffs = hunk.word_diffs.get(
+            &hunk.buffer_range,
+            &hunk.diff_base_byte_range,
+            &diff,
+            &buffer,
+        );
+        assert_eq!(
+            word_diffs
+                .base_byte_ranges
+                .iter()
+                .map(|range| &diff_base[range.clone()])
+                .collect::<Vec<_>>(),
+            ["value", "two"]
+        );
+        assert_eq!(
+            word_diffs
+                .buffer_ranges
+                .iter()
+                .map(|range| buffer.text_for_range(range.clone()).collect::<String>())
+                .collect::<Vec<_>>(),
+            ["result", "three"]
+        );
+
+        // The word diffs are computed once and shared by every copy of the hunk.
+        let hunk = diff
+            .hunks_intersecting_range(Anchor::MIN..Anchor::MAX, &buffer)
+            .next()
+            .unwrap();
+        assert!(hunk.word_diffs.0.get().is_some());
+
+        // Hunks that rewrite many lines are not diffed word by word.
+        let hunk = &hunks[1];
+        let word_diffs = hunk.word_diffs.get(
+            &hunk.buffer_range,
+            &hunk.diff_base_byte_range,
+            &diff,
+            &buffer,
+        );
+        assert_eq!(word_diffs, &WordDiffs::default());
+    }
+
     #[gpui::test]
     async fn test_buffer_diff_with_secondary(cx: &mut gpui::TestAppContext) {
         let head_text = "
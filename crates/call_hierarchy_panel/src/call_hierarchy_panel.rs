@@ -0,0 +1,847 @@
+mod call_hierarchy_panel_settings;
+
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use call_hierarchy_panel_settings::{CallHierarchyPanelDockPosition, CallHierarchyPanelSettings};
+use collections::{HashMap, HashSet};
+use db::kvp::KEY_VALUE_STORE;
+use editor::{Editor, SelectionEffects, scroll::Autoscroll};
+use fs::Fs;
+use gpui::{
+    Action, App, AsyncWindowContext, Entity, EventEmitter, FocusHandle, Focusable, KeyContext,
+    ListSizingBehavior, Pixels, ScrollStrategy, SharedString, Task, UniformListScrollHandle,
+    WeakEntity, actions, px, uniform_list,
+};
+use language::ToPoint as _;
+use menu::{Confirm, SecondaryConfirm, SelectFirst, SelectLast, SelectNext, SelectPrevious};
+use project::{CallHierarchyItem, Location, Project, TypeHierarchyItem};
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use ui::{ListItem, prelude::*};
+use util::ResultExt;
+use workspace::{
+    Workspace,
+    dock::{DockPosition, Panel, PanelEvent},
+};
+
+actions!(
+    call_hierarchy_panel,
+    [
+        /// Toggles focus on the call hierarchy panel.
+        ToggleFocus,
+        /// Shows the functions that call the symbol under the cursor.
+        ShowIncomingCalls,
+        /// Shows the functions called by the symbol under the cursor.
+        ShowOutgoingCalls,
+        /// Shows the supertypes of the type under the cursor.
+        ShowSupertypes,
+        /// Shows the subtypes of the type under the cursor.
+        ShowSubtypes,
+        /// Switches the panel between incoming and outgoing calls, or between
+        /// supertypes and subtypes.
+        ToggleDirection,
+        /// Expands the selected entry to show its calls.
+        ExpandSelectedEntry,
+        /// Collapses the selected entry, or selects its parent if already collapsed.
+        CollapseSelectedEntry,
+    ]
+);
+
+const CALL_HIERARCHY_PANEL_KEY: &str = "CallHierarchyPanel";
+
+pub fn init_settings(cx: &mut App) {
+    CallHierarchyPanelSettings::register(cx);
+}
+
+pub fn init(cx: &mut App) {
+    init_settings(cx);
+
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, window, cx| {
+            workspace.toggle_panel_focus::<CallHierarchyPanel>(window, cx);
+        });
+        workspace.register_action(|workspace, _: &ShowIncomingCalls, window, cx| {
+            show_hierarchy(workspace, HierarchyMode::IncomingCalls, window, cx);
+        });
+        workspace.register_action(|workspace, _: &ShowOutgoingCalls, window, cx| {
+            show_hierarchy(workspace, HierarchyMode::OutgoingCalls, window, cx);
+        });
+        workspace.register_action(|workspace, _: &ShowSupertypes, window, cx| {
+            show_hierarchy(workspace, HierarchyMode::Supertypes, window, cx);
+        });
+        workspace.register_action(|workspace, _: &ShowSubtypes, window, cx| {
+            show_hierarchy(workspace, HierarchyMode::Subtypes, window, cx);
+        });
+    })
+    .detach();
+}
+
+fn show_hierarchy(
+    workspace: &mut Workspace,
+    mode: HierarchyMode,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        return;
+    };
+    let Some((buffer, position)) = editor.update(cx, |editor, cx| {
+        let head = editor.selections.newest_anchor().head();
+        editor.buffer().read(cx).text_anchor_for_position(head, cx)
+    }) else {
+        return;
+    };
+    if let Some(panel) = workspace.focus_panel::<CallHierarchyPanel>(window, cx) {
+        panel.update(cx, |panel, cx| {
+            panel.show_hierarchy_at(buffer, position, mode, window, cx)
+        });
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HierarchyMode {
+    IncomingCalls,
+    OutgoingCalls,
+    Supertypes,
+    Subtypes,
+}
+
+impl HierarchyMode {
+    fn label(self) -> &'static str {
+        match self {
+            HierarchyMode::IncomingCalls => "Incoming Calls",
+            HierarchyMode::OutgoingCalls => "Outgoing Calls",
+            HierarchyMode::Supertypes => "Supertypes",
+            HierarchyMode::Subtypes => "Subtypes",
+        }
+    }
+
+    fn toggled(self) -> Self {
+        match self {
+            HierarchyMode::IncomingCalls => HierarchyMode::OutgoingCalls,
+            HierarchyMode::OutgoingCalls => HierarchyMode::IncomingCalls,
+            HierarchyMode::Supertypes => HierarchyMode::Subtypes,
+            HierarchyMode::Subtypes => HierarchyMode::Supertypes,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum HierarchyItem {
+    Call(CallHierarchyItem),
+    Type(TypeHierarchyItem),
+}
+
+impl HierarchyItem {
+    fn name(&self) -> &str {
+        match self {
+            HierarchyItem::Call(item) => &item.name,
+            HierarchyItem::Type(item) => &item.name,
+        }
+    }
+
+    fn detail(&self) -> Option<&str> {
+        match self {
+            HierarchyItem::Call(item) => item.detail.as_deref(),
+            HierarchyItem::Type(item) => item.detail.as_deref(),
+        }
+    }
+
+    fn location(&self) -> &Location {
+        match self {
+            HierarchyItem::Call(item) => &item.location,
+            HierarchyItem::Type(item) => &item.location,
+        }
+    }
+}
+
+type NodeId = usize;
+
+struct HierarchyNode {
+    item: HierarchyItem,
+    /// The calls connecting this node to its parent. Empty for the roots and
+    /// for types.
+    call_sites: Vec<Location>,
+    parent: Option<NodeId>,
+    depth: usize,
+    children: HierarchyChildren,
+}
+
+enum HierarchyChildren {
+    Unloaded,
+    Loading,
+    Loaded(Vec<NodeId>),
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedCallHierarchyPanel {
+    width: Option<Pixels>,
+    active: Option<bool>,
+}
+
+/// A tree of the callers or callees of a function, or of the supertypes or
+/// subtypes of a type, expanded lazily so that recursive call chains and deep
+/// type hierarchies can be explored as far as needed.
+pub struct CallHierarchyPanel {
+    workspace: WeakEntity<Workspace>,
+    project: Entity<Project>,
+    fs: Arc<dyn Fs>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    width: Option<Pixels>,
+    active: bool,
+    mode: HierarchyMode,
+    nodes: Vec<HierarchyNode>,
+    roots: Vec<NodeId>,
+    expanded: HashSet<NodeId>,
+    visible_entries: Vec<NodeId>,
+    selected_entry: Option<NodeId>,
+    status: Option<SharedString>,
+    prepare_task: Task<()>,
+    load_tasks: HashMap<NodeId, Task<()>>,
+    pending_serialization: Task<Option<()>>,
+}
+
+impl CallHierarchyPanel {
+    pub async fn load(
+        workspace: WeakEntity<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> anyhow::Result<Entity<Self>> {
+        let serialized_panel = match workspace
+            .read_with(&cx, |workspace, _| {
+                CallHierarchyPanel::serialization_key(workspace)
+            })
+            .ok()
+            .flatten()
+        {
+            Some(serialization_key) => cx
+                .background_spawn(async move { KEY_VALUE_STORE.read_kvp(&serialization_key) })
+                .await
+                .context("loading call hierarchy panel")
+                .log_err()
+                .flatten()
+                .map(|panel| serde_json::from_str::<SerializedCallHierarchyPanel>(&panel))
+                .transpose()
+                .log_err()
+                .flatten(),
+            None => None,
+        };
+
+        workspace.update_in(&mut cx, |workspace, _, cx| {
+            let panel = Self::new(workspace, cx);
+            if let Some(serialized_panel) = serialized_panel {
+                panel.update(cx, |panel, cx| {
+                    panel.width = serialized_panel.width.map(|px| px.round());
+                    panel.active = serialized_panel.active.unwrap_or(false);
+                    cx.notify();
+                });
+            }
+            panel
+        })
+    }
+
+    fn new(workspace: &mut Workspace, cx: &mut Context<Workspace>) -> Entity<Self> {
+        let project = workspace.project().clone();
+        let workspace_handle = cx.entity().downgrade();
+        let fs = workspace.app_state().fs.clone();
+        cx.new(|cx| Self {
+            workspace: workspace_handle,
+            project,
+            fs,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            width: None,
+            active: false,
+            mode: HierarchyMode::IncomingCalls,
+            nodes: Vec::new(),
+            roots: Vec::new(),
+            expanded: HashSet::default(),
+            visible_entries: Vec::new(),
+            selected_entry: None,
+            status: None,
+            prepare_task: Task::ready(()),
+            load_tasks: HashMap::default(),
+            pending_serialization: Task::ready(None),
+        })
+    }
+
+    fn serialization_key(workspace: &Workspace) -> Option<String> {
+        workspace
+            .database_id()
+            .map(|id| i64::from(id).to_string())
+            .or(workspace.session_id())
+            .map(|id| format!("{}-{:?}", CALL_HIERARCHY_PANEL_KEY, id))
+    }
+
+    fn serialize(&mut self, cx: &mut Context<Self>) {
+        let Some(serialization_key) = self
+            .workspace
+            .read_with(cx, |workspace, _| {
+                CallHierarchyPanel::serialization_key(workspace)
+            })
+            .ok()
+            .flatten()
+        else {
+            return;
+        };
+        let width = self.width;
+        let active = Some(self.active);
+        self.pending_serialization = cx.background_spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        serialization_key,
+                        serde_json::to_string(&SerializedCallHierarchyPanel { width, active })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    /// Replaces the tree with the call or type hierarchy of the symbol at
+    /// `position`.
+    pub fn show_hierarchy_at(
+        &mut self,
+        buffer: Entity<language::Buffer>,
+        position: language::Anchor,
+        mode: HierarchyMode,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.mode = mode;
+        self.status = Some("Loading…".into());
+        let items: Task<anyhow::Result<Vec<HierarchyItem>>> =
+            self.project.update(cx, |project, cx| match mode {
+                HierarchyMode::IncomingCalls | HierarchyMode::OutgoingCalls => {
+                    let items = project.prepare_call_hierarchy(&buffer, position, cx);
+                    cx.background_spawn(async move {
+                        anyhow::Ok(items.await?.into_iter().map(HierarchyItem::Call).collect())
+                    })
+                }
+                HierarchyMode::Supertypes | HierarchyMode::Subtypes => {
+                    let items = project.prepare_type_hierarchy(&buffer, position, cx);
+                    cx.background_spawn(async move {
+                        anyhow::Ok(items.await?.into_iter().map(HierarchyItem::Type).collect())
+                    })
+                }
+            });
+        self.prepare_task = cx.spawn_in(window, async move |this, cx| {
+            let items = items.await;
+            this.update_in(cx, |this, window, cx| match items {
+                Ok(items) if items.is_empty() => {
+                    this.set_roots(Vec::new(), window, cx);
+                    this.status = Some("Nothing to show for the symbol at the cursor".into());
+                }
+                Ok(items) => this.set_roots(items, window, cx),
+                Err(error) => {
+                    this.set_roots(Vec::new(), window, cx);
+                    this.status = Some(format!("Failed to load hierarchy: {error}").into());
+                }
+            })
+            .ok();
+        });
+        cx.notify();
+    }
+
+    fn set_roots(
+        &mut self,
+        items: Vec<HierarchyItem>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.nodes.clear();
+        self.roots.clear();
+        self.expanded.clear();
+        self.load_tasks.clear();
+        self.status = None;
+        for item in items {
+            let node_id = self.push_node(item, Vec::new(), None, 0);
+            self.roots.push(node_id);
+        }
+        self.selected_entry = self.roots.first().copied();
+        for root in self.roots.clone() {
+            self.expand(root, window, cx);
+        }
+        self.update_visible_entries(cx);
+    }
+
+    fn push_node(
+        &mut self,
+        item: HierarchyItem,
+        call_sites: Vec<Location>,
+        parent: Option<NodeId>,
+        depth: usize,
+    ) -> NodeId {
+        self.nodes.push(HierarchyNode {
+            item,
+            call_sites,
+            parent,
+            depth,
+            children: HierarchyChildren::Unloaded,
+        });
+        self.nodes.len() - 1
+    }
+
+    fn toggle_direction(
+        &mut self,
+        _: &ToggleDirection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.mode = self.mode.toggled();
+        let roots = self
+            .roots
+            .iter()
+            .map(|root| self.nodes[*root].item.clone())
+            .collect();
+        self.set_roots(roots, window, cx);
+    }
+
+    fn expand(&mut self, node_id: NodeId, window: &mut Window, cx: &mut Context<Self>) {
+        self.expanded.insert(node_id);
+        if !matches!(self.nodes[node_id].children, HierarchyChildren::Unloaded) {
+            return;
+        }
+
+        self.nodes[node_id].children = HierarchyChildren::Loading;
+        let mode = self.mode;
+        let item = &self.nodes[node_id].item;
+        let children: Task<anyhow::Result<Vec<(HierarchyItem, Vec<Location>)>>> =
+            self.project.update(cx, |project, cx| match item {
+                HierarchyItem::Call(item) => {
+                    let calls = if mode == HierarchyMode::IncomingCalls {
+                        project.incoming_calls(item, cx)
+                    } else {
+                        project.outgoing_calls(item, cx)
+                    };
+                    cx.background_spawn(async move {
+                        anyhow::Ok(
+                            calls
+                                .await?
+                                .into_iter()
+                                .map(|call| (HierarchyItem::Call(call.item), call.call_sites))
+                                .collect(),
+                        )
+                    })
+                }
+                HierarchyItem::Type(item) => {
+                    let types = if mode == HierarchyMode::Supertypes {
+                        project.supertypes(item, cx)
+                    } else {
+                        project.subtypes(item, cx)
+                    };
+                    cx.background_spawn(async move {
+                        anyhow::Ok(
+                            types
+                                .await?
+                                .into_iter()
+                                .map(|item| (HierarchyItem::Type(item), Vec::new()))
+                                .collect(),
+                        )
+                    })
+                }
+            });
+        let task = cx.spawn_in(window, async move |this, cx| {
+            let children = children.await;
+            this.update(cx, |this, cx| {
+                let children = children.log_err().unwrap_or_default();
+                this.add_children(node_id, children);
+                this.update_visible_entries(cx);
+            })
+            .ok();
+        });
+        self.load_tasks.insert(node_id, task);
+    }
+
+    fn add_children(&mut self, parent: NodeId, children: Vec<(HierarchyItem, Vec<Location>)>) {
+        let depth = self.nodes[parent].depth + 1;
+        let children = children
+            .into_iter()
+            .map(|(item, call_sites)| self.push_node(item, call_sites, Some(parent), depth))
+            .collect();
+        self.nodes[parent].children = HierarchyChildren::Loaded(children);
+    }
+
+    fn toggle_expanded(&mut self, node_id: NodeId, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.expanded.remove(&node_id) {
+            self.expand(node_id, window, cx);
+        }
+        self.update_visible_entries(cx);
+    }
+
+    fn update_visible_entries(&mut self, cx: &mut Context<Self>) {
+        let mut visible_entries = Vec::new();
+        let mut stack = self.roots.iter().rev().copied().collect::<Vec<_>>();
+        while let Some(node_id) = stack.pop() {
+            visible_entries.push(node_id);
+            if self.expanded.contains(&node_id)
+                && let HierarchyChildren::Loaded(children) = &self.nodes[node_id].children
+            {
+                stack.extend(children.iter().rev().copied());
+            }
+        }
+        self.visible_entries = visible_entries;
+        cx.notify();
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        let selected_entry = self.selected_entry?;
+        self.visible_entries
+            .iter()
+            .position(|node_id| *node_id == selected_entry)
+    }
+
+    fn select_index(&mut self, index: usize, cx: &mut Context<Self>) {
+        if let Some(node_id) = self.visible_entries.get(index) {
+            self.selected_entry = Some(*node_id);
+            self.scroll_handle
+                .scroll_to_item(index, ScrollStrategy::Center);
+            cx.notify();
+        }
+    }
+
+    fn select_next(&mut self, _: &SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        let index = self.selected_index().map_or(0, |index| index + 1);
+        self.select_index(index, cx);
+    }
+
+    fn select_previous(&mut self, _: &SelectPrevious, _: &mut Window, cx: &mut Context<Self>) {
+        let index = self
+            .selected_index()
+            .map_or(0, |index| index.saturating_sub(1));
+        self.select_index(index, cx);
+    }
+
+    fn select_first(&mut self, _: &SelectFirst, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_index(0, cx);
+    }
+
+    fn select_last(&mut self, _: &SelectLast, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_index(self.visible_entries.len().saturating_sub(1), cx);
+    }
+
+    fn expand_selected_entry(
+        &mut self,
+        _: &ExpandSelectedEntry,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(node_id) = self.selected_entry {
+            self.expand(node_id, window, cx);
+            self.update_visible_entries(cx);
+        }
+    }
+
+    fn collapse_selected_entry(
+        &mut self,
+        _: &CollapseSelectedEntry,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(node_id) = self.selected_entry else {
+            return;
+        };
+        if !self.expanded.remove(&node_id)
+            && let Some(parent) = self.nodes[node_id].parent
+        {
+            self.selected_entry = Some(parent);
+        }
+        self.update_visible_entries(cx);
+    }
+
+    fn confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(node_id) = self.selected_entry {
+            self.open_call_site(node_id, window, cx);
+        }
+    }
+
+    fn secondary_confirm(
+        &mut self,
+        _: &SecondaryConfirm,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(node_id) = self.selected_entry {
+            let location = self.nodes[node_id].item.location().clone();
+            self.open_location(location, window, cx);
+        }
+    }
+
+    /// Opens the first call connecting the node to its parent, or the node's
+    /// own definition for the roots of the tree.
+    fn open_call_site(&mut self, node_id: NodeId, window: &mut Window, cx: &mut Context<Self>) {
+        let node = &self.nodes[node_id];
+        let location = node
+            .call_sites
+            .first()
+            .unwrap_or(node.item.location())
+            .clone();
+        self.open_location(location, window, cx);
+    }
+
+    fn open_location(&mut self, location: Location, window: &mut Window, cx: &mut Context<Self>) {
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let range = {
+                    let buffer = location.buffer.read(cx);
+                    location.range.start.to_point(buffer)..location.range.end.to_point(buffer)
+                };
+                let pane = workspace.active_pane().clone();
+                let editor = workspace.open_project_item::<Editor>(
+                    pane,
+                    location.buffer,
+                    true,
+                    true,
+                    window,
+                    cx,
+                );
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(
+                        SelectionEffects::scroll(Autoscroll::center()),
+                        window,
+                        cx,
+                        |s| s.select_ranges([range]),
+                    );
+                });
+            })
+            .log_err();
+    }
+
+    fn dispatch_context(&self) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("CallHierarchyPanel");
+        dispatch_context.add("menu");
+        dispatch_context
+    }
+
+    fn render_entry(&self, node_id: NodeId, cx: &mut Context<Self>) -> impl IntoElement {
+        let node = &self.nodes[node_id];
+        let indent_size = CallHierarchyPanelSettings::get_global(cx).indent_size;
+        let is_expanded = self.expanded.contains(&node_id);
+        let has_children = match &node.children {
+            HierarchyChildren::Loaded(children) => !children.is_empty(),
+            HierarchyChildren::Unloaded | HierarchyChildren::Loading => true,
+        };
+        let is_loading = is_expanded && matches!(node.children, HierarchyChildren::Loading);
+
+        let location = node.call_sites.first().unwrap_or(node.item.location());
+        let location_label = {
+            let buffer = location.buffer.read(cx);
+            let row = location.range.start.to_point(buffer).row + 1;
+            match buffer.file() {
+                Some(file) => format!("{}:{row}", file.file_name(cx).to_string_lossy()),
+                None => format!("{row}"),
+            }
+        };
+        let call_count = node.call_sites.len();
+
+        ListItem::new(node_id)
+            .indent_level(node.depth)
+            .indent_step_size(px(indent_size))
+            .toggle(has_children.then_some(is_expanded))
+            .on_toggle(cx.listener(move |this, _, window, cx| {
+                this.toggle_expanded(node_id, window, cx);
+            }))
+            .toggle_state(self.selected_entry == Some(node_id))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(node.item.name().to_string()))
+                    .when_some(node.item.detail(), |this, detail| {
+                        this.child(
+                            Label::new(detail.to_string())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .truncate(),
+                        )
+                    })
+                    .child(
+                        Label::new(location_label)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .end_slot(
+                h_flex()
+                    .when(is_loading, |this| {
+                        this.child(
+                            Label::new("Loading…")
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                    })
+                    .when(call_count > 1, |this| {
+                        this.child(
+                            Label::new(format!("×{call_count}"))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                    }),
+            )
+            .on_click(cx.listener(move |this, _, window, cx| {
+                this.selected_entry = Some(node_id);
+                this.open_call_site(node_id, window, cx);
+                cx.notify();
+            }))
+    }
+
+    fn render_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .px_2()
+            .py_1()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(Label::new(self.mode.label()).size(LabelSize::Small))
+            .child(
+                IconButton::new("toggle-hierarchy-direction", IconName::ArrowRightLeft)
+                    .icon_size(IconSize::Small)
+                    .tooltip(ui::Tooltip::for_action_title(
+                        "Toggle Direction",
+                        &ToggleDirection,
+                    ))
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.toggle_direction(&ToggleDirection, window, cx);
+                    })),
+            )
+    }
+}
+
+impl Panel for CallHierarchyPanel {
+    fn persistent_name() -> &'static str {
+        "Call Hierarchy Panel"
+    }
+
+    fn position(&self, _: &Window, cx: &App) -> DockPosition {
+        match CallHierarchyPanelSettings::get_global(cx).dock {
+            CallHierarchyPanelDockPosition::Left => DockPosition::Left,
+            CallHierarchyPanelDockPosition::Right => DockPosition::Right,
+        }
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, _: &mut Window, cx: &mut Context<Self>) {
+        settings::update_settings_file::<CallHierarchyPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings, _| {
+                let dock = match position {
+                    DockPosition::Left | DockPosition::Bottom => {
+                        CallHierarchyPanelDockPosition::Left
+                    }
+                    DockPosition::Right => CallHierarchyPanelDockPosition::Right,
+                };
+                settings.dock = Some(dock);
+            },
+        );
+    }
+
+    fn size(&self, _: &Window, cx: &App) -> Pixels {
+        self.width
+            .unwrap_or_else(|| CallHierarchyPanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, window: &mut Window, cx: &mut Context<Self>) {
+        self.width = size;
+        cx.notify();
+        cx.defer_in(window, |this, _, cx| {
+            this.serialize(cx);
+        });
+    }
+
+    fn icon(&self, _: &Window, cx: &App) -> Option<IconName> {
+        CallHierarchyPanelSettings::get_global(cx)
+            .button
+            .then_some(IconName::ArrowRightLeft)
+    }
+
+    fn icon_tooltip(&self, _window: &Window, _: &App) -> Option<&'static str> {
+        Some("Call and Type Hierarchy")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn starts_open(&self, _window: &Window, _: &App) -> bool {
+        self.active
+    }
+
+    fn set_active(&mut self, active: bool, _: &mut Window, cx: &mut Context<Self>) {
+        if self.active != active {
+            self.active = active;
+            self.serialize(cx);
+        }
+    }
+
+    fn activation_priority(&self) -> u32 {
+        6
+    }
+}
+
+impl Focusable for CallHierarchyPanel {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for CallHierarchyPanel {}
+
+impl Render for CallHierarchyPanel {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .id("call-hierarchy-panel")
+            .size_full()
+            .overflow_hidden()
+            .key_context(self.dispatch_context())
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::expand_selected_entry))
+            .on_action(cx.listener(Self::collapse_selected_entry))
+            .on_action(cx.listener(Self::toggle_direction))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::secondary_confirm))
+            .track_focus(&self.focus_handle)
+            .child(self.render_header(cx))
+            .map(|this| {
+                if self.visible_entries.is_empty() {
+                    let message = self.status.clone().unwrap_or_else(|| {
+                        "Place the cursor on a function or type to show its hierarchy".into()
+                    });
+                    this.child(
+                        v_flex()
+                            .flex_1()
+                            .p_4()
+                            .justify_center()
+                            .items_center()
+                            .child(Label::new(message).color(Color::Muted)),
+                    )
+                } else {
+                    this.child(
+                        uniform_list(
+                            "call-hierarchy-entries",
+                            self.visible_entries.len(),
+                            cx.processor(|this, range: std::ops::Range<usize>, _, cx| {
+                                this.visible_entries[range]
+                                    .to_vec()
+                                    .into_iter()
+                                    .map(|node_id| this.render_entry(node_id, cx))
+                                    .collect()
+                            }),
+                        )
+                        .size_full()
+                        .with_sizing_behavior(ListSizingBehavior::Infer)
+                        .track_scroll(self.scroll_handle.clone()),
+                    )
+                }
+            })
+    }
+}
@@ -119,6 +119,7 @@ CREATE TABLE "project_repositories" (
     "merge_message" VARCHAR,
     "branch_summary" VARCHAR,
     "head_commit_details" VARCHAR,
+    "rebase" VARCHAR,
     PRIMARY KEY (project_id, id)
 );
 
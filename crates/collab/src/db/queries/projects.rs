@@ -349,11 +349,12 @@ impl Database {
                                     serde_json::to_string(&repository.current_merge_conflicts)
                                         .unwrap(),
                                 )),
-                                // Old clients do not use abs path, entry ids, head_commit_details, or merge_message.
+                                // Old clients do not use abs path, entry ids, head_commit_details, merge_message, or rebase.
                                 abs_path: ActiveValue::set(String::new()),
                                 entry_ids: ActiveValue::set("[]".into()),
                                 head_commit_details: ActiveValue::set(None),
                                 merge_message: ActiveValue::set(None),
+                                rebase: ActiveValue::set(None),
                             }
                         }),
                     )
@@ -503,6 +504,12 @@ impl Database {
                     serde_json::to_string(&update.current_merge_conflicts).unwrap(),
                 )),
                 merge_message: ActiveValue::set(update.merge_message.clone()),
+                rebase: ActiveValue::Set(
+                    update
+                        .rebase
+                        .as_ref()
+                        .map(|rebase| serde_json::to_string(rebase).unwrap()),
+                ),
             })
             .on_conflict(
                 OnConflict::columns([
@@ -517,6 +524,7 @@ impl Database {
                     project_repository::Column::CurrentMergeConflicts,
                     project_repository::Column::HeadCommitDetails,
                     project_repository::Column::MergeMessage,
+                    project_repository::Column::Rebase,
                 ])
                 .to_owned(),
             )
@@ -965,6 +973,12 @@ impl Database {
                     .transpose()?
                     .unwrap_or_default();
 
+                let rebase = db_repository_entry
+                    .rebase
+                    .as_ref()
+                    .map(|rebase| serde_json::from_str(rebase))
+                    .transpose()?;
+
                 let entry_ids = serde_json::from_str(&db_repository_entry.entry_ids)
                     .context("failed to deserialize repository's entry ids")?;
 
@@ -996,6 +1010,7 @@ impl Database {
                         is_last_update: true,
                         merge_message: db_repository_entry.merge_message,
                         stash_entries: Vec::new(),
+                        rebase,
                     });
                 }
             }
@@ -764,6 +764,12 @@ impl Database {
                         .transpose()?
                         .unwrap_or_default();
 
+                    let rebase = db_repository
+                        .rebase
+                        .as_ref()
+                        .map(|rebase| serde_json::from_str(rebase))
+                        .transpose()?;
+
                     let entry_ids = serde_json::from_str(&db_repository.entry_ids)
                         .context("failed to deserialize repository's entry ids")?;
 
@@ -795,6 +801,7 @@ impl Database {
                             is_last_update: true,
                             merge_message: db_repository.merge_message,
                             stash_entries: Vec::new(),
+                            rebase,
                         });
                     }
                 }
@@ -1204,6 +1204,7 @@ impl RunningState {
             show_summary: false,
             show_command: false,
             show_rerun: false,
+            problem_matcher: Vec::new(),
         };
 
         let workspace = self.workspace.clone();
@@ -53,7 +53,7 @@ use multi_buffer::{
     Anchor, AnchorRangeExt, ExcerptId, MultiBuffer, MultiBufferPoint, MultiBufferRow,
     MultiBufferSnapshot, RowInfo, ToOffset, ToPoint,
 };
-use project::project_settings::DiagnosticSeverity;
+use project::{FoldingRange, FoldingRangeKind, project_settings::DiagnosticSeverity};
 use serde::Deserialize;
 
 use std::{
@@ -74,6 +74,15 @@ use wrap_map::{WrapMap, WrapSnapshot};
 
 pub use crate::display_map::{fold_map::FoldMap, inlay_map::InlayMap, tab_map::TabMap};
 
+/// A source of fold ranges for a buffer, other than its indentation.
+#[derive(Clone, Debug)]
+pub enum BufferFoldRanges {
+    /// Folding ranges reported by a language server, sorted by their start.
+    Lsp(Arc<[FoldingRange]>),
+    /// Multi-line bracket pairs found by the buffer's tree-sitter grammar.
+    TreeSitter,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum FoldStatus {
     Folded,
@@ -117,6 +126,8 @@ pub struct DisplayMap {
     inlay_highlights: InlayHighlights,
     /// A container for explicitly foldable ranges, which supersede indentation based fold range suggestions.
     crease_map: CreaseMap,
+    /// Buffers whose fold ranges are not computed from indentation.
+    fold_ranges: TreeMap<BufferId, BufferFoldRanges>,
     pub(crate) fold_placeholder: FoldPlaceholder,
     pub clip_at_line_ends: bool,
     pub(crate) masked: bool,
@@ -157,6 +168,7 @@ impl DisplayMap {
             wrap_map,
             block_map,
             crease_map,
+            fold_ranges: Default::default(),
             fold_placeholder,
             diagnostics_max_severity,
             text_highlights: Default::default(),
@@ -187,6 +199,7 @@ impl DisplayMap {
             block_snapshot,
             diagnostics_max_severity: self.diagnostics_max_severity,
             crease_snapshot: self.crease_map.snapshot(),
+            fold_ranges: self.fold_ranges.clone(),
             text_highlights: self.text_highlights.clone(),
             inlay_highlights: self.inlay_highlights.clone(),
             clip_at_line_ends: self.clip_at_line_ends,
@@ -513,7 +526,7 @@ impl DisplayMap {
         Some((highlights.0, &highlights.1))
     }
 
-    #[cfg(feature = "test-support")]
+    #[cfg(any(test, feature = "test-support"))]
     pub fn all_text_highlights(
         &self,
     ) -> impl Iterator<Item = &Arc<(HighlightStyle, Vec<Range<Anchor>>)>> {
@@ -529,6 +542,23 @@ impl DisplayMap {
         cleared
     }
 
+    /// Sets where the fold ranges of the given buffer come from, or `None` to fold it by indentation.
+    pub fn set_fold_ranges(&mut self, buffer_id: BufferId, fold_ranges: Option<BufferFoldRanges>) {
+        match fold_ranges {
+            Some(fold_ranges) => self.fold_ranges.insert(buffer_id, fold_ranges),
+            None => {
+                self.fold_ranges.remove(&buffer_id);
+            }
+        }
+    }
+
+    /// Removes every text highlight registered under [`HighlightKey::TypePlus`] for the given type.
+    pub fn clear_keyed_text_highlights(&mut self, type_id: TypeId) {
+        self.text_highlights.retain(
+            |key, _| !matches!(key, HighlightKey::TypePlus(key_type_id, _) if *key_type_id == type_id),
+        );
+    }
+
     pub fn set_font(&self, font: Font, font_size: Pixels, cx: &mut Context<Self>) -> bool {
         self.wrap_map
             .update(cx, |map, cx| map.set_font_with_size(font, font_size, cx))
@@ -765,6 +795,7 @@ pub struct DisplaySnapshot {
     pub buffer_snapshot: MultiBufferSnapshot,
     pub fold_snapshot: FoldSnapshot,
     pub crease_snapshot: CreaseSnapshot,
+    fold_ranges: TreeMap<BufferId, BufferFoldRanges>,
     inlay_snapshot: InlaySnapshot,
     tab_snapshot: TabSnapshot,
     wrap_snapshot: WrapSnapshot,
@@ -1271,7 +1302,6 @@ impl DisplaySnapshot {
     }
 
     pub fn crease_for_buffer_row(&self, buffer_row: MultiBufferRow) -> Option<Crease<Point>> {
-        let start = MultiBufferPoint::new(buffer_row.0, self.buffer_snapshot.line_len(buffer_row));
         if let Some(crease) = self
             .crease_snapshot
             .query_row(buffer_row, &self.buffer_snapshot)
@@ -1306,54 +1336,152 @@ impl DisplaySnapshot {
                     render_toggle: render_toggle.clone(),
                 }),
             }
-        } else if self.starts_indent(MultiBufferRow(start.row))
-            && !self.is_line_folded(MultiBufferRow(start.row))
-        {
-            let start_line_indent = self.line_indent_for_buffer_row(buffer_row);
-            let max_point = self.buffer_snapshot.max_point();
-            let mut end = None;
-
-            for row in (buffer_row.0 + 1)..=max_point.row {
-                let line_indent = self.line_indent_for_buffer_row(MultiBufferRow(row));
-                if !line_indent.is_line_blank()
-                    && line_indent.raw_len() <= start_line_indent.raw_len()
-                {
-                    let prev_row = row - 1;
-                    end = Some(Point::new(
-                        prev_row,
-                        self.buffer_snapshot.line_len(MultiBufferRow(prev_row)),
-                    ));
-                    break;
-                }
-            }
-
-            let mut row_before_line_breaks = end.unwrap_or(max_point);
-            while row_before_line_breaks.row > start.row
-                && self
-                    .buffer_snapshot
-                    .is_line_blank(MultiBufferRow(row_before_line_breaks.row))
-            {
-                row_before_line_breaks.row -= 1;
-            }
-
-            row_before_line_breaks = Point::new(
-                row_before_line_breaks.row,
-                self.buffer_snapshot
-                    .line_len(MultiBufferRow(row_before_line_breaks.row)),
-            );
-
+        } else if self.is_line_folded(buffer_row) {
+            None
+        } else {
+            let range = match self.provided_fold_range_for_buffer_row(buffer_row) {
+                Some(range) => range?,
+                None => self.indent_fold_range_for_buffer_row(buffer_row)?,
+            };
             Some(Crease::Inline {
-                range: start..row_before_line_breaks,
+                range,
                 placeholder: self.fold_placeholder.clone(),
                 render_toggle: None,
                 render_trailer: None,
                 metadata: None,
             })
-        } else {
-            None
         }
     }
 
+    /// Returns the fold range starting on the given row according to the buffer's
+    /// [`BufferFoldRanges`], or `None` if the buffer is folded by indentation.
+    fn provided_fold_range_for_buffer_row(
+        &self,
+        buffer_row: MultiBufferRow,
+    ) -> Option<Option<Range<Point>>> {
+        let start = Point::new(buffer_row.0, self.buffer_snapshot.line_len(buffer_row));
+        let (buffer, buffer_start, excerpt_id) =
+            self.buffer_snapshot.point_to_buffer_point(start)?;
+        let buffer_end = match self.fold_ranges.get(&buffer.remote_id())? {
+            BufferFoldRanges::Lsp(ranges) => {
+                let first_on_row = ranges.partition_point(|range| {
+                    buffer.summary_for_anchor::<Point>(&range.range.start).row < buffer_start.row
+                });
+                ranges[first_on_row..]
+                    .iter()
+                    .take_while(|range| {
+                        buffer.summary_for_anchor::<Point>(&range.range.start).row
+                            == buffer_start.row
+                    })
+                    .map(|range| buffer.summary_for_anchor::<Point>(&range.range.end))
+                    .max()
+            }
+            BufferFoldRanges::TreeSitter => {
+                buffer.language().and_then(|language| language.grammar())?;
+                let line_start = Point::new(buffer_start.row, 0);
+                buffer
+                    .bracket_ranges(line_start..buffer_start)
+                    .filter(|pair| {
+                        let open = buffer.offset_to_point(pair.open_range.start);
+                        let close = buffer.offset_to_point(pair.close_range.start);
+                        open.row == buffer_start.row && close.row > open.row
+                    })
+                    .map(|pair| pair.close_range.start)
+                    .max()
+                    .map(|close_start| {
+                        // Keep the closing bracket visible, like `fn main() {⋯}`.
+                        let close = buffer.offset_to_point(close_start);
+                        if buffer.line_indent_for_row(close.row).raw_len() == close.column {
+                            let row = close.row - 1;
+                            Point::new(row, buffer.line_len(row))
+                        } else {
+                            close
+                        }
+                    })
+            }
+        };
+        let Some(buffer_end) = buffer_end else {
+            return Some(None);
+        };
+        let end = self
+            .buffer_snapshot
+            .anchor_in_excerpt(excerpt_id, buffer.anchor_after(buffer_end))?
+            .to_point(&self.buffer_snapshot);
+        Some((end.row > start.row).then_some(start..end))
+    }
+
+    fn indent_fold_range_for_buffer_row(&self, buffer_row: MultiBufferRow) -> Option<Range<Point>> {
+        if !self.starts_indent(buffer_row) {
+            return None;
+        }
+        let start = Point::new(buffer_row.0, self.buffer_snapshot.line_len(buffer_row));
+        let start_line_indent = self.line_indent_for_buffer_row(buffer_row);
+        let max_point = self.buffer_snapshot.max_point();
+        let mut end = None;
+
+        for row in (buffer_row.0 + 1)..=max_point.row {
+            let line_indent = self.line_indent_for_buffer_row(MultiBufferRow(row));
+            if !line_indent.is_line_blank() && line_indent.raw_len() <= start_line_indent.raw_len()
+            {
+                let prev_row = row - 1;
+                end = Some(Point::new(
+                    prev_row,
+                    self.buffer_snapshot.line_len(MultiBufferRow(prev_row)),
+                ));
+                break;
+            }
+        }
+
+        let mut row_before_line_breaks = end.unwrap_or(max_point);
+        while row_before_line_breaks.row > start.row
+            && self
+                .buffer_snapshot
+                .is_line_blank(MultiBufferRow(row_before_line_breaks.row))
+        {
+            row_before_line_breaks.row -= 1;
+        }
+
+        row_before_line_breaks = Point::new(
+            row_before_line_breaks.row,
+            self.buffer_snapshot
+                .line_len(MultiBufferRow(row_before_line_breaks.row)),
+        );
+
+        Some(start..row_before_line_breaks)
+    }
+
+    /// Returns the language server folding ranges of the given kind, in multibuffer coordinates.
+    pub fn lsp_fold_ranges_of_kind(&self, kind: FoldingRangeKind) -> Vec<Range<Point>> {
+        let mut fold_ranges = Vec::new();
+        for (excerpt_id, buffer, excerpt_range) in self.buffer_snapshot.excerpts() {
+            let Some(BufferFoldRanges::Lsp(ranges)) = self.fold_ranges.get(&buffer.remote_id())
+            else {
+                continue;
+            };
+            let excerpt_start = buffer.summary_for_anchor::<Point>(&excerpt_range.context.start);
+            let excerpt_end = buffer.summary_for_anchor::<Point>(&excerpt_range.context.end);
+            for range in ranges.iter().filter(|range| range.kind == Some(kind)) {
+                let range = buffer.summary_for_anchor::<Point>(&range.range.start)
+                    ..buffer.summary_for_anchor::<Point>(&range.range.end);
+                if range.start < excerpt_start || range.end > excerpt_end {
+                    continue;
+                }
+                let (Some(start), Some(end)) = (
+                    self.buffer_snapshot
+                        .anchor_in_excerpt(excerpt_id, buffer.anchor_before(range.start)),
+                    self.buffer_snapshot
+                        .anchor_in_excerpt(excerpt_id, buffer.anchor_after(range.end)),
+                ) else {
+                    continue;
+                };
+                fold_ranges.push(
+                    start.to_point(&self.buffer_snapshot)..end.to_point(&self.buffer_snapshot),
+                );
+            }
+        }
+        fold_ranges
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     pub fn text_highlight_ranges<Tag: ?Sized + 'static>(
         &self,
@@ -0,0 +1,133 @@
+use std::{ops::Range, time::Duration};
+
+use collections::HashMap;
+use futures::future::join_all;
+use gpui::{App, Entity, Task};
+use itertools::Itertools as _;
+use language::Buffer;
+use project::{DocumentLink, DocumentLinkTarget, ResolvedPath};
+use text::BufferId;
+use ui::{Context, Window};
+
+use crate::{Editor, hover_links::HoverLink};
+
+const DOCUMENT_LINKS_DEBOUNCE: Duration = Duration::from_millis(150);
+
+#[derive(Default)]
+pub(super) struct DocumentLinksState {
+    buffer_links: HashMap<BufferId, Vec<DocumentLink>>,
+    /// The buffer versions the links were last fetched for.
+    fetched_versions: HashMap<BufferId, clock::Global>,
+    refresh_task: Option<Task<()>>,
+}
+
+impl Editor {
+    /// Requests the document links of the editor's visible buffers from their language servers,
+    /// skipping buffers that have not changed since their links were last fetched.
+    pub(super) fn refresh_document_links(
+        &mut self,
+        ignore_cache: bool,
+        for_buffer: Option<BufferId>,
+        _: &Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.mode().is_full() {
+            return;
+        }
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+
+        let buffers = self
+            .visible_excerpts(None, cx)
+            .into_values()
+            .map(|(buffer, ..)| buffer)
+            .filter(|editor_buffer| {
+                let editor_buffer = editor_buffer.read(cx);
+                let buffer_id = editor_buffer.remote_id();
+                for_buffer.is_none_or(|for_buffer| for_buffer == buffer_id)
+                    && (ignore_cache
+                        || self
+                            .document_links
+                            .fetched_versions
+                            .get(&buffer_id)
+                            .is_none_or(|version| version != &editor_buffer.version()))
+            })
+            .unique_by(|buffer| buffer.read(cx).remote_id())
+            .collect::<Vec<_>>();
+        if buffers.is_empty() {
+            return;
+        }
+
+        self.document_links.refresh_task = Some(cx.spawn(async move |editor, cx| {
+            cx.background_executor()
+                .timer(DOCUMENT_LINKS_DEBOUNCE)
+                .await;
+            let Ok(links_tasks) = project.update(cx, |project, cx| {
+                buffers
+                    .into_iter()
+                    .map(|buffer| {
+                        let buffer_id = buffer.read(cx).remote_id();
+                        let version = buffer.read(cx).version();
+                        let links_task = project.document_links(&buffer, cx);
+                        async move { (buffer_id, version, links_task.await) }
+                    })
+                    .collect::<Vec<_>>()
+            }) else {
+                return;
+            };
+            let all_links = join_all(links_tasks).await;
+
+            editor
+                .update(cx, |editor, _| {
+                    for (buffer_id, version, links) in all_links {
+                        if links.is_ok() {
+                            editor
+                                .document_links
+                                .fetched_versions
+                                .insert(buffer_id, version);
+                        }
+                        match links {
+                            Ok(links) if links.is_empty() => {
+                                editor.document_links.buffer_links.remove(&buffer_id);
+                            }
+                            Ok(links) => {
+                                editor.document_links.buffer_links.insert(buffer_id, links);
+                            }
+                            Err(e) => log::error!("Failed to fetch document links: {e:#}"),
+                        }
+                    }
+                })
+                .ok();
+        }));
+    }
+
+    /// Returns the language server provided link at the given position, if any.
+    pub(crate) fn document_link_at(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: text::Anchor,
+        cx: &App,
+    ) -> Option<(Range<text::Anchor>, HoverLink)> {
+        let buffer = buffer.read(cx);
+        let links = self.document_links.buffer_links.get(&buffer.remote_id())?;
+        let link = links.iter().find(|link| {
+            link.range.start.cmp(&position, buffer).is_le()
+                && link.range.end.cmp(&position, buffer).is_ge()
+        })?;
+        let hover_link = match link.target.clone() {
+            DocumentLinkTarget::Url(url) => HoverLink::Url(url),
+            DocumentLinkTarget::File { abs_path, position } => {
+                let path = ResolvedPath::AbsPath {
+                    path: abs_path,
+                    is_dir: false,
+                };
+                match position {
+                    Some(position) => HoverLink::FileAtPosition(path, position),
+                    None => HoverLink::File(path),
+                }
+            }
+        };
+        Some((link.range.clone(), hover_link))
+    }
+}
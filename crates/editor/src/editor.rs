@@ -17,9 +17,11 @@ mod blink_manager;
 mod clangd_ext;
 pub mod code_context_menus;
 pub mod display_map;
+mod document_links;
 mod editor_settings;
 mod editor_settings_controls;
 mod element;
+mod fold_ranges;
 mod git;
 mod highlight_matching_bracket;
 mod hover_links;
@@ -37,8 +39,11 @@ mod persistence;
 mod proposed_changes_editor;
 mod rust_analyzer_ext;
 pub mod scroll;
+mod selection_ranges;
 mod selections_collection;
+mod semantic_tokens;
 pub mod tasks;
+mod undo_tree;
 
 #[cfg(test)]
 mod code_completion_tests;
@@ -93,6 +98,7 @@ use collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use convert_case::{Case, Casing};
 use dap::TelemetrySpawnLocation;
 use display_map::*;
+use document_links::DocumentLinksState;
 use edit_prediction::{EditPredictionProvider, EditPredictionProviderHandle};
 use editor_settings::{GoToDefinitionFallback, Minimap as MinimapSettings};
 use element::{AcceptEditPredictionBinding, LineWithInvisibles, PositionMap, layout_line};
@@ -148,8 +154,8 @@ use parking_lot::Mutex;
 use persistence::DB;
 use project::{
     BreakpointWithPosition, CodeAction, Completion, CompletionDisplayOptions, CompletionIntent,
-    CompletionResponse, CompletionSource, DisableAiSettings, DocumentHighlight, InlayHint,
-    Location, LocationLink, PrepareRenameResponse, Project, ProjectItem, ProjectPath,
+    CompletionResponse, CompletionSource, DisableAiSettings, DocumentHighlight, FoldingRangeKind,
+    InlayHint, Location, LocationLink, PrepareRenameResponse, Project, ProjectItem, ProjectPath,
     ProjectTransaction, TaskSourceKind,
     debugger::{
         breakpoint_store::{
@@ -167,9 +173,11 @@ use project::{
 use rand::seq::SliceRandom;
 use rpc::{ErrorCode, ErrorExt, proto::PeerId};
 use scroll::{Autoscroll, OngoingScroll, ScrollAnchor, ScrollManager};
+use selection_ranges::LspSelectionRanges;
 use selections_collection::{
     MutableSelectionsCollection, SelectionsCollection, resolve_selections,
 };
+use semantic_tokens::SemanticTokensState;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsLocation, SettingsStore, update_settings_file};
 use smallvec::{SmallVec, smallvec};
@@ -1175,6 +1183,10 @@ pub struct Editor {
     selection_drag_state: SelectionDragState,
     next_color_inlay_id: usize,
     colors: Option<LspColorData>,
+    semantic_tokens: SemanticTokensState,
+    fold_ranges_task: Task<()>,
+    fetched_fold_ranges: HashMap<BufferId, clock::Global>,
+    document_links: DocumentLinksState,
     folding_newlines: Task<()>,
     pub lookup_key: Option<Box<dyn Any + Send + Sync>>,
 }
@@ -1563,12 +1575,20 @@ struct SelectSyntaxNodeHistory {
     stack: Vec<SelectSyntaxNodeHistoryState>,
     // disable temporarily to allow changing selections without losing the stack
     pub disable_clearing: bool,
+    // language server selection ranges around the selections the stack started from
+    lsp_selection_ranges: Option<LspSelectionRanges>,
+    // expansions requested while the selection ranges are fetched, applied once they arrive
+    pending_lsp_expansions: usize,
+    fetch_lsp_selection_ranges_task: Option<Task<()>>,
 }
 
 impl SelectSyntaxNodeHistory {
     pub fn try_clear(&mut self) {
         if !self.disable_clearing {
             self.stack.clear();
+            self.lsp_selection_ranges = None;
+            self.pending_lsp_expansions = 0;
+            self.fetch_lsp_selection_ranges_task = None;
         }
     }
 
@@ -2230,6 +2250,10 @@ impl Editor {
             tasks_update_task: None,
             pull_diagnostics_task: Task::ready(()),
             colors: None,
+            semantic_tokens: SemanticTokensState::default(),
+            fold_ranges_task: Task::ready(()),
+            fetched_fold_ranges: HashMap::default(),
+            document_links: DocumentLinksState::default(),
             next_color_inlay_id: 0,
             linked_edit_ranges: Default::default(),
             in_project_search: false,
@@ -14943,6 +14967,71 @@ impl Editor {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.select_larger_syntax_node_by(1, window, cx);
+    }
+
+    /// Expands the selections `count` times. Selections in buffers that use the `lsp`
+    /// selection range provider are expanded with the language server's selection ranges,
+    /// which are fetched first, and with the syntax tree once those are exhausted.
+    /// Expansions requested while the ranges are fetched are applied once they arrive.
+    pub fn select_larger_syntax_node_by(
+        &mut self,
+        count: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.select_syntax_node_history.pending_lsp_expansions > 0 {
+            self.select_syntax_node_history.pending_lsp_expansions += count;
+            return;
+        }
+        if self
+            .select_syntax_node_history
+            .lsp_selection_ranges
+            .is_none()
+            && let Some(fetch_task) = self.fetch_lsp_selection_ranges(cx)
+        {
+            let selections_before_fetch = self
+                .selections
+                .all::<usize>(cx)
+                .into_iter()
+                .map(|selection| selection.range())
+                .collect::<Vec<_>>();
+            self.select_syntax_node_history.pending_lsp_expansions = count;
+            self.select_syntax_node_history
+                .fetch_lsp_selection_ranges_task =
+                Some(cx.spawn_in(window, async move |editor, cx| {
+                    let lsp_selection_ranges = fetch_task.await;
+                    editor
+                        .update_in(cx, |editor, window, cx| {
+                            let count = mem::take(
+                                &mut editor.select_syntax_node_history.pending_lsp_expansions,
+                            );
+                            let selections = editor
+                                .selections
+                                .all::<usize>(cx)
+                                .into_iter()
+                                .map(|selection| selection.range())
+                                .collect::<Vec<_>>();
+                            if selections != selections_before_fetch {
+                                return;
+                            }
+                            editor.select_syntax_node_history.lsp_selection_ranges =
+                                Some(lsp_selection_ranges);
+                            for _ in 0..count {
+                                editor.select_larger_syntax_node_once(window, cx);
+                            }
+                        })
+                        .ok();
+                }));
+            return;
+        }
+
+        for _ in 0..count {
+            self.select_larger_syntax_node_once(window, cx);
+        }
+    }
+
+    fn select_larger_syntax_node_once(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         let Some(visible_row_count) = self.visible_row_count() else {
             return;
         };
@@ -14956,12 +15045,29 @@ impl Editor {
         let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
         let buffer = self.buffer.read(cx).snapshot(cx);
 
+        let lsp_selection_ranges = self
+            .select_syntax_node_history
+            .lsp_selection_ranges
+            .as_ref();
         let mut selected_larger_node = false;
         let mut new_selections = old_selections
             .iter()
             .map(|selection| {
                 let old_range = selection.start..selection.end;
 
+                if let Some(lsp_range) = lsp_selection_ranges.and_then(|lsp_selection_ranges| {
+                    lsp_selection_ranges.larger_range(selection.id, &old_range, &buffer)
+                }) {
+                    selected_larger_node = true;
+                    return Selection {
+                        id: selection.id,
+                        start: lsp_range.start,
+                        end: lsp_range.end,
+                        goal: SelectionGoal::None,
+                        reversed: selection.reversed,
+                    };
+                }
+
                 if let Some((node, _)) = buffer.syntax_ancestor(old_range.clone()) {
                     // manually select word at selection
                     if ["string_content", "inline"].contains(&node.kind()) {
@@ -16330,7 +16436,11 @@ impl Editor {
                     None
                 }
                 HoverLink::File(path) => {
-                    first_url_or_file = Some(Either::Right(path));
+                    first_url_or_file = Some(Either::Right((path, None)));
+                    None
+                }
+                HoverLink::FileAtPosition(path, position) => {
+                    first_url_or_file = Some(Either::Right((path, Some(position))));
                     None
                 }
             })
@@ -16402,16 +16512,24 @@ impl Editor {
                         acx.update(|_, cx| cx.open_url(&url))?;
                         Ok(Navigated::Yes)
                     }
-                    Some(Either::Right(path)) => {
+                    Some(Either::Right((path, position))) => {
                         let Some(workspace) = workspace else {
                             return Ok(Navigated::No);
                         };
 
-                        workspace
+                        let item = workspace
                             .update_in(acx, |workspace, window, cx| {
                                 workspace.open_resolved_path(path, window, cx)
                             })?
                             .await?;
+                        if let Some(position) = position
+                            && let Some(target_editor) =
+                                acx.update(|_, cx| item.act_as::<Editor>(cx))?
+                        {
+                            target_editor.update_in(acx, |target_editor, window, cx| {
+                                target_editor.go_to_singleton_buffer_point(position, window, cx);
+                            })?;
+                        }
                         Ok(Navigated::Yes)
                     }
                     None => Ok(Navigated::No),
@@ -17978,6 +18096,39 @@ impl Editor {
         }
     }
 
+    pub fn fold_all_comments(
+        &mut self,
+        _: &actions::FoldAllComments,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.fold_lsp_ranges_of_kind(FoldingRangeKind::Comment, window, cx);
+    }
+
+    pub fn fold_all_imports(
+        &mut self,
+        _: &actions::FoldAllImports,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.fold_lsp_ranges_of_kind(FoldingRangeKind::Imports, window, cx);
+    }
+
+    fn fold_lsp_ranges_of_kind(
+        &mut self,
+        kind: FoldingRangeKind,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let creases = display_map
+            .lsp_fold_ranges_of_kind(kind)
+            .into_iter()
+            .map(|range| Crease::simple(range, display_map.fold_placeholder.clone()))
+            .collect();
+        self.fold_creases(creases, true, window, cx);
+    }
+
     pub fn fold_function_bodies(
         &mut self,
         _: &actions::FoldFunctionBodies,
@@ -18540,6 +18691,7 @@ impl Editor {
                         buffer_range: hunk.buffer_range,
                         diff_base_byte_range: hunk.diff_base_byte_range,
                         secondary_status: hunk.secondary_status,
+                        word_diffs: hunk.word_diffs,
                         range: Point::zero()..Point::zero(), // unused
                     })
                     .collect::<Vec<_>>(),
@@ -20677,6 +20829,8 @@ impl Editor {
             )),
             cx,
         );
+        self.refresh_semantic_tokens(None, window, cx);
+        self.refresh_fold_ranges(false, None, window, cx);
 
         let old_cursor_shape = self.cursor_shape;
         let old_show_breadcrumbs = self.show_breadcrumbs;
@@ -21656,6 +21810,9 @@ impl Editor {
     ) {
         self.pull_diagnostics(for_buffer, window, cx);
         self.refresh_colors(ignore_cache, for_buffer, window, cx);
+        self.refresh_semantic_tokens(for_buffer, window, cx);
+        self.refresh_fold_ranges(ignore_cache, for_buffer, window, cx);
+        self.refresh_document_links(ignore_cache, for_buffer, window, cx);
     }
 }
 
@@ -8706,6 +8706,107 @@ async fn test_select_larger_smaller_syntax_node(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_select_larger_syntax_node_with_lsp_selection_ranges(cx: &mut TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.selection_range_provider =
+            Some(language::language_settings::SelectionRangeProvider::Lsp);
+    });
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            selection_range_provider: Some(lsp::SelectionRangeProviderCapability::Simple(true)),
+            ..lsp::ServerCapabilities::default()
+        },
+        cx,
+    )
+    .await;
+    let mut selection_range_requests = cx
+        .set_request_handler::<lsp::request::SelectionRangeRequest, _, _>(
+            move |_, params, _| async move {
+                assert_eq!(params.positions, vec![lsp::Position::new(0, 14)]);
+                let outer = lsp::SelectionRange {
+                    range: lsp::Range::new(lsp::Position::new(0, 8), lsp::Position::new(0, 25)),
+                    parent: None,
+                };
+                Ok(Some(vec![lsp::SelectionRange {
+                    range: lsp::Range::new(lsp::Position::new(0, 13), lsp::Position::new(0, 17)),
+                    parent: Some(Box::new(outer)),
+                }]))
+            },
+        );
+
+    cx.set_state(r#"let a = "hi {naˇme} there";"#);
+    cx.update_editor(|editor, window, cx| {
+        editor.select_larger_syntax_node(&SelectLargerSyntaxNode, window, cx);
+    });
+    selection_range_requests.next().await;
+    cx.run_until_parked();
+    cx.assert_editor_state(r#"let a = "hi {«nameˇ»} there";"#);
+
+    cx.update_editor(|editor, window, cx| {
+        editor.select_larger_syntax_node(&SelectLargerSyntaxNode, window, cx);
+    });
+    cx.assert_editor_state(r#"let a = «"hi {name} there"ˇ»;"#);
+
+    cx.update_editor(|editor, window, cx| {
+        editor.select_smaller_syntax_node(&SelectSmallerSyntaxNode, window, cx);
+    });
+    cx.assert_editor_state(r#"let a = "hi {«nameˇ»} there";"#);
+}
+
+#[gpui::test]
+async fn test_select_larger_syntax_node_repeatedly_while_fetching_lsp_selection_ranges(
+    cx: &mut TestAppContext,
+) {
+    init_test(cx, |settings| {
+        settings.defaults.selection_range_provider =
+            Some(language::language_settings::SelectionRangeProvider::Lsp);
+    });
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            selection_range_provider: Some(lsp::SelectionRangeProviderCapability::Simple(true)),
+            ..lsp::ServerCapabilities::default()
+        },
+        cx,
+    )
+    .await;
+    let request_count = Arc::new(AtomicUsize::new(0));
+    cx.set_request_handler::<lsp::request::SelectionRangeRequest, _, _>({
+        let request_count = request_count.clone();
+        move |_, _, _| {
+            request_count.fetch_add(1, atomic::Ordering::SeqCst);
+            async move {
+                let outer = lsp::SelectionRange {
+                    range: lsp::Range::new(lsp::Position::new(0, 8), lsp::Position::new(0, 25)),
+                    parent: None,
+                };
+                Ok(Some(vec![lsp::SelectionRange {
+                    range: lsp::Range::new(lsp::Position::new(0, 13), lsp::Position::new(0, 17)),
+                    parent: Some(Box::new(outer)),
+                }]))
+            }
+        }
+    });
+
+    // Both expansions are requested before the server responds, and are applied once it does.
+    cx.set_state(r#"let a = "hi {naˇme} there";"#);
+    cx.update_editor(|editor, window, cx| {
+        editor.select_larger_syntax_node(&SelectLargerSyntaxNode, window, cx);
+        editor.select_larger_syntax_node(&SelectLargerSyntaxNode, window, cx);
+    });
+    cx.assert_editor_state(r#"let a = "hi {naˇme} there";"#);
+    cx.run_until_parked();
+    cx.assert_editor_state(r#"let a = «"hi {name} there"ˇ»;"#);
+    assert_eq!(request_count.load(atomic::Ordering::SeqCst), 1);
+
+    cx.update_editor(|editor, window, cx| {
+        editor.select_smaller_syntax_node(&SelectSmallerSyntaxNode, window, cx);
+    });
+    cx.assert_editor_state(r#"let a = "hi {«nameˇ»} there";"#);
+}
+
 #[gpui::test]
 async fn test_select_larger_syntax_node_for_cursor_at_end(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -9252,6 +9353,84 @@ async fn test_fold_function_bodies(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_fold_all_imports_with_lsp_folding_ranges(cx: &mut TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.fold_provider = Some(language::language_settings::FoldProvider::Lsp);
+    });
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            folding_range_provider: Some(lsp::FoldingRangeProviderCapability::Simple(true)),
+            ..lsp::ServerCapabilities::default()
+        },
+        cx,
+    )
+    .await;
+    let mut folding_range_requests = cx
+        .set_request_handler::<lsp::request::FoldingRangeRequest, _, _>(
+            move |_, _, _| async move {
+                Ok(Some(vec![
+                    lsp::FoldingRange {
+                        start_line: 0,
+                        end_line: 2,
+                        kind: Some(lsp::FoldingRangeKind::Imports),
+                        ..lsp::FoldingRange::default()
+                    },
+                    lsp::FoldingRange {
+                        start_line: 5,
+                        end_line: 7,
+                        kind: None,
+                        ..lsp::FoldingRange::default()
+                    },
+                ]))
+            },
+        );
+
+    cx.set_state(indoc! {"
+        ˇuse std::{
+            fmt,
+            io,
+        };
+
+        fn main() {
+            println!();
+            println!();
+        }
+    "});
+    cx.executor().advance_clock(Duration::from_millis(200));
+    folding_range_requests.next().await;
+    cx.run_until_parked();
+
+    cx.update_editor(|editor, window, cx| {
+        editor.fold_all_imports(&FoldAllImports, window, cx);
+        assert_eq!(
+            editor.display_text(cx),
+            indoc! {"
+                use std::{⋯
+                };
+
+                fn main() {
+                    println!();
+                    println!();
+                }
+            "}
+        );
+
+        editor.fold_all(&FoldAll, window, cx);
+        assert_eq!(
+            editor.display_text(cx),
+            indoc! {"
+                use std::{⋯
+                };
+
+                fn main() {⋯
+                }
+            "}
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_autoindent(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -21639,6 +21818,116 @@ async fn test_find_enclosing_node_with_task(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_spawning_runnable_with_task_inputs(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    struct RecordingTerminalProvider {
+        spawned_args: Rc<RefCell<Vec<Vec<String>>>>,
+    }
+
+    impl workspace::TerminalProvider for RecordingTerminalProvider {
+        fn spawn(
+            &self,
+            task: task::SpawnInTerminal,
+            _: &mut Window,
+            _: &mut App,
+        ) -> Task<Option<anyhow::Result<std::process::ExitStatus>>> {
+            self.spawned_args.borrow_mut().push(task.args);
+            Task::ready(Some(Ok(std::process::ExitStatus::default())))
+        }
+    }
+
+    struct AnsweringTaskInputProvider;
+
+    impl workspace::TaskInputProvider for AnsweringTaskInputProvider {
+        fn resolve_inputs(
+            &self,
+            task_source_kind: TaskSourceKind,
+            task: ResolvedTask,
+            _: &mut Window,
+            _: &mut Context<Workspace>,
+        ) -> Task<Option<ResolvedTask>> {
+            let mut task_context = task.task_context().clone();
+            for input in &task.original_task().inputs {
+                task_context.task_variables.insert(
+                    task::VariableName::Input(input.id.clone().into()),
+                    format!("{}-value", input.id),
+                );
+            }
+            Task::ready(
+                task.original_task()
+                    .resolve_task(&task_source_kind.to_id_base(), &task_context),
+            )
+        }
+    }
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(path!("/a"), json!({ "main.txt": "one\ntwo\nthree\n" }))
+        .await;
+    let project = Project::test(fs, [path!("/a").as_ref()], cx).await;
+    let workspace = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+    let spawned_args = Rc::new(RefCell::new(Vec::new()));
+    let worktree_id = workspace
+        .update(cx, |workspace, _, cx| {
+            workspace.set_terminal_provider(RecordingTerminalProvider {
+                spawned_args: spawned_args.clone(),
+            });
+            workspace.set_task_input_provider(AnsweringTaskInputProvider);
+            workspace.project().update(cx, |project, cx| {
+                project.worktrees(cx).next().unwrap().read(cx).id()
+            })
+        })
+        .unwrap();
+    let editor = workspace
+        .update(cx, |workspace, window, cx| {
+            workspace.open_path((worktree_id, "main.txt"), None, true, window, cx)
+        })
+        .unwrap()
+        .await
+        .unwrap()
+        .downcast::<Editor>()
+        .unwrap();
+
+    editor.update_in(cx, |editor, window, cx| {
+        let buffer = editor.buffer().read(cx).as_singleton().unwrap();
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        editor.tasks.insert(
+            (buffer.read(cx).remote_id(), 1),
+            RunnableTasks {
+                templates: vec![(
+                    TaskSourceKind::UserInput,
+                    TaskTemplate {
+                        label: "deploy".into(),
+                        command: "deploy".into(),
+                        args: vec!["${ZED_INPUT_environment}".into()],
+                        inputs: vec![task::TaskInput {
+                            id: "environment".into(),
+                            description: None,
+                            default: None,
+                            kind: task::TaskInputKind::PromptString,
+                        }],
+                        ..TaskTemplate::default()
+                    },
+                )],
+                offset: snapshot.anchor_before(4),
+                column: 0,
+                extra_variables: HashMap::default(),
+                context_range: BufferOffset(4)..BufferOffset(7),
+            },
+        );
+        editor.spawn_nearest_task(&SpawnNearestTask::default(), window, cx);
+    });
+    cx.run_until_parked();
+
+    assert_eq!(
+        *spawned_args.borrow(),
+        vec![vec!["environment-value".to_string()]],
+        "Runnables should ask for the task inputs before spawning the task"
+    );
+}
+
 #[gpui::test]
 async fn test_folding_buffers(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -428,6 +428,8 @@ impl EditorElement {
         register_action(editor, window, Editor::fold);
         register_action(editor, window, Editor::fold_at_level);
         register_action(editor, window, Editor::fold_all);
+        register_action(editor, window, Editor::fold_all_comments);
+        register_action(editor, window, Editor::fold_all_imports);
         register_action(editor, window, Editor::fold_function_bodies);
         register_action(editor, window, Editor::fold_recursive);
         register_action(editor, window, Editor::toggle_fold);
@@ -7294,6 +7296,52 @@ impl EditorElement {
         )
     }
 
+    /// Emphasizes the words that changed within the expanded diff hunks in view, on top of the
+    /// background of their rows.
+    fn layout_word_diff_highlights(
+        range: Range<Anchor>,
+        start_row: DisplayRow,
+        row_infos: &[RowInfo],
+        snapshot: &EditorSnapshot,
+        cx: &App,
+    ) -> Vec<(Range<DisplayPoint>, Hsla)> {
+        let buffer_snapshot = &snapshot.buffer_snapshot;
+        let colors = cx.theme().colors();
+        let opacity = if cx.theme().appearance().is_light() {
+            0.32
+        } else {
+            0.24
+        };
+
+        let mut highlights = Vec::new();
+        for hunk in buffer_snapshot.diff_hunks_in_range(range) {
+            if !hunk.status().is_modified() {
+                continue;
+            }
+            for (word_range, kind) in buffer_snapshot.diff_hunk_word_ranges(&hunk) {
+                let start = word_range.start.to_display_point(snapshot);
+                let end = word_range.end.to_display_point(snapshot);
+                // Collapsed hunks show neither their deleted words nor their inserted words as
+                // such, so only rows that are displayed as part of the hunk are emphasized.
+                let row_status = start
+                    .row()
+                    .0
+                    .checked_sub(start_row.0)
+                    .and_then(|ix| row_infos.get(ix as usize))
+                    .and_then(|row_info| row_info.diff_status);
+                if row_status.is_none_or(|status| status.kind != kind) {
+                    continue;
+                }
+                let color = match kind {
+                    DiffHunkStatusKind::Deleted => colors.version_control_deleted,
+                    _ => colors.version_control_added,
+                };
+                highlights.push((start..end, color.opacity(opacity)));
+            }
+        }
+        highlights
+    }
+
     fn diff_hunk_hollow(status: DiffHunkStatus, cx: &mut App) -> bool {
         let unstaged = status.has_secondary_hunk();
         let unstaged_hollow = ProjectSettings::get_global(cx)
@@ -8611,7 +8659,7 @@ impl Element for EditorElement {
                             .or_insert(background);
                     }
 
-                    let highlighted_ranges = self
+                    let mut highlighted_ranges = self
                         .editor_with_selections(cx)
                         .map(|editor| {
                             editor.read(cx).background_highlights_in_range(
@@ -8621,6 +8669,13 @@ impl Element for EditorElement {
                             )
                         })
                         .unwrap_or_default();
+                    highlighted_ranges.extend(Self::layout_word_diff_highlights(
+                        start_anchor..end_anchor,
+                        start_row,
+                        &row_infos,
+                        &snapshot,
+                        cx,
+                    ));
                     let highlighted_gutter_ranges =
                         self.editor.read(cx).gutter_highlights_in_range(
                             start_anchor..end_anchor,
@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use futures::future::join_all;
+use itertools::Itertools as _;
+use language::language_settings::{FoldProvider, language_settings};
+use text::BufferId;
+use ui::{Context, Window};
+
+use crate::{Editor, display_map::BufferFoldRanges};
+
+const FOLDING_RANGES_DEBOUNCE: Duration = Duration::from_millis(150);
+
+impl Editor {
+    /// Updates the fold ranges of the editor's visible buffers according to their `fold_provider`
+    /// setting, querying the language servers for buffers that use `lsp` and have changed since
+    /// their ranges were last fetched.
+    pub(super) fn refresh_fold_ranges(
+        &mut self,
+        ignore_cache: bool,
+        for_buffer: Option<BufferId>,
+        _: &Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.mode().is_full() {
+            return;
+        }
+
+        let visible_buffers = self
+            .visible_excerpts(None, cx)
+            .into_values()
+            .map(|(buffer, ..)| buffer)
+            .filter(|editor_buffer| {
+                for_buffer.is_none_or(|buffer_id| buffer_id == editor_buffer.read(cx).remote_id())
+            })
+            .unique_by(|buffer| buffer.read(cx).remote_id())
+            .collect::<Vec<_>>();
+        let mut lsp_buffers = Vec::new();
+        for buffer in visible_buffers {
+            let buffer_ref = buffer.read(cx);
+            let buffer_id = buffer_ref.remote_id();
+            let fold_provider = language_settings(
+                buffer_ref.language().map(|language| language.name()),
+                buffer_ref.file(),
+                cx,
+            )
+            .fold_provider;
+            match fold_provider {
+                FoldProvider::Indent => {
+                    self.fetched_fold_ranges.remove(&buffer_id);
+                    self.display_map.update(cx, |display_map, _| {
+                        display_map.set_fold_ranges(buffer_id, None)
+                    })
+                }
+                FoldProvider::TreeSitter => {
+                    self.fetched_fold_ranges.remove(&buffer_id);
+                    self.display_map.update(cx, |display_map, _| {
+                        display_map.set_fold_ranges(buffer_id, Some(BufferFoldRanges::TreeSitter))
+                    })
+                }
+                // Keep the previous ranges until the language server responds.
+                FoldProvider::Lsp => {
+                    let up_to_date = self
+                        .fetched_fold_ranges
+                        .get(&buffer_id)
+                        .is_some_and(|version| version == &buffer_ref.version());
+                    if ignore_cache || !up_to_date {
+                        lsp_buffers.push(buffer);
+                    }
+                }
+            }
+        }
+
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        if lsp_buffers.is_empty() {
+            return;
+        }
+        self.fold_ranges_task = cx.spawn(async move |editor, cx| {
+            cx.background_executor()
+                .timer(FOLDING_RANGES_DEBOUNCE)
+                .await;
+            let Ok(fold_ranges_tasks) = project.update(cx, |project, cx| {
+                lsp_buffers
+                    .into_iter()
+                    .map(|buffer| {
+                        let buffer_id = buffer.read(cx).remote_id();
+                        let version = buffer.read(cx).version();
+                        let fold_ranges_task = project.folding_ranges(&buffer, cx);
+                        async move { (buffer_id, version, fold_ranges_task.await) }
+                    })
+                    .collect::<Vec<_>>()
+            }) else {
+                return;
+            };
+            let all_fold_ranges = join_all(fold_ranges_tasks).await;
+
+            editor
+                .update(cx, |editor, cx| {
+                    for (buffer_id, version, fold_ranges) in &all_fold_ranges {
+                        if fold_ranges.is_ok() {
+                            editor
+                                .fetched_fold_ranges
+                                .insert(*buffer_id, version.clone());
+                        }
+                    }
+                    editor.display_map.update(cx, |display_map, _| {
+                        for (buffer_id, _, fold_ranges) in all_fold_ranges {
+                            match fold_ranges {
+                                // Fall back to indentation when no server provides folding ranges.
+                                Ok(fold_ranges) if fold_ranges.is_empty() => {
+                                    display_map.set_fold_ranges(buffer_id, None)
+                                }
+                                Ok(fold_ranges) => display_map.set_fold_ranges(
+                                    buffer_id,
+                                    Some(BufferFoldRanges::Lsp(fold_ranges.into())),
+                                ),
+                                Err(e) => log::error!("Failed to fetch folding ranges: {e:#}"),
+                            }
+                        }
+                    });
+                    cx.notify();
+                })
+                .ok();
+        });
+    }
+}
@@ -6,7 +6,7 @@ use crate::{
     scroll::ScrollAmount,
 };
 use gpui::{App, AsyncWindowContext, Context, Entity, Modifiers, Task, Window, px};
-use language::{Bias, ToOffset};
+use language::{Bias, Point, ToOffset};
 use linkify::{LinkFinder, LinkKind};
 use lsp::LanguageServerId;
 use project::{
@@ -66,6 +66,8 @@ impl RangeInEditor {
 pub enum HoverLink {
     Url(String),
     File(ResolvedPath),
+    /// A file to open at the given position, from a language server's document link.
+    FileAtPosition(ResolvedPath, Point),
     Text(LocationLink),
     InlayHint(lsp::Location, LanguageServerId),
 }
@@ -531,6 +533,10 @@ pub fn show_link_definition(
     } else {
         editor.hide_hovered_link(cx)
     }
+    let document_link = match &trigger_point {
+        TriggerPoint::Text(_) => editor.document_link_at(&buffer, buffer_position, cx),
+        TriggerPoint::InlayHint(..) => None,
+    };
     let project = editor.project.clone();
     let provider = editor.semantics_provider.clone();
 
@@ -539,7 +545,16 @@ pub fn show_link_definition(
         async move {
             let result = match &trigger_point {
                 TriggerPoint::Text(_) => {
-                    if let Some((url_range, url)) = find_url(&buffer, buffer_position, cx.clone()) {
+                    if let Some((link_range, link)) = document_link {
+                        let range = maybe!({
+                            let start = snapshot.anchor_in_excerpt(excerpt_id, link_range.start)?;
+                            let end = snapshot.anchor_in_excerpt(excerpt_id, link_range.end)?;
+                            Some(RangeInEditor::Text(start..end))
+                        });
+                        Some((range, vec![link]))
+                    } else if let Some((url_range, url)) =
+                        find_url(&buffer, buffer_position, cx.clone())
+                    {
                         this.read_with(cx, |_, _| {
                             let range = maybe!({
                                 let start =
@@ -933,6 +948,7 @@ mod tests {
     use indoc::indoc;
     use language::language_settings::InlayHintSettings;
     use lsp::request::{GotoDefinition, GotoTypeDefinition};
+    use std::time::Duration;
     use util::{assert_set_eq, path};
     use workspace::item::Item;
 
@@ -1459,6 +1475,49 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_document_links(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                document_link_provider: Some(lsp::DocumentLinkOptions {
+                    resolve_provider: Some(false),
+                    work_done_progress_options: Default::default(),
+                }),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        let mut document_link_requests = cx
+            .set_request_handler::<lsp::request::DocumentLinkRequest, _, _>(|_, _, _| async move {
+                Ok(Some(vec![lsp::DocumentLink {
+                    range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, 5)),
+                    target: Some("https://docs.rs/serde".parse().unwrap()),
+                    tooltip: None,
+                    data: None,
+                }]))
+            });
+        cx.set_state(indoc! {"
+            serde = \"1.0\"ˇ
+        "});
+        cx.executor().advance_clock(Duration::from_millis(200));
+        document_link_requests.next().await;
+        cx.run_until_parked();
+
+        let screen_coord = cx.pixel_position(indoc! {"
+            serˇde = \"1.0\"
+        "});
+        cx.simulate_mouse_move(screen_coord, None, Modifiers::secondary_key());
+        cx.assert_editor_text_highlights::<HoveredLinkState>(indoc! {"
+            «serdeˇ» = \"1.0\"
+        "});
+
+        cx.simulate_click(screen_coord, Modifiers::secondary_key());
+        assert_eq!(cx.opened_url(), Some("https://docs.rs/serde".into()));
+    }
+
     #[gpui::test]
     async fn test_urls_at_beginning_of_buffer(cx: &mut gpui::TestAppContext) {
         init_test(cx, |_| {});
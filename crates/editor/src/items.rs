@@ -1420,6 +1420,20 @@ fn clip_ranges<'a>(
         .collect()
 }
 
+/// Structural replacements depend on the syntax of the match, so they are made from the buffer
+/// it is in rather than from its text.
+fn structural_replacement(
+    snapshot: &MultiBufferSnapshot,
+    range: &Range<Anchor>,
+    query: &SearchQuery,
+) -> Option<Cow<'static, str>> {
+    let buffer_ranges = snapshot.range_to_buffer_ranges(range.clone());
+    let [(buffer, range, _)] = buffer_ranges.as_slice() else {
+        return None;
+    };
+    query.replacement_in_buffer(buffer, range.clone())
+}
+
 impl EventEmitter<SearchEvent> for Editor {}
 
 impl Editor {
@@ -1619,6 +1633,14 @@ impl SearchableItem for Editor {
     ) {
         let text = self.buffer.read(cx);
         let text = text.snapshot(cx);
+        if query.is_structural() {
+            if let Some(replacement) = structural_replacement(&text, identifier, query) {
+                self.transact(window, cx, |this, _, cx| {
+                    this.edit([(identifier.clone(), Arc::from(&*replacement))], cx);
+                });
+            }
+            return;
+        }
         let text = text.text_for_range(identifier.clone()).collect::<Vec<_>>();
         let text: Cow<_> = if text.len() == 1 {
             text.first().cloned().unwrap().into()
@@ -1645,6 +1667,12 @@ impl SearchableItem for Editor {
         let mut edits = vec![];
 
         for m in matches {
+            if query.is_structural() {
+                if let Some(replacement) = structural_replacement(&text, m, query) {
+                    edits.push((m.clone(), Arc::from(&*replacement)));
+                }
+                continue;
+            }
             let text = text.text_for_range(m.clone()).collect::<Vec<_>>();
 
             let text: Cow<_> = if text.len() == 1 {
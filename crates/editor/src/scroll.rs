@@ -494,6 +494,8 @@ impl Editor {
                     .update_in(cx, |editor, window, cx| {
                         editor.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
                         editor.refresh_colors(false, None, window, cx);
+                        editor.refresh_fold_ranges(false, None, window, cx);
+                        editor.refresh_document_links(false, None, window, cx);
                     })
                     .ok()
             })
@@ -609,6 +611,8 @@ impl Editor {
 
         self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
         self.refresh_colors(false, None, window, cx);
+        self.refresh_fold_ranges(false, None, window, cx);
+        self.refresh_document_links(false, None, window, cx);
         editor_was_scrolled
     }
 
@@ -0,0 +1,128 @@
+use std::ops::Range;
+
+use collections::HashMap;
+use futures::future::join_all;
+use gpui::Task;
+use language::language_settings::{SelectionRangeProvider, language_settings};
+use multi_buffer::{Anchor, ExcerptId, MultiBufferSnapshot, ToOffset as _};
+use text::BufferId;
+use ui::Context;
+
+use crate::Editor;
+
+/// The ranges a language server reported around each selection, used to expand them.
+#[derive(Debug, Default)]
+pub(crate) struct LspSelectionRanges {
+    /// The enclosing ranges of each selection, from the innermost to the outermost one.
+    ranges: HashMap<usize, Vec<Range<Anchor>>>,
+}
+
+impl LspSelectionRanges {
+    /// Returns the smallest range that strictly contains the given selection range.
+    pub(crate) fn larger_range(
+        &self,
+        selection_id: usize,
+        range: &Range<usize>,
+        buffer: &MultiBufferSnapshot,
+    ) -> Option<Range<usize>> {
+        self.ranges
+            .get(&selection_id)?
+            .iter()
+            .map(|candidate| candidate.start.to_offset(buffer)..candidate.end.to_offset(buffer))
+            .find(|candidate| {
+                candidate.start <= range.start && range.end <= candidate.end && candidate != range
+            })
+    }
+}
+
+impl Editor {
+    /// Requests selection ranges for the selections in buffers that use the `lsp`
+    /// selection range provider, or returns `None` if there are no such selections.
+    pub(crate) fn fetch_lsp_selection_ranges(
+        &mut self,
+        cx: &mut Context<Self>,
+    ) -> Option<Task<LspSelectionRanges>> {
+        let project = self.project.clone()?;
+        let selections = self.selections.all_anchors(cx);
+        let mut positions_by_buffer =
+            HashMap::<BufferId, Vec<(usize, ExcerptId, text::Anchor)>>::default();
+        for selection in selections.iter() {
+            let head = selection.head();
+            let Some(buffer_id) = head.buffer_id else {
+                continue;
+            };
+            positions_by_buffer.entry(buffer_id).or_default().push((
+                selection.id,
+                head.excerpt_id,
+                head.text_anchor,
+            ));
+        }
+
+        let requests = positions_by_buffer
+            .into_iter()
+            .filter_map(|(buffer_id, positions)| {
+                let buffer = self.buffer.read(cx).buffer(buffer_id)?;
+                let provider = {
+                    let buffer = buffer.read(cx);
+                    language_settings(
+                        buffer.language().map(|language| language.name()),
+                        buffer.file(),
+                        cx,
+                    )
+                    .selection_range_provider
+                };
+                (provider == SelectionRangeProvider::Lsp).then_some((buffer, positions))
+            })
+            .collect::<Vec<_>>();
+        if requests.is_empty() {
+            return None;
+        }
+
+        let selection_ranges_tasks = project.update(cx, |project, cx| {
+            requests
+                .into_iter()
+                .map(|(buffer, positions)| {
+                    let task = project.selection_ranges(
+                        &buffer,
+                        positions.iter().map(|(_, _, position)| *position).collect(),
+                        cx,
+                    );
+                    async move { (positions, task.await) }
+                })
+                .collect::<Vec<_>>()
+        });
+        Some(cx.spawn(async move |editor, cx| {
+            let all_selection_ranges = join_all(selection_ranges_tasks).await;
+            let Ok(snapshot) = editor.update(cx, |editor, cx| editor.buffer.read(cx).snapshot(cx))
+            else {
+                return LspSelectionRanges::default();
+            };
+
+            let mut lsp_selection_ranges = LspSelectionRanges::default();
+            for (positions, selection_ranges) in all_selection_ranges {
+                let selection_ranges = match selection_ranges {
+                    Ok(selection_ranges) => selection_ranges,
+                    Err(e) => {
+                        log::error!("Failed to fetch selection ranges: {e:#}");
+                        continue;
+                    }
+                };
+                for ((selection_id, excerpt_id, _), ranges) in
+                    positions.into_iter().zip(selection_ranges)
+                {
+                    let ranges = ranges
+                        .into_iter()
+                        .filter_map(|range| {
+                            Some(
+                                snapshot.anchor_in_excerpt(excerpt_id, range.start)?
+                                    ..snapshot.anchor_in_excerpt(excerpt_id, range.end)?,
+                            )
+                        })
+                        .collect();
+                    lsp_selection_ranges.ranges.insert(selection_id, ranges);
+                }
+            }
+            lsp_selection_ranges
+        }))
+    }
+}
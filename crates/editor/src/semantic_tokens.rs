@@ -0,0 +1,330 @@
+use std::{any::TypeId, ops::Range, time::Duration};
+
+use collections::HashMap;
+use futures::future::join_all;
+use gpui::{HighlightStyle, Task};
+use itertools::Itertools as _;
+use language::language_settings::language_settings;
+use multi_buffer::Anchor;
+use project::lsp_store::semantic_tokens::{BufferSemanticTokens, SemanticToken};
+use text::BufferId;
+use theme::{ActiveTheme as _, SyntaxTheme};
+use ui::{Context, Window};
+
+use crate::Editor;
+
+const SEMANTIC_TOKENS_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Text highlights produced from language server semantic tokens, keyed by theme syntax style.
+enum SemanticTokenHighlight {}
+
+#[derive(Default)]
+pub(super) struct SemanticTokensState {
+    buffer_tokens: HashMap<BufferId, BufferSemanticTokens>,
+    refresh_task: Option<Task<()>>,
+}
+
+impl Editor {
+    pub(super) fn refresh_semantic_tokens(
+        &mut self,
+        for_buffer: Option<BufferId>,
+        _: &Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.mode().is_full() {
+            return;
+        }
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+
+        let visible_buffers = self
+            .visible_excerpts(None, cx)
+            .into_values()
+            .map(|(buffer, ..)| buffer)
+            .filter(|buffer| {
+                for_buffer.is_none_or(|buffer_id| buffer_id == buffer.read(cx).remote_id())
+            })
+            .unique_by(|buffer| buffer.read(cx).remote_id())
+            .collect::<Vec<_>>();
+        let (enabled_buffers, disabled_buffers): (Vec<_>, Vec<_>) =
+            visible_buffers.into_iter().partition(|buffer| {
+                let buffer = buffer.read(cx);
+                language_settings(
+                    buffer.language().map(|language| language.name()),
+                    buffer.file(),
+                    cx,
+                )
+                .semantic_tokens
+            });
+
+        let mut highlights_changed = false;
+        for buffer in disabled_buffers {
+            highlights_changed |= self
+                .semantic_tokens
+                .buffer_tokens
+                .remove(&buffer.read(cx).remote_id())
+                .is_some();
+        }
+        if highlights_changed {
+            self.update_semantic_token_highlights(cx);
+        }
+        if enabled_buffers.is_empty() {
+            return;
+        }
+
+        self.semantic_tokens.refresh_task = Some(cx.spawn(async move |editor, cx| {
+            cx.background_executor()
+                .timer(SEMANTIC_TOKENS_DEBOUNCE)
+                .await;
+            let Ok(tokens_tasks) = project.update(cx, |project, cx| {
+                project.lsp_store().update(cx, |lsp_store, cx| {
+                    enabled_buffers
+                        .into_iter()
+                        .map(|buffer| {
+                            let buffer_id = buffer.read(cx).remote_id();
+                            let tokens_task = lsp_store.semantic_tokens(&buffer, cx);
+                            async move { (buffer_id, tokens_task.await) }
+                        })
+                        .collect::<Vec<_>>()
+                })
+            }) else {
+                return;
+            };
+            let all_tokens = join_all(tokens_tasks).await;
+
+            editor
+                .update(cx, |editor, cx| {
+                    for (buffer_id, tokens) in all_tokens {
+                        match tokens {
+                            Ok(Some(tokens)) => {
+                                editor
+                                    .semantic_tokens
+                                    .buffer_tokens
+                                    .insert(buffer_id, tokens);
+                            }
+                            Ok(None) => {
+                                editor.semantic_tokens.buffer_tokens.remove(&buffer_id);
+                            }
+                            Err(e) => log::error!("Failed to fetch semantic tokens: {e:#}"),
+                        }
+                    }
+                    editor.update_semantic_token_highlights(cx);
+                })
+                .ok();
+        }));
+    }
+
+    fn update_semantic_token_highlights(&mut self, cx: &mut Context<Self>) {
+        let multi_buffer_snapshot = self.buffer().read(cx).snapshot(cx);
+        let syntax_theme = cx.theme().syntax().clone();
+
+        let mut ranges_by_theme_key = HashMap::<String, Vec<Range<Anchor>>>::default();
+        for (excerpt_id, buffer_snapshot, excerpt_range) in multi_buffer_snapshot.excerpts() {
+            let Some(buffer_tokens) = self
+                .semantic_tokens
+                .buffer_tokens
+                .get(&buffer_snapshot.remote_id())
+            else {
+                continue;
+            };
+            let mut theme_keys = HashMap::<(u32, u32), Option<String>>::default();
+            for token in &buffer_tokens.tokens {
+                if excerpt_range
+                    .context
+                    .start
+                    .cmp(&token.range.start, buffer_snapshot)
+                    .is_gt()
+                    || token
+                        .range
+                        .end
+                        .cmp(&excerpt_range.context.end, buffer_snapshot)
+                        .is_gt()
+                {
+                    continue;
+                }
+                let Some(theme_key) = theme_keys
+                    .entry((token.token_type, token.token_modifiers))
+                    .or_insert_with(|| theme_key_for_token(buffer_tokens, token, &syntax_theme))
+                else {
+                    continue;
+                };
+                let (Some(start), Some(end)) = (
+                    multi_buffer_snapshot.anchor_in_excerpt(excerpt_id, token.range.start),
+                    multi_buffer_snapshot.anchor_in_excerpt(excerpt_id, token.range.end),
+                ) else {
+                    continue;
+                };
+                ranges_by_theme_key
+                    .entry(theme_key.clone())
+                    .or_default()
+                    .push(start..end);
+            }
+        }
+
+        self.display_map.update(cx, |display_map, _| {
+            display_map.clear_keyed_text_highlights(TypeId::of::<SemanticTokenHighlight>())
+        });
+        for (ix, (theme_key, ranges)) in ranges_by_theme_key
+            .into_iter()
+            .sorted_by(|(a, _), (b, _)| a.cmp(b))
+            .enumerate()
+        {
+            let style: HighlightStyle = syntax_theme.get(&theme_key);
+            self.highlight_text_key::<SemanticTokenHighlight>(ix, ranges, style, cx);
+        }
+        cx.notify();
+    }
+}
+
+/// Picks the most specific theme syntax key for a token, e.g. `variable.mutable` for a
+/// `variable` token with the `mutable` modifier, falling back to `variable`.
+fn theme_key_for_token(
+    buffer_tokens: &BufferSemanticTokens,
+    token: &SemanticToken,
+    syntax_theme: &SyntaxTheme,
+) -> Option<String> {
+    let base_key = theme_key_for_token_type(buffer_tokens.token_type(token)?);
+    let modifier_keys = buffer_tokens
+        .token_modifiers(token)
+        .map(|modifier| format!("{base_key}.{modifier}"));
+    let parent_keys = base_key
+        .match_indices('.')
+        .map(|(ix, _)| base_key[..ix].to_string())
+        .rev();
+    modifier_keys
+        .chain([base_key.to_string()])
+        .chain(parent_keys)
+        .find(|key| syntax_theme.highlight_id(key).is_some())
+}
+
+fn theme_key_for_token_type(token_type: &str) -> &str {
+    match token_type {
+        "class" | "struct" | "interface" | "typeParameter" | "typeAlias" | "builtinType" => "type",
+        "enumMember" => "variant",
+        "parameter" => "variable.parameter",
+        "method" => "function.method",
+        "macro" => "function.macro",
+        "decorator" => "attribute",
+        "regexp" => "string.regex",
+        "modifier" => "keyword",
+        "event" => "property",
+        token_type => token_type,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{editor_tests::init_test, test::editor_lsp_test_context::EditorLspTestContext};
+    use gpui::TestAppContext;
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering::SeqCst},
+    };
+
+    fn token(delta_line: u32, delta_start: u32, length: u32) -> lsp::SemanticToken {
+        lsp::SemanticToken {
+            delta_line,
+            delta_start,
+            length,
+            token_type: 0,
+            token_modifiers_bitset: 0,
+        }
+    }
+
+    fn highlighted_texts(cx: &mut EditorLspTestContext) -> Vec<String> {
+        cx.update_editor(|editor, _, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            editor
+                .display_map
+                .read(cx)
+                .all_text_highlights()
+                .flat_map(|highlights| highlights.1.clone())
+                .map(|range| snapshot.text_for_range(range).collect::<String>())
+                .sorted()
+                .collect()
+        })
+    }
+
+    #[gpui::test]
+    async fn test_semantic_token_highlights(cx: &mut TestAppContext) {
+        init_test(cx, |settings| {
+            settings.defaults.semantic_tokens = Some(true)
+        });
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                semantic_tokens_provider: Some(
+                    lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        lsp::SemanticTokensOptions {
+                            legend: lsp::SemanticTokensLegend {
+                                token_types: vec![lsp::SemanticTokenType::FUNCTION],
+                                token_modifiers: Vec::new(),
+                            },
+                            full: Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                            ..lsp::SemanticTokensOptions::default()
+                        },
+                    ),
+                ),
+                ..lsp::ServerCapabilities::default()
+            },
+            cx,
+        )
+        .await;
+
+        let full_requests = Arc::new(AtomicUsize::new(0));
+        let delta_requests = Arc::new(AtomicUsize::new(0));
+        cx.set_request_handler::<lsp::request::SemanticTokensFullRequest, _, _>({
+            let full_requests = full_requests.clone();
+            move |_, _, _| {
+                full_requests.fetch_add(1, SeqCst);
+                async move {
+                    Ok(Some(lsp::SemanticTokensResult::Tokens(
+                        lsp::SemanticTokens {
+                            result_id: Some("1".into()),
+                            data: vec![token(0, 3, 1), token(1, 3, 1)],
+                        },
+                    )))
+                }
+            }
+        });
+        // After a line is inserted above both functions, the server only reports `a`.
+        cx.set_request_handler::<lsp::request::SemanticTokensFullDeltaRequest, _, _>({
+            let delta_requests = delta_requests.clone();
+            move |_, params, _| {
+                delta_requests.fetch_add(1, SeqCst);
+                assert_eq!(params.previous_result_id, "1");
+                async move {
+                    Ok(Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(
+                        lsp::SemanticTokensDelta {
+                            result_id: Some("2".into()),
+                            edits: vec![lsp::SemanticTokensEdit {
+                                start: 0,
+                                delete_count: 10,
+                                data: Some(vec![token(1, 3, 1)]),
+                            }],
+                        },
+                    )))
+                }
+            }
+        });
+
+        cx.set_state("ˇfn a() {}\nfn b() {}");
+        cx.executor().advance_clock(SEMANTIC_TOKENS_DEBOUNCE);
+        cx.run_until_parked();
+        assert_eq!(highlighted_texts(&mut cx), ["a", "b"]);
+        assert_eq!(full_requests.load(SeqCst), 1);
+
+        // Until the server answers for the edited buffer, the highlights move along with the text.
+        cx.update_editor(|editor, window, cx| editor.handle_input("\n", window, cx));
+        assert_eq!(highlighted_texts(&mut cx), ["a", "b"]);
+
+        cx.executor().advance_clock(SEMANTIC_TOKENS_DEBOUNCE);
+        cx.run_until_parked();
+        cx.assert_editor_state("\nˇfn a() {}\nfn b() {}");
+        assert_eq!(highlighted_texts(&mut cx), ["a"]);
+        assert_eq!(full_requests.load(SeqCst), 1);
+        assert_eq!(delta_requests.load(SeqCst), 1);
+    }
+}
@@ -0,0 +1,44 @@
+use language::TransactionId;
+use ui::{Context, Window};
+
+use crate::{Editor, EditorEvent, SelectionEffects, scroll::Autoscroll};
+
+impl Editor {
+    /// Moves the buffer to the given state of its undo tree and places the cursor at the first
+    /// change this made. Only editors of a single buffer can navigate its undo tree.
+    pub fn go_to_undo_tree_state(
+        &mut self,
+        target: Option<TransactionId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        if self.read_only(cx) {
+            return false;
+        }
+        let Some(buffer) = self.buffer.read(cx).as_singleton() else {
+            return false;
+        };
+
+        let old_version = buffer.read(cx).version();
+        if !buffer.update(cx, |buffer, cx| buffer.go_to_undo_tree_state(target, cx)) {
+            return false;
+        }
+        let first_change = buffer
+            .read(cx)
+            .edits_since::<usize>(&old_version)
+            .next()
+            .map(|edit| edit.new.start);
+        if let Some(offset) = first_change {
+            self.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+                s.select_ranges([offset..offset]);
+            });
+        }
+        self.request_autoscroll(Autoscroll::fit(), cx);
+        self.unmark_text(window, cx);
+        self.refresh_edit_prediction(true, false, window, cx);
+        if let Some(transaction_id) = target {
+            cx.emit(EditorEvent::Edited { transaction_id });
+        }
+        true
+    }
+}
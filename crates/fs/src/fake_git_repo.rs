@@ -7,7 +7,8 @@ use git::{
     blame::Blame,
     repository::{
         AskPassDelegate, Branch, CommitDetails, CommitOptions, FetchOptions, GitRepository,
-        GitRepositoryCheckpoint, PushOptions, Remote, RepoPath, ResetMode,
+        GitRepositoryCheckpoint, GitWorktree, LogEntry, LogOptions, MergeOptions, PushOptions,
+        RebaseAction, RebaseControl, RebaseProgress, RebaseTodoEntry, Remote, RepoPath, ResetMode,
     },
     status::{FileStatus, GitStatus, StatusCode, TrackedStatus, UnmergedStatus},
 };
@@ -16,7 +17,7 @@ use ignore::gitignore::GitignoreBuilder;
 use parking_lot::Mutex;
 use rope::Rope;
 use smol::future::FutureExt as _;
-use std::{path::PathBuf, sync::Arc};
+use std::{collections::VecDeque, path::PathBuf, sync::Arc};
 
 #[derive(Clone)]
 pub struct FakeGitRepository {
@@ -39,6 +40,25 @@ pub struct FakeGitRepositoryState {
     pub branches: HashSet<String>,
     pub simulated_index_write_error_message: Option<String>,
     pub refs: HashMap<String, String>,
+    /// The linked worktrees of the repository, besides the main one.
+    pub worktrees: Vec<GitWorktree>,
+    /// The commits of the repository, each listed before its parents, as `git log` does.
+    pub commits: Vec<LogEntry>,
+    pub rebase: Option<FakeRebase>,
+}
+
+/// An interactive rebase of a fake repository. Commits are rewritten without conflicts, so the
+/// rebase only stops at the commits to edit.
+#[derive(Debug, Clone)]
+pub struct FakeRebase {
+    pub original_head: SharedString,
+    pub onto: SharedString,
+    pub head_name: Option<SharedString>,
+    /// The commit that the remaining commits are applied on top of.
+    pub tip: SharedString,
+    pub remaining: VecDeque<RebaseTodoEntry>,
+    pub total: usize,
+    pub stopped_at: Option<SharedString>,
 }
 
 impl FakeGitRepositoryState {
@@ -53,7 +73,101 @@ impl FakeGitRepositoryState {
             branches: Default::default(),
             simulated_index_write_error_message: Default::default(),
             refs: HashMap::from_iter([("HEAD".into(), "abc".into())]),
+            worktrees: Default::default(),
+            commits: Default::default(),
+            rebase: Default::default(),
+        }
+    }
+
+    fn resolve_revision(&self, revision: &str) -> SharedString {
+        let revision = if revision.is_empty() {
+            "HEAD"
+        } else {
+            revision
+        };
+        let sha = self.refs.get(revision).map_or(revision, String::as_str);
+        SharedString::from(sha.to_string())
+    }
+
+    fn commit(&self, sha: &SharedString) -> Result<LogEntry> {
+        self.commits
+            .iter()
+            .find(|commit| &commit.sha == sha)
+            .cloned()
+            .with_context(|| format!("no commit {sha}"))
+    }
+
+    fn set_head(&mut self, sha: &SharedString) {
+        if let Some(branch) = &self.current_branch_name
+            && self.refs.contains_key(branch)
+        {
+            self.refs.insert(branch.clone(), sha.to_string());
         }
+        self.refs.insert("HEAD".into(), sha.to_string());
+    }
+
+    /// Applies the remaining commits of the rebase, until one of them is to be edited. Rewritten
+    /// commits get the sha of the commit they were rewritten from, followed by `'`.
+    fn apply_rebase(&mut self, mut rebase: FakeRebase) -> Result<()> {
+        while let Some(entry) = rebase.remaining.pop_front() {
+            let original = self.commit(&entry.sha)?;
+            let mut commit = original.clone();
+            match entry.action {
+                RebaseAction::Drop => continue,
+                RebaseAction::Squash | RebaseAction::Fixup => {
+                    let tip = self.commit(&rebase.tip)?;
+                    commit.sha = tip.sha;
+                    commit.parents = tip.parents;
+                    commit.subject = tip.subject;
+                }
+                RebaseAction::Pick | RebaseAction::Reword | RebaseAction::Edit => {
+                    commit.parents = vec![rebase.tip.clone()];
+                    if let Some(message) = &entry.message {
+                        commit.subject = message.lines().next().unwrap_or_default().into();
+                    }
+                }
+            }
+            if commit != original {
+                commit.sha = self.rewritten_sha(&commit.sha);
+                commit.refs.clear();
+                self.commits.insert(0, commit.clone());
+            }
+            rebase.tip = commit.sha;
+            if entry.action == RebaseAction::Edit {
+                rebase.stopped_at = Some(rebase.tip.clone());
+                self.set_head(&rebase.tip);
+                self.rebase = Some(rebase);
+                return Ok(());
+            }
+        }
+        self.set_head(&rebase.tip);
+        Ok(())
+    }
+
+    fn rewritten_sha(&self, sha: &SharedString) -> SharedString {
+        let mut rewritten_sha = format!("{sha}'");
+        while self
+            .commits
+            .iter()
+            .any(|commit| commit.sha.as_ref() == rewritten_sha)
+        {
+            rewritten_sha.push('\'');
+        }
+        rewritten_sha.into()
+    }
+
+    /// Returns the given commit and all of its ancestors.
+    fn ancestors(&self, sha: SharedString) -> HashSet<SharedString> {
+        let mut ancestors = HashSet::default();
+        let mut stack = vec![sha];
+        while let Some(sha) = stack.pop() {
+            if let Some(commit) = self.commits.iter().find(|commit| commit.sha == sha)
+                && ancestors.insert(sha)
+            {
+                stack.extend(commit.parents.iter().cloned());
+            }
+        }
+        ancestors
     }
 }
 
@@ -72,6 +186,25 @@ impl FakeGitRepository {
         }
         .boxed()
     }
+
+    /// There are no commits to apply in a fake repository, so merging them only simulates
+    /// stopping on conflicts: when the repository has unmerged paths, the merged revision is
+    /// recorded in the given head, as git does.
+    fn simulate_merge(
+        &self,
+        head: Option<&'static str>,
+        revision: String,
+    ) -> BoxFuture<'static, Result<()>> {
+        self.with_state_async(true, move |state| {
+            if let Some(head) = head
+                && !state.unmerged_paths.is_empty()
+            {
+                let sha = state.refs.get(&revision).cloned().unwrap_or(revision);
+                state.refs.insert(head.into(), sha);
+            }
+            Ok(())
+        })
+    }
 }
 
 impl GitRepository for FakeGitRepository {
@@ -115,6 +248,40 @@ impl GitRepository for FakeGitRepository {
         unimplemented!()
     }
 
+    fn log(&self, options: LogOptions) -> BoxFuture<'_, Result<Vec<LogEntry>>> {
+        self.with_state_async(false, move |state| {
+            anyhow::ensure!(
+                options.path.is_none(),
+                "the fake repository doesn't track the paths that commits change"
+            );
+            let revision_range = options.revision_range.as_deref().unwrap_or("HEAD");
+            let (excluded, included) = match revision_range.split_once("..") {
+                Some((base, tip)) => (
+                    state.ancestors(state.resolve_revision(base)),
+                    state.ancestors(state.resolve_revision(tip)),
+                ),
+                None => (
+                    HashSet::default(),
+                    state.ancestors(state.resolve_revision(revision_range)),
+                ),
+            };
+            Ok(state
+                .commits
+                .iter()
+                .filter(|commit| included.contains(&commit.sha) && !excluded.contains(&commit.sha))
+                .filter(|commit| {
+                    options.author.as_ref().is_none_or(|author| {
+                        commit.author_name.contains(author.as_str())
+                            || commit.author_email.contains(author.as_str())
+                    })
+                })
+                .skip(options.skip)
+                .take(options.limit.unwrap_or(usize::MAX))
+                .cloned()
+                .collect())
+        })
+    }
+
     fn set_index_text(
         &self,
         path: RepoPath,
@@ -364,6 +531,83 @@ impl GitRepository for FakeGitRepository {
         })
     }
 
+    fn worktrees(&self) -> BoxFuture<'_, Result<Vec<GitWorktree>>> {
+        let main_path = self.dot_git_path.parent().unwrap().to_path_buf();
+        self.with_state_async(false, move |state| {
+            let main_worktree = GitWorktree {
+                path: main_path,
+                sha: state.refs.get("HEAD").map(|sha| sha.clone().into()),
+                ref_name: state
+                    .current_branch_name
+                    .as_ref()
+                    .map(|name| format!("refs/heads/{name}").into()),
+                is_main: true,
+                is_locked: false,
+                is_prunable: false,
+            };
+            Ok(std::iter::once(main_worktree)
+                .chain(state.worktrees.iter().cloned())
+                .collect())
+        })
+    }
+
+    fn create_worktree(
+        &self,
+        path: PathBuf,
+        branch: String,
+        new_branch: bool,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        self.with_state_async(true, move |state| {
+            if new_branch {
+                anyhow::ensure!(
+                    state.branches.insert(branch.clone()),
+                    "branch {branch:?} already exists"
+                );
+            } else {
+                anyhow::ensure!(
+                    state.branches.contains(&branch),
+                    "no branch named {branch:?}"
+                );
+            }
+            let ref_name = format!("refs/heads/{branch}");
+            anyhow::ensure!(
+                state.current_branch_name.as_ref() != Some(&branch)
+                    && !state
+                        .worktrees
+                        .iter()
+                        .any(|worktree| worktree.ref_name.as_deref() == Some(ref_name.as_str())),
+                "branch {branch:?} is already checked out"
+            );
+            state.worktrees.push(GitWorktree {
+                path,
+                sha: state.refs.get("HEAD").map(|sha| sha.clone().into()),
+                ref_name: Some(ref_name.into()),
+                is_main: false,
+                is_locked: false,
+                is_prunable: false,
+            });
+            Ok(())
+        })
+    }
+
+    fn remove_worktree(
+        &self,
+        path: PathBuf,
+        _force: bool,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        self.with_state_async(true, move |state| {
+            let ix = state
+                .worktrees
+                .iter()
+                .position(|worktree| worktree.path == path)
+                .with_context(|| format!("{path:?} is not a linked worktree"))?;
+            state.worktrees.remove(ix);
+            Ok(())
+        })
+    }
+
     fn stage_paths(
         &self,
         paths: Vec<RepoPath>,
@@ -440,6 +684,108 @@ impl GitRepository for FakeGitRepository {
         unimplemented!()
     }
 
+    fn rebase(
+        &self,
+        base: String,
+        todo: Vec<RebaseTodoEntry>,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        self.with_state_async(true, move |state| {
+            anyhow::ensure!(state.rebase.is_none(), "a rebase is already in progress");
+            let original_head = state.resolve_revision("HEAD");
+            let onto = state.resolve_revision(&base);
+            let head_name = state.current_branch_name.clone().map(Into::into);
+            state.apply_rebase(FakeRebase {
+                original_head,
+                onto: onto.clone(),
+                head_name,
+                tip: onto,
+                total: todo.len(),
+                remaining: todo.into(),
+                stopped_at: None,
+            })
+        })
+    }
+
+    fn control_rebase(
+        &self,
+        control: RebaseControl,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        self.with_state_async(true, move |state| {
+            let mut rebase = state.rebase.take().context("no rebase in progress")?;
+            match control {
+                RebaseControl::Continue => {
+                    rebase.stopped_at = None;
+                    state.apply_rebase(rebase)
+                }
+                RebaseControl::Skip => {
+                    if let Some(stopped_at) = rebase.stopped_at.take() {
+                        let commit = state.commit(&stopped_at)?;
+                        rebase.tip = commit
+                            .parents
+                            .first()
+                            .cloned()
+                            .unwrap_or_else(|| rebase.onto.clone());
+                    }
+                    state.apply_rebase(rebase)
+                }
+                RebaseControl::Abort => {
+                    state.set_head(&rebase.original_head);
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    fn rebase_progress(&self) -> BoxFuture<'_, Option<RebaseProgress>> {
+        let progress = self.with_state_async(false, |state| {
+            Ok(state.rebase.as_ref().map(|rebase| RebaseProgress {
+                current: rebase.total - rebase.remaining.len(),
+                total: rebase.total,
+                onto: Some(rebase.onto.clone()),
+                head_name: rebase.head_name.clone(),
+                stopped_at: rebase.stopped_at.clone(),
+            }))
+        });
+        async move { progress.await.ok().flatten() }.boxed()
+    }
+
+    fn cherry_pick(
+        &self,
+        commit: String,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        self.simulate_merge(Some("CHERRY_PICK_HEAD"), commit)
+    }
+
+    fn revert(
+        &self,
+        commit: String,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        self.simulate_merge(Some("REVERT_HEAD"), commit)
+    }
+
+    fn merge(
+        &self,
+        branch: String,
+        options: MergeOptions,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let branch_exists = self.with_state_async(false, {
+            let branch = branch.clone();
+            move |state| Ok(state.branches.contains(&branch))
+        });
+        async move {
+            anyhow::ensure!(branch_exists.await?, "no branch named {branch:?}");
+            // A squash merge does not record the branch it merges.
+            let head = (!options.squash).then_some("MERGE_HEAD");
+            self.simulate_merge(head, branch).await
+        }
+        .boxed()
+    }
+
     fn commit(
         &self,
         _message: gpui::SharedString,
@@ -565,6 +911,7 @@ impl GitRepository for FakeGitRepository {
 #[cfg(test)]
 mod tests {
     use crate::{FakeFs, Fs};
+    use git::repository::{LogEntry, LogOptions};
     use gpui::BackgroundExecutor;
     use serde_json::json;
     use std::path::Path;
@@ -620,4 +967,81 @@ mod tests {
             ]
         );
     }
+
+    #[gpui::test]
+    async fn test_log(executor: BackgroundExecutor) {
+        let fs = FakeFs::new(executor);
+        fs.insert_tree(path!("/foo"), json!({ ".git": {}, "a": "lorem" }))
+            .await;
+        let commit = |sha: &str, parents: &[&str], author: &str| LogEntry {
+            sha: sha.to_string().into(),
+            parents: parents
+                .iter()
+                .map(|parent| parent.to_string().into())
+                .collect(),
+            subject: format!("commit {sha}").into(),
+            commit_timestamp: 0,
+            author_name: author.to_string().into(),
+            author_email: format!("{author}@example.com").into(),
+            refs: Vec::new(),
+        };
+        fs.set_commits_for_repo(
+            Path::new("/foo/.git"),
+            vec![
+                commit("d", &["b", "c"], "alice"),
+                commit("c", &["a"], "bob"),
+                commit("b", &["a"], "alice"),
+                commit("a", &[], "bob"),
+            ],
+        );
+        fs.with_git_state(Path::new("/foo/.git"), true, |state| {
+            state.refs.insert("side".into(), "c".into());
+        })
+        .unwrap();
+        let repository = fs.open_repo(Path::new("/foo/.git")).unwrap();
+
+        let log = async |options: LogOptions| {
+            repository
+                .log(options)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.sha.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(log(LogOptions::default()).await, ["d", "c", "b", "a"]);
+        assert_eq!(
+            log(LogOptions {
+                revision_range: Some("side".into()),
+                ..Default::default()
+            })
+            .await,
+            ["c", "a"]
+        );
+        assert_eq!(
+            log(LogOptions {
+                revision_range: Some("side..".into()),
+                ..Default::default()
+            })
+            .await,
+            ["d", "b"]
+        );
+        assert_eq!(
+            log(LogOptions {
+                author: Some("alice".into()),
+                skip: 1,
+                ..Default::default()
+            })
+            .await,
+            ["b"]
+        );
+        assert_eq!(
+            log(LogOptions {
+                limit: Some(2),
+                ..Default::default()
+            })
+            .await,
+            ["d", "c"]
+        );
+    }
 }
@@ -26,6 +26,7 @@ use std::mem::MaybeUninit;
 use async_tar::Archive;
 use futures::{AsyncRead, Stream, StreamExt, future::BoxFuture};
 use git::repository::{GitRepository, RealGitRepository};
+use jj::{JujutsuRepository, RealJujutsuRepository};
 use rope::Rope;
 use serde::{Deserialize, Serialize};
 use smol::io::AsyncWriteExt;
@@ -135,6 +136,8 @@ pub trait Fs: Send + Sync {
     );
 
     fn open_repo(&self, abs_dot_git: &Path) -> Option<Arc<dyn GitRepository>>;
+    /// Opens the Jujutsu workspace whose `.jj` directory is directly under the given path.
+    fn open_jj_repo(&self, abs_workspace_root: &Path) -> Option<Arc<dyn JujutsuRepository>>;
     fn git_init(&self, abs_work_directory: &Path, fallback_branch_name: String) -> Result<()>;
     async fn git_clone(&self, repo_url: &str, abs_work_directory: &Path) -> Result<()>;
     fn is_fake(&self) -> bool;
@@ -835,6 +838,14 @@ impl Fs for RealFs {
         )?))
     }
 
+    fn open_jj_repo(&self, abs_workspace_root: &Path) -> Option<Arc<dyn JujutsuRepository>> {
+        use util::ResultExt as _;
+
+        Some(Arc::new(
+            RealJujutsuRepository::new(abs_workspace_root).log_err()?,
+        ))
+    }
+
     fn git_init(&self, abs_work_directory_path: &Path, fallback_branch_name: String) -> Result<()> {
         let config = new_std_command("git")
             .current_dir(abs_work_directory_path)
@@ -949,6 +960,7 @@ struct FakeFsState {
     read_dir_call_count: usize,
     path_write_counts: std::collections::HashMap<PathBuf, usize>,
     moves: std::collections::HashMap<u64, PathBuf>,
+    jj_repositories: std::collections::HashMap<PathBuf, Arc<jj::FakeJujutsuRepository>>,
 }
 
 #[cfg(any(test, feature = "test-support"))]
@@ -1233,6 +1245,7 @@ impl FakeFs {
                 metadata_call_count: 0,
                 path_write_counts: Default::default(),
                 moves: Default::default(),
+                jj_repositories: Default::default(),
             })),
         });
 
@@ -1687,6 +1700,35 @@ impl FakeFs {
         .unwrap();
     }
 
+    /// Sets the commits of the given git repository, each listed before its parents, and points
+    /// HEAD at the first one.
+    pub fn set_commits_for_repo(&self, dot_git: &Path, commits: Vec<git::repository::LogEntry>) {
+        self.with_git_state(dot_git, true, |state| {
+            if let Some(head) = commits.first() {
+                state.refs.insert("HEAD".into(), head.sha.to_string());
+            }
+            state.commits = commits;
+        })
+        .unwrap();
+    }
+
+    /// Creates a Jujutsu workspace at the given path, returning the repository that backs it so
+    /// that tests can change its working copy and history.
+    pub async fn insert_jj_repository(
+        &self,
+        abs_workspace_root: &Path,
+    ) -> Arc<jj::FakeJujutsuRepository> {
+        self.create_dir(&abs_workspace_root.join(".jj"))
+            .await
+            .unwrap();
+        self.state
+            .lock()
+            .jj_repositories
+            .entry(abs_workspace_root.to_path_buf())
+            .or_default()
+            .clone()
+    }
+
     /// Put the given git repository into a state with the given status,
     /// by mutating the head, index, and unmerged state.
     pub fn set_status_for_repo(&self, dot_git: &Path, statuses: &[(&Path, FileStatus)]) {
@@ -2464,6 +2506,16 @@ impl Fs for FakeFs {
         .log_err()
     }
 
+    fn open_jj_repo(&self, abs_workspace_root: &Path) -> Option<Arc<dyn JujutsuRepository>> {
+        let repository = self
+            .state
+            .lock()
+            .jj_repositories
+            .get(abs_workspace_root)?
+            .clone();
+        Some(repository)
+    }
+
     fn git_init(
         &self,
         abs_work_directory_path: &Path,
@@ -68,6 +68,12 @@ actions!(
         TrashUntrackedFiles,
         /// Undoes the last commit, keeping changes in the working directory.
         Uncommit,
+        /// Continues the rebase in progress, after resolving its conflicts.
+        ContinueRebase,
+        /// Skips the commit that the rebase in progress stopped at.
+        SkipRebase,
+        /// Aborts the rebase in progress, restoring the branch to its original state.
+        AbortRebase,
         /// Pushes commits to the remote repository.
         Push,
         /// Pushes commits to a specific remote branch.
@@ -22,6 +22,7 @@ use std::{
     cmp::Ordering,
     future,
     io::{BufRead, BufReader, BufWriter, Read},
+    ops::Range,
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -182,6 +183,160 @@ impl CommitDetails {
     }
 }
 
+/// Which commits to list from the history of a repository, newest first.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct LogOptions {
+    /// A revision or a revision range like `main..HEAD`, `HEAD` if not set.
+    pub revision_range: Option<String>,
+    /// Only list the commits that changed this path.
+    pub path: Option<RepoPath>,
+    /// Only list the commits that changed these rows of `path`, zero-based and end-exclusive.
+    pub line_range: Option<Range<u32>>,
+    /// Only list the commits whose author matches this pattern.
+    pub author: Option<String>,
+    /// The number of commits to skip, for paginating the history.
+    pub skip: usize,
+    /// The maximum number of commits to list.
+    pub limit: Option<usize>,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct LogEntry {
+    pub sha: SharedString,
+    /// The parents of the commit. When filtering by path, these are the closest ancestors that
+    /// are also listed.
+    pub parents: Vec<SharedString>,
+    pub subject: SharedString,
+    /// This is a unix timestamp
+    pub commit_timestamp: i64,
+    pub author_name: SharedString,
+    pub author_email: SharedString,
+    /// The branches and tags pointing to the commit.
+    pub refs: Vec<SharedString>,
+}
+
+impl LogEntry {
+    pub fn short_sha(&self) -> SharedString {
+        self.sha[..SHORT_SHA_LENGTH.min(self.sha.len())]
+            .to_string()
+            .into()
+    }
+
+    pub fn summary(&self) -> CommitSummary {
+        CommitSummary {
+            sha: self.sha.clone(),
+            subject: self.subject.clone(),
+            commit_timestamp: self.commit_timestamp,
+            author_name: self.author_name.clone(),
+            has_parent: !self.parents.is_empty(),
+        }
+    }
+}
+
+/// What to do with a commit during an interactive rebase.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub enum RebaseAction {
+    /// Keep the commit.
+    #[default]
+    Pick,
+    /// Keep the commit, with a new message.
+    Reword,
+    /// Keep the commit, stopping after it so that it can be amended.
+    Edit,
+    /// Meld the commit into the previous one, combining their messages.
+    Squash,
+    /// Meld the commit into the previous one, keeping the previous commit's message.
+    Fixup,
+    /// Remove the commit.
+    Drop,
+}
+
+impl RebaseAction {
+    pub const ALL: [RebaseAction; 6] = [
+        RebaseAction::Pick,
+        RebaseAction::Reword,
+        RebaseAction::Edit,
+        RebaseAction::Squash,
+        RebaseAction::Fixup,
+        RebaseAction::Drop,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RebaseAction::Pick => "pick",
+            RebaseAction::Reword => "reword",
+            RebaseAction::Edit => "edit",
+            RebaseAction::Squash => "squash",
+            RebaseAction::Fixup => "fixup",
+            RebaseAction::Drop => "drop",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct RebaseTodoEntry {
+    pub action: RebaseAction,
+    pub sha: SharedString,
+    /// The new message of a reworded commit.
+    pub message: Option<String>,
+}
+
+/// Resumes or ends a rebase that stopped.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum RebaseControl {
+    Continue,
+    Skip,
+    Abort,
+}
+
+/// The state of a rebase in progress.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct RebaseProgress {
+    /// The number of the commit being applied, starting from 1.
+    pub current: usize,
+    /// The number of commits to apply.
+    pub total: usize,
+    /// The commit that the rebased commits are applied on top of.
+    pub onto: Option<SharedString>,
+    /// The branch being rebased, if any.
+    pub head_name: Option<SharedString>,
+    /// The commit that the rebase stopped at, to resolve conflicts or amend it.
+    pub stopped_at: Option<SharedString>,
+}
+
+/// How to merge a branch into the current one.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct MergeOptions {
+    /// Create a merge commit even when the current branch could be fast-forwarded.
+    pub no_ff: bool,
+    /// Apply the changes of the branch to the working tree and the index without committing them
+    /// or recording a merge.
+    pub squash: bool,
+}
+
+/// A working tree of a repository, as listed by `git worktree list`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct GitWorktree {
+    pub path: PathBuf,
+    /// The commit checked out in the worktree, unless the repository is bare.
+    pub sha: Option<SharedString>,
+    /// The branch checked out in the worktree, unless its HEAD is detached.
+    pub ref_name: Option<SharedString>,
+    /// Whether this is the main worktree of the repository, rather than one linked to it with
+    /// `git worktree add`.
+    pub is_main: bool,
+    pub is_locked: bool,
+    /// Whether the worktree's directory is gone, so that `git worktree prune` would remove it.
+    pub is_prunable: bool,
+}
+
+impl GitWorktree {
+    pub fn branch_name(&self) -> Option<&str> {
+        let ref_name = self.ref_name.as_ref()?;
+        Some(ref_name.strip_prefix("refs/heads/").unwrap_or(ref_name))
+    }
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct Remote {
     pub name: SharedString,
@@ -364,6 +519,10 @@ pub trait GitRepository: Send + Sync {
     fn show(&self, commit: String) -> BoxFuture<'_, Result<CommitDetails>>;
 
     fn load_commit(&self, commit: String, cx: AsyncApp) -> BoxFuture<'_, Result<CommitDiff>>;
+
+    /// Lists commits from the history of the repository, newest first.
+    fn log(&self, options: LogOptions) -> BoxFuture<'_, Result<Vec<LogEntry>>>;
+
     fn blame(&self, path: RepoPath, content: Rope) -> BoxFuture<'_, Result<crate::blame::Blame>>;
 
     /// Returns the absolute path to the repository. For worktrees, this will be the path to the
@@ -421,6 +580,75 @@ pub trait GitRepository: Send + Sync {
         env: Arc<HashMap<String, String>>,
     ) -> BoxFuture<'_, Result<()>>;
 
+    /// Rebases the commits after `base` like `git rebase --interactive`, following the todo list
+    /// instead of prompting for it. The todo list is ordered from the oldest commit to the newest.
+    ///
+    /// Stopping on conflicts or `edit` commits is not an error: the rebase stays in progress
+    /// until it is resumed or ended with [`GitRepository::control_rebase`].
+    fn rebase(
+        &self,
+        base: String,
+        todo: Vec<RebaseTodoEntry>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    fn control_rebase(
+        &self,
+        control: RebaseControl,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    fn rebase_progress(&self) -> BoxFuture<'_, Option<RebaseProgress>>;
+
+    /// Applies the changes of a commit on top of the current branch, like `git cherry-pick`.
+    ///
+    /// Stopping on conflicts is not an error: like those of a merge, the conflicts are left in the
+    /// working tree to be resolved and committed.
+    fn cherry_pick(
+        &self,
+        commit: String,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Commits the reverse of the changes of a commit, like `git revert`. Conflicts are left to be
+    /// resolved, as with [`GitRepository::cherry_pick`].
+    fn revert(
+        &self,
+        commit: String,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Merges a branch into the current one. Conflicts are left to be resolved, as with
+    /// [`GitRepository::cherry_pick`].
+    fn merge(
+        &self,
+        branch: String,
+        options: MergeOptions,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Lists the working trees of the repository, starting with the main one.
+    fn worktrees(&self) -> BoxFuture<'_, Result<Vec<GitWorktree>>>;
+
+    /// Creates a working tree at `path` with `branch` checked out. When `new_branch` is set, the
+    /// branch is created first, pointing at the current HEAD.
+    fn create_worktree(
+        &self,
+        path: PathBuf,
+        branch: String,
+        new_branch: bool,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Removes a linked working tree. Unless `force` is set, this fails when the worktree has
+    /// uncommitted changes.
+    fn remove_worktree(
+        &self,
+        path: PathBuf,
+        force: bool,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
     fn push(
         &self,
         branch_name: String,
@@ -736,6 +964,25 @@ impl GitRepository for RealGitRepository {
         .boxed()
     }
 
+    fn log(&self, options: LogOptions) -> BoxFuture<'_, Result<Vec<LogEntry>>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let output = new_std_command(&git_binary_path)
+                    .current_dir(working_directory?)
+                    .args(log_args(&options)?)
+                    .output()?;
+                if output.status.success() {
+                    parse_log_output(&String::from_utf8_lossy(&output.stdout))
+                } else {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    anyhow::bail!("git log failed: {stderr}");
+                }
+            })
+            .boxed()
+    }
+
     fn reset(
         &self,
         commit: String,
@@ -965,9 +1212,14 @@ impl GitRepository for RealGitRepository {
     }
 
     fn merge_message(&self) -> BoxFuture<'_, Option<String>> {
-        let path = self.path().join("MERGE_MSG");
+        let git_dir = self.path();
         self.executor
-            .spawn(async move { std::fs::read_to_string(&path).ok() })
+            .spawn(async move {
+                // A squash merge prepares the message of the commit without recording a merge.
+                std::fs::read_to_string(git_dir.join("MERGE_MSG"))
+                    .or_else(|_| std::fs::read_to_string(git_dir.join("SQUASH_MSG")))
+                    .ok()
+            })
             .boxed()
     }
 
@@ -1355,6 +1607,215 @@ impl GitRepository for RealGitRepository {
             .boxed()
     }
 
+    fn rebase(
+        &self,
+        base: String,
+        todo: Vec<RebaseTodoEntry>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        let git_dir = self.path();
+        self.executor
+            .spawn(async move {
+                anyhow::ensure!(!base.starts_with('-'), "invalid rebase base {base:?}");
+                let state_dir = git_dir.join(REBASE_STATE_DIR);
+                write_rebase_state(&state_dir, &todo)?;
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory?)
+                    .envs(env.iter())
+                    .envs(rebase_editor_env(&state_dir))
+                    .args(["rebase", "--interactive", base.as_str()])
+                    .output()
+                    .await?;
+                finish_rebase_command(&git_dir, None, &output, "rebase")
+            })
+            .boxed()
+    }
+
+    fn control_rebase(
+        &self,
+        control: RebaseControl,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        let git_dir = self.path();
+        self.executor
+            .spawn(async move {
+                let (flag, description) = match control {
+                    RebaseControl::Continue => ("--continue", "continue rebase"),
+                    RebaseControl::Skip => ("--skip", "skip commit"),
+                    RebaseControl::Abort => ("--abort", "abort rebase"),
+                };
+                let progress = read_rebase_progress(&git_dir);
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory?)
+                    .envs(env.iter())
+                    .envs(rebase_editor_env(&git_dir.join(REBASE_STATE_DIR)))
+                    .args(["rebase", flag])
+                    .output()
+                    .await?;
+                finish_rebase_command(&git_dir, progress, &output, description)
+            })
+            .boxed()
+    }
+
+    fn rebase_progress(&self) -> BoxFuture<'_, Option<RebaseProgress>> {
+        let git_dir = self.path();
+        self.executor
+            .spawn(async move { read_rebase_progress(&git_dir) })
+            .boxed()
+    }
+
+    fn cherry_pick(
+        &self,
+        commit: String,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                anyhow::ensure!(!commit.starts_with('-'), "invalid commit {commit:?}");
+                run_merging_command(
+                    &git_binary_path,
+                    &working_directory?,
+                    &env,
+                    &["cherry-pick", commit.as_str()],
+                    "cherry-pick",
+                )
+                .await
+            })
+            .boxed()
+    }
+
+    fn revert(
+        &self,
+        commit: String,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                anyhow::ensure!(!commit.starts_with('-'), "invalid commit {commit:?}");
+                run_merging_command(
+                    &git_binary_path,
+                    &working_directory?,
+                    &env,
+                    &["revert", "--no-edit", commit.as_str()],
+                    "revert",
+                )
+                .await
+            })
+            .boxed()
+    }
+
+    fn merge(
+        &self,
+        branch: String,
+        options: MergeOptions,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                anyhow::ensure!(!branch.starts_with('-'), "invalid branch {branch:?}");
+                anyhow::ensure!(
+                    !(options.no_ff && options.squash),
+                    "A squash merge cannot create a merge commit"
+                );
+                let mut args = vec!["merge", "--no-edit"];
+                if options.no_ff {
+                    args.push("--no-ff");
+                }
+                if options.squash {
+                    args.push("--squash");
+                }
+                args.push(branch.as_str());
+                run_merging_command(&git_binary_path, &working_directory?, &env, &args, "merge")
+                    .await
+            })
+            .boxed()
+    }
+
+    fn worktrees(&self) -> BoxFuture<'_, Result<Vec<GitWorktree>>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        let executor = self.executor.clone();
+        self.executor
+            .spawn(async move {
+                let git = GitBinary::new(git_binary_path, working_directory?, executor);
+                let output = git.run(&["worktree", "list", "--porcelain"]).await?;
+                Ok(parse_worktree_list(&output))
+            })
+            .boxed()
+    }
+
+    fn create_worktree(
+        &self,
+        path: PathBuf,
+        branch: String,
+        new_branch: bool,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                anyhow::ensure!(!branch.starts_with('-'), "invalid branch {branch:?}");
+                let mut command = new_smol_command(&git_binary_path);
+                command
+                    .current_dir(&working_directory?)
+                    .envs(env.iter())
+                    .args(["worktree", "add"]);
+                if new_branch {
+                    command.arg("-b").arg(&branch).arg("--").arg(&path);
+                } else {
+                    command.arg("--").arg(&path).arg(&branch);
+                }
+                let output = command.output().await?;
+                anyhow::ensure!(
+                    output.status.success(),
+                    "Failed to create worktree:\n{}",
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                Ok(())
+            })
+            .boxed()
+    }
+
+    fn remove_worktree(
+        &self,
+        path: PathBuf,
+        force: bool,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let mut command = new_smol_command(&git_binary_path);
+                command
+                    .current_dir(&working_directory?)
+                    .envs(env.iter())
+                    .args(["worktree", "remove"]);
+                if force {
+                    command.arg("--force");
+                }
+                let output = command.arg("--").arg(&path).output().await?;
+                anyhow::ensure!(
+                    output.status.success(),
+                    "Failed to remove worktree:\n{}",
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                Ok(())
+            })
+            .boxed()
+    }
+
     fn commit(
         &self,
         message: SharedString,
@@ -2115,6 +2576,271 @@ impl MapSeekTarget<RepoPath> for RepoPathDescendants<'_> {
     }
 }
 
+/// Every commit is printed after a record separator, so that the patches `git log -L` prints
+/// after the commits can be skipped.
+const LOG_FORMAT: &str = "%x1e%H%x00%P%x00%at%x00%an%x00%ae%x00%D%x00%s";
+
+fn log_args(options: &LogOptions) -> Result<Vec<OsString>> {
+    let mut args: Vec<OsString> = vec![
+        "--no-optional-locks".into(),
+        "log".into(),
+        "--topo-order".into(),
+        format!("--format={LOG_FORMAT}").into(),
+        format!("--skip={}", options.skip).into(),
+    ];
+    if let Some(limit) = options.limit {
+        args.push(format!("--max-count={limit}").into());
+    }
+    if let Some(author) = &options.author {
+        args.push(format!("--author={author}").into());
+    }
+    if options.path.is_some() {
+        args.push("--parents".into());
+    }
+    if let Some((path, line_range)) = options.path.as_ref().zip(options.line_range.as_ref()) {
+        let mut arg = OsString::from(format!(
+            "-L{},{}:",
+            line_range.start + 1,
+            line_range.end.max(line_range.start + 1)
+        ));
+        arg.push(path.to_unix_style());
+        args.push(arg);
+    }
+    let revision_range = options.revision_range.as_deref().unwrap_or("HEAD");
+    anyhow::ensure!(
+        !revision_range.starts_with('-'),
+        "invalid revision range {revision_range:?}"
+    );
+    args.push(revision_range.into());
+    args.push("--".into());
+    if let Some(path) = &options.path {
+        if options.line_range.is_none() {
+            args.push(path.to_unix_style().into_owned());
+        }
+    }
+    Ok(args)
+}
+
+fn parse_log_output(output: &str) -> Result<Vec<LogEntry>> {
+    let mut entries = Vec::new();
+    for record in output.split('\x1e').skip(1) {
+        let line = record.lines().next().unwrap_or_default();
+        let mut fields = line.splitn(7, '\0');
+        let sha = fields.next().context("no commit hash")?.to_string().into();
+        let parents = fields
+            .next()
+            .context("no parent hashes")?
+            .split_whitespace()
+            .map(|parent| parent.to_string().into())
+            .collect();
+        let commit_timestamp = fields.next().context("no author date")?.parse()?;
+        let author_name = fields.next().context("no author name")?.to_string().into();
+        let author_email = fields.next().context("no author email")?.to_string().into();
+        let refs = fields
+            .next()
+            .context("no ref names")?
+            .split(", ")
+            .filter(|name| !name.is_empty())
+            .map(|name| name.to_string().into())
+            .collect();
+        let subject = fields.next().context("no subject")?.to_string().into();
+        entries.push(LogEntry {
+            sha,
+            parents,
+            subject,
+            commit_timestamp,
+            author_name,
+            author_email,
+            refs,
+        });
+    }
+    Ok(entries)
+}
+
+/// The directory within the git directory that holds the todo list and the new messages of a
+/// rebase started with [`GitRepository::rebase`], which must outlive the git process when the
+/// rebase stops.
+const REBASE_STATE_DIR: &str = "zed-rebase";
+
+/// Used as the editor of commit messages during a rebase, to replace the message of reworded
+/// commits. Git passes it the path of the message within the git directory, and the commit being
+/// applied is the last entry of the todo list that git has processed.
+const REBASE_MESSAGE_EDITOR: &str = r#"git_dir=$(dirname "$1")
+sha=$(tail -n 1 "$git_dir/rebase-merge/done" | cut -d ' ' -f 2)
+message="$git_dir/zed-rebase/$sha"
+if [ -f "$message" ]; then cp "$message" "$1"; fi
+"#;
+
+fn write_rebase_state(state_dir: &Path, todo: &[RebaseTodoEntry]) -> Result<()> {
+    if state_dir.exists() {
+        std::fs::remove_dir_all(state_dir)?;
+    }
+    std::fs::create_dir_all(state_dir)?;
+
+    let mut todo_list = String::new();
+    for entry in todo {
+        anyhow::ensure!(
+            !entry.sha.is_empty() && entry.sha.chars().all(|c| c.is_ascii_hexdigit()),
+            "invalid commit sha {:?}",
+            entry.sha
+        );
+        todo_list.push_str(&format!("{} {}\n", entry.action.as_str(), entry.sha));
+        if let Some(message) = &entry.message {
+            std::fs::write(state_dir.join(entry.sha.to_string()), message)?;
+        }
+    }
+    if todo_list.is_empty() {
+        // An empty todo list makes git abort the rebase instead of dropping every commit.
+        todo_list.push_str("noop\n");
+    }
+    std::fs::write(state_dir.join("git-rebase-todo"), todo_list)?;
+    std::fs::write(state_dir.join("message-editor.sh"), REBASE_MESSAGE_EDITOR)?;
+    Ok(())
+}
+
+/// Makes git use the todo list and messages written by [`write_rebase_state`] instead of prompting
+/// for them, and never open an interactive editor.
+fn rebase_editor_env(state_dir: &Path) -> Vec<(&'static str, String)> {
+    let quote = |path: PathBuf| format!("'{}'", path.to_string_lossy().replace('\'', r"'\''"));
+    let message_editor = state_dir.join("message-editor.sh");
+    let message_editor = if message_editor.exists() {
+        format!("sh {}", quote(message_editor))
+    } else {
+        "true".to_string()
+    };
+    vec![
+        (
+            "GIT_SEQUENCE_EDITOR",
+            format!("cp {}", quote(state_dir.join("git-rebase-todo"))),
+        ),
+        ("GIT_EDITOR", message_editor),
+    ]
+}
+
+/// Checks the outcome of a rebase command that started at the given progress. Stopping at a new
+/// commit is not a failure, but stopping where it started is.
+fn finish_rebase_command(
+    git_dir: &Path,
+    initial_progress: Option<RebaseProgress>,
+    output: &std::process::Output,
+    description: &str,
+) -> Result<()> {
+    let progress = read_rebase_progress(git_dir);
+    if progress.is_none() {
+        std::fs::remove_dir_all(git_dir.join(REBASE_STATE_DIR)).ok();
+    }
+    anyhow::ensure!(
+        output.status.success() || (progress.is_some() && progress != initial_progress),
+        "Failed to {description}:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(())
+}
+
+/// Runs a command that merges changes into the working tree, like `git merge`. Stopping on
+/// conflicts is not a failure, as they are left in the working tree to be resolved.
+async fn run_merging_command(
+    git_binary_path: &Path,
+    working_directory: &Path,
+    env: &HashMap<String, String>,
+    args: &[&str],
+    description: &str,
+) -> Result<()> {
+    anyhow::ensure!(
+        !has_unmerged_paths(git_binary_path, working_directory).await?,
+        "Cannot {description} before the current conflicts are resolved"
+    );
+    let output = new_smol_command(git_binary_path)
+        .current_dir(working_directory)
+        .envs(env.iter())
+        .args(args)
+        .output()
+        .await?;
+    anyhow::ensure!(
+        output.status.success() || has_unmerged_paths(git_binary_path, working_directory).await?,
+        "Failed to {description}:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(())
+}
+
+async fn has_unmerged_paths(git_binary_path: &Path, working_directory: &Path) -> Result<bool> {
+    let output = new_smol_command(git_binary_path)
+        .current_dir(working_directory)
+        .args(["ls-files", "--unmerged"])
+        .output()
+        .await?;
+    anyhow::ensure!(
+        output.status.success(),
+        "Failed to list unmerged paths:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(!output.stdout.is_empty())
+}
+
+fn read_rebase_progress(git_dir: &Path) -> Option<RebaseProgress> {
+    let (state_dir, current, total) = if git_dir.join("rebase-merge").is_dir() {
+        (git_dir.join("rebase-merge"), "msgnum", "end")
+    } else if git_dir.join("rebase-apply").join("rebasing").exists() {
+        (git_dir.join("rebase-apply"), "next", "last")
+    } else {
+        return None;
+    };
+    let read = |name: &str| {
+        let contents = std::fs::read_to_string(state_dir.join(name)).ok()?;
+        let contents = contents.trim();
+        (!contents.is_empty()).then(|| contents.to_string())
+    };
+    let head_name = read("head-name").filter(|name| name != "detached HEAD");
+    Some(RebaseProgress {
+        current: read(current).and_then(|n| n.parse().ok()).unwrap_or(0),
+        total: read(total).and_then(|n| n.parse().ok()).unwrap_or(0),
+        onto: read("onto").map(SharedString::from),
+        head_name: head_name.map(|name| {
+            name.strip_prefix("refs/heads/")
+                .unwrap_or(&name)
+                .to_string()
+                .into()
+        }),
+        stopped_at: read("stopped-sha").map(SharedString::from),
+    })
+}
+
+/// Parses the output of `git worktree list --porcelain`, which describes each worktree with a
+/// block of lines like `worktree <path>` and `branch <ref>`.
+fn parse_worktree_list(output: &str) -> Vec<GitWorktree> {
+    let mut worktrees = Vec::new();
+    for block in output.split("\n\n") {
+        let mut worktree = None;
+        for line in block.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            if key == "worktree" {
+                worktree = Some(GitWorktree {
+                    path: PathBuf::from(value),
+                    sha: None,
+                    ref_name: None,
+                    is_main: worktrees.is_empty(),
+                    is_locked: false,
+                    is_prunable: false,
+                });
+                continue;
+            }
+            let Some(worktree) = worktree.as_mut() else {
+                continue;
+            };
+            match key {
+                "HEAD" => worktree.sha = Some(value.to_string().into()),
+                "branch" => worktree.ref_name = Some(value.to_string().into()),
+                "locked" => worktree.is_locked = true,
+                "prunable" => worktree.is_prunable = true,
+                _ => {}
+            }
+        }
+        worktrees.extend(worktree);
+    }
+    worktrees
+}
+
 fn parse_branch_input(input: &str) -> Result<Vec<Branch>> {
     let mut branches = Vec::new();
     for line in input.split('\n') {
@@ -2478,6 +3204,495 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_log_parsing() {
+        // suppress "help: octal escapes are not supported, `\0` is always null"
+        #[allow(clippy::octal_escapes)]
+        let input = "\x1ea1b2c3d4e5f6a1b2c3d4e5f6a1b2c3d4e5f6a1b2\0f00d f11e\01733187470\0John Doe\0john@example.com\0HEAD -> main, tag: v1.0\0Merge branch 'feature'\n\
+            \x1ef00d\0\01733187000\0Jane Doe\0jane@example.com\0\0Initial commit\n\n\
+            diff --git a/file b/file\n";
+        assert_eq!(
+            parse_log_output(input).unwrap(),
+            vec![
+                LogEntry {
+                    sha: "a1b2c3d4e5f6a1b2c3d4e5f6a1b2c3d4e5f6a1b2".into(),
+                    parents: vec!["f00d".into(), "f11e".into()],
+                    subject: "Merge branch 'feature'".into(),
+                    commit_timestamp: 1733187470,
+                    author_name: "John Doe".into(),
+                    author_email: "john@example.com".into(),
+                    refs: vec!["HEAD -> main".into(), "tag: v1.0".into()],
+                },
+                LogEntry {
+                    sha: "f00d".into(),
+                    parents: Vec::new(),
+                    subject: "Initial commit".into(),
+                    commit_timestamp: 1733187000,
+                    author_name: "Jane Doe".into(),
+                    author_email: "jane@example.com".into(),
+                    refs: Vec::new(),
+                },
+            ]
+        );
+        assert_eq!(parse_log_output("").unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn test_worktree_list_parsing() {
+        let input = "worktree /code/zed\n\
+            HEAD a1b2c3\n\
+            branch refs/heads/main\n\
+            \n\
+            worktree /code/zed-review\n\
+            HEAD d4e5f6\n\
+            detached\n\
+            locked reviewing\n\
+            \n\
+            worktree /tmp/gone\n\
+            HEAD a1b2c3\n\
+            branch refs/heads/feature/old\n\
+            prunable gitdir file points to non-existent location";
+        let worktrees = parse_worktree_list(input);
+        assert_eq!(
+            worktrees,
+            vec![
+                GitWorktree {
+                    path: PathBuf::from("/code/zed"),
+                    sha: Some("a1b2c3".into()),
+                    ref_name: Some("refs/heads/main".into()),
+                    is_main: true,
+                    is_locked: false,
+                    is_prunable: false,
+                },
+                GitWorktree {
+                    path: PathBuf::from("/code/zed-review"),
+                    sha: Some("d4e5f6".into()),
+                    ref_name: None,
+                    is_main: false,
+                    is_locked: true,
+                    is_prunable: false,
+                },
+                GitWorktree {
+                    path: PathBuf::from("/tmp/gone"),
+                    sha: Some("a1b2c3".into()),
+                    ref_name: Some("refs/heads/feature/old".into()),
+                    is_main: false,
+                    is_locked: false,
+                    is_prunable: true,
+                },
+            ]
+        );
+        assert_eq!(worktrees[2].branch_name(), Some("feature/old"));
+    }
+
+    #[gpui::test]
+    async fn test_log(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let repo =
+            RealGitRepository::new(&repo_dir.path().join(".git"), None, cx.executor()).unwrap();
+
+        for (message, path, content) in [
+            ("Add a", "a", "one\ntwo\nthree\n"),
+            ("Add b", "b", "b\n"),
+            ("Change the last line of a", "a", "one\ntwo\nTHREE\n"),
+            ("Change the first line of a", "a", "ONE\ntwo\nTHREE\n"),
+        ] {
+            smol::fs::write(repo_dir.path().join(path), content)
+                .await
+                .unwrap();
+            repo.stage_paths(vec![RepoPath::from_str(path)], Arc::new(HashMap::default()))
+                .await
+                .unwrap();
+            repo.commit(
+                message.into(),
+                None,
+                CommitOptions::default(),
+                Arc::new(checkpoint_author_envs()),
+            )
+            .await
+            .unwrap();
+        }
+
+        let subjects = |entries: Vec<LogEntry>| {
+            entries
+                .into_iter()
+                .map(|entry| entry.subject.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let entries = repo.log(LogOptions::default()).await.unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].author_name.as_ref(), "Zed");
+        assert_eq!(entries[0].parents, vec![entries[1].sha.clone()]);
+        assert!(entries[3].parents.is_empty());
+        assert!(entries[0].refs.iter().any(|name| name.starts_with("HEAD")));
+
+        let page = repo
+            .log(LogOptions {
+                skip: 1,
+                limit: Some(2),
+                ..LogOptions::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(subjects(page), ["Change the last line of a", "Add b"]);
+
+        let file_history = repo
+            .log(LogOptions {
+                path: Some(RepoPath::from_str("a")),
+                ..LogOptions::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(file_history[0].parents, vec![file_history[1].sha.clone()]);
+        assert_eq!(
+            subjects(file_history),
+            [
+                "Change the first line of a",
+                "Change the last line of a",
+                "Add a"
+            ]
+        );
+
+        let line_history = repo
+            .log(LogOptions {
+                path: Some(RepoPath::from_str("a")),
+                line_range: Some(2..3),
+                ..LogOptions::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            subjects(line_history),
+            ["Change the last line of a", "Add a"]
+        );
+
+        let by_author = repo
+            .log(LogOptions {
+                author: Some("somebody else".into()),
+                ..LogOptions::default()
+            })
+            .await
+            .unwrap();
+        assert!(by_author.is_empty());
+
+        let range = repo
+            .log(LogOptions {
+                revision_range: Some("HEAD~2..HEAD".into()),
+                ..LogOptions::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            subjects(range),
+            ["Change the first line of a", "Change the last line of a"]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_rebase(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let repo =
+            RealGitRepository::new(&repo_dir.path().join(".git"), None, cx.executor()).unwrap();
+        let env = Arc::new(checkpoint_author_envs());
+
+        let mut shas = Vec::new();
+        for (message, path, content) in [
+            ("Add a", "a", "one\n"),
+            ("Add b", "b", "b\n"),
+            ("Add c", "c", "c\n"),
+            ("Add d", "d", "d\n"),
+            ("Change a to two", "a", "two\n"),
+            ("Change a to three", "a", "three\n"),
+        ] {
+            smol::fs::write(repo_dir.path().join(path), content)
+                .await
+                .unwrap();
+            repo.stage_paths(vec![RepoPath::from_str(path)], Arc::new(HashMap::default()))
+                .await
+                .unwrap();
+            repo.commit(message.into(), None, CommitOptions::default(), env.clone())
+                .await
+                .unwrap();
+            shas.push(repo.head_sha().await.unwrap());
+        }
+        let todo_entry = |action, ix: usize, message: Option<&str>| RebaseTodoEntry {
+            action,
+            sha: shas[ix].clone().into(),
+            message: message.map(ToString::to_string),
+        };
+        let subjects = |entries: Vec<LogEntry>| {
+            entries
+                .into_iter()
+                .map(|entry| entry.subject.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // Stopping on conflicts leaves the rebase in progress.
+        repo.rebase(
+            shas[3].clone(),
+            vec![
+                todo_entry(RebaseAction::Pick, 5, None),
+                todo_entry(RebaseAction::Pick, 4, None),
+            ],
+            env.clone(),
+        )
+        .await
+        .unwrap();
+        let progress = repo.rebase_progress().await.unwrap();
+        assert_eq!(progress.current, 1);
+        assert_eq!(progress.total, 2);
+        assert_eq!(progress.stopped_at.as_deref(), Some(shas[5].as_str()));
+        assert!(progress.head_name.is_some());
+
+        // Continuing fails until the conflicts are resolved.
+        assert!(
+            repo.control_rebase(RebaseControl::Continue, env.clone())
+                .await
+                .is_err()
+        );
+        repo.control_rebase(RebaseControl::Abort, env.clone())
+            .await
+            .unwrap();
+        assert_eq!(repo.rebase_progress().await, None);
+        assert_eq!(repo.head_sha().await.as_ref(), Some(&shas[5]));
+
+        repo.rebase(
+            shas[0].clone(),
+            vec![
+                todo_entry(
+                    RebaseAction::Reword,
+                    2,
+                    Some("Add the c file\n\nWith a body.\n"),
+                ),
+                todo_entry(RebaseAction::Pick, 1, None),
+                todo_entry(RebaseAction::Fixup, 3, None),
+                todo_entry(RebaseAction::Drop, 4, None),
+                todo_entry(RebaseAction::Drop, 5, None),
+            ],
+            env.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(repo.rebase_progress().await, None);
+        assert_eq!(
+            subjects(repo.log(LogOptions::default()).await.unwrap()),
+            ["Add b", "Add the c file", "Add a"]
+        );
+        assert_eq!(
+            smol::fs::read_to_string(repo_dir.path().join("a"))
+                .await
+                .unwrap(),
+            "one\n"
+        );
+        assert!(repo_dir.path().join("d").exists());
+        assert!(!repo_dir.path().join(".git").join(REBASE_STATE_DIR).exists());
+    }
+
+    #[gpui::test]
+    async fn test_cherry_pick_revert_and_merge(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let repo =
+            RealGitRepository::new(&repo_dir.path().join(".git"), None, cx.executor()).unwrap();
+        let env = Arc::new(checkpoint_author_envs());
+        let commit = async |message: &str, path: &str, content: &str| {
+            smol::fs::write(repo_dir.path().join(path), content)
+                .await
+                .unwrap();
+            repo.stage_paths(vec![RepoPath::from_str(path)], Arc::new(HashMap::default()))
+                .await
+                .unwrap();
+            repo.commit(message.into(), None, CommitOptions::default(), env.clone())
+                .await
+                .unwrap();
+            repo.head_sha().await.unwrap()
+        };
+        let log = async || {
+            repo.log(LogOptions::default())
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|entry| (entry.subject.to_string(), entry.parents.len()))
+                .collect::<Vec<_>>()
+        };
+
+        commit("Add a", "a", "one\n").await;
+        repo.create_branch("side".into()).await.unwrap();
+        repo.create_branch("base".into()).await.unwrap();
+        repo.change_branch("side".into()).await.unwrap();
+        let change_a = commit("Change a to two", "a", "two\n").await;
+        let add_b = commit("Add b", "b", "b\n").await;
+        repo.change_branch("base".into()).await.unwrap();
+
+        repo.cherry_pick(add_b, env.clone()).await.unwrap();
+        assert!(repo_dir.path().join("b").exists());
+        repo.revert("HEAD".into(), env.clone()).await.unwrap();
+        assert!(!repo_dir.path().join("b").exists());
+        assert_eq!(
+            log().await,
+            [
+                ("Revert \"Add b\"".to_string(), 1),
+                ("Add b".to_string(), 1),
+                ("Add a".to_string(), 1),
+            ]
+        );
+
+        // Stopping on conflicts leaves them to be resolved and committed, as for merges.
+        commit("Change a to three", "a", "three\n").await;
+        repo.cherry_pick(change_a.clone(), env.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.revparse_batch(vec!["CHERRY_PICK_HEAD".into()])
+                .await
+                .unwrap(),
+            [Some(change_a.clone())]
+        );
+        assert!(repo.merge_message().await.is_some());
+        assert!(repo.cherry_pick(change_a, env.clone()).await.is_err());
+        commit("Change a to two", "a", "two\n").await;
+
+        assert!(
+            repo.merge(
+                "side".into(),
+                MergeOptions {
+                    no_ff: true,
+                    squash: true,
+                },
+                env.clone(),
+            )
+            .await
+            .is_err()
+        );
+        repo.merge(
+            "side".into(),
+            MergeOptions {
+                squash: true,
+                ..MergeOptions::default()
+            },
+            env.clone(),
+        )
+        .await
+        .unwrap();
+        assert!(repo_dir.path().join("b").exists());
+        assert!(
+            repo.merge_message()
+                .await
+                .unwrap()
+                .starts_with("Squashed commit")
+        );
+        repo.commit(
+            "Squash side".into(),
+            None,
+            CommitOptions::default(),
+            env.clone(),
+        )
+        .await
+        .unwrap();
+
+        repo.merge(
+            "side".into(),
+            MergeOptions {
+                no_ff: true,
+                ..MergeOptions::default()
+            },
+            env.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            log().await[..2],
+            [
+                ("Merge branch 'side' into base".to_string(), 2),
+                ("Squash side".to_string(), 1),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_worktrees(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        let worktrees_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let repo =
+            RealGitRepository::new(&repo_dir.path().join(".git"), None, cx.executor()).unwrap();
+        let env = Arc::new(checkpoint_author_envs());
+        smol::fs::write(repo_dir.path().join("a"), "a\n")
+            .await
+            .unwrap();
+        repo.stage_paths(vec![RepoPath::from_str("a")], Arc::new(HashMap::default()))
+            .await
+            .unwrap();
+        repo.commit("Add a".into(), None, CommitOptions::default(), env.clone())
+            .await
+            .unwrap();
+        repo.create_branch("existing".into()).await.unwrap();
+
+        let new_path = worktrees_dir.path().join("new");
+        let existing_path = worktrees_dir.path().join("existing");
+        repo.create_worktree(new_path.clone(), "feature".into(), true, env.clone())
+            .await
+            .unwrap();
+        repo.create_worktree(existing_path.clone(), "existing".into(), false, env.clone())
+            .await
+            .unwrap();
+        assert!(new_path.join("a").exists());
+        // A branch can only be checked out in one worktree at a time.
+        assert!(
+            repo.create_worktree(
+                worktrees_dir.path().join("again"),
+                "existing".into(),
+                false,
+                env.clone()
+            )
+            .await
+            .is_err()
+        );
+
+        // Linked worktrees are listed after the main one, ordered by path.
+        let worktrees = repo.worktrees().await.unwrap();
+        assert!(worktrees[0].is_main);
+        assert_eq!(
+            worktrees[1..]
+                .iter()
+                .map(|worktree| (worktree.is_main, worktree.branch_name()))
+                .collect::<Vec<_>>(),
+            [(false, Some("existing")), (false, Some("feature"))]
+        );
+        assert_eq!(
+            worktrees[2].path.canonicalize().unwrap(),
+            new_path.canonicalize().unwrap()
+        );
+        assert_eq!(worktrees[2].sha, repo.head_sha().await.map(Into::into));
+
+        // Worktrees with changes are only removed when forced.
+        smol::fs::write(new_path.join("a"), "b\n").await.unwrap();
+        assert!(
+            repo.remove_worktree(new_path.clone(), false, env.clone())
+                .await
+                .is_err()
+        );
+        repo.remove_worktree(new_path.clone(), true, env.clone())
+            .await
+            .unwrap();
+        repo.remove_worktree(existing_path, false, env.clone())
+            .await
+            .unwrap();
+        assert!(!new_path.exists());
+        assert_eq!(repo.worktrees().await.unwrap().len(), 1);
+    }
+
     impl RealGitRepository {
         /// Force a Git garbage collection on the repository.
         fn gc(&self) -> BoxFuture<'_, Result<()>> {
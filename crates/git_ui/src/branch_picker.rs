@@ -2,11 +2,11 @@ use anyhow::Context as _;
 use fuzzy::StringMatchCandidate;
 
 use collections::HashSet;
-use git::repository::Branch;
+use git::repository::{Branch, GitWorktree, MergeOptions};
 use gpui::{
-    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, InteractiveElement,
-    IntoElement, Modifiers, ModifiersChangedEvent, ParentElement, Render, SharedString, Styled,
-    Subscription, Task, Window, rems,
+    Action, AnyElement, App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
+    InteractiveElement, IntoElement, Modifiers, ModifiersChangedEvent, ParentElement, Render,
+    SharedString, Styled, Subscription, Task, Window, actions, rems,
 };
 use picker::{Picker, PickerDelegate, PickerEditorPosition};
 use project::git_store::Repository;
@@ -15,15 +15,25 @@ use settings::Settings;
 use std::sync::Arc;
 use time::OffsetDateTime;
 use time_format::format_local_timestamp;
-use ui::{HighlightedLabel, ListItem, ListItemSpacing, Tooltip, prelude::*};
+use ui::{HighlightedLabel, KeyBinding, ListItem, ListItemSpacing, Tooltip, prelude::*};
 use util::ResultExt;
 use workspace::notifications::DetachAndPromptErr;
 use workspace::{ModalView, Workspace};
 
+actions!(
+    branch_picker,
+    [
+        /// Merges the selected branch into the current one as a single set of staged changes,
+        /// without committing them.
+        SquashMerge,
+    ]
+);
+
 pub fn register(workspace: &mut Workspace) {
     workspace.register_action(open);
     workspace.register_action(switch);
     workspace.register_action(checkout_branch);
+    workspace.register_action(merge_branch);
 }
 
 pub fn checkout_branch(
@@ -53,7 +63,34 @@ pub fn open(
     let repository = workspace.project().read(cx).active_repository(cx);
     let style = BranchListStyle::Modal;
     workspace.toggle_modal(window, cx, |window, cx| {
-        BranchList::new(repository, style, rems(34.), window, cx)
+        BranchList::new(
+            repository,
+            style,
+            BranchListMode::Switch,
+            rems(34.),
+            window,
+            cx,
+        )
+    })
+}
+
+pub fn merge_branch(
+    workspace: &mut Workspace,
+    _: &zed_actions::git::MergeBranch,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let repository = workspace.project().read(cx).active_repository(cx);
+    let style = BranchListStyle::Modal;
+    workspace.toggle_modal(window, cx, |window, cx| {
+        BranchList::new(
+            repository,
+            style,
+            BranchListMode::Merge,
+            rems(34.),
+            window,
+            cx,
+        )
     })
 }
 
@@ -63,7 +100,14 @@ pub fn popover(
     cx: &mut App,
 ) -> Entity<BranchList> {
     cx.new(|cx| {
-        let list = BranchList::new(repository, BranchListStyle::Popover, rems(20.), window, cx);
+        let list = BranchList::new(
+            repository,
+            BranchListStyle::Popover,
+            BranchListMode::Switch,
+            rems(20.),
+            window,
+            cx,
+        );
         list.focus_handle(cx).focus(window);
         list
     })
@@ -75,6 +119,15 @@ enum BranchListStyle {
     Popover,
 }
 
+/// What confirming a branch does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum BranchListMode {
+    /// Switches to the branch, creating it if needed.
+    Switch,
+    /// Merges the branch into the current one.
+    Merge,
+}
+
 pub struct BranchList {
     width: Rems,
     pub picker: Entity<Picker<BranchListDelegate>>,
@@ -85,6 +138,7 @@ impl BranchList {
     fn new(
         repository: Option<Entity<Repository>>,
         style: BranchListStyle,
+        mode: BranchListMode,
         width: Rems,
         window: &mut Window,
         cx: &mut Context<Self>,
@@ -95,6 +149,12 @@ impl BranchList {
         let default_branch_request = repository
             .clone()
             .map(|repository| repository.update(cx, |repository, _| repository.default_branch()));
+        let worktrees_request = repository
+            .clone()
+            .map(|repository| repository.update(cx, |repository, _| repository.worktrees()));
+        let work_directory_abs_path = repository
+            .as_ref()
+            .map(|repository| repository.read(cx).work_directory_abs_path.clone());
 
         cx.spawn_in(window, async move |this, cx| {
             let mut all_branches = all_branches_request
@@ -107,6 +167,19 @@ impl BranchList {
                 .ok()
                 .flatten()
                 .flatten();
+            // Branches checked out in other worktrees of the repository, which cannot be checked
+            // out here as well.
+            let mut worktrees = match worktrees_request {
+                Some(request) => request.await.map(Result::ok).ok().flatten(),
+                None => None,
+            }
+            .unwrap_or_default();
+            worktrees.retain(|worktree| {
+                worktree.ref_name.is_some()
+                    && work_directory_abs_path
+                        .as_ref()
+                        .is_none_or(|path| worktree.path.as_path() != &**path)
+            });
 
             let all_branches = cx
                 .background_spawn(async move {
@@ -140,6 +213,7 @@ impl BranchList {
             this.update_in(cx, |this, window, cx| {
                 this.picker.update(cx, |picker, cx| {
                     picker.delegate.default_branch = default_branch;
+                    picker.delegate.other_worktrees = worktrees;
                     picker.delegate.all_branches = Some(all_branches);
                     picker.refresh(window, cx);
                 })
@@ -149,8 +223,11 @@ impl BranchList {
         })
         .detach_and_log_err(cx);
 
-        let delegate = BranchListDelegate::new(repository, style);
+        let delegate = BranchListDelegate::new(repository, style, mode, cx);
         let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        picker.update(cx, |picker, cx| {
+            picker.delegate.focus_handle = picker.focus_handle(cx);
+        });
 
         let _subscription = cx.subscribe(&picker, |_, _, _, cx| {
             cx.emit(DismissEvent);
@@ -172,6 +249,23 @@ impl BranchList {
         self.picker
             .update(cx, |picker, _| picker.delegate.modifiers = ev.modifiers)
     }
+
+    fn handle_squash_merge(
+        &mut self,
+        _: &SquashMerge,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.picker.update(cx, |picker, cx| {
+            if picker.delegate.mode == BranchListMode::Merge {
+                let options = MergeOptions {
+                    squash: true,
+                    ..MergeOptions::default()
+                };
+                picker.delegate.merge_branch(options, window, cx);
+            }
+        });
+    }
 }
 impl ModalView for BranchList {}
 impl EventEmitter<DismissEvent> for BranchList {}
@@ -188,6 +282,7 @@ impl Render for BranchList {
             .key_context("GitBranchSelector")
             .w(self.width)
             .on_modifiers_changed(cx.listener(Self::handle_modifiers_changed))
+            .on_action(cx.listener(Self::handle_squash_merge))
             .child(self.picker.clone())
             .on_mouse_down_out({
                 cx.listener(move |this, _, window, cx| {
@@ -210,27 +305,64 @@ pub struct BranchListDelegate {
     matches: Vec<BranchEntry>,
     all_branches: Option<Vec<Branch>>,
     default_branch: Option<SharedString>,
+    other_worktrees: Vec<GitWorktree>,
     repo: Option<Entity<Repository>>,
     style: BranchListStyle,
+    mode: BranchListMode,
     selected_index: usize,
     last_query: String,
     modifiers: Modifiers,
+    focus_handle: FocusHandle,
 }
 
 impl BranchListDelegate {
-    fn new(repo: Option<Entity<Repository>>, style: BranchListStyle) -> Self {
+    fn new(
+        repo: Option<Entity<Repository>>,
+        style: BranchListStyle,
+        mode: BranchListMode,
+        cx: &mut App,
+    ) -> Self {
         Self {
             matches: vec![],
             repo,
             style,
+            mode,
             all_branches: None,
             default_branch: None,
+            other_worktrees: Vec::new(),
             selected_index: 0,
             last_query: Default::default(),
             modifiers: Default::default(),
+            focus_handle: cx.focus_handle(),
         }
     }
 
+    /// Merges the selected branch into the current one. Conflicts are left to be resolved from
+    /// the git panel, like those of any merge.
+    fn merge_branch(
+        &self,
+        options: MergeOptions,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) {
+        let Some(entry) = self.matches.get(self.selected_index) else {
+            return;
+        };
+        let Some(repo) = self.repo.clone() else {
+            return;
+        };
+        let branch = entry.branch.name().to_string();
+        cx.spawn(async move |_, cx| {
+            repo.update(cx, |repo, _| repo.merge(branch, options))?
+                .await??;
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to merge branch", window, cx, |e, _, _| {
+            Some(e.to_string())
+        });
+        cx.emit(DismissEvent);
+    }
+
     fn create_branch(
         &self,
         from_branch: Option<SharedString>,
@@ -270,7 +402,10 @@ impl PickerDelegate for BranchListDelegate {
     type ListItem = ListItem;
 
     fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
-        "Select branch…".into()
+        match self.mode {
+            BranchListMode::Switch => "Select branch…".into(),
+            BranchListMode::Merge => "Select branch to merge…".into(),
+        }
     }
 
     fn editor_position(&self) -> PickerEditorPosition {
@@ -307,6 +442,16 @@ impl PickerDelegate for BranchListDelegate {
             return Task::ready(());
         };
 
+        let mode = self.mode;
+        let all_branches = match mode {
+            BranchListMode::Switch => all_branches,
+            // The current branch cannot be merged into itself.
+            BranchListMode::Merge => all_branches
+                .into_iter()
+                .filter(|branch| !branch.is_head)
+                .collect(),
+        };
+
         const RECENT_BRANCHES_COUNT: usize = 10;
         cx.spawn_in(window, async move |picker, cx| {
             let mut matches: Vec<BranchEntry> = if query.is_empty() {
@@ -346,7 +491,8 @@ impl PickerDelegate for BranchListDelegate {
             };
             picker
                 .update(cx, |picker, _| {
-                    if !query.is_empty()
+                    if mode == BranchListMode::Switch
+                        && !query.is_empty()
                         && !matches
                             .first()
                             .is_some_and(|entry| entry.branch.name() == query)
@@ -381,6 +527,14 @@ impl PickerDelegate for BranchListDelegate {
         let Some(entry) = self.matches.get(self.selected_index()) else {
             return;
         };
+        if self.mode == BranchListMode::Merge {
+            let options = MergeOptions {
+                no_ff: secondary,
+                ..MergeOptions::default()
+            };
+            self.merge_branch(options, window, cx);
+            return;
+        }
         if entry.is_new {
             let from_branch = if secondary {
                 self.default_branch.clone()
@@ -496,6 +650,27 @@ impl PickerDelegate for BranchListDelegate {
             None
         };
 
+        let worktree = self.other_worktrees.iter().find(|worktree| {
+            !entry.is_new && worktree.ref_name.as_ref() == Some(&entry.branch.ref_name)
+        });
+        let worktree_label = worktree.map(|worktree| {
+            let directory_name = worktree
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| worktree.path.to_string_lossy().into_owned());
+            let path = SharedString::from(worktree.path.to_string_lossy().into_owned());
+            div()
+                .id(SharedString::from(format!("branch-worktree-{ix}")))
+                .flex_none()
+                .child(
+                    Label::new(format!("in worktree {directory_name}"))
+                        .size(LabelSize::Small)
+                        .color(Color::Accent),
+                )
+                .tooltip(Tooltip::text(format!("Checked out in {path}")))
+        });
+
         let branch_name = if entry.is_new {
             h_flex()
                 .gap_1()
@@ -531,6 +706,7 @@ impl PickerDelegate for BranchListDelegate {
                                 .justify_between()
                                 .overflow_x_hidden()
                                 .child(branch_name)
+                                .children(worktree_label)
                                 .when_some(commit_time, |label, commit_time| {
                                     label.child(
                                         Label::new(commit_time)
@@ -581,4 +757,45 @@ impl PickerDelegate for BranchListDelegate {
     fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
         None
     }
+
+    fn render_footer(
+        &self,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<AnyElement> {
+        if self.mode != BranchListMode::Merge {
+            return None;
+        }
+        let focus_handle = self.focus_handle.clone();
+        let merge_button = |id: &'static str, label: &'static str, action: Box<dyn Action>| {
+            Button::new(id, label)
+                .key_binding(
+                    KeyBinding::for_action_in(&*action, &focus_handle, window, cx)
+                        .map(|kb| kb.size(rems_from_px(12.))),
+                )
+                .on_click(move |_, window, cx| window.dispatch_action(action.boxed_clone(), cx))
+        };
+
+        Some(
+            h_flex()
+                .w_full()
+                .p_1p5()
+                .gap_0p5()
+                .justify_end()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(merge_button(
+                    "squash-merge",
+                    "Squash",
+                    SquashMerge.boxed_clone(),
+                ))
+                .child(merge_button(
+                    "merge-no-ff",
+                    "No Fast-forward",
+                    menu::SecondaryConfirm.boxed_clone(),
+                ))
+                .child(merge_button("merge", "Merge", menu::Confirm.boxed_clone()))
+                .into_any(),
+        )
+    }
 }
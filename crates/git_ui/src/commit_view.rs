@@ -3,8 +3,8 @@ use buffer_diff::{BufferDiff, BufferDiffSnapshot};
 use editor::{Editor, EditorEvent, MultiBuffer, SelectionEffects, multibuffer_context_lines};
 use git::repository::{CommitDetails, CommitDiff, CommitSummary, RepoPath};
 use gpui::{
-    AnyElement, AnyView, App, AppContext as _, AsyncApp, Context, Entity, EventEmitter,
-    FocusHandle, Focusable, IntoElement, Render, WeakEntity, Window,
+    Action, AnyElement, AnyView, App, AppContext as _, AsyncApp, Context, Entity, EventEmitter,
+    FocusHandle, Focusable, IntoElement, Render, WeakEntity, Window, actions,
 };
 use language::{
     Anchor, Buffer, Capability, DiskState, File, LanguageRegistry, LineEnding, OffsetRangeExt as _,
@@ -19,18 +19,31 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
-use ui::{Color, Icon, IconName, Label, LabelCommon as _, SharedString};
+use ui::{Tooltip, prelude::*};
 use util::{ResultExt, truncate_and_trailoff};
 use workspace::{
     Item, ItemHandle as _, ItemNavHistory, ToolbarItemLocation, Workspace,
     item::{BreadcrumbText, ItemEvent, TabContentParams},
+    notifications::DetachAndPromptErr,
     searchable::SearchableItemHandle,
 };
 
+actions!(
+    commit_view,
+    [
+        /// Applies the changes of the viewed commit on top of the current branch.
+        CherryPick,
+        /// Commits the reverse of the changes of the viewed commit.
+        Revert,
+    ]
+);
+
 pub struct CommitView {
     commit: CommitDetails,
     editor: Entity<Editor>,
     multibuffer: Entity<MultiBuffer>,
+    /// The repository of the commit, unless it comes from another version control system.
+    repository: Option<Entity<Repository>>,
 }
 
 struct GitBlob {
@@ -71,47 +84,70 @@ impl CommitView {
 
                 workspace
                     .update_in(cx, |workspace, window, cx| {
-                        let project = workspace.project();
-                        let commit_view = cx.new(|cx| {
-                            CommitView::new(
-                                commit_details,
-                                commit_diff,
-                                repo,
-                                project.clone(),
-                                window,
-                                cx,
-                            )
-                        });
-
-                        let pane = workspace.active_pane();
-                        pane.update(cx, |pane, cx| {
-                            let ix = pane.items().position(|item| {
-                                let commit_view = item.downcast::<CommitView>();
-                                commit_view
-                                    .is_some_and(|view| view.read(cx).commit.sha == commit.sha)
-                            });
-                            if let Some(ix) = ix {
-                                pane.activate_item(ix, true, true, window, cx);
-                            } else {
-                                pane.add_item(Box::new(commit_view), true, true, None, window, cx);
-                            }
-                        })
+                        Self::open_in_workspace(
+                            commit_details,
+                            commit_diff,
+                            Some(repo),
+                            workspace,
+                            window,
+                            cx,
+                        );
                     })
                     .log_err()
             })
             .detach();
     }
 
+    /// Opens a view of an already loaded commit, for version control systems
+    /// other than git that can produce a [`CommitDiff`] themselves.
+    pub fn open_with_diff(
+        commit: CommitDetails,
+        commit_diff: CommitDiff,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        Self::open_in_workspace(commit, commit_diff, None, workspace, window, cx);
+    }
+
+    fn open_in_workspace(
+        commit: CommitDetails,
+        commit_diff: CommitDiff,
+        repository: Option<Entity<Repository>>,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let sha = commit.sha.clone();
+        let project = workspace.project().clone();
+        let pane = workspace.active_pane().clone();
+        pane.update(cx, |pane, cx| {
+            let ix = pane.items().position(|item| {
+                let commit_view = item.downcast::<CommitView>();
+                commit_view.is_some_and(|view| view.read(cx).commit.sha == sha)
+            });
+            if let Some(ix) = ix {
+                pane.activate_item(ix, true, true, window, cx);
+            } else {
+                let commit_view = cx.new(|cx| {
+                    CommitView::new(commit, commit_diff, repository, project, window, cx)
+                });
+                pane.add_item(Box::new(commit_view), true, true, None, window, cx);
+            }
+        })
+    }
+
     fn new(
         commit: CommitDetails,
         commit_diff: CommitDiff,
-        repository: Entity<Repository>,
+        repository: Option<Entity<Repository>>,
         project: Entity<Project>,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> Self {
         let language_registry = project.read(cx).languages().clone();
         let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadOnly));
+        let commit_repository = repository.clone();
         let editor = cx.new(|cx| {
             let mut editor =
                 Editor::for_multibuffer(multibuffer.clone(), Some(project.clone()), window, cx);
@@ -164,14 +200,16 @@ impl CommitView {
                 let is_deleted = file.new_text.is_none();
                 let new_text = file.new_text.unwrap_or_default();
                 let old_text = file.old_text;
-                let worktree_id = repository
-                    .update(cx, |repository, cx| {
+                let worktree_id = match &repository {
+                    Some(repository) => repository.update(cx, |repository, cx| {
                         repository
                             .repo_path_to_project_path(&file.path, cx)
                             .map(|path| path.worktree_id)
-                            .or(first_worktree_id)
-                    })?
-                    .context("project has no worktrees")?;
+                    })?,
+                    None => None,
+                }
+                .or(first_worktree_id)
+                .context("project has no worktrees")?;
                 let file = Arc::new(GitBlob {
                     path: file.path.clone(),
                     is_deleted,
@@ -210,8 +248,66 @@ impl CommitView {
             commit,
             editor,
             multibuffer,
+            repository: commit_repository,
         }
     }
+
+    fn cherry_pick(&mut self, _: &CherryPick, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(repository) = self.repository.clone() else {
+            return;
+        };
+        let cherry_pick = repository.update(cx, |repository, _| {
+            repository.cherry_pick(self.commit.sha.to_string())
+        });
+        cx.spawn_in(window, async move |_, _| {
+            cherry_pick
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|r| r)
+        })
+        .detach_and_prompt_err("Failed to cherry-pick commit", window, cx, |e, _, _| {
+            Some(e.to_string())
+        });
+    }
+
+    fn revert(&mut self, _: &Revert, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(repository) = self.repository.clone() else {
+            return;
+        };
+        let revert = repository.update(cx, |repository, _| {
+            repository.revert(self.commit.sha.to_string())
+        });
+        cx.spawn_in(window, async move |_, _| {
+            revert.await.map_err(anyhow::Error::from).and_then(|r| r)
+        })
+        .detach_and_prompt_err("Failed to revert commit", window, cx, |e, _, _| {
+            Some(e.to_string())
+        });
+    }
+
+    fn render_toolbar(&self, cx: &App) -> impl IntoElement {
+        h_flex()
+            .w_full()
+            .px_2()
+            .py_1()
+            .gap_1()
+            .justify_end()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                Button::new("cherry-pick", "Cherry-pick")
+                    .tooltip(Tooltip::for_action_title(
+                        "Apply This Commit to the Current Branch",
+                        &CherryPick,
+                    ))
+                    .on_click(|_, window, cx| window.dispatch_action(CherryPick.boxed_clone(), cx)),
+            )
+            .child(
+                Button::new("revert", "Revert")
+                    .tooltip(Tooltip::for_action_title("Revert This Commit", &Revert))
+                    .on_click(|_, window, cx| window.dispatch_action(Revert.boxed_clone(), cx)),
+            )
+    }
 }
 
 impl language::File for GitBlob {
@@ -516,7 +612,15 @@ impl Item for CommitView {
 }
 
 impl Render for CommitView {
-    fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
-        self.editor.clone()
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("CommitView")
+            .size_full()
+            .when(self.repository.is_some(), |this| {
+                this.on_action(cx.listener(Self::cherry_pick))
+                    .on_action(cx.listener(Self::revert))
+                    .child(self.render_toolbar(cx))
+            })
+            .child(self.editor.clone())
     }
 }
@@ -0,0 +1,709 @@
+use std::ops::Range;
+use std::time::Duration;
+
+use anyhow::Result;
+use buffer_diff::BufferDiff;
+use editor::{Editor, EditorEvent};
+use git::repository::{LogEntry, LogOptions, RepoPath};
+use gpui::{
+    Action, AnyElement, App, Bounds, Entity, EventEmitter, FocusHandle, Focusable, Hsla,
+    KeyContext, PathBuilder, ScrollStrategy, Subscription, Task, UniformListScrollHandle,
+    WeakEntity, actions, canvas, fill, point, size, uniform_list,
+};
+use language::Point;
+use project::git_store::Repository;
+use time::{OffsetDateTime, UtcOffset};
+use ui::utils::{GraphLayout, GraphSegment};
+use ui::{Tooltip, WithScrollbar, prelude::*};
+use workspace::{Item, Workspace, item::ItemEvent};
+
+use crate::commit_view::CommitView;
+use crate::rebase_editor;
+
+actions!(
+    git_log,
+    [
+        /// Interactively rebases the commits of the current branch that follow the selected one.
+        RebaseInteractively,
+    ]
+);
+
+const FILTER_DEBOUNCE: Duration = Duration::from_millis(300);
+const PAGE_SIZE: usize = 200;
+const ROW_HEIGHT: Pixels = px(26.);
+const LANE_WIDTH: Pixels = px(14.);
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(view_history);
+    workspace.register_action(file_history);
+    workspace.register_action(selection_history);
+}
+
+fn view_history(
+    workspace: &mut Workspace,
+    _: &zed_actions::git::ViewHistory,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+        return;
+    };
+    open(workspace, repository, None, None, window, cx);
+}
+
+fn file_history(
+    workspace: &mut Workspace,
+    _: &zed_actions::git::FileHistory,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some((repository, path, _)) = active_file_selection(workspace, cx) else {
+        return;
+    };
+    open(workspace, repository, Some(path), None, window, cx);
+}
+
+fn selection_history(
+    workspace: &mut Workspace,
+    _: &zed_actions::git::SelectionHistory,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some((repository, path, rows)) = active_file_selection(workspace, cx) else {
+        return;
+    };
+    open(workspace, repository, Some(path), Some(rows), window, cx);
+}
+
+/// Returns the repository and path of the file under the newest selection of the active editor,
+/// along with the rows the selection spans in that file as of HEAD.
+fn active_file_selection(
+    workspace: &Workspace,
+    cx: &mut Context<Workspace>,
+) -> Option<(Entity<Repository>, RepoPath, Range<u32>)> {
+    let editor = workspace.active_item_as::<Editor>(cx)?;
+    let selection = editor.update(cx, |editor, cx| editor.selections.newest::<Point>(cx));
+    let multibuffer = editor.read(cx).buffer().read(cx);
+    let (buffer, start, _) = multibuffer.point_to_buffer_point(selection.start, cx)?;
+    let (end_buffer, mut end, _) = multibuffer.point_to_buffer_point(selection.end, cx)?;
+    if end_buffer != buffer {
+        end = start;
+    }
+    // A selection ending at the start of a line doesn't include that line.
+    let end_row = if end.column == 0 && end.row > start.row {
+        end.row
+    } else {
+        end.row + 1
+    };
+
+    let buffer_id = buffer.read(cx).remote_id();
+    let git_store = workspace.project().read(cx).git_store().read(cx);
+    let (repository, path) = git_store.repository_and_path_for_buffer_id(buffer_id, cx)?;
+    let mut rows = start.row..end_row;
+    // `git log -L` interprets the rows in HEAD, so account for the uncommitted changes above and
+    // within the selection.
+    if let Some(diff) = git_store.get_uncommitted_diff(buffer_id, cx) {
+        rows = rows_in_head(rows, diff.read(cx), &buffer.read(cx).snapshot(), cx);
+    }
+    Some((repository, path, rows))
+}
+
+/// Maps a range of rows of the working copy of a file to the rows of the file in HEAD, given the
+/// diff between them. Rows within a hunk are widened to the rows that the hunk replaced.
+fn rows_in_head(
+    rows: Range<u32>,
+    diff: &BufferDiff,
+    buffer: &language::BufferSnapshot,
+    cx: &App,
+) -> Range<u32> {
+    let base_text = diff.base_text();
+    // Hunks end at the start of the row after their last one, unless they end the file.
+    let end_row = |point: Point| {
+        if point.column > 0 {
+            point.row + 1
+        } else {
+            point.row
+        }
+    };
+    let map_row = |row: u32, is_end: bool| {
+        let mut delta = 0i64;
+        for hunk in diff.hunks(buffer, cx) {
+            let buffer_rows = hunk.range.start.row..end_row(hunk.range.end);
+            if row < buffer_rows.start {
+                break;
+            }
+            let base_start = base_text
+                .offset_to_point(hunk.diff_base_byte_range.start)
+                .row;
+            let base_end = end_row(base_text.offset_to_point(hunk.diff_base_byte_range.end));
+            if row < buffer_rows.end {
+                return if is_end { base_end } else { base_start };
+            }
+            delta = base_end as i64 - buffer_rows.end as i64;
+        }
+        (row as i64 + delta + is_end as i64).max(0) as u32
+    };
+
+    let start = map_row(rows.start, false);
+    let last_row = rows.end.saturating_sub(1).max(rows.start);
+    let end = map_row(last_row, true);
+    // Rows that were all added since HEAD have no history of their own, so follow the row
+    // they were added at instead.
+    start..end.max(start + 1)
+}
+
+fn open(
+    workspace: &mut Workspace,
+    repository: Entity<Repository>,
+    path: Option<RepoPath>,
+    line_range: Option<Range<u32>>,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let existing = workspace.items_of_type::<GitLogView>(cx).find(|view| {
+        let view = view.read(cx);
+        view.repository == repository && view.path == path && view.line_range == line_range
+    });
+    if let Some(existing) = existing {
+        workspace.activate_item(&existing, true, true, window, cx);
+        return;
+    }
+
+    let workspace_handle = workspace.weak_handle();
+    let log_view =
+        cx.new(|cx| GitLogView::new(repository, path, line_range, workspace_handle, window, cx));
+    workspace.add_item_to_active_pane(Box::new(log_view), None, true, window, cx);
+}
+
+/// Shows the commit history of a repository, a file or a range of lines of a file, next to a graph
+/// of the commits' ancestry.
+pub struct GitLogView {
+    repository: Entity<Repository>,
+    path: Option<RepoPath>,
+    line_range: Option<Range<u32>>,
+    workspace: WeakEntity<Workspace>,
+    focus_handle: FocusHandle,
+    revision_range_editor: Entity<Editor>,
+    author_editor: Entity<Editor>,
+    scroll_handle: UniformListScrollHandle,
+    entries: Vec<LogEntry>,
+    graph: GraphLayout,
+    selected_ix: Option<usize>,
+    has_more: bool,
+    loading: bool,
+    error: Option<SharedString>,
+    timezone: UtcOffset,
+    _load_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl GitLogView {
+    pub fn new(
+        repository: Entity<Repository>,
+        path: Option<RepoPath>,
+        line_range: Option<Range<u32>>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let revision_range_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("HEAD", window, cx);
+            editor
+        });
+        let author_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Any author", window, cx);
+            editor
+        });
+
+        let reload_on_edit =
+            |this: &mut Self, _: Entity<Editor>, event: &EditorEvent, cx: &mut Context<Self>| {
+                if let EditorEvent::BufferEdited = event {
+                    this.reload(true, cx);
+                }
+            };
+        let subscriptions = vec![
+            cx.subscribe(&revision_range_editor, reload_on_edit),
+            cx.subscribe(&author_editor, reload_on_edit),
+        ];
+
+        let timezone =
+            UtcOffset::from_whole_seconds(chrono::Local::now().offset().local_minus_utc())
+                .unwrap_or(UtcOffset::UTC);
+
+        let mut this = Self {
+            repository,
+            path,
+            line_range,
+            workspace,
+            focus_handle: cx.focus_handle(),
+            revision_range_editor,
+            author_editor,
+            scroll_handle: UniformListScrollHandle::new(),
+            entries: Vec::new(),
+            graph: GraphLayout::default(),
+            selected_ix: None,
+            has_more: false,
+            loading: false,
+            error: None,
+            timezone,
+            _load_task: Task::ready(()),
+            _subscriptions: subscriptions,
+        };
+        this.reload(false, cx);
+        this
+    }
+
+    fn log_options(&self, skip: usize, cx: &App) -> LogOptions {
+        let non_empty = |editor: &Entity<Editor>| {
+            let text = editor.read(cx).text(cx).trim().to_string();
+            (!text.is_empty()).then_some(text)
+        };
+        LogOptions {
+            revision_range: non_empty(&self.revision_range_editor),
+            path: self.path.clone(),
+            line_range: self.line_range.clone(),
+            author: non_empty(&self.author_editor),
+            skip,
+            limit: Some(PAGE_SIZE),
+        }
+    }
+
+    /// Loads the first page of the history again, after the filters changed.
+    fn reload(&mut self, debounce: bool, cx: &mut Context<Self>) {
+        let options = self.log_options(0, cx);
+        self.loading = true;
+        self._load_task = cx.spawn(async move |this, cx| {
+            if debounce {
+                cx.background_executor().timer(FILTER_DEBOUNCE).await;
+            }
+            let Ok(log) = this.update(cx, |this, cx| {
+                this.repository
+                    .update(cx, |repository, _| repository.log(options))
+            }) else {
+                return;
+            };
+            let result = log.await.map_err(anyhow::Error::from).and_then(|log| log);
+
+            this.update(cx, |this, cx| {
+                let selected_sha = this
+                    .selected_ix
+                    .and_then(|ix| this.entries.get(ix))
+                    .map(|entry| entry.sha.clone());
+                this.entries.clear();
+                this.graph = GraphLayout::default();
+                this.append_page(result, cx);
+                this.selected_ix = selected_sha
+                    .and_then(|sha| this.entries.iter().position(|entry| entry.sha == sha))
+                    .or_else(|| (!this.entries.is_empty()).then_some(0));
+            })
+            .ok();
+        });
+    }
+
+    /// Loads the next page of the history, unless it is already loading or fully loaded.
+    fn load_more(&mut self, cx: &mut Context<Self>) {
+        if self.loading || !self.has_more {
+            return;
+        }
+        let options = self.log_options(self.entries.len(), cx);
+        let log = self
+            .repository
+            .update(cx, |repository, _| repository.log(options));
+        self.loading = true;
+        self._load_task = cx.spawn(async move |this, cx| {
+            let result = log.await.map_err(anyhow::Error::from).and_then(|log| log);
+            this.update(cx, |this, cx| this.append_page(result, cx))
+                .ok();
+        });
+    }
+
+    fn append_page(&mut self, result: Result<Vec<LogEntry>>, cx: &mut Context<Self>) {
+        self.loading = false;
+        match result {
+            Ok(entries) => {
+                self.has_more = entries.len() == PAGE_SIZE;
+                for entry in &entries {
+                    self.graph.push(&entry.sha, &entry.parents);
+                }
+                self.entries.extend(entries);
+                self.error = None;
+            }
+            Err(error) => {
+                self.has_more = false;
+                self.error = Some(format!("{error:#}").into());
+            }
+        }
+        cx.notify();
+    }
+
+    fn open_commit(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(entry) = self.entries.get(ix) else {
+            return;
+        };
+        CommitView::open(
+            entry.summary(),
+            self.repository.downgrade(),
+            self.workspace.clone(),
+            window,
+            cx,
+        );
+    }
+
+    fn select_ix(&mut self, ix: Option<usize>, cx: &mut Context<Self>) {
+        self.selected_ix = ix;
+        if let Some(ix) = ix {
+            self.scroll_handle
+                .scroll_to_item(ix, ScrollStrategy::Center);
+        }
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, _window: &mut Window, cx: &mut Context<Self>) {
+        let ix = match self.selected_ix {
+            _ if self.entries.is_empty() => None,
+            None => Some(0),
+            Some(ix) => Some((ix + 1).min(self.entries.len() - 1)),
+        };
+        self.select_ix(ix, cx);
+    }
+
+    fn select_previous(
+        &mut self,
+        _: &menu::SelectPrevious,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let ix = match self.selected_ix {
+            _ if self.entries.is_empty() => None,
+            None => Some(0),
+            Some(ix) => Some(ix.saturating_sub(1)),
+        };
+        self.select_ix(ix, cx);
+    }
+
+    fn select_first(
+        &mut self,
+        _: &menu::SelectFirst,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let ix = (!self.entries.is_empty()).then_some(0);
+        self.select_ix(ix, cx);
+    }
+
+    fn select_last(&mut self, _: &menu::SelectLast, _window: &mut Window, cx: &mut Context<Self>) {
+        let ix = self.entries.len().checked_sub(1);
+        self.select_ix(ix, cx);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.selected_ix {
+            self.open_commit(ix, window, cx);
+        }
+    }
+
+    fn rebase_interactively(
+        &mut self,
+        _: &RebaseInteractively,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(base) = self
+            .selected_ix
+            .and_then(|ix| self.entries.get(ix))
+            .cloned()
+        else {
+            return;
+        };
+        let repository = self.repository.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                rebase_editor::open(repository, base, workspace, window, cx);
+            })
+            .ok();
+    }
+
+    fn dispatch_context(&self, window: &Window, cx: &App) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("GitLog");
+        if !self
+            .revision_range_editor
+            .focus_handle(cx)
+            .is_focused(window)
+            && !self.author_editor.focus_handle(cx).is_focused(window)
+        {
+            dispatch_context.add("menu");
+        }
+        dispatch_context
+    }
+
+    fn format_timestamp(&self, timestamp: i64) -> String {
+        let timestamp =
+            OffsetDateTime::from_unix_timestamp(timestamp).unwrap_or(OffsetDateTime::now_utc());
+        time_format::format_localized_timestamp(
+            timestamp,
+            OffsetDateTime::now_utc(),
+            self.timezone,
+            time_format::TimestampFormat::Relative,
+        )
+    }
+
+    fn render_graph(&self, ix: usize, cx: &App) -> impl IntoElement {
+        let row = self.graph.rows()[ix].clone();
+        let accents = cx.theme().accents().clone();
+        let lane_color = move |lane: usize| -> Hsla { accents.color_for_index(lane as u32) };
+
+        canvas(
+            |_, _, _| {},
+            move |bounds, _, window, _| {
+                let lane_x = |lane: usize| bounds.left() + LANE_WIDTH * (lane as f32 + 0.5);
+                let center_y = bounds.center().y;
+
+                // Lines that bend into another lane are colored after the lane other than the
+                // commit's, so that branches and merges keep the color of the side branch.
+                let incoming = row
+                    .incoming
+                    .iter()
+                    .map(|segment| (segment, bounds.top(), center_y, segment.from_lane));
+                let outgoing = row
+                    .outgoing
+                    .iter()
+                    .map(|segment| (segment, center_y, bounds.bottom(), segment.to_lane));
+                for (GraphSegment { from_lane, to_lane }, start_y, end_y, color_lane) in
+                    incoming.chain(outgoing)
+                {
+                    let start = point(lane_x(*from_lane), start_y);
+                    let end = point(lane_x(*to_lane), end_y);
+                    let mut builder = PathBuilder::stroke(px(1.5));
+                    builder.move_to(start);
+                    if from_lane == to_lane {
+                        builder.line_to(end);
+                    } else {
+                        builder.curve_to(end, point(start.x, end.y));
+                    }
+                    if let Ok(path) = builder.build() {
+                        window.paint_path(path, lane_color(color_lane));
+                    }
+                }
+
+                let dot =
+                    Bounds::centered_at(point(lane_x(row.lane), center_y), size(px(8.), px(8.)));
+                window.paint_quad(fill(dot, lane_color(row.lane)).corner_radii(px(4.)));
+            },
+        )
+        .w(LANE_WIDTH * self.graph.lane_count() as f32)
+        .h_full()
+        .flex_none()
+    }
+
+    fn render_entry(&self, ix: usize, cx: &mut Context<Self>) -> AnyElement {
+        let entry = &self.entries[ix];
+        let is_selected = Some(ix) == self.selected_ix;
+        let selected_background = cx.theme().colors().element_selected;
+        let hover_background = cx.theme().colors().element_hover;
+        let ref_background = cx.theme().colors().element_background;
+
+        h_flex()
+            .id(ix)
+            .h(ROW_HEIGHT)
+            .w_full()
+            .px_2()
+            .gap_2()
+            .cursor_pointer()
+            .when(is_selected, |this| this.bg(selected_background))
+            .hover(|this| this.bg(hover_background))
+            .on_click(cx.listener(move |this, _, window, cx| {
+                this.select_ix(Some(ix), cx);
+                this.open_commit(ix, window, cx);
+            }))
+            .child(self.render_graph(ix, cx))
+            .children(entry.refs.iter().map(|git_ref| {
+                div().px_1().rounded_sm().bg(ref_background).child(
+                    Label::new(git_ref.clone())
+                        .size(LabelSize::Small)
+                        .color(Color::Info),
+                )
+            }))
+            .child(
+                div()
+                    .flex_1()
+                    .min_w_0()
+                    .child(Label::new(entry.subject.clone()).single_line().truncate()),
+            )
+            .child(
+                Label::new(entry.author_name.clone())
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                Label::new(self.format_timestamp(entry.commit_timestamp))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                Label::new(entry.short_sha())
+                    .buffer_font(cx)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .into_any_element()
+    }
+
+    fn render_list(&mut self, cx: &mut Context<Self>) -> impl IntoElement {
+        uniform_list(
+            "git-log",
+            self.entries.len(),
+            cx.processor(|this, range: Range<usize>, _window, cx| {
+                if range.end + PAGE_SIZE / 4 >= this.entries.len() {
+                    this.load_more(cx);
+                }
+                range.map(|ix| this.render_entry(ix, cx)).collect()
+            }),
+        )
+        .track_scroll(self.scroll_handle.clone())
+        .size_full()
+    }
+
+    fn render_filter(&self, label: &'static str, editor: &Entity<Editor>) -> impl IntoElement {
+        h_flex()
+            .flex_1()
+            .gap_2()
+            .child(Label::new(label).color(Color::Muted))
+            .child(div().flex_1().child(editor.clone()))
+    }
+}
+
+impl EventEmitter<()> for GitLogView {}
+
+impl Focusable for GitLogView {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for GitLogView {
+    type Event = ();
+
+    fn to_item_events(_: &Self::Event, _: impl FnMut(ItemEvent)) {}
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        let Some(path) = &self.path else {
+            return "History".into();
+        };
+        let file_name = path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string());
+        match &self.line_range {
+            Some(rows) if rows.len() > 1 => {
+                format!("History of {file_name}:{}-{}", rows.start + 1, rows.end).into()
+            }
+            Some(rows) => format!("History of {file_name}:{}", rows.start + 1).into(),
+            None => format!("History of {file_name}").into(),
+        }
+    }
+
+    fn tab_tooltip_text(&self, _cx: &App) -> Option<SharedString> {
+        self.path.as_ref().map(|path| path.to_string().into())
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::HistoryRerun).color(Color::Muted))
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+impl Render for GitLogView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context(self.dispatch_context(window, cx))
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::rebase_interactively))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .gap_4()
+                    .px_2()
+                    .py_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(self.render_filter("Revisions", &self.revision_range_editor))
+                    .child(self.render_filter("Author", &self.author_editor))
+                    .child(
+                        Button::new("rebase-interactively", "Rebase")
+                            .disabled(self.selected_ix.is_none())
+                            .tooltip(Tooltip::for_action_title(
+                                "Rebase the Commits After the Selected One",
+                                &RebaseInteractively,
+                            ))
+                            .on_click(|_, window, cx| {
+                                window.dispatch_action(RebaseInteractively.boxed_clone(), cx)
+                            }),
+                    ),
+            )
+            .when_some(self.error.clone(), |this, error| {
+                this.child(
+                    h_flex()
+                        .px_2()
+                        .py_1()
+                        .child(Label::new(error).color(Color::Error)),
+                )
+            })
+            .when(
+                self.entries.is_empty() && !self.loading && self.error.is_none(),
+                |this| {
+                    this.child(
+                        h_flex()
+                            .px_2()
+                            .py_1()
+                            .child(Label::new("No commits").color(Color::Muted)),
+                    )
+                },
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .size_full()
+                    .child(self.render_list(cx))
+                    .vertical_scrollbar_for(self.scroll_handle.clone(), window, cx),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use language::Buffer;
+
+    #[gpui::test]
+    fn test_rows_in_head(cx: &mut TestAppContext) {
+        let base_text = "one\ntwo\nthree\nfour\nfive\nsix\n";
+        // "two" was replaced by two rows, "four" was removed and "seven" was added.
+        let text = "one\nTWO\nTWO AND A HALF\nthree\nfive\nsix\nseven\n";
+        let buffer = cx.new(|cx| Buffer::local(text, cx));
+        let diff = cx.new(|cx| BufferDiff::new_with_base_text(base_text, &buffer, cx));
+
+        cx.update(|cx| {
+            let buffer = buffer.read(cx).snapshot();
+            let diff = diff.read(cx);
+            let rows_in_head = |rows: Range<u32>| rows_in_head(rows, diff, &buffer, cx);
+            assert_eq!(rows_in_head(0..1), 0..1);
+            assert_eq!(rows_in_head(2..3), 1..2);
+            assert_eq!(rows_in_head(3..5), 2..5);
+            assert_eq!(rows_in_head(5..6), 5..6);
+            assert_eq!(rows_in_head(6..7), 6..7);
+        });
+    }
+}
@@ -18,16 +18,16 @@ use futures::StreamExt as _;
 use git::blame::ParsedCommitMessage;
 use git::repository::{
     Branch, CommitDetails, CommitOptions, CommitSummary, DiffType, FetchOptions, GitCommitter,
-    PushOptions, Remote, RemoteCommandOutput, ResetMode, Upstream, UpstreamTracking,
+    PushOptions, RebaseControl, Remote, RemoteCommandOutput, ResetMode, Upstream, UpstreamTracking,
     UpstreamTrackingStatus, get_git_committer,
 };
 use git::stash::GitStash;
 use git::status::StageStatus;
-use git::{Amend, Signoff, ToggleStaged, repository::RepoPath, status::FileStatus};
 use git::{
-    ExpandCommitEditor, RestoreTrackedFiles, StageAll, StashAll, StashApply, StashPop,
-    TrashUntrackedFiles, UnstageAll,
+    AbortRebase, ContinueRebase, ExpandCommitEditor, RestoreTrackedFiles, SHORT_SHA_LENGTH,
+    SkipRebase, StageAll, StashAll, StashApply, StashPop, TrashUntrackedFiles, UnstageAll,
 };
+use git::{Amend, Signoff, ToggleStaged, repository::RepoPath, status::FileStatus};
 use gpui::{
     Action, AsyncApp, AsyncWindowContext, ClickEvent, Corner, DismissEvent, Entity, EventEmitter,
     FocusHandle, Focusable, KeyContext, ListHorizontalSizingBehavior, ListSizingBehavior,
@@ -150,6 +150,7 @@ fn git_panel_context_menu(
             .action("View Stash", zed_actions::git::ViewStash.boxed_clone())
             .separator()
             .action("Open Diff", project_diff::Diff.boxed_clone())
+            .action("View History", zed_actions::git::ViewHistory.boxed_clone())
             .separator()
             .action_disabled_when(
                 !state.has_tracked_changes,
@@ -1316,6 +1317,51 @@ impl GitPanel {
         .detach();
     }
 
+    pub fn continue_rebase(
+        &mut self,
+        _: &ContinueRebase,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.control_rebase(RebaseControl::Continue, cx);
+    }
+
+    pub fn skip_rebase(&mut self, _: &SkipRebase, _window: &mut Window, cx: &mut Context<Self>) {
+        self.control_rebase(RebaseControl::Skip, cx);
+    }
+
+    pub fn abort_rebase(&mut self, _: &AbortRebase, _window: &mut Window, cx: &mut Context<Self>) {
+        self.control_rebase(RebaseControl::Abort, cx);
+    }
+
+    fn control_rebase(&mut self, control: RebaseControl, cx: &mut Context<Self>) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+
+        cx.spawn(async move |this, cx| {
+            let rebase_task = active_repository
+                .update(cx, |repo, _| repo.control_rebase(control))?
+                .await;
+            this.update(cx, |this, cx| {
+                rebase_task
+                    .map_err(anyhow::Error::from)
+                    .and_then(|result| result)
+                    .map_err(|e| {
+                        let action = match control {
+                            RebaseControl::Continue => "continue rebase",
+                            RebaseControl::Skip => "skip commit",
+                            RebaseControl::Abort => "abort rebase",
+                        };
+                        this.show_error_toast(action, e, cx);
+                    })
+                    .ok();
+                cx.notify();
+            })
+        })
+        .detach();
+    }
+
     pub fn stash_all(&mut self, _: &StashAll, _window: &mut Window, cx: &mut Context<Self>) {
         let Some(active_repository) = self.active_repository.clone() else {
             return;
@@ -3520,6 +3566,63 @@ impl GitPanel {
             )
     }
 
+    fn render_rebase_in_progress(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let active_repository = self.active_repository.as_ref()?;
+        let rebase = active_repository.read(cx).rebase.clone()?;
+        let mut status = format!("Rebasing ({}/{})", rebase.current, rebase.total);
+        if let Some(head_name) = &rebase.head_name {
+            status = format!("{status} {head_name}");
+        }
+        if let Some(onto) = &rebase.onto {
+            status = format!(
+                "{status} onto {}",
+                &onto[..SHORT_SHA_LENGTH.min(onto.len())]
+            );
+        }
+
+        Some(
+            h_flex()
+                .py_1p5()
+                .px_2()
+                .gap_1()
+                .justify_between()
+                .border_b_1()
+                .border_color(cx.theme().colors().border.opacity(0.8))
+                .child(
+                    div().flex_grow().overflow_hidden().child(
+                        Label::new(status)
+                            .size(LabelSize::Small)
+                            .color(Color::Warning)
+                            .truncate(),
+                    ),
+                )
+                .child(
+                    panel_button("Abort")
+                        .tooltip(Tooltip::for_action_title("Abort Rebase", &AbortRebase))
+                        .on_click(|_, window, cx| {
+                            window.dispatch_action(AbortRebase.boxed_clone(), cx)
+                        }),
+                )
+                .child(
+                    panel_button("Skip")
+                        .tooltip(Tooltip::for_action_title("Skip Commit", &SkipRebase))
+                        .on_click(|_, window, cx| {
+                            window.dispatch_action(SkipRebase.boxed_clone(), cx)
+                        }),
+                )
+                .child(
+                    panel_filled_button("Continue")
+                        .tooltip(Tooltip::for_action_title(
+                            "Continue Rebase",
+                            &ContinueRebase,
+                        ))
+                        .on_click(|_, window, cx| {
+                            window.dispatch_action(ContinueRebase.boxed_clone(), cx)
+                        }),
+                ),
+        )
+    }
+
     fn render_previous_commit(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
         let active_repository = self.active_repository.as_ref()?;
         let branch = active_repository.read(cx).branch.as_ref()?;
@@ -4251,6 +4354,9 @@ impl Render for GitPanel {
                     .on_action(cx.listener(Self::generate_commit_message_action))
                     .on_action(cx.listener(Self::stash_all))
                     .on_action(cx.listener(Self::stash_pop))
+                    .on_action(cx.listener(Self::continue_rebase))
+                    .on_action(cx.listener(Self::skip_rebase))
+                    .on_action(cx.listener(Self::abort_rebase))
             })
             .on_action(cx.listener(Self::select_first))
             .on_action(cx.listener(Self::select_next))
@@ -4273,6 +4379,7 @@ impl Render for GitPanel {
                 v_flex()
                     .size_full()
                     .children(self.render_panel_header(window, cx))
+                    .children(self.render_rebase_in_progress(cx))
                     .map(|this| {
                         if has_entries {
                             this.child(self.render_entries(has_write_access, window, cx))
@@ -0,0 +1,832 @@
+use anyhow::Result;
+use editor::Editor;
+use git::repository::{LogEntry, LogOptions, RebaseAction, RebaseTodoEntry};
+use git::{AbortRebase, ContinueRebase, SkipRebase};
+use gpui::{
+    Action, AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable, KeyContext, Point,
+    ScrollHandle, Subscription, Task, WeakEntity, actions,
+};
+use project::git_store::Repository;
+use ui::{Tooltip, WithScrollbar, prelude::*};
+use workspace::{Item, Workspace, item::ItemEvent};
+
+actions!(
+    git_rebase,
+    [
+        /// Keeps the selected commit.
+        PickCommit,
+        /// Keeps the selected commit, with a new message.
+        RewordCommit,
+        /// Keeps the selected commit, stopping after it so that it can be amended.
+        EditCommit,
+        /// Melds the selected commit into the previous one, combining their messages.
+        SquashCommit,
+        /// Melds the selected commit into the previous one, discarding its message.
+        FixupCommit,
+        /// Removes the selected commit.
+        DropCommit,
+        /// Moves the selected commit before the previous one.
+        MoveCommitUp,
+        /// Moves the selected commit after the next one.
+        MoveCommitDown,
+        /// Starts the rebase.
+        StartRebase,
+    ]
+);
+
+/// Opens an editor for interactively rebasing the commits of the current branch that follow
+/// `base`.
+pub fn open(
+    repository: Entity<Repository>,
+    base: LogEntry,
+    workspace: &mut Workspace,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let existing = workspace
+        .items_of_type::<RebaseEditor>(cx)
+        .find(|editor| editor.read(cx).repository == repository);
+    if let Some(existing) = existing {
+        existing.update(cx, |editor, cx| editor.set_base(base, cx));
+        workspace.activate_item(&existing, true, true, window, cx);
+        return;
+    }
+
+    let workspace_handle = workspace.weak_handle();
+    let rebase_editor =
+        cx.new(|cx| RebaseEditor::new(repository, base, workspace_handle, window, cx));
+    workspace.add_item_to_active_pane(Box::new(rebase_editor), None, true, window, cx);
+}
+
+struct RebaseEditorEntry {
+    commit: LogEntry,
+    action: RebaseAction,
+    /// The editor of the new message, once the commit is reworded and its message is loaded.
+    message_editor: Option<Entity<Editor>>,
+}
+
+#[derive(Clone)]
+struct DraggedRebaseEntry {
+    ix: usize,
+    subject: SharedString,
+    click_offset: Point<Pixels>,
+}
+
+impl Render for DraggedRebaseEntry {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        div().pl(self.click_offset.x).pt(self.click_offset.y).child(
+            div()
+                .py_1()
+                .px_2()
+                .rounded_md()
+                .bg(cx.theme().colors().background)
+                .child(Label::new(self.subject.clone())),
+        )
+    }
+}
+
+/// Edits the todo list of an interactive rebase: the commits following a base commit, from the
+/// oldest to the newest, each with the action to apply to it.
+pub struct RebaseEditor {
+    repository: Entity<Repository>,
+    workspace: WeakEntity<Workspace>,
+    base: LogEntry,
+    entries: Vec<RebaseEditorEntry>,
+    /// Whether some merge commits were left out, as rebasing linearizes the history.
+    skipped_merges: bool,
+    selected_ix: usize,
+    focus_handle: FocusHandle,
+    scroll_handle: ScrollHandle,
+    error: Option<SharedString>,
+    was_rebasing: bool,
+    rebase_task: Option<Task<()>>,
+    _load_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl RebaseEditor {
+    pub fn new(
+        repository: Entity<Repository>,
+        base: LogEntry,
+        workspace: WeakEntity<Workspace>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let was_rebasing = repository.read(cx).rebase.is_some();
+        let subscriptions = vec![cx.observe(&repository, |this, repository, cx| {
+            // Show the rewritten history once a rebase that stopped is over.
+            let is_rebasing = repository.read(cx).rebase.is_some();
+            if this.was_rebasing && !is_rebasing {
+                this.reload(cx);
+            }
+            this.was_rebasing = is_rebasing;
+            cx.notify();
+        })];
+
+        let mut this = Self {
+            repository,
+            workspace,
+            base,
+            entries: Vec::new(),
+            skipped_merges: false,
+            selected_ix: 0,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: ScrollHandle::new(),
+            error: None,
+            was_rebasing,
+            rebase_task: None,
+            _load_task: Task::ready(()),
+            _subscriptions: subscriptions,
+        };
+        this.reload(cx);
+        this
+    }
+
+    fn set_base(&mut self, base: LogEntry, cx: &mut Context<Self>) {
+        if base.sha != self.base.sha {
+            self.base = base;
+            self.reload(cx);
+        }
+    }
+
+    /// Lists the commits following the base again, resetting their actions.
+    fn reload(&mut self, cx: &mut Context<Self>) {
+        let log = self.repository.update(cx, |repository, _| {
+            repository.log(LogOptions {
+                revision_range: Some(format!("{}..HEAD", self.base.sha)),
+                ..LogOptions::default()
+            })
+        });
+        self._load_task = cx.spawn(async move |this, cx| {
+            let result = log.await.map_err(anyhow::Error::from).and_then(|log| log);
+            this.update(cx, |this, cx| {
+                match result {
+                    Ok(commits) => {
+                        this.skipped_merges = commits.iter().any(|commit| commit.parents.len() > 1);
+                        this.entries = commits
+                            .into_iter()
+                            .rev()
+                            .filter(|commit| commit.parents.len() <= 1)
+                            .map(|commit| RebaseEditorEntry {
+                                commit,
+                                action: RebaseAction::Pick,
+                                message_editor: None,
+                            })
+                            .collect();
+                        this.selected_ix = 0;
+                        this.error = None;
+                    }
+                    Err(error) => this.error = Some(format!("{error:#}").into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn set_action(
+        &mut self,
+        ix: usize,
+        action: RebaseAction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(entry) = self.entries.get_mut(ix) else {
+            return;
+        };
+        entry.action = action;
+        if action == RebaseAction::Reword && entry.message_editor.is_none() {
+            self.load_message(ix, window, cx);
+        }
+        cx.notify();
+    }
+
+    fn load_message(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let sha = self.entries[ix].commit.sha.clone();
+        let details = self
+            .repository
+            .update(cx, |repository, _| repository.show(sha.to_string()));
+        cx.spawn_in(window, async move |this, cx| {
+            let details = details.await??;
+            this.update_in(cx, |this, window, cx| {
+                let Some(entry) = this
+                    .entries
+                    .iter_mut()
+                    .find(|entry| entry.commit.sha == sha)
+                else {
+                    return;
+                };
+                let message_editor = cx.new(|cx| {
+                    let mut editor = Editor::auto_height(1, 12, window, cx);
+                    editor.set_text(details.message.trim_end(), window, cx);
+                    editor
+                });
+                window.focus(&message_editor.focus_handle(cx));
+                entry.message_editor = Some(message_editor);
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn set_selected_action(
+        &mut self,
+        action: RebaseAction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.set_action(self.selected_ix, action, window, cx);
+    }
+
+    fn pick(&mut self, _: &PickCommit, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_selected_action(RebaseAction::Pick, window, cx);
+    }
+
+    fn reword(&mut self, _: &RewordCommit, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_selected_action(RebaseAction::Reword, window, cx);
+    }
+
+    fn edit(&mut self, _: &EditCommit, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_selected_action(RebaseAction::Edit, window, cx);
+    }
+
+    fn squash(&mut self, _: &SquashCommit, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_selected_action(RebaseAction::Squash, window, cx);
+    }
+
+    fn fixup(&mut self, _: &FixupCommit, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_selected_action(RebaseAction::Fixup, window, cx);
+    }
+
+    fn drop_commit(&mut self, _: &DropCommit, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_selected_action(RebaseAction::Drop, window, cx);
+    }
+
+    fn move_entry(&mut self, from_ix: usize, to_ix: usize, cx: &mut Context<Self>) {
+        if from_ix >= self.entries.len() || to_ix >= self.entries.len() || from_ix == to_ix {
+            return;
+        }
+        let entry = self.entries.remove(from_ix);
+        self.entries.insert(to_ix, entry);
+        self.selected_ix = to_ix;
+        self.scroll_handle.scroll_to_item(to_ix);
+        cx.notify();
+    }
+
+    fn move_up(&mut self, _: &MoveCommitUp, _window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(to_ix) = self.selected_ix.checked_sub(1) {
+            self.move_entry(self.selected_ix, to_ix, cx);
+        }
+    }
+
+    fn move_down(&mut self, _: &MoveCommitDown, _window: &mut Window, cx: &mut Context<Self>) {
+        self.move_entry(self.selected_ix, self.selected_ix + 1, cx);
+    }
+
+    fn select_ix(&mut self, ix: usize, cx: &mut Context<Self>) {
+        if ix < self.entries.len() {
+            self.selected_ix = ix;
+            self.scroll_handle.scroll_to_item(ix);
+            cx.notify();
+        }
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, _window: &mut Window, cx: &mut Context<Self>) {
+        self.select_ix(self.selected_ix + 1, cx);
+    }
+
+    fn select_previous(
+        &mut self,
+        _: &menu::SelectPrevious,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(ix) = self.selected_ix.checked_sub(1) {
+            self.select_ix(ix, cx);
+        }
+    }
+
+    fn select_first(
+        &mut self,
+        _: &menu::SelectFirst,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.select_ix(0, cx);
+    }
+
+    fn select_last(&mut self, _: &menu::SelectLast, _window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.entries.len().checked_sub(1) {
+            self.select_ix(ix, cx);
+        }
+    }
+
+    fn todo_list(&self, cx: &App) -> Result<Vec<RebaseTodoEntry>> {
+        let first_kept = self
+            .entries
+            .iter()
+            .find(|entry| entry.action != RebaseAction::Drop);
+        if let Some(entry) = first_kept {
+            anyhow::ensure!(
+                !matches!(entry.action, RebaseAction::Squash | RebaseAction::Fixup),
+                "Cannot {} {} without a previous commit",
+                entry.action.as_str(),
+                entry.commit.short_sha()
+            );
+        }
+
+        self.entries
+            .iter()
+            .map(|entry| {
+                let message = match (&entry.action, &entry.message_editor) {
+                    (RebaseAction::Reword, Some(message_editor)) => {
+                        let message = message_editor.read(cx).text(cx);
+                        anyhow::ensure!(
+                            !message.trim().is_empty(),
+                            "The new message of {} is empty",
+                            entry.commit.short_sha()
+                        );
+                        Some(format!("{}\n", message.trim_end()))
+                    }
+                    (RebaseAction::Reword, None) => {
+                        anyhow::bail!(
+                            "The message of {} is still loading",
+                            entry.commit.short_sha()
+                        )
+                    }
+                    _ => None,
+                };
+                Ok(RebaseTodoEntry {
+                    action: entry.action,
+                    sha: entry.commit.sha.clone(),
+                    message,
+                })
+            })
+            .collect()
+    }
+
+    fn start_rebase(&mut self, _: &StartRebase, _window: &mut Window, cx: &mut Context<Self>) {
+        if self.rebase_task.is_some() || self.repository.read(cx).rebase.is_some() {
+            return;
+        }
+        let todo = match self.todo_list(cx) {
+            Ok(todo) => todo,
+            Err(error) => {
+                self.error = Some(error.to_string().into());
+                cx.notify();
+                return;
+            }
+        };
+        let base = self.base.sha.to_string();
+        let rebase = self
+            .repository
+            .update(cx, |repository, _| repository.rebase(base, todo));
+        self.error = None;
+        self.rebase_task = Some(cx.spawn(async move |this, cx| {
+            let result = rebase.await.map_err(anyhow::Error::from).and_then(|r| r);
+            this.update(cx, |this, cx| {
+                this.rebase_task = None;
+                match result {
+                    Ok(()) => this.reload(cx),
+                    Err(error) => {
+                        this.error = Some(format!("{error:#}").into());
+                        cx.notify();
+                    }
+                }
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    fn dispatch_context(&self, window: &Window, cx: &App) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("GitRebaseEditor");
+        let editing_message = self.entries.iter().any(|entry| {
+            entry
+                .message_editor
+                .as_ref()
+                .is_some_and(|editor| editor.focus_handle(cx).contains_focused(window, cx))
+        });
+        if !editing_message {
+            dispatch_context.add("menu");
+        }
+        dispatch_context
+    }
+
+    fn render_action_button(
+        &self,
+        ix: usize,
+        action: RebaseAction,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let is_selected = self.entries[ix].action == action;
+        let tooltip_action: Box<dyn Action> = match action {
+            RebaseAction::Pick => Box::new(PickCommit),
+            RebaseAction::Reword => Box::new(RewordCommit),
+            RebaseAction::Edit => Box::new(EditCommit),
+            RebaseAction::Squash => Box::new(SquashCommit),
+            RebaseAction::Fixup => Box::new(FixupCommit),
+            RebaseAction::Drop => Box::new(DropCommit),
+        };
+        Button::new(
+            SharedString::from(format!("{ix}-{}", action.as_str())),
+            action.as_str(),
+        )
+        .label_size(LabelSize::Small)
+        .toggle_state(is_selected)
+        .selected_style(ButtonStyle::Tinted(TintColor::Accent))
+        .tooltip(move |window, cx| {
+            Tooltip::for_action(action.as_str(), &*tooltip_action, window, cx)
+        })
+        .on_click(cx.listener(move |this, _, window, cx| {
+            this.selected_ix = ix;
+            this.set_action(ix, action, window, cx);
+        }))
+    }
+
+    fn render_entry(&self, ix: usize, cx: &mut Context<Self>) -> AnyElement {
+        let entry = &self.entries[ix];
+        let is_selected = ix == self.selected_ix;
+        let is_dropped = entry.action == RebaseAction::Drop;
+        let selected_background = cx.theme().colors().element_selected;
+        let hover_background = cx.theme().colors().element_hover;
+        let drop_target_border = cx.theme().colors().drop_target_border;
+        let subject = entry.commit.subject.clone();
+
+        v_flex()
+            .id(ix)
+            .w_full()
+            .px_2()
+            .py_1()
+            .gap_1()
+            .border_t_2()
+            .border_color(gpui::transparent_black())
+            .when(is_selected, |this| this.bg(selected_background))
+            .hover(|this| this.bg(hover_background))
+            .on_click(cx.listener(move |this, _, _, cx| this.select_ix(ix, cx)))
+            .on_drag(
+                DraggedRebaseEntry {
+                    ix,
+                    subject: subject.clone(),
+                    click_offset: Point::default(),
+                },
+                |dragged, click_offset, _, cx| {
+                    cx.new(|_| DraggedRebaseEntry {
+                        click_offset,
+                        ..dragged.clone()
+                    })
+                },
+            )
+            .drag_over::<DraggedRebaseEntry>(move |style, _, _, _| {
+                style.border_color(drop_target_border)
+            })
+            .on_drop(
+                cx.listener(move |this, dragged: &DraggedRebaseEntry, _, cx| {
+                    this.move_entry(dragged.ix, ix, cx);
+                }),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        h_flex().gap_0p5().children(
+                            RebaseAction::ALL
+                                .into_iter()
+                                .map(|action| self.render_action_button(ix, action, cx)),
+                        ),
+                    )
+                    .child(
+                        Label::new(entry.commit.short_sha())
+                            .buffer_font(cx)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        div().flex_1().min_w_0().child(
+                            Label::new(subject)
+                                .single_line()
+                                .truncate()
+                                .strikethrough(is_dropped)
+                                .when(is_dropped, |label| label.color(Color::Disabled)),
+                        ),
+                    )
+                    .child(
+                        Label::new(entry.commit.author_name.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .when(entry.action == RebaseAction::Reword, |this| {
+                this.child(match &entry.message_editor {
+                    Some(message_editor) => div()
+                        .ml_4()
+                        .p_1()
+                        .border_1()
+                        .rounded_sm()
+                        .border_color(cx.theme().colors().border_variant)
+                        .child(message_editor.clone())
+                        .into_any_element(),
+                    None => Label::new("Loading message…")
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                        .into_any_element(),
+                })
+            })
+            .into_any_element()
+    }
+
+    fn render_rebase_in_progress(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let rebase = self.repository.read(cx).rebase.clone()?;
+        let status = match &rebase.stopped_at {
+            Some(sha) => format!(
+                "Stopped at {} ({}/{}). Resolve the conflicts or amend the commit, then continue.",
+                &sha[..git::SHORT_SHA_LENGTH.min(sha.len())],
+                rebase.current,
+                rebase.total
+            ),
+            None => format!("Rebasing ({}/{})", rebase.current, rebase.total),
+        };
+        Some(
+            h_flex()
+                .gap_2()
+                .px_2()
+                .py_1()
+                .border_b_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    div()
+                        .flex_1()
+                        .child(Label::new(status).color(Color::Warning)),
+                )
+                .child(
+                    Button::new("abort-rebase", "Abort")
+                        .tooltip(Tooltip::for_action_title("Abort Rebase", &AbortRebase))
+                        .on_click(|_, window, cx| {
+                            window.dispatch_action(AbortRebase.boxed_clone(), cx)
+                        }),
+                )
+                .child(
+                    Button::new("skip-rebase", "Skip")
+                        .tooltip(Tooltip::for_action_title("Skip Commit", &SkipRebase))
+                        .on_click(|_, window, cx| {
+                            window.dispatch_action(SkipRebase.boxed_clone(), cx)
+                        }),
+                )
+                .child(
+                    Button::new("continue-rebase", "Continue")
+                        .style(ButtonStyle::Filled)
+                        .tooltip(Tooltip::for_action_title(
+                            "Continue Rebase",
+                            &ContinueRebase,
+                        ))
+                        .on_click(|_, window, cx| {
+                            window.dispatch_action(ContinueRebase.boxed_clone(), cx)
+                        }),
+                ),
+        )
+    }
+}
+
+impl EventEmitter<()> for RebaseEditor {}
+
+impl Focusable for RebaseEditor {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for RebaseEditor {
+    type Event = ();
+
+    fn to_item_events(_: &Self::Event, _: impl FnMut(ItemEvent)) {}
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        format!("Rebase onto {}", self.base.short_sha()).into()
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch).color(Color::Muted))
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+impl Render for RebaseEditor {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let is_rebasing = self.rebase_task.is_some() || self.repository.read(cx).rebase.is_some();
+        let commit_count = self.entries.len();
+
+        v_flex()
+            .key_context(self.dispatch_context(window, cx))
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::pick))
+            .on_action(cx.listener(Self::reword))
+            .on_action(cx.listener(Self::edit))
+            .on_action(cx.listener(Self::squash))
+            .on_action(cx.listener(Self::fixup))
+            .on_action(cx.listener(Self::drop_commit))
+            .on_action(cx.listener(Self::move_up))
+            .on_action(cx.listener(Self::move_down))
+            .on_action(cx.listener(Self::start_rebase))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .px_2()
+                    .py_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(
+                        Label::new(format!(
+                            "Rebase {commit_count} {} onto {}",
+                            if commit_count == 1 {
+                                "commit"
+                            } else {
+                                "commits"
+                            },
+                            self.base.short_sha()
+                        ))
+                        .color(Color::Muted),
+                    )
+                    .child(
+                        div().flex_1().min_w_0().child(
+                            Label::new(self.base.subject.clone())
+                                .single_line()
+                                .truncate(),
+                        ),
+                    )
+                    .child(
+                        Button::new("start-rebase", "Start Rebase")
+                            .style(ButtonStyle::Filled)
+                            .disabled(is_rebasing || self.entries.is_empty())
+                            .tooltip(Tooltip::for_action_title("Start Rebase", &StartRebase))
+                            .on_click(|_, window, cx| {
+                                window.dispatch_action(StartRebase.boxed_clone(), cx)
+                            }),
+                    ),
+            )
+            .children(self.render_rebase_in_progress(cx))
+            .when_some(self.error.clone(), |this, error| {
+                this.child(
+                    h_flex()
+                        .px_2()
+                        .py_1()
+                        .child(Label::new(error).color(Color::Error)),
+                )
+            })
+            .when(self.skipped_merges, |this| {
+                this.child(
+                    h_flex().px_2().py_1().child(
+                        Label::new("Merge commits are left out, as rebasing linearizes history.")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+                )
+            })
+            .child(
+                div()
+                    .flex_1()
+                    .size_full()
+                    .child(
+                        v_flex()
+                            .id("rebase-todo")
+                            .size_full()
+                            .overflow_y_scroll()
+                            .track_scroll(&self.scroll_handle)
+                            .children((0..self.entries.len()).map(|ix| self.render_entry(ix, cx))),
+                    )
+                    .vertical_scrollbar_for(self.scroll_handle.clone(), window, cx),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use git::repository::RebaseControl;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::{FakeFs, Project, WorktreeSettings};
+    use serde_json::json;
+    use settings::{Settings as _, SettingsStore};
+    use std::path::Path;
+    use theme::LoadThemes;
+    use util::path;
+
+    use super::*;
+
+    fn init_test(cx: &mut TestAppContext) {
+        zlog::init_test();
+
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            WorktreeSettings::register(cx);
+            workspace::init_settings(cx);
+            theme::init(LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            Project::init_settings(cx);
+        });
+    }
+
+    fn commit(sha: &str, parents: &[&str]) -> LogEntry {
+        LogEntry {
+            sha: sha.to_string().into(),
+            parents: parents
+                .iter()
+                .map(|parent| parent.to_string().into())
+                .collect(),
+            subject: format!("commit {sha}").into(),
+            commit_timestamp: 0,
+            author_name: "author".into(),
+            author_email: "author@example.com".into(),
+            refs: Vec::new(),
+        }
+    }
+
+    fn todo(rebase_editor: &Entity<RebaseEditor>, cx: &mut VisualTestContext) -> Vec<String> {
+        rebase_editor.read_with(cx, |rebase_editor, _| {
+            rebase_editor
+                .entries
+                .iter()
+                .map(|entry| format!("{} {}", entry.action.as_str(), entry.commit.sha))
+                .collect()
+        })
+    }
+
+    #[gpui::test]
+    async fn test_rebase_editor(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(path!("/project"), json!({ ".git": {}, "a.txt": "a" }))
+            .await;
+        let base = commit("a", &[]);
+        fs.set_commits_for_repo(
+            Path::new(path!("/project/.git")),
+            vec![
+                commit("d", &["c"]),
+                commit("c", &["b"]),
+                commit("b", &["a"]),
+                base.clone(),
+            ],
+        );
+
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        let workspace =
+            cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        cx.run_until_parked();
+
+        let repository =
+            project.read_with(cx, |project, cx| project.active_repository(cx).unwrap());
+        let workspace_handle = workspace
+            .update(cx, |workspace, _, _| workspace.weak_handle())
+            .unwrap();
+        let rebase_editor = cx.new_window_entity(|window, cx| {
+            RebaseEditor::new(repository.clone(), base, workspace_handle, window, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(todo(&rebase_editor, cx), ["pick b", "pick c", "pick d"]);
+
+        // Move d before c, drop c and stop at d to amend it.
+        rebase_editor.update_in(cx, |rebase_editor, window, cx| {
+            rebase_editor.select_ix(2, cx);
+            rebase_editor.move_up(&MoveCommitUp, window, cx);
+            rebase_editor.set_action(1, RebaseAction::Edit, window, cx);
+            rebase_editor.set_action(2, RebaseAction::Drop, window, cx);
+        });
+        assert_eq!(todo(&rebase_editor, cx), ["pick b", "edit d", "drop c"]);
+
+        rebase_editor.update_in(cx, |rebase_editor, window, cx| {
+            rebase_editor.start_rebase(&StartRebase, window, cx)
+        });
+        cx.run_until_parked();
+        repository.read_with(cx, |repository, _| {
+            let rebase = repository.rebase.as_ref().unwrap();
+            assert_eq!(rebase.stopped_at.as_deref(), Some("d'"));
+            assert_eq!((rebase.current, rebase.total), (2, 3));
+        });
+        rebase_editor.read_with(cx, |rebase_editor, _| {
+            assert_eq!(rebase_editor.error, None);
+        });
+
+        repository
+            .update(cx, |repository, _| {
+                repository.control_rebase(RebaseControl::Continue)
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        cx.run_until_parked();
+        repository.read_with(cx, |repository, _| assert_eq!(repository.rebase, None));
+        // b already followed a, so only d was rewritten on top of it.
+        assert_eq!(todo(&rebase_editor, cx), ["pick b", "pick d'"]);
+    }
+}
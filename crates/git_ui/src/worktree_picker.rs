@@ -0,0 +1,602 @@
+use fuzzy::StringMatchCandidate;
+
+use collections::HashSet;
+use git::repository::{Branch, GitWorktree};
+use gpui::{
+    Action, AnyElement, App, AsyncApp, Context, DismissEvent, Entity, EventEmitter, FocusHandle,
+    Focusable, InteractiveElement, IntoElement, ParentElement, Render, SharedString, Styled,
+    Subscription, Task, WeakEntity, Window, actions, rems,
+};
+use picker::{Picker, PickerDelegate};
+use project::git_store::Repository;
+use std::{path::PathBuf, sync::Arc};
+use ui::{HighlightedLabel, KeyBinding, ListItem, ListItemSpacing, Tooltip, prelude::*};
+use util::{ResultExt, paths::PathExt};
+use workspace::notifications::DetachAndPromptErr;
+use workspace::{ModalView, OpenOptions, Workspace};
+
+use crate::worktree_picker;
+
+actions!(
+    worktree_picker,
+    [
+        /// Removes the selected linked worktree.
+        RemoveWorktree,
+    ]
+);
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(open);
+}
+
+pub fn open(
+    workspace: &mut Workspace,
+    _: &zed_actions::git::Worktrees,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let repository = workspace.project().read(cx).active_repository(cx);
+    let weak_workspace = workspace.weak_handle();
+    workspace.toggle_modal(window, cx, |window, cx| {
+        WorktreeList::new(repository, weak_workspace, rems(34.), window, cx)
+    })
+}
+
+pub struct WorktreeList {
+    width: Rems,
+    pub picker: Entity<Picker<WorktreeListDelegate>>,
+    picker_focus_handle: FocusHandle,
+    _subscription: Subscription,
+}
+
+impl WorktreeList {
+    fn new(
+        repository: Option<Entity<Repository>>,
+        workspace: WeakEntity<Workspace>,
+        width: Rems,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate = WorktreeListDelegate::new(repository, workspace, cx);
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        let picker_focus_handle = picker.focus_handle(cx);
+        picker.update(cx, |picker, cx| {
+            picker.delegate.focus_handle = picker_focus_handle.clone();
+            picker.delegate.reload(window, cx);
+        });
+
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| {
+            cx.emit(DismissEvent);
+        });
+
+        Self {
+            picker,
+            picker_focus_handle,
+            width,
+            _subscription,
+        }
+    }
+
+    fn handle_remove_worktree(
+        &mut self,
+        _: &RemoveWorktree,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.picker.update(cx, |picker, cx| {
+            picker
+                .delegate
+                .remove_worktree_at(picker.delegate.selected_index(), window, cx);
+        });
+    }
+}
+
+impl ModalView for WorktreeList {}
+impl EventEmitter<DismissEvent> for WorktreeList {}
+impl Focusable for WorktreeList {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.picker_focus_handle.clone()
+    }
+}
+
+impl Render for WorktreeList {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("WorktreeList")
+            .w(self.width)
+            .on_action(cx.listener(Self::handle_remove_worktree))
+            .child(self.picker.clone())
+    }
+}
+
+#[derive(Debug, Clone)]
+enum WorktreeEntry {
+    /// An existing worktree of the repository.
+    Worktree(GitWorktree),
+    /// A local branch that is not checked out in any worktree yet.
+    Branch(Branch),
+    /// A branch to create from `HEAD`, named after the query.
+    NewBranch(SharedString),
+}
+
+impl WorktreeEntry {
+    fn label(&self) -> SharedString {
+        match self {
+            WorktreeEntry::Worktree(worktree) => match worktree.branch_name() {
+                Some(branch_name) => branch_name.to_string().into(),
+                None => match &worktree.sha {
+                    Some(sha) => format!("detached at {}", &sha[..sha.len().min(7)]).into(),
+                    None => "bare".into(),
+                },
+            },
+            WorktreeEntry::Branch(branch) => branch.name().to_string().into(),
+            WorktreeEntry::NewBranch(branch_name) => branch_name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct WorktreeMatch {
+    entry: WorktreeEntry,
+    positions: Vec<usize>,
+}
+
+pub struct WorktreeListDelegate {
+    matches: Vec<WorktreeMatch>,
+    all_entries: Option<Vec<WorktreeEntry>>,
+    main_worktree_path: Option<PathBuf>,
+    repo: Option<Entity<Repository>>,
+    workspace: WeakEntity<Workspace>,
+    selected_index: usize,
+    focus_handle: FocusHandle,
+}
+
+impl WorktreeListDelegate {
+    fn new(
+        repo: Option<Entity<Repository>>,
+        workspace: WeakEntity<Workspace>,
+        cx: &mut Context<WorktreeList>,
+    ) -> Self {
+        Self {
+            matches: vec![],
+            all_entries: None,
+            main_worktree_path: None,
+            repo,
+            workspace,
+            selected_index: 0,
+            focus_handle: cx.focus_handle(),
+        }
+    }
+
+    /// Lists the worktrees of the repository, followed by the local branches that could be
+    /// checked out in a new one.
+    fn reload(&mut self, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(repo) = self.repo.clone() else {
+            return;
+        };
+        let worktrees_request = repo.update(cx, |repo, _| repo.worktrees());
+        let branches_request = repo.update(cx, |repo, _| repo.branches());
+
+        cx.spawn_in(window, async move |picker, cx| {
+            let worktrees = worktrees_request.await??;
+            let mut branches = branches_request.await??;
+
+            let checked_out_branches = worktrees
+                .iter()
+                .filter_map(|worktree| worktree.ref_name.clone())
+                .collect::<HashSet<_>>();
+            branches.retain(|branch| {
+                !branch.is_remote() && !checked_out_branches.contains(&branch.ref_name)
+            });
+            branches.sort_by_key(|branch| {
+                branch
+                    .most_recent_commit
+                    .as_ref()
+                    .map(|commit| 0 - commit.commit_timestamp)
+            });
+
+            let main_worktree_path = worktrees
+                .iter()
+                .find(|worktree| worktree.is_main)
+                .map(|worktree| worktree.path.clone());
+            let entries = worktrees
+                .into_iter()
+                .map(WorktreeEntry::Worktree)
+                .chain(branches.into_iter().map(WorktreeEntry::Branch))
+                .collect::<Vec<_>>();
+
+            picker.update_in(cx, |picker, window, cx| {
+                picker.delegate.main_worktree_path = main_worktree_path;
+                picker.delegate.all_entries = Some(entries);
+                picker.refresh(window, cx);
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Where a new worktree for the given branch is created: next to the main worktree, in a
+    /// directory named after the repository and the branch.
+    fn new_worktree_path(&self, branch_name: &str, cx: &App) -> Option<PathBuf> {
+        let main_worktree_path = self.main_worktree_path.clone().or_else(|| {
+            let repo = self.repo.as_ref()?.read(cx);
+            Some(repo.work_directory_abs_path.to_path_buf())
+        })?;
+        let repo_name = main_worktree_path.file_name()?.to_string_lossy();
+        let directory_name = format!("{repo_name}-{}", branch_name.replace('/', "-"));
+        Some(main_worktree_path.with_file_name(directory_name))
+    }
+
+    fn create_worktree(
+        &self,
+        branch_name: SharedString,
+        new_branch: bool,
+        add_to_project: bool,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) {
+        let Some(repo) = self.repo.clone() else {
+            return;
+        };
+        let Some(path) = self.new_worktree_path(&branch_name, cx) else {
+            return;
+        };
+        let workspace = self.workspace.clone();
+
+        cx.spawn(async move |_, cx| {
+            repo.update(cx, |repo, _| {
+                repo.create_worktree(path.clone(), branch_name.to_string(), new_branch)
+            })?
+            .await??;
+            open_worktree(workspace, path, add_to_project, cx).await
+        })
+        .detach_and_prompt_err("Failed to create worktree", window, cx, |e, _, _| {
+            Some(e.to_string())
+        });
+        cx.emit(DismissEvent);
+    }
+
+    fn remove_worktree_at(&self, ix: usize, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(WorktreeEntry::Worktree(worktree)) = self.matches.get(ix).map(|m| &m.entry) else {
+            return;
+        };
+        // The main worktree holds the repository itself, so git refuses to remove it.
+        if worktree.is_main {
+            return;
+        }
+        let Some(repo) = self.repo.clone() else {
+            return;
+        };
+        let path = worktree.path.clone();
+
+        cx.spawn_in(window, async move |picker, cx| {
+            repo.update(cx, |repo, _| repo.remove_worktree(path, false))?
+                .await??;
+            picker.update_in(cx, |picker, window, cx| picker.delegate.reload(window, cx))?;
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to remove worktree", window, cx, |e, _, _| {
+            Some(e.to_string())
+        });
+    }
+}
+
+/// Opens a worktree as a new workspace, or adds it to the current project as another folder.
+/// Remote projects can only do the latter.
+async fn open_worktree(
+    workspace: WeakEntity<Workspace>,
+    path: PathBuf,
+    add_to_project: bool,
+    cx: &mut AsyncApp,
+) -> anyhow::Result<()> {
+    let open_task = workspace.update(cx, |workspace, cx| {
+        let project = workspace.project().clone();
+        if add_to_project || !project.read(cx).is_local() {
+            let create_task =
+                project.update(cx, |project, cx| project.create_worktree(&path, true, cx));
+            cx.spawn(async move |_, _| create_task.await.map(|_| ()))
+        } else {
+            let open_task = workspace::open_paths(
+                &[path],
+                workspace.app_state().clone(),
+                OpenOptions {
+                    open_new_workspace: Some(true),
+                    ..Default::default()
+                },
+                cx,
+            );
+            cx.spawn(async move |_, _| open_task.await.map(|_| ()))
+        }
+    })?;
+    open_task.await
+}
+
+impl PickerDelegate for WorktreeListDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Select a worktree or branch…".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let Some(all_entries) = self.all_entries.clone() else {
+            return Task::ready(());
+        };
+
+        cx.spawn_in(window, async move |picker, cx| {
+            let mut matches: Vec<WorktreeMatch> = if query.is_empty() {
+                all_entries
+                    .into_iter()
+                    .map(|entry| WorktreeMatch {
+                        entry,
+                        positions: Vec::new(),
+                    })
+                    .collect()
+            } else {
+                let candidates = all_entries
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, entry)| StringMatchCandidate::new(ix, &entry.label()))
+                    .collect::<Vec<StringMatchCandidate>>();
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    true,
+                    true,
+                    10000,
+                    &Default::default(),
+                    cx.background_executor().clone(),
+                )
+                .await
+                .into_iter()
+                .map(|candidate| WorktreeMatch {
+                    entry: all_entries[candidate.candidate_id].clone(),
+                    positions: candidate.positions,
+                })
+                .collect()
+            };
+
+            let branch_name = query.trim().replace(' ', "-");
+            if !branch_name.is_empty()
+                && !matches
+                    .iter()
+                    .any(|entry_match| entry_match.entry.label().as_ref() == branch_name)
+            {
+                matches.push(WorktreeMatch {
+                    entry: WorktreeEntry::NewBranch(branch_name.into()),
+                    positions: Vec::new(),
+                });
+            }
+
+            picker
+                .update(cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches;
+                    if delegate.matches.is_empty() {
+                        delegate.selected_index = 0;
+                    } else {
+                        delegate.selected_index =
+                            core::cmp::min(delegate.selected_index, delegate.matches.len() - 1);
+                    }
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry_match) = self.matches.get(self.selected_index()) else {
+            return;
+        };
+        match entry_match.entry.clone() {
+            WorktreeEntry::Worktree(worktree) => {
+                let workspace = self.workspace.clone();
+                cx.spawn(async move |_, cx| {
+                    open_worktree(workspace, worktree.path, secondary, cx).await
+                })
+                .detach_and_prompt_err(
+                    "Failed to open worktree",
+                    window,
+                    cx,
+                    |e, _, _| Some(e.to_string()),
+                );
+                cx.emit(DismissEvent);
+            }
+            WorktreeEntry::Branch(branch) => {
+                let branch_name = branch.name().to_string().into();
+                self.create_worktree(branch_name, false, secondary, window, cx);
+            }
+            WorktreeEntry::NewBranch(branch_name) => {
+                self.create_worktree(branch_name, true, secondary, window, cx);
+            }
+        }
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let entry_match = self.matches.get(ix)?;
+        let entry = &entry_match.entry;
+
+        let (icon, title, detail) = match entry {
+            WorktreeEntry::Worktree(worktree) => {
+                let title = HighlightedLabel::new(entry.label(), entry_match.positions.clone())
+                    .truncate()
+                    .into_any_element();
+                let detail = worktree.path.compact().to_string_lossy().into_owned();
+                (IconName::Folder, title, detail)
+            }
+            WorktreeEntry::Branch(_) => {
+                let title = HighlightedLabel::new(entry.label(), entry_match.positions.clone())
+                    .truncate()
+                    .into_any_element();
+                let detail = match self.new_worktree_path(&entry.label(), cx) {
+                    Some(path) => format!("create worktree in {}", path.compact().display()),
+                    None => "create worktree".to_string(),
+                };
+                (IconName::GitBranch, title, detail)
+            }
+            WorktreeEntry::NewBranch(branch_name) => {
+                let title = Label::new(format!("Create branch \"{branch_name}\" in a worktree…"))
+                    .single_line()
+                    .truncate()
+                    .into_any_element();
+                let detail = match self.new_worktree_path(branch_name, cx) {
+                    Some(path) => format!("based off HEAD, in {}", path.compact().display()),
+                    None => "based off HEAD".to_string(),
+                };
+                (IconName::Plus, title, detail)
+            }
+        };
+
+        let status = match entry {
+            WorktreeEntry::Worktree(worktree) if worktree.is_main => Some("main"),
+            WorktreeEntry::Worktree(worktree) if worktree.is_prunable => Some("missing"),
+            WorktreeEntry::Worktree(worktree) if worktree.is_locked => Some("locked"),
+            _ => None,
+        };
+
+        Some(
+            ListItem::new(SharedString::from(format!("worktree-{ix}")))
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(Icon::new(icon).color(Color::Muted).size(IconSize::Small))
+                .child(
+                    v_flex()
+                        .w_full()
+                        .overflow_hidden()
+                        .child(
+                            h_flex()
+                                .gap_6()
+                                .justify_between()
+                                .overflow_x_hidden()
+                                .child(title)
+                                .when_some(status, |this, status| {
+                                    this.child(
+                                        Label::new(status)
+                                            .size(LabelSize::Small)
+                                            .color(Color::Muted),
+                                    )
+                                }),
+                        )
+                        .child(
+                            Label::new(detail.clone())
+                                .size(LabelSize::Small)
+                                .truncate()
+                                .color(Color::Muted),
+                        ),
+                )
+                .tooltip(Tooltip::text(detail)),
+        )
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some("No worktrees found".into())
+    }
+
+    fn render_footer(
+        &self,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<AnyElement> {
+        let focus_handle = self.focus_handle.clone();
+        let selected_entry = self
+            .matches
+            .get(self.selected_index)
+            .map(|entry_match| &entry_match.entry);
+        let confirm_label = match selected_entry {
+            Some(WorktreeEntry::Worktree(_)) | None => "Open",
+            Some(WorktreeEntry::Branch(_) | WorktreeEntry::NewBranch(_)) => "Create",
+        };
+        let can_remove = matches!(
+            selected_entry,
+            Some(WorktreeEntry::Worktree(worktree)) if !worktree.is_main
+        );
+
+        Some(
+            h_flex()
+                .w_full()
+                .p_1p5()
+                .gap_0p5()
+                .justify_end()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .when(can_remove, |this| {
+                    this.child(
+                        Button::new("remove-worktree", "Remove")
+                            .key_binding(
+                                KeyBinding::for_action_in(
+                                    &worktree_picker::RemoveWorktree,
+                                    &focus_handle,
+                                    window,
+                                    cx,
+                                )
+                                .map(|kb| kb.size(rems_from_px(12.))),
+                            )
+                            .on_click(|_, window, cx| {
+                                window.dispatch_action(
+                                    worktree_picker::RemoveWorktree.boxed_clone(),
+                                    cx,
+                                )
+                            }),
+                    )
+                })
+                .child(
+                    Button::new("add-worktree-to-project", "Add to Project")
+                        .key_binding(
+                            KeyBinding::for_action_in(
+                                &menu::SecondaryConfirm,
+                                &focus_handle,
+                                window,
+                                cx,
+                            )
+                            .map(|kb| kb.size(rems_from_px(12.))),
+                        )
+                        .on_click(|_, window, cx| {
+                            window.dispatch_action(menu::SecondaryConfirm.boxed_clone(), cx)
+                        }),
+                )
+                .child(
+                    Button::new("open-worktree", confirm_label)
+                        .key_binding(
+                            KeyBinding::for_action_in(&menu::Confirm, &focus_handle, window, cx)
+                                .map(|kb| kb.size(rems_from_px(12.))),
+                        )
+                        .on_click(|_, window, cx| {
+                            window.dispatch_action(menu::Confirm.boxed_clone(), cx)
+                        }),
+                )
+                .into_any(),
+        )
+    }
+}
@@ -0,0 +1,785 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use gpui::SharedString;
+use parking_lot::Mutex;
+
+use crate::{
+    Bookmark, BookmarkUpdate, Change, ChangeDiff, ChangeEdge, ChangeEdgeKind, ChangeFile,
+    FileChange, FileChangeKind, JujutsuRepository, jj_repository::combine_descriptions,
+};
+
+const ROOT_CHANGE_ID: &str = "zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz";
+
+/// An in-memory model of a Jujutsu repository, for use in tests.
+///
+/// Revsets are not evaluated: [`JujutsuRepository::log`] always returns every
+/// visible change. Rebasing descendants applies the rewritten change's delta to
+/// paths the descendant didn't modify itself, which is enough for tests that
+/// don't involve conflicts.
+pub struct FakeJujutsuRepository {
+    state: Mutex<FakeJujutsuRepositoryState>,
+}
+
+#[derive(Debug, Clone)]
+struct FakeChange {
+    change_id: SharedString,
+    commit_id: SharedString,
+    description: SharedString,
+    /// Change IDs of the parents.
+    parents: Vec<SharedString>,
+    files: BTreeMap<Arc<Path>, String>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct FakeRepoView {
+    /// Changes in creation order, which is also a valid topological order.
+    changes: Vec<FakeChange>,
+    bookmarks: BTreeMap<String, SharedString>,
+    working_copy: SharedString,
+}
+
+#[derive(Debug)]
+struct FakeJujutsuRepositoryState {
+    view: FakeRepoView,
+    /// Views before each operation, most recent last.
+    operations: Vec<FakeRepoView>,
+    next_id: u64,
+}
+
+impl Default for FakeJujutsuRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakeJujutsuRepository {
+    /// Creates a repository containing the root change and an empty
+    /// working-copy change on top of it, like `jj git init` does.
+    pub fn new() -> Self {
+        let root = FakeChange {
+            change_id: ROOT_CHANGE_ID.into(),
+            commit_id: "0".repeat(40).into(),
+            description: SharedString::default(),
+            parents: Vec::new(),
+            files: BTreeMap::new(),
+        };
+        let mut state = FakeJujutsuRepositoryState {
+            view: FakeRepoView {
+                changes: vec![root],
+                bookmarks: BTreeMap::new(),
+                working_copy: ROOT_CHANGE_ID.into(),
+            },
+            operations: Vec::new(),
+            next_id: 1,
+        };
+        let working_copy = state.new_change(vec![ROOT_CHANGE_ID.into()], BTreeMap::new());
+        state.view.working_copy = working_copy;
+        Self {
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Replaces the contents of the working-copy change, simulating edits on disk.
+    pub fn set_working_copy_files<'a>(&self, files: impl IntoIterator<Item = (&'a str, &'a str)>) {
+        let mut state = self.state.lock();
+        let working_copy = state.view.working_copy.clone();
+        let files = files
+            .into_iter()
+            .map(|(path, text)| (Arc::from(Path::new(path)), text.to_string()))
+            .collect();
+        state
+            .rewrite(&working_copy, |change| change.files = files)
+            .expect("working copy must exist");
+    }
+
+    /// Returns the commit ID of the current working-copy change.
+    pub fn working_copy_commit_id(&self) -> SharedString {
+        let state = self.state.lock();
+        state
+            .view
+            .change(&state.view.working_copy)
+            .map(|change| change.commit_id.clone())
+            .unwrap_or_default()
+    }
+
+    fn transact(
+        &self,
+        f: impl FnOnce(&mut FakeJujutsuRepositoryState) -> Result<()>,
+    ) -> Result<()> {
+        let mut state = self.state.lock();
+        let view_before = state.view.clone();
+        let next_id_before = state.next_id;
+        match f(&mut state) {
+            Ok(()) => {
+                state.operations.push(view_before);
+                Ok(())
+            }
+            Err(error) => {
+                state.view = view_before;
+                state.next_id = next_id_before;
+                Err(error)
+            }
+        }
+    }
+}
+
+impl FakeRepoView {
+    fn change(&self, change_id: &str) -> Option<&FakeChange> {
+        self.changes
+            .iter()
+            .find(|change| change.change_id.as_ref() == change_id)
+    }
+
+    fn change_mut(&mut self, change_id: &str) -> Option<&mut FakeChange> {
+        self.changes
+            .iter_mut()
+            .find(|change| change.change_id.as_ref() == change_id)
+    }
+
+    fn change_id_for_commit(&self, commit_id: &str) -> Result<SharedString> {
+        self.changes
+            .iter()
+            .find(|change| change.commit_id.as_ref() == commit_id)
+            .map(|change| change.change_id.clone())
+            .with_context(|| format!("commit {commit_id} not found"))
+    }
+
+    fn children(&self, change_id: &str) -> Vec<SharedString> {
+        self.changes
+            .iter()
+            .filter(|change| {
+                change
+                    .parents
+                    .iter()
+                    .any(|parent| parent.as_ref() == change_id)
+            })
+            .map(|change| change.change_id.clone())
+            .collect()
+    }
+
+    fn to_change(&self, change: &FakeChange) -> Change {
+        let parent_files = self.parent_files(change);
+        Change {
+            change_id: change.change_id.clone(),
+            commit_id: change.commit_id.clone(),
+            description: change.description.clone(),
+            author_name: "Test User".into(),
+            author_email: "test@example.com".into(),
+            timestamp: 0,
+            bookmarks: self
+                .bookmarks
+                .iter()
+                .filter(|(_, target)| **target == change.change_id)
+                .map(|(name, _)| name.clone().into())
+                .collect(),
+            is_working_copy: self.working_copy == change.change_id,
+            is_empty: change.change_id.as_ref() != ROOT_CHANGE_ID && change.files == parent_files,
+            has_conflict: false,
+            parents: change
+                .parents
+                .iter()
+                .filter_map(|parent| self.change(parent))
+                .map(|parent| ChangeEdge {
+                    target: parent.commit_id.clone(),
+                    kind: ChangeEdgeKind::Direct,
+                })
+                .collect(),
+        }
+    }
+
+    fn parent_files(&self, change: &FakeChange) -> BTreeMap<Arc<Path>, String> {
+        change
+            .parents
+            .first()
+            .and_then(|parent| self.change(parent))
+            .map(|parent| parent.files.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl FakeJujutsuRepositoryState {
+    fn next_ids(&mut self) -> (SharedString, SharedString) {
+        let id = self.next_id;
+        self.next_id += 1;
+        let change_id = format!("{:k>32}", encode_reverse_hex(id));
+        let commit_id = format!("{id:040x}");
+        (change_id.into(), commit_id.into())
+    }
+
+    fn new_change(
+        &mut self,
+        parents: Vec<SharedString>,
+        files: BTreeMap<Arc<Path>, String>,
+    ) -> SharedString {
+        let (change_id, commit_id) = self.next_ids();
+        self.view.changes.push(FakeChange {
+            change_id: change_id.clone(),
+            commit_id,
+            description: SharedString::default(),
+            parents,
+            files,
+        });
+        change_id
+    }
+
+    /// Rewrites a change, assigning it a new commit ID and rebasing its
+    /// descendants onto the new contents.
+    fn rewrite(&mut self, change_id: &str, f: impl FnOnce(&mut FakeChange)) -> Result<()> {
+        if change_id == ROOT_CHANGE_ID {
+            bail!("cannot rewrite the root commit");
+        }
+        let (_, commit_id) = self.next_ids();
+        let change = self
+            .view
+            .change_mut(change_id)
+            .with_context(|| format!("change {change_id} not found"))?;
+        let old_files = change.files.clone();
+        f(change);
+        change.commit_id = commit_id;
+        let new_files = change.files.clone();
+        self.rebase_children(change_id, &old_files, &new_files, None);
+        Ok(())
+    }
+
+    fn rebase_children(
+        &mut self,
+        change_id: &str,
+        old_base: &BTreeMap<Arc<Path>, String>,
+        new_base: &BTreeMap<Arc<Path>, String>,
+        skip: Option<&str>,
+    ) {
+        if old_base == new_base {
+            return;
+        }
+        for child_id in self.view.children(change_id) {
+            if skip == Some(child_id.as_ref()) {
+                continue;
+            }
+            let (_, commit_id) = self.next_ids();
+            let Some(child) = self.view.change_mut(&child_id) else {
+                continue;
+            };
+            let old_files = child.files.clone();
+            child.files = apply_delta(&child.files, old_base, new_base);
+            child.commit_id = commit_id;
+            let new_files = child.files.clone();
+            self.rebase_children(&child_id, &old_files, &new_files, None);
+        }
+    }
+
+    fn abandon(&mut self, change_id: &str) -> Result<()> {
+        if change_id == ROOT_CHANGE_ID {
+            bail!("cannot abandon the root commit");
+        }
+        let change = self
+            .view
+            .change(change_id)
+            .cloned()
+            .with_context(|| format!("change {change_id} not found"))?;
+        let parent_files = self.view.parent_files(&change);
+        self.rebase_children(change_id, &change.files, &parent_files, None);
+
+        for other in &mut self.view.changes {
+            if other
+                .parents
+                .iter()
+                .any(|parent| parent.as_ref() == change_id)
+            {
+                other.parents = other
+                    .parents
+                    .iter()
+                    .flat_map(|parent| {
+                        if parent.as_ref() == change_id {
+                            change.parents.clone()
+                        } else {
+                            vec![parent.clone()]
+                        }
+                    })
+                    .collect();
+            }
+        }
+        self.view
+            .changes
+            .retain(|other| other.change_id.as_ref() != change_id);
+        for target in self.view.bookmarks.values_mut() {
+            if target.as_ref() == change_id {
+                *target = change.parents[0].clone();
+            }
+        }
+
+        if self.view.working_copy.as_ref() == change_id {
+            self.view.working_copy = self.new_change(change.parents.clone(), parent_files);
+        }
+        Ok(())
+    }
+}
+
+/// Applies the changes between `old_base` and `new_base` to `files`, for every
+/// path that `files` hasn't changed relative to `old_base`.
+fn apply_delta(
+    files: &BTreeMap<Arc<Path>, String>,
+    old_base: &BTreeMap<Arc<Path>, String>,
+    new_base: &BTreeMap<Arc<Path>, String>,
+) -> BTreeMap<Arc<Path>, String> {
+    let mut result = files.clone();
+    for path in old_base.keys().chain(new_base.keys()) {
+        let old_value = old_base.get(path);
+        let new_value = new_base.get(path);
+        if old_value == new_value || files.get(path) != old_value {
+            continue;
+        }
+        match new_value {
+            Some(text) => result.insert(path.clone(), text.clone()),
+            None => result.remove(path),
+        };
+    }
+    result
+}
+
+fn encode_reverse_hex(value: u64) -> String {
+    format!("{value:x}")
+        .chars()
+        .map(|digit| {
+            let digit = digit.to_digit(16).unwrap_or_default() as u8;
+            (b'z' - digit) as char
+        })
+        .collect()
+}
+
+impl JujutsuRepository for FakeJujutsuRepository {
+    fn list_bookmarks(&self) -> Vec<Bookmark> {
+        self.state
+            .lock()
+            .view
+            .bookmarks
+            .keys()
+            .map(|name| Bookmark {
+                ref_name: name.clone().into(),
+            })
+            .collect()
+    }
+
+    fn log(&self, _revset: &str, limit: usize) -> Result<Vec<Change>> {
+        let state = self.state.lock();
+        let view = &state.view;
+        Ok(view
+            .changes
+            .iter()
+            .rev()
+            .take(limit)
+            .map(|change| view.to_change(change))
+            .collect())
+    }
+
+    fn load_change_diff(&self, commit_id: &str) -> Result<ChangeDiff> {
+        let state = self.state.lock();
+        let view = &state.view;
+        let change_id = view.change_id_for_commit(commit_id)?;
+        let change = view.change(&change_id).context("change not found")?;
+        let parent_files = view.parent_files(change);
+
+        let mut paths = parent_files
+            .keys()
+            .chain(change.files.keys())
+            .cloned()
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths.dedup();
+
+        Ok(ChangeDiff {
+            files: paths
+                .into_iter()
+                .filter(|path| parent_files.get(path) != change.files.get(path))
+                .map(|path| ChangeFile {
+                    old_text: parent_files.get(&path).cloned(),
+                    new_text: change.files.get(&path).cloned(),
+                    path,
+                })
+                .collect(),
+        })
+    }
+
+    fn new_change(&self, parent_commit_ids: &[SharedString]) -> Result<()> {
+        self.transact(|state| {
+            let parents = parent_commit_ids
+                .iter()
+                .map(|commit_id| state.view.change_id_for_commit(commit_id))
+                .collect::<Result<Vec<_>>>()?;
+            let first_parent = parents.first().context("no parents given")?;
+            let files = state
+                .view
+                .change(first_parent)
+                .map(|parent| parent.files.clone())
+                .unwrap_or_default();
+            state.view.working_copy = state.new_change(parents, files);
+            Ok(())
+        })
+    }
+
+    fn describe(&self, commit_id: &str, description: &str) -> Result<()> {
+        self.transact(|state| {
+            let change_id = state.view.change_id_for_commit(commit_id)?;
+            state.rewrite(&change_id, |change| {
+                change.description = description.to_string().into();
+            })
+        })
+    }
+
+    fn squash(&self, commit_id: &str, paths: Option<&[Arc<Path>]>) -> Result<()> {
+        self.transact(|state| {
+            let change_id = state.view.change_id_for_commit(commit_id)?;
+            let source = state
+                .view
+                .change(&change_id)
+                .cloned()
+                .context("change not found")?;
+            let [parent_id] = source.parents.as_slice() else {
+                bail!("cannot squash a merge commit");
+            };
+            let parent = state
+                .view
+                .change(parent_id)
+                .cloned()
+                .context("parent not found")?;
+
+            let is_selected = |path: &Path| {
+                paths.is_none_or(|paths| paths.iter().any(|selected| path.starts_with(selected)))
+            };
+            let mut parent_files = parent.files.clone();
+            for path in parent.files.keys().chain(source.files.keys()) {
+                if !is_selected(path) {
+                    continue;
+                }
+                match source.files.get(path) {
+                    Some(text) => parent_files.insert(path.clone(), text.clone()),
+                    None => parent_files.remove(path),
+                };
+            }
+            if parent_files == parent.files {
+                return Ok(());
+            }
+
+            let squashes_everything = parent_files == source.files;
+            let description = if squashes_everything {
+                combine_descriptions(&parent.description, &source.description)
+            } else {
+                parent.description.to_string()
+            };
+
+            if parent_id.as_ref() == ROOT_CHANGE_ID {
+                bail!("cannot rewrite the root commit");
+            }
+            let (_, new_parent_commit_id) = state.next_ids();
+            let parent_change = state
+                .view
+                .change_mut(parent_id)
+                .context("parent not found")?;
+            parent_change.files = parent_files.clone();
+            parent_change.description = description.into();
+            parent_change.commit_id = new_parent_commit_id;
+            state.rebase_children(
+                parent_id,
+                &parent.files,
+                &parent_files,
+                Some(change_id.as_ref()),
+            );
+
+            if squashes_everything {
+                state.abandon(&change_id)?;
+            } else {
+                let (_, new_commit_id) = state.next_ids();
+                if let Some(source) = state.view.change_mut(&change_id) {
+                    source.commit_id = new_commit_id;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn edit(&self, commit_id: &str) -> Result<()> {
+        self.transact(|state| {
+            let change_id = state.view.change_id_for_commit(commit_id)?;
+            if change_id.as_ref() == ROOT_CHANGE_ID {
+                bail!("cannot edit the root commit");
+            }
+            state.view.working_copy = change_id;
+            Ok(())
+        })
+    }
+
+    fn abandon(&self, commit_id: &str) -> Result<()> {
+        self.transact(|state| {
+            let change_id = state.view.change_id_for_commit(commit_id)?;
+            state.abandon(&change_id)
+        })
+    }
+
+    fn update_bookmark(&self, name: &str, commit_id: &str, update: BookmarkUpdate) -> Result<()> {
+        self.transact(|state| {
+            let change_id = state.view.change_id_for_commit(commit_id)?;
+            let exists = state.view.bookmarks.contains_key(name);
+            match update {
+                BookmarkUpdate::Create if exists => bail!("bookmark {name} already exists"),
+                BookmarkUpdate::Move if !exists => bail!("no such bookmark: {name}"),
+                BookmarkUpdate::Create | BookmarkUpdate::Set | BookmarkUpdate::Move => {}
+            }
+            state.view.bookmarks.insert(name.to_string(), change_id);
+            Ok(())
+        })
+    }
+
+    fn undo(&self) -> Result<()> {
+        let mut state = self.state.lock();
+        let view = state
+            .operations
+            .pop()
+            .ok_or_else(|| anyhow!("cannot undo the root operation"))?;
+        state.view = view;
+        Ok(())
+    }
+
+    fn snapshot_working_copy(&self) -> Result<()> {
+        // The working copy only changes through `set_working_copy_files`, which
+        // records it right away.
+        Ok(())
+    }
+
+    fn status(&self) -> Result<Vec<FileChange>> {
+        let state = self.state.lock();
+        let view = &state.view;
+        let change = view
+            .change(&view.working_copy)
+            .context("working copy not found")?;
+        let parent_files = view.parent_files(change);
+
+        let mut paths = parent_files
+            .keys()
+            .chain(change.files.keys())
+            .cloned()
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths.dedup();
+
+        Ok(paths
+            .into_iter()
+            .filter_map(|path| {
+                let kind = match (parent_files.get(&path), change.files.get(&path)) {
+                    (None, Some(_)) => FileChangeKind::Added,
+                    (Some(_), None) => FileChangeKind::Deleted,
+                    (Some(old), Some(new)) if old != new => FileChangeKind::Modified,
+                    _ => return None,
+                };
+                Some(FileChange { path, kind })
+            })
+            .collect())
+    }
+
+    fn working_copy_change(&self) -> Result<Option<Change>> {
+        let state = self.state.lock();
+        let view = &state.view;
+        Ok(view
+            .change(&view.working_copy)
+            .map(|change| view.to_change(change)))
+    }
+
+    fn load_parent_text(&self, path: &Path) -> Result<Option<String>> {
+        let state = self.state.lock();
+        let view = &state.view;
+        let change = view
+            .change(&view.working_copy)
+            .context("working copy not found")?;
+        Ok(view.parent_files(change).get(path).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(repository: &FakeJujutsuRepository) -> Vec<Change> {
+        repository.log("all()", usize::MAX).unwrap()
+    }
+
+    fn working_copy(repository: &FakeJujutsuRepository) -> Change {
+        log(repository)
+            .into_iter()
+            .find(|change| change.is_working_copy)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_new_and_describe() {
+        let repository = FakeJujutsuRepository::new();
+        repository.set_working_copy_files([("a.txt", "one\n")]);
+        let first = working_copy(&repository);
+
+        repository
+            .describe(&first.commit_id, "first change")
+            .unwrap();
+        let first = working_copy(&repository);
+        assert_eq!(first.description.as_ref(), "first change");
+
+        repository
+            .new_change(std::slice::from_ref(&first.commit_id))
+            .unwrap();
+        let second = working_copy(&repository);
+        assert_ne!(second.change_id, first.change_id);
+        assert!(second.is_empty);
+        assert_eq!(second.parents[0].target, first.commit_id);
+        assert_eq!(log(&repository).len(), 3);
+    }
+
+    #[test]
+    fn test_squash_whole_and_per_path() {
+        let repository = FakeJujutsuRepository::new();
+        repository.set_working_copy_files([("a.txt", "a\n")]);
+        let parent = working_copy(&repository);
+        repository.describe(&parent.commit_id, "parent").unwrap();
+        let parent = working_copy(&repository);
+        repository
+            .new_change(std::slice::from_ref(&parent.commit_id))
+            .unwrap();
+        repository.set_working_copy_files([("a.txt", "a\n"), ("b.txt", "b\n"), ("c.txt", "c\n")]);
+
+        let child = working_copy(&repository);
+        repository
+            .squash(&child.commit_id, Some(&[Arc::from(Path::new("b.txt"))]))
+            .unwrap();
+
+        let child = working_copy(&repository);
+        let diff = repository.load_change_diff(&child.commit_id).unwrap();
+        assert_eq!(
+            diff.files
+                .iter()
+                .map(|file| file.path.as_ref())
+                .collect::<Vec<_>>(),
+            [Path::new("c.txt")]
+        );
+
+        repository.squash(&child.commit_id, None).unwrap();
+        let changes = log(&repository);
+        let parent = changes
+            .iter()
+            .find(|change| change.change_id == parent.change_id)
+            .unwrap();
+        let diff = repository.load_change_diff(&parent.commit_id).unwrap();
+        assert_eq!(diff.files.len(), 3);
+        let new_working_copy = working_copy(&repository);
+        assert_ne!(new_working_copy.change_id, child.change_id);
+        assert!(new_working_copy.is_empty);
+        assert_eq!(new_working_copy.parents[0].target, parent.commit_id);
+    }
+
+    #[test]
+    fn test_edit_abandon_and_undo() {
+        let repository = FakeJujutsuRepository::new();
+        repository.set_working_copy_files([("a.txt", "a\n")]);
+        let first = working_copy(&repository);
+        repository
+            .new_change(std::slice::from_ref(&first.commit_id))
+            .unwrap();
+        let second = working_copy(&repository);
+
+        repository.edit(&first.commit_id).unwrap();
+        assert_eq!(working_copy(&repository).change_id, first.change_id);
+
+        repository.abandon(&second.commit_id).unwrap();
+        assert!(
+            log(&repository)
+                .iter()
+                .all(|change| change.change_id != second.change_id)
+        );
+
+        repository.undo().unwrap();
+        assert!(
+            log(&repository)
+                .iter()
+                .any(|change| change.change_id == second.change_id)
+        );
+
+        repository.undo().unwrap();
+        assert_eq!(working_copy(&repository).change_id, second.change_id);
+    }
+
+    #[test]
+    fn test_bookmarks() {
+        let repository = FakeJujutsuRepository::new();
+        let working_copy_commit_id = repository.working_copy_commit_id();
+
+        repository
+            .update_bookmark("main", &working_copy_commit_id, BookmarkUpdate::Create)
+            .unwrap();
+        assert!(
+            repository
+                .update_bookmark("main", &working_copy_commit_id, BookmarkUpdate::Create)
+                .is_err()
+        );
+        assert!(
+            repository
+                .update_bookmark("feature", &working_copy_commit_id, BookmarkUpdate::Move)
+                .is_err()
+        );
+
+        repository
+            .new_change(std::slice::from_ref(&working_copy_commit_id))
+            .unwrap();
+        let new_commit_id = repository.working_copy_commit_id();
+        repository
+            .update_bookmark("main", &new_commit_id, BookmarkUpdate::Move)
+            .unwrap();
+        assert_eq!(working_copy(&repository).bookmarks, ["main"]);
+        assert_eq!(
+            repository
+                .list_bookmarks()
+                .into_iter()
+                .map(|bookmark| bookmark.ref_name)
+                .collect::<Vec<_>>(),
+            ["main"]
+        );
+    }
+
+    #[test]
+    fn test_status_and_parent_text() {
+        let repository = FakeJujutsuRepository::new();
+        repository.set_working_copy_files([("a.txt", "one\n"), ("b.txt", "b\n")]);
+        let parent = working_copy(&repository);
+        repository
+            .new_change(std::slice::from_ref(&parent.commit_id))
+            .unwrap();
+        assert_eq!(repository.status().unwrap(), []);
+
+        repository.set_working_copy_files([("a.txt", "two\n"), ("c.txt", "c\n")]);
+        assert_eq!(
+            repository.status().unwrap(),
+            [
+                FileChange {
+                    path: Path::new("a.txt").into(),
+                    kind: FileChangeKind::Modified,
+                },
+                FileChange {
+                    path: Path::new("b.txt").into(),
+                    kind: FileChangeKind::Deleted,
+                },
+                FileChange {
+                    path: Path::new("c.txt").into(),
+                    kind: FileChangeKind::Added,
+                },
+            ]
+        );
+        assert_eq!(
+            repository.load_parent_text(Path::new("a.txt")).unwrap(),
+            Some("one\n".to_string())
+        );
+        assert_eq!(
+            repository.load_parent_text(Path::new("c.txt")).unwrap(),
+            None
+        );
+
+        let working_copy_change = repository.working_copy_change().unwrap().unwrap();
+        assert!(working_copy_change.is_working_copy);
+        assert_eq!(working_copy_change.parents[0].target, parent.commit_id);
+    }
+}
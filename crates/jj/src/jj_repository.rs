@@ -1,56 +1,354 @@
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{Context as _, Result, anyhow};
+use futures::StreamExt as _;
+use futures::executor::block_on;
 use gpui::SharedString;
+use jj_lib::backend::{CommitId, TreeValue};
+use jj_lib::commit::Commit;
 use jj_lib::config::StackedConfig;
-use jj_lib::repo::StoreFactories;
+use jj_lib::conflicts::{
+    ConflictMarkerStyle, MaterializedTreeValue, materialize_merge_result_to_bytes,
+    materialize_tree_value,
+};
+use jj_lib::fsmonitor::FsmonitorSettings;
+use jj_lib::git_backend::GitBackend;
+use jj_lib::gitignore::GitIgnoreFile;
+use jj_lib::graph::GraphEdgeType;
+use jj_lib::matchers::{EverythingMatcher, PrefixMatcher};
+use jj_lib::merge::Merge;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::op_store::RefTarget;
+use jj_lib::ref_name::{RefName, WorkspaceNameBuf};
+use jj_lib::repo::{MutableRepo, ReadonlyRepo, Repo as _, RepoLoader, StoreFactories};
+use jj_lib::repo_path::{RepoPath, RepoPathBuf, RepoPathUiConverter};
+use jj_lib::revset::{
+    self, DefaultSymbolResolver, RevsetAliasesMap, RevsetDiagnostics, RevsetExtensions,
+    RevsetParseContext, RevsetWorkspaceContext,
+};
+use jj_lib::rewrite::{CommitWithSelection, merge_commit_trees, restore_tree, squash_commits};
 use jj_lib::settings::UserSettings;
-use jj_lib::workspace::{self, DefaultWorkspaceLoaderFactory, WorkspaceLoaderFactory};
+use jj_lib::store::Store;
+use jj_lib::working_copy::{CheckoutOptions, SnapshotOptions};
+use jj_lib::workspace::{self, DefaultWorkspaceLoaderFactory, Workspace, WorkspaceLoaderFactory};
+
+/// The revset shown by the change log when the user hasn't entered one.
+pub const DEFAULT_LOG_REVSET: &str = "present(@) | ancestors(visible_heads(), 8) | bookmarks()";
+
+/// The maximum number of changes returned by a single [`JujutsuRepository::log`] call.
+pub const LOG_LIMIT: usize = 500;
+
+/// The largest file that snapshotting the working copy will start tracking,
+/// matching jj's default `snapshot.max-new-file-size`.
+const MAX_NEW_FILE_SIZE: u64 = 1024 * 1024;
 
 #[derive(Debug, Clone)]
 pub struct Bookmark {
     pub ref_name: SharedString,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookmarkUpdate {
+    /// Creates a new bookmark, failing if it already exists.
+    Create,
+    /// Points a bookmark at the change, creating it if it doesn't exist.
+    Set,
+    /// Moves an existing bookmark, failing if it doesn't exist.
+    Move,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeEdgeKind {
+    /// The parent is a direct parent of the change.
+    Direct,
+    /// The parent is an ancestor of the change, with commits elided in between.
+    Indirect,
+    /// The parent is not part of the evaluated revset.
+    Missing,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChangeEdge {
+    /// The commit ID of the parent this edge points to.
+    pub target: SharedString,
+    pub kind: ChangeEdgeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Change {
+    pub change_id: SharedString,
+    pub commit_id: SharedString,
+    pub description: SharedString,
+    pub author_name: SharedString,
+    pub author_email: SharedString,
+    /// This is a unix timestamp
+    pub timestamp: i64,
+    pub bookmarks: Vec<SharedString>,
+    pub is_working_copy: bool,
+    pub is_empty: bool,
+    pub has_conflict: bool,
+    /// Edges to the parents of this change, in the order that jj reports them.
+    pub parents: Vec<ChangeEdge>,
+}
+
+impl Change {
+    pub fn short_change_id(&self) -> SharedString {
+        self.change_id
+            .get(..8)
+            .unwrap_or(&self.change_id)
+            .to_string()
+            .into()
+    }
+
+    pub fn short_commit_id(&self) -> SharedString {
+        self.commit_id
+            .get(..8)
+            .unwrap_or(&self.commit_id)
+            .to_string()
+            .into()
+    }
+
+    pub fn subject(&self) -> &str {
+        self.description.lines().next().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ChangeDiff {
+    pub files: Vec<ChangeFile>,
+}
+
+#[derive(Debug)]
+pub struct ChangeFile {
+    /// The path of the file, relative to the workspace root.
+    pub path: Arc<Path>,
+    pub old_text: Option<String>,
+    pub new_text: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileChangeKind {
+    Added,
+    Modified,
+    Deleted,
+    Conflicted,
+}
+
+/// A file changed by the working-copy change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    /// The path of the file, relative to the workspace root.
+    pub path: Arc<Path>,
+    pub kind: FileChangeKind,
+}
+
 pub trait JujutsuRepository: Send + Sync {
     fn list_bookmarks(&self) -> Vec<Bookmark>;
+
+    /// Evaluates the given revset, returning at most `limit` changes in
+    /// topological order (children before parents).
+    fn log(&self, revset: &str, limit: usize) -> Result<Vec<Change>>;
+
+    /// Loads the contents of every file modified by the commit with the given ID,
+    /// both before and after the change.
+    fn load_change_diff(&self, commit_id: &str) -> Result<ChangeDiff>;
+
+    /// Creates a new empty change on top of the given commits and makes it the
+    /// working-copy change.
+    fn new_change(&self, parent_commit_ids: &[SharedString]) -> Result<()>;
+
+    fn describe(&self, commit_id: &str, description: &str) -> Result<()>;
+
+    /// Moves the changes in the given commit into its parent. When `paths` is
+    /// provided, only changes to those paths (and their descendants) are moved.
+    fn squash(&self, commit_id: &str, paths: Option<&[Arc<Path>]>) -> Result<()>;
+
+    /// Makes the given commit the working-copy change.
+    fn edit(&self, commit_id: &str) -> Result<()>;
+
+    /// Abandons the given commit, rebasing its descendants onto its parents.
+    fn abandon(&self, commit_id: &str) -> Result<()>;
+
+    fn update_bookmark(&self, name: &str, commit_id: &str, update: BookmarkUpdate) -> Result<()>;
+
+    /// Undoes the most recent operation that wasn't a working-copy snapshot.
+    fn undo(&self) -> Result<()>;
+
+    /// Records the current contents of the working copy into the working-copy
+    /// change. Operations that rewrite changes do this on their own.
+    fn snapshot_working_copy(&self) -> Result<()>;
+
+    /// Returns the files changed by the working-copy change, as of the last
+    /// snapshot of the working copy.
+    fn status(&self) -> Result<Vec<FileChange>>;
+
+    fn working_copy_change(&self) -> Result<Option<Change>>;
+
+    /// Loads the contents of `path` (relative to the workspace root) as of the
+    /// parents of the working-copy change, which serves as the diff base for
+    /// files in the working copy.
+    fn load_parent_text(&self, path: &Path) -> Result<Option<String>>;
 }
 
 pub struct RealJujutsuRepository {
-    repository: Arc<jj_lib::repo::ReadonlyRepo>,
+    repository: Arc<ReadonlyRepo>,
+    repo_loader: RepoLoader,
+    settings: UserSettings,
+    workspace_root: PathBuf,
+    workspace_name: WorkspaceNameBuf,
 }
 
 impl RealJujutsuRepository {
-    pub fn new(cwd: &Path) -> Result<Self> {
-        let workspace_loader_factory = DefaultWorkspaceLoaderFactory;
-        let workspace_loader = workspace_loader_factory.create(Self::find_workspace_dir(cwd))?;
-
+    /// Loads the workspace whose `.jj` directory is directly under `workspace_root`.
+    pub fn new(workspace_root: &Path) -> Result<Self> {
         let config = StackedConfig::with_defaults();
         let settings = UserSettings::from_config(config)?;
 
+        let workspace = Self::load_workspace(workspace_root, &settings)?;
+
+        let repo_loader = workspace.repo_loader().clone();
+        let repository = repo_loader.load_at_head()?;
+
+        Ok(Self {
+            repository,
+            repo_loader,
+            settings,
+            workspace_root: workspace.workspace_root().to_path_buf(),
+            workspace_name: workspace.workspace_name().to_owned(),
+        })
+    }
+
+    pub fn workspace_root(&self) -> &Path {
+        &self.workspace_root
+    }
+
+    fn load_workspace(workspace_root: &Path, settings: &UserSettings) -> Result<Workspace> {
+        let workspace_loader_factory = DefaultWorkspaceLoaderFactory;
+        let workspace_loader = workspace_loader_factory.create(workspace_root)?;
         let workspace = workspace_loader.load(
-            &settings,
+            settings,
             &StoreFactories::default(),
             &workspace::default_working_copy_factories(),
         )?;
+        Ok(workspace)
+    }
 
-        let repo_loader = workspace.repo_loader();
-        let repository = repo_loader.load_at_head()?;
+    /// Records the current contents of the working copy into the working-copy
+    /// commit, so that operations never lose edits made on disk.
+    fn snapshot_workspace(&self, workspace: &mut Workspace) -> Result<Arc<ReadonlyRepo>> {
+        let repo = self.load_head();
+        let Some(wc_commit_id) = repo.view().get_wc_commit_id(&self.workspace_name) else {
+            return Ok(repo);
+        };
+        let wc_commit = repo.store().get_commit(wc_commit_id)?;
+
+        let mut locked_workspace = workspace.start_working_copy_mutation()?;
+        let (tree_id, _stats) = locked_workspace.locked_wc().snapshot(&SnapshotOptions {
+            base_ignores: base_ignores(&repo)?,
+            fsmonitor_settings: FsmonitorSettings::None,
+            progress: None,
+            start_tracking_matcher: &EverythingMatcher,
+            max_new_file_size: MAX_NEW_FILE_SIZE,
+            conflict_marker_style: ConflictMarkerStyle::default(),
+        })?;
+
+        if &tree_id == wc_commit.tree_id() {
+            locked_workspace.finish(repo.op_id().clone())?;
+            return Ok(repo);
+        }
+
+        let mut transaction = repo.start_transaction();
+        transaction.set_is_snapshot(true);
+        transaction
+            .repo_mut()
+            .rewrite_commit(&wc_commit)
+            .set_tree_id(tree_id)
+            .write()?;
+        transaction.repo_mut().rebase_descendants()?;
+        let repo = transaction.commit("snapshot working copy")?;
+        locked_workspace.finish(repo.op_id().clone())?;
+        Ok(repo)
+    }
+
+    /// Updates the files on disk to match the working-copy commit of `repo`.
+    fn update_working_copy(&self, workspace: &mut Workspace, repo: &ReadonlyRepo) -> Result<()> {
+        let Some(wc_commit_id) = repo.view().get_wc_commit_id(&self.workspace_name) else {
+            return Ok(());
+        };
+        let wc_commit = repo.store().get_commit(wc_commit_id)?;
+        workspace.check_out(
+            repo.op_id().clone(),
+            None,
+            &wc_commit,
+            &CheckoutOptions {
+                conflict_marker_style: ConflictMarkerStyle::default(),
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Snapshots the working copy, applies `f` in a new transaction, and checks
+    /// out the resulting working-copy commit.
+    fn transact(
+        &self,
+        description: &str,
+        f: impl FnOnce(&mut MutableRepo, &WorkspaceNameBuf) -> Result<()>,
+    ) -> Result<()> {
+        let mut workspace = Self::load_workspace(&self.workspace_root, &self.settings)?;
+        let repo = self.snapshot_workspace(&mut workspace)?;
+
+        let mut transaction = repo.start_transaction();
+        f(transaction.repo_mut(), &self.workspace_name)?;
+        transaction.repo_mut().rebase_descendants()?;
+        let repo = transaction.commit(description)?;
+
+        self.update_working_copy(&mut workspace, &repo)
+    }
 
-        Ok(Self { repository })
+    /// Reloads the repository at the latest operation, so that changes made
+    /// by other processes (e.g. the `jj` CLI) are picked up.
+    fn load_head(&self) -> Arc<ReadonlyRepo> {
+        self.repo_loader
+            .load_at_head()
+            .unwrap_or_else(|_| self.repository.clone())
     }
 
-    fn find_workspace_dir(cwd: &Path) -> &Path {
-        cwd.ancestors()
-            .find(|path| path.join(".jj").is_dir())
-            .unwrap_or(cwd)
+    fn to_change(
+        &self,
+        repo: &ReadonlyRepo,
+        commit_id: &CommitId,
+        parents: Vec<ChangeEdge>,
+    ) -> Result<Change> {
+        let commit = repo.store().get_commit(commit_id)?;
+        let author = commit.author();
+        let bookmarks = repo
+            .view()
+            .local_bookmarks_for_commit(commit_id)
+            .map(|(ref_name, _target)| ref_name.as_str().to_string().into())
+            .collect();
+        let is_working_copy = repo.view().get_wc_commit_id(&self.workspace_name) == Some(commit_id);
+
+        Ok(Change {
+            change_id: commit.change_id().reverse_hex().into(),
+            commit_id: commit_id.hex().into(),
+            description: commit.description().to_string().into(),
+            author_name: author.name.clone().into(),
+            author_email: author.email.clone().into(),
+            timestamp: author.timestamp.timestamp.0 / 1000,
+            bookmarks,
+            is_working_copy,
+            is_empty: commit.is_empty(repo)?,
+            has_conflict: commit.has_conflict()?,
+            parents,
+        })
     }
 }
 
 impl JujutsuRepository for RealJujutsuRepository {
     fn list_bookmarks(&self) -> Vec<Bookmark> {
-        self.repository
+        self.load_head()
             .view()
             .bookmarks()
             .map(|(ref_name, _target)| Bookmark {
@@ -58,12 +356,582 @@ impl JujutsuRepository for RealJujutsuRepository {
             })
             .collect()
     }
+
+    fn log(&self, revset: &str, limit: usize) -> Result<Vec<Change>> {
+        let repo = self.load_head();
+
+        let aliases_map = RevsetAliasesMap::new();
+        let extensions = RevsetExtensions::new();
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: self.workspace_root.clone(),
+            base: self.workspace_root.clone(),
+        };
+        let context = RevsetParseContext {
+            aliases_map: &aliases_map,
+            local_variables: HashMap::new(),
+            user_email: self.settings.user_email(),
+            date_pattern_context: chrono::Local::now().into(),
+            extensions: &extensions,
+            workspace: Some(RevsetWorkspaceContext {
+                path_converter: &path_converter,
+                workspace_name: &self.workspace_name,
+            }),
+        };
+
+        let mut diagnostics = RevsetDiagnostics::new();
+        let expression = revset::parse(&mut diagnostics, revset, &context)
+            .map_err(|error| anyhow!("failed to parse revset: {error}"))?;
+        let symbol_resolver =
+            DefaultSymbolResolver::new(repo.as_ref(), extensions.symbol_resolvers());
+        let evaluated = expression
+            .resolve_user_expression(repo.as_ref(), &symbol_resolver)
+            .map_err(|error| anyhow!("failed to resolve revset: {error}"))?
+            .evaluate(repo.as_ref())
+            .map_err(|error| anyhow!("failed to evaluate revset: {error}"))?;
+
+        let mut changes = Vec::new();
+        for node in evaluated.iter_graph().take(limit) {
+            let (commit_id, edges) =
+                node.map_err(|error| anyhow!("failed to evaluate revset: {error}"))?;
+            let parents = edges
+                .into_iter()
+                .map(|edge| ChangeEdge {
+                    target: edge.target.hex().into(),
+                    kind: match edge.edge_type {
+                        GraphEdgeType::Direct => ChangeEdgeKind::Direct,
+                        GraphEdgeType::Indirect => ChangeEdgeKind::Indirect,
+                        GraphEdgeType::Missing => ChangeEdgeKind::Missing,
+                    },
+                })
+                .collect();
+            changes.push(self.to_change(&repo, &commit_id, parents)?);
+        }
+
+        Ok(changes)
+    }
+
+    fn load_change_diff(&self, commit_id: &str) -> Result<ChangeDiff> {
+        let repo = self.load_head();
+        let commit_id = CommitId::try_from_hex(commit_id)
+            .with_context(|| format!("invalid commit id {commit_id}"))?;
+        let commit = repo.store().get_commit(&commit_id)?;
+        let from_tree = commit.parent_tree(repo.as_ref())?;
+        let to_tree = commit.tree()?;
+
+        let entries = block_on(
+            from_tree
+                .diff_stream(&to_tree, &EverythingMatcher)
+                .collect::<Vec<_>>(),
+        );
+
+        let mut files = Vec::new();
+        for entry in entries {
+            let (before, after) = entry.values?;
+            let old_text = materialize_text(repo.store(), &entry.path, before)?;
+            let new_text = materialize_text(repo.store(), &entry.path, after)?;
+            files.push(ChangeFile {
+                path: Path::new(entry.path.as_internal_file_string()).into(),
+                old_text,
+                new_text,
+            });
+        }
+
+        Ok(ChangeDiff { files })
+    }
+
+    fn new_change(&self, parent_commit_ids: &[SharedString]) -> Result<()> {
+        self.transact("new empty commit", |repo, workspace_name| {
+            let parents = parent_commit_ids
+                .iter()
+                .map(|commit_id| resolve_commit(repo, commit_id))
+                .collect::<Result<Vec<_>>>()?;
+            anyhow::ensure!(!parents.is_empty(), "no parents given");
+            let tree = merge_commit_trees(&*repo, &parents)?;
+            let parent_ids = parents.iter().map(|parent| parent.id().clone()).collect();
+            let commit = repo.new_commit(parent_ids, tree.id()).write()?;
+            repo.edit(workspace_name.clone(), &commit)?;
+            Ok(())
+        })
+    }
+
+    fn describe(&self, commit_id: &str, description: &str) -> Result<()> {
+        self.transact("describe commit", |repo, _| {
+            let commit = resolve_mutable_commit(repo, commit_id)?;
+            repo.rewrite_commit(&commit)
+                .set_description(description)
+                .write()?;
+            Ok(())
+        })
+    }
+
+    fn squash(&self, commit_id: &str, paths: Option<&[Arc<Path>]>) -> Result<()> {
+        self.transact("squash commit into its parent", |repo, _| {
+            let commit = resolve_mutable_commit(repo, commit_id)?;
+            let [parent_id] = commit.parent_ids() else {
+                anyhow::bail!("cannot squash a merge commit");
+            };
+            let parent = resolve_mutable_commit(repo, &parent_id.hex())?;
+
+            let parent_tree = commit.parent_tree(&*repo)?;
+            let selected_tree = match paths {
+                Some(paths) => {
+                    let paths = paths
+                        .iter()
+                        .map(RepoPathBuf::from_relative_path)
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let matcher = PrefixMatcher::new(&paths);
+                    let tree_id = restore_tree(&commit.tree()?, &parent_tree, &matcher)?;
+                    repo.store().get_root_tree(&tree_id)?
+                }
+                None => commit.tree()?,
+            };
+            let source = CommitWithSelection {
+                commit: commit.clone(),
+                selected_tree,
+                parent_tree,
+            };
+
+            let description = if source.is_full_selection() {
+                combine_descriptions(parent.description(), commit.description())
+            } else {
+                parent.description().to_string()
+            };
+
+            let Some(squashed) = squash_commits(repo, &[source], &parent, false)? else {
+                return Ok(());
+            };
+            squashed
+                .commit_builder
+                .set_description(description)
+                .write()?;
+            Ok(())
+        })
+    }
+
+    fn edit(&self, commit_id: &str) -> Result<()> {
+        self.transact("edit commit", |repo, workspace_name| {
+            let commit = resolve_mutable_commit(repo, commit_id)?;
+            repo.edit(workspace_name.clone(), &commit)?;
+            Ok(())
+        })
+    }
+
+    fn abandon(&self, commit_id: &str) -> Result<()> {
+        self.transact("abandon commit", |repo, _| {
+            let commit = resolve_mutable_commit(repo, commit_id)?;
+            repo.record_abandoned_commit(&commit);
+            Ok(())
+        })
+    }
+
+    fn update_bookmark(&self, name: &str, commit_id: &str, update: BookmarkUpdate) -> Result<()> {
+        let description = match update {
+            BookmarkUpdate::Create => format!("create bookmark {name}"),
+            BookmarkUpdate::Set => format!("point bookmark {name} to commit {commit_id}"),
+            BookmarkUpdate::Move => format!("move bookmark {name} to commit {commit_id}"),
+        };
+        self.transact(&description, |repo, _| {
+            let commit = resolve_commit(repo, commit_id)?;
+            let ref_name = RefName::new(name);
+            let exists = repo.get_local_bookmark(ref_name).is_present();
+            match update {
+                BookmarkUpdate::Create if exists => {
+                    anyhow::bail!("bookmark {name} already exists")
+                }
+                BookmarkUpdate::Move if !exists => anyhow::bail!("no such bookmark: {name}"),
+                BookmarkUpdate::Create | BookmarkUpdate::Set | BookmarkUpdate::Move => {}
+            }
+            repo.set_local_bookmark_target(ref_name, RefTarget::normal(commit.id().clone()));
+            Ok(())
+        })
+    }
+
+    fn undo(&self) -> Result<()> {
+        let mut workspace = Self::load_workspace(&self.workspace_root, &self.settings)?;
+        let repo = self.snapshot_workspace(&mut workspace)?;
+
+        let mut bad_operation = repo.operation().clone();
+        while bad_operation.metadata().is_snapshot {
+            bad_operation = bad_operation
+                .parents()
+                .next()
+                .context("cannot undo the root operation")??;
+        }
+        anyhow::ensure!(
+            bad_operation.parent_ids().len() == 1,
+            "cannot undo a merge operation"
+        );
+        let parent_operation = bad_operation
+            .parents()
+            .next()
+            .context("cannot undo the root operation")??;
+
+        let bad_repo = self.repo_loader.load_at(&bad_operation)?;
+        let parent_repo = self.repo_loader.load_at(&parent_operation)?;
+        let mut transaction = repo.start_transaction();
+        transaction.repo_mut().merge(&bad_repo, &parent_repo)?;
+        let repo = transaction.commit(format!("undo operation {}", bad_operation.id().hex()))?;
+
+        self.update_working_copy(&mut workspace, &repo)
+    }
+
+    fn snapshot_working_copy(&self) -> Result<()> {
+        let mut workspace = Self::load_workspace(&self.workspace_root, &self.settings)?;
+        self.snapshot_workspace(&mut workspace)?;
+        Ok(())
+    }
+
+    fn status(&self) -> Result<Vec<FileChange>> {
+        let repo = self.load_head();
+        let Some(wc_commit_id) = repo.view().get_wc_commit_id(&self.workspace_name) else {
+            return Ok(Vec::new());
+        };
+        let wc_commit = repo.store().get_commit(wc_commit_id)?;
+        let from_tree = wc_commit.parent_tree(repo.as_ref())?;
+        let to_tree = wc_commit.tree()?;
+
+        let entries = block_on(
+            from_tree
+                .diff_stream(&to_tree, &EverythingMatcher)
+                .collect::<Vec<_>>(),
+        );
+
+        let mut changes = Vec::new();
+        for entry in entries {
+            let (before, after) = entry.values?;
+            let kind = if !after.is_resolved() {
+                FileChangeKind::Conflicted
+            } else if before.is_absent() {
+                FileChangeKind::Added
+            } else if after.is_absent() {
+                FileChangeKind::Deleted
+            } else {
+                FileChangeKind::Modified
+            };
+            changes.push(FileChange {
+                path: Path::new(entry.path.as_internal_file_string()).into(),
+                kind,
+            });
+        }
+
+        Ok(changes)
+    }
+
+    fn working_copy_change(&self) -> Result<Option<Change>> {
+        let repo = self.load_head();
+        let Some(wc_commit_id) = repo.view().get_wc_commit_id(&self.workspace_name) else {
+            return Ok(None);
+        };
+        let wc_commit = repo.store().get_commit(wc_commit_id)?;
+        let parents = wc_commit
+            .parent_ids()
+            .iter()
+            .map(|parent_id| ChangeEdge {
+                target: parent_id.hex().into(),
+                kind: ChangeEdgeKind::Direct,
+            })
+            .collect();
+        Ok(Some(self.to_change(&repo, wc_commit_id, parents)?))
+    }
+
+    fn load_parent_text(&self, path: &Path) -> Result<Option<String>> {
+        let repo = self.load_head();
+        let Some(wc_commit_id) = repo.view().get_wc_commit_id(&self.workspace_name) else {
+            return Ok(None);
+        };
+        let wc_commit = repo.store().get_commit(wc_commit_id)?;
+        let parent_tree = wc_commit.parent_tree(repo.as_ref())?;
+        let repo_path = RepoPathBuf::from_relative_path(path)?;
+        let value = parent_tree.path_value(&repo_path)?;
+        materialize_text(repo.store(), &repo_path, value)
+    }
 }
 
-pub struct FakeJujutsuRepository {}
+/// The ignore rules that apply on top of the repository's `.gitignore` files:
+/// git's global excludes file and, for git-backed repositories, `info/exclude`.
+/// Mirrors how jj-cli builds them, so snapshots skip the same files `jj` would.
+fn base_ignores(repo: &ReadonlyRepo) -> Result<Arc<GitIgnoreFile>> {
+    let mut ignores = GitIgnoreFile::empty();
+    let git_backend = repo.store().backend_impl().downcast_ref::<GitBackend>();
+    let excludes_file = match git_backend {
+        Some(git_backend) => {
+            let config = git_backend.git_repo().config_snapshot();
+            match config.string("core.excludesFile") {
+                Some(value) => std::str::from_utf8(&value).ok().map(expand_home_path),
+                None => Some(default_excludes_file()),
+            }
+        }
+        None => Some(default_excludes_file()),
+    };
+    if let Some(excludes_file) = excludes_file {
+        ignores = ignores.chain_with_file("", excludes_file)?;
+    }
+    if let Some(git_backend) = git_backend {
+        let info_exclude = git_backend.git_repo_path().join("info").join("exclude");
+        ignores = ignores.chain_with_file("", info_exclude)?;
+    }
+    Ok(ignores)
+}
 
-impl JujutsuRepository for FakeJujutsuRepository {
-    fn list_bookmarks(&self) -> Vec<Bookmark> {
-        Vec::new()
+/// Git's default global excludes file, `$XDG_CONFIG_HOME/git/ignore`.
+fn default_excludes_file() -> PathBuf {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .unwrap_or_else(|| paths::home_dir().join(".config"));
+    config_home.join("git").join("ignore")
+}
+
+fn expand_home_path(path: &str) -> PathBuf {
+    if let Some(relative) = path.strip_prefix("~/") {
+        return paths::home_dir().join(relative);
+    }
+    PathBuf::from(path)
+}
+
+fn resolve_commit(repo: &MutableRepo, commit_id: &str) -> Result<Commit> {
+    let commit_id = CommitId::try_from_hex(commit_id)
+        .with_context(|| format!("invalid commit id {commit_id}"))?;
+    Ok(repo.store().get_commit(&commit_id)?)
+}
+
+fn resolve_mutable_commit(repo: &MutableRepo, commit_id: &str) -> Result<Commit> {
+    let commit = resolve_commit(repo, commit_id)?;
+    anyhow::ensure!(
+        commit.id() != repo.store().root_commit_id(),
+        "cannot rewrite the root commit"
+    );
+    Ok(commit)
+}
+
+/// Joins the descriptions of two changes that are being squashed together, the
+/// way `jj squash` does when neither description is empty.
+pub(crate) fn combine_descriptions(destination: &str, source: &str) -> String {
+    match (destination.trim().is_empty(), source.trim().is_empty()) {
+        (true, _) => source.to_string(),
+        (false, true) => destination.to_string(),
+        (false, false) => format!("{}\n\n{}", destination.trim_end(), source),
+    }
+}
+
+/// Reads the textual contents of a tree value, materializing conflicts with
+/// conflict markers. Returns `None` for absent values and non-file entries.
+fn materialize_text(
+    store: &Arc<Store>,
+    path: &RepoPath,
+    value: Merge<Option<TreeValue>>,
+) -> Result<Option<String>> {
+    let text = match block_on(materialize_tree_value(store, path, value))? {
+        MaterializedTreeValue::File(mut file) => {
+            Some(String::from_utf8_lossy(&file.read_all(path)?).into_owned())
+        }
+        MaterializedTreeValue::Symlink { target, .. } => Some(target),
+        MaterializedTreeValue::FileConflict(file) => Some(
+            String::from_utf8_lossy(&materialize_merge_result_to_bytes(
+                &file.contents,
+                ConflictMarkerStyle::Diff,
+            ))
+            .into_owned(),
+        ),
+        MaterializedTreeValue::Absent
+        | MaterializedTreeValue::AccessDenied(_)
+        | MaterializedTreeValue::OtherConflict { .. }
+        | MaterializedTreeValue::GitSubmodule(_)
+        | MaterializedTreeValue::Tree(_) => None,
+    };
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn init_repository() -> (TempDir, RealJujutsuRepository) {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = UserSettings::from_config(StackedConfig::with_defaults()).unwrap();
+        Workspace::init_internal_git(&settings, dir.path()).unwrap();
+        let repository = RealJujutsuRepository::new(dir.path()).unwrap();
+        (dir, repository)
+    }
+
+    fn log(repository: &RealJujutsuRepository) -> Vec<Change> {
+        repository.log("all()", usize::MAX).unwrap()
+    }
+
+    fn working_copy(repository: &RealJujutsuRepository) -> Change {
+        repository.working_copy_change().unwrap().unwrap()
+    }
+
+    fn change(repository: &RealJujutsuRepository, change_id: &str) -> Option<Change> {
+        log(repository)
+            .into_iter()
+            .find(|change| change.change_id.as_ref() == change_id)
+    }
+
+    /// Writes a file into the working copy and snapshots it, so that the commit id of the
+    /// working-copy change stays current.
+    fn write(dir: &TempDir, repository: &RealJujutsuRepository, path: &str, text: &str) {
+        std::fs::write(dir.path().join(path), text).unwrap();
+        repository.snapshot_working_copy().unwrap();
+    }
+
+    #[test]
+    fn test_status_after_snapshot() {
+        let (dir, repository) = init_repository();
+        std::fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        assert_eq!(repository.status().unwrap(), []);
+
+        repository.snapshot_working_copy().unwrap();
+        assert_eq!(
+            repository.status().unwrap(),
+            [FileChange {
+                path: Path::new("a.txt").into(),
+                kind: FileChangeKind::Added,
+            }]
+        );
+        assert_eq!(
+            repository.load_parent_text(Path::new("a.txt")).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_new_describe_and_squash() {
+        let (dir, repository) = init_repository();
+        write(&dir, &repository, "a.txt", "one\n");
+        repository
+            .describe(&working_copy(&repository).commit_id, "first")
+            .unwrap();
+        let first = working_copy(&repository);
+        assert_eq!(first.description.as_ref(), "first");
+
+        repository
+            .new_change(std::slice::from_ref(&first.commit_id))
+            .unwrap();
+        let second = working_copy(&repository);
+        assert_ne!(second.change_id, first.change_id);
+        assert!(second.is_empty);
+        assert_eq!(
+            repository.load_parent_text(Path::new("a.txt")).unwrap(),
+            Some("one\n".to_string())
+        );
+
+        write(&dir, &repository, "a.txt", "two\n");
+        write(&dir, &repository, "b.txt", "b\n");
+        repository
+            .describe(&working_copy(&repository).commit_id, "second")
+            .unwrap();
+        repository
+            .squash(
+                &working_copy(&repository).commit_id,
+                Some(&[Arc::from(Path::new("a.txt"))]),
+            )
+            .unwrap();
+        assert_eq!(
+            repository.status().unwrap(),
+            [FileChange {
+                path: Path::new("b.txt").into(),
+                kind: FileChangeKind::Added,
+            }]
+        );
+        assert_eq!(
+            repository.load_parent_text(Path::new("a.txt")).unwrap(),
+            Some("two\n".to_string())
+        );
+        assert_eq!(
+            change(&repository, &first.change_id)
+                .unwrap()
+                .description
+                .as_ref(),
+            "first"
+        );
+
+        repository
+            .squash(&working_copy(&repository).commit_id, None)
+            .unwrap();
+        assert_eq!(repository.status().unwrap(), []);
+        assert!(change(&repository, &second.change_id).is_none());
+        assert_eq!(
+            change(&repository, &first.change_id)
+                .unwrap()
+                .description
+                .as_ref(),
+            "first\n\nsecond"
+        );
+        assert_eq!(
+            repository.load_parent_text(Path::new("b.txt")).unwrap(),
+            Some("b\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_edit_abandon_and_undo() {
+        let (dir, repository) = init_repository();
+        write(&dir, &repository, "a.txt", "one\n");
+        let first = working_copy(&repository);
+        repository.describe(&first.commit_id, "first").unwrap();
+        let first = working_copy(&repository);
+        repository
+            .new_change(std::slice::from_ref(&first.commit_id))
+            .unwrap();
+        write(&dir, &repository, "b.txt", "b\n");
+        let second = working_copy(&repository);
+
+        repository.edit(&first.commit_id).unwrap();
+        assert_eq!(working_copy(&repository).change_id, first.change_id);
+        assert!(!dir.path().join("b.txt").exists());
+
+        repository.abandon(&second.commit_id).unwrap();
+        assert!(change(&repository, &second.change_id).is_none());
+
+        repository.undo().unwrap();
+        assert!(change(&repository, &second.change_id).is_some());
+        assert_eq!(working_copy(&repository).change_id, first.change_id);
+    }
+
+    #[test]
+    fn test_update_bookmarks() {
+        let (dir, repository) = init_repository();
+        write(&dir, &repository, "a.txt", "one\n");
+        let first = working_copy(&repository);
+        repository.describe(&first.commit_id, "first").unwrap();
+        let first = working_copy(&repository);
+        repository
+            .new_change(std::slice::from_ref(&first.commit_id))
+            .unwrap();
+        let second = working_copy(&repository);
+
+        repository
+            .update_bookmark("main", &first.commit_id, BookmarkUpdate::Create)
+            .unwrap();
+        assert!(
+            repository
+                .update_bookmark("main", &second.commit_id, BookmarkUpdate::Create)
+                .is_err()
+        );
+        assert!(
+            repository
+                .update_bookmark("feature", &second.commit_id, BookmarkUpdate::Move)
+                .is_err()
+        );
+        repository
+            .update_bookmark("feature", &second.commit_id, BookmarkUpdate::Set)
+            .unwrap();
+        repository
+            .update_bookmark("main", &second.commit_id, BookmarkUpdate::Move)
+            .unwrap();
+
+        let mut bookmarks = repository
+            .list_bookmarks()
+            .into_iter()
+            .map(|bookmark| bookmark.ref_name.to_string())
+            .collect::<Vec<_>>();
+        bookmarks.sort();
+        assert_eq!(bookmarks, ["feature", "main"]);
+        assert_eq!(
+            change(&repository, &second.change_id).unwrap().bookmarks,
+            [SharedString::from("feature"), SharedString::from("main")]
+        );
     }
 }
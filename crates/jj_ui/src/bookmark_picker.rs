@@ -5,7 +5,7 @@ use gpui::{
     App, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Task, WeakEntity, Window,
     prelude::*,
 };
-use jj::{Bookmark, JujutsuStore};
+use jj::{Bookmark, JujutsuRepository};
 use picker::{Picker, PickerDelegate};
 use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
 use util::ResultExt as _;
@@ -21,12 +21,12 @@ fn open(
     window: &mut Window,
     cx: &mut Context<Workspace>,
 ) {
-    let Some(jj_store) = JujutsuStore::try_global(cx) else {
+    let Some(repository) = crate::active_repository(workspace, cx) else {
         return;
     };
 
     workspace.toggle_modal(window, cx, |window, cx| {
-        let delegate = BookmarkPickerDelegate::new(cx.entity().downgrade(), jj_store, cx);
+        let delegate = BookmarkPickerDelegate::new(cx.entity().downgrade(), repository);
         BookmarkPicker::new(delegate, window, cx)
     });
 }
@@ -76,12 +76,8 @@ pub struct BookmarkPickerDelegate {
 }
 
 impl BookmarkPickerDelegate {
-    fn new(
-        picker: WeakEntity<BookmarkPicker>,
-        jj_store: Entity<JujutsuStore>,
-        cx: &mut Context<BookmarkPicker>,
-    ) -> Self {
-        let bookmarks = jj_store.read(cx).repository().list_bookmarks();
+    fn new(picker: WeakEntity<BookmarkPicker>, repository: Arc<dyn JujutsuRepository>) -> Self {
+        let bookmarks = repository.list_bookmarks();
 
         Self {
             picker,
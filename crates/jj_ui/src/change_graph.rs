@@ -0,0 +1,171 @@
+use jj::{Change, ChangeEdgeKind};
+use ui::utils::GraphLayout;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphCell {
+    Empty,
+    /// The change displayed on this row.
+    Node,
+    /// A lane passing through this row on the way to an ancestor.
+    Line,
+    /// Like [`GraphCell::Line`], but with changes elided between the endpoints.
+    ElidedLine,
+    /// A lane that ends at this row because it converges into the node.
+    Join,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphRow {
+    pub cells: Vec<GraphCell>,
+}
+
+impl GraphRow {
+    /// Renders the row as text, using `node` for the node cell.
+    pub fn to_text(&self, node: &str) -> String {
+        let mut text = String::new();
+        for cell in &self.cells {
+            text.push_str(match cell {
+                GraphCell::Empty => " ",
+                GraphCell::Node => node,
+                GraphCell::Line => "│",
+                GraphCell::ElidedLine => "╎",
+                GraphCell::Join => "╯",
+            });
+            text.push(' ');
+        }
+        text
+    }
+}
+
+/// Assigns each change to a column, so that every change is drawn in the same
+/// column as the lane that leads to it from its children.
+///
+/// `changes` must be in topological order, children before parents, which is
+/// the order in which `JujutsuRepository::log` returns them.
+pub fn layout_graph(changes: &[Change]) -> Vec<GraphRow> {
+    let mut layout = GraphLayout::default();
+    // The kind of the edge that each lane follows below the last row.
+    let mut lane_kinds: Vec<Option<ChangeEdgeKind>> = Vec::new();
+    let mut rows = Vec::with_capacity(changes.len());
+
+    for change in changes {
+        let parents = change
+            .parents
+            .iter()
+            .filter(|edge| edge.kind != ChangeEdgeKind::Missing)
+            .collect::<Vec<_>>();
+        let parent_ids = parents
+            .iter()
+            .map(|edge| edge.target.clone())
+            .collect::<Vec<_>>();
+        let row = layout.push(&change.commit_id, &parent_ids);
+
+        let mut cells = vec![GraphCell::Empty; row.width()];
+        for segment in &row.incoming {
+            cells[segment.from_lane] = if segment.from_lane != segment.to_lane {
+                GraphCell::Join
+            } else if lane_kinds.get(segment.from_lane) == Some(&Some(ChangeEdgeKind::Indirect)) {
+                GraphCell::ElidedLine
+            } else {
+                GraphCell::Line
+            };
+        }
+        cells[row.lane] = GraphCell::Node;
+        rows.push(GraphRow { cells });
+
+        // Lanes passing through keep their kind, and a parent that was already tracked
+        // keeps the kind of the lane that reached it first.
+        let mut parent_kinds = parents.iter().map(|edge| edge.kind);
+        let mut next_lane_kinds = Vec::new();
+        for segment in &row.outgoing {
+            let kind = if segment.from_lane == row.lane {
+                parent_kinds.next()
+            } else {
+                lane_kinds.get(segment.from_lane).copied().flatten()
+            };
+            if next_lane_kinds.len() <= segment.to_lane {
+                next_lane_kinds.resize(segment.to_lane + 1, None);
+            }
+            if next_lane_kinds[segment.to_lane].is_none() {
+                next_lane_kinds[segment.to_lane] = kind;
+            }
+        }
+        lane_kinds = next_lane_kinds;
+    }
+
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::SharedString;
+    use jj::ChangeEdge;
+
+    fn change(commit_id: &str, parents: &[(&str, ChangeEdgeKind)]) -> Change {
+        Change {
+            change_id: commit_id.to_string().into(),
+            commit_id: commit_id.to_string().into(),
+            description: SharedString::default(),
+            author_name: SharedString::default(),
+            author_email: SharedString::default(),
+            timestamp: 0,
+            bookmarks: Vec::new(),
+            is_working_copy: false,
+            is_empty: false,
+            has_conflict: false,
+            parents: parents
+                .iter()
+                .map(|(target, kind)| ChangeEdge {
+                    target: target.to_string().into(),
+                    kind: *kind,
+                })
+                .collect(),
+        }
+    }
+
+    fn render(changes: &[Change]) -> Vec<String> {
+        layout_graph(changes)
+            .iter()
+            .map(|row| row.to_text("o").trim_end().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_linear_history() {
+        let changes = [
+            change("c", &[("b", ChangeEdgeKind::Direct)]),
+            change("b", &[("a", ChangeEdgeKind::Indirect)]),
+            change("a", &[]),
+        ];
+        assert_eq!(render(&changes), ["o", "o", "o"]);
+    }
+
+    #[test]
+    fn test_fork_and_merge() {
+        let changes = [
+            change(
+                "merge",
+                &[
+                    ("left", ChangeEdgeKind::Direct),
+                    ("right", ChangeEdgeKind::Direct),
+                ],
+            ),
+            change("left", &[("base", ChangeEdgeKind::Direct)]),
+            change("right", &[("base", ChangeEdgeKind::Indirect)]),
+            change("base", &[("root", ChangeEdgeKind::Missing)]),
+        ];
+        assert_eq!(render(&changes), ["o", "o │", "│ o", "o ╯"]);
+    }
+
+    #[test]
+    fn test_unrelated_heads_reuse_free_columns() {
+        let changes = [
+            change("x", &[("base", ChangeEdgeKind::Direct)]),
+            change("y", &[("base", ChangeEdgeKind::Indirect)]),
+            change("base", &[]),
+            change("other", &[]),
+        ];
+        assert_eq!(render(&changes), ["o", "│ o", "o ╯", "o"]);
+    }
+}
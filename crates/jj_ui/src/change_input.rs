@@ -0,0 +1,91 @@
+use editor::{Editor, EditorEvent, actions::SelectAll};
+use gpui::{App, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Subscription};
+use ui::prelude::*;
+use workspace::ModalView;
+
+type ConfirmCallback = Box<dyn FnOnce(String, &mut Window, &mut App)>;
+
+/// A single-line prompt used to collect a description or bookmark name before
+/// running a Jujutsu operation.
+pub struct ChangeInput {
+    editor: Entity<Editor>,
+    label: SharedString,
+    on_confirm: Option<ConfirmCallback>,
+    _subscription: Subscription,
+}
+
+impl ModalView for ChangeInput {}
+
+impl EventEmitter<DismissEvent> for ChangeInput {}
+
+impl Focusable for ChangeInput {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl ChangeInput {
+    pub fn new(
+        label: impl Into<SharedString>,
+        initial_text: &str,
+        on_confirm: impl FnOnce(String, &mut Window, &mut App) + 'static,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_text(initial_text, window, cx);
+            editor.select_all(&SelectAll, window, cx);
+            editor
+        });
+        let subscription = cx.subscribe_in(&editor, window, |_, _, event, _, cx| {
+            if let EditorEvent::Blurred = event {
+                cx.emit(DismissEvent);
+            }
+        });
+
+        Self {
+            editor,
+            label: label.into(),
+            on_confirm: Some(Box::new(on_confirm)),
+            _subscription: subscription,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let text = self.editor.read(cx).text(cx);
+        if let Some(on_confirm) = self.on_confirm.take() {
+            on_confirm(text, window, cx);
+        }
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for ChangeInput {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .w(rems(34.))
+            .elevation_2(cx)
+            .key_context("JujutsuChangeInput")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .child(Label::new(self.label.clone()).color(Color::Muted)),
+            )
+            .child(
+                div()
+                    .border_t_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .px_2()
+                    .py_1()
+                    .child(self.editor.clone()),
+            )
+    }
+}
@@ -0,0 +1,667 @@
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use editor::{Editor, EditorEvent};
+use git::repository::{CommitDetails, CommitDiff, CommitFile, RepoPath};
+use git_ui::commit_view::CommitView;
+use gpui::{
+    AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable, KeyContext, ScrollStrategy,
+    Subscription, Task, UniformListScrollHandle, WeakEntity, uniform_list,
+};
+use jj::{BookmarkUpdate, Change, ChangeDiff, DEFAULT_LOG_REVSET, JujutsuRepository, LOG_LIMIT};
+use project::git_store::jj_store::JujutsuStore;
+use ui::{ListItem, ListItemSpacing, WithScrollbar, prelude::*};
+use workspace::{Item, Workspace, item::ItemEvent};
+
+use crate::change_graph::{GraphRow, layout_graph};
+use crate::change_input::ChangeInput;
+
+const REVSET_DEBOUNCE: Duration = Duration::from_millis(300);
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(open);
+}
+
+fn open(
+    workspace: &mut Workspace,
+    _: &zed_actions::jj::Log,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let jj_store = workspace.project().read(cx).jj_store(cx);
+    let Some(repository) = crate::active_repository(workspace, cx) else {
+        return;
+    };
+
+    if let Some(existing) = workspace.item_of_type::<ChangeLog>(cx) {
+        workspace.activate_item(&existing, true, true, window, cx);
+        return;
+    }
+
+    let workspace_handle = workspace.weak_handle();
+    let change_log =
+        cx.new(|cx| ChangeLog::new(jj_store, repository, workspace_handle, window, cx));
+    workspace.add_item_to_active_pane(Box::new(change_log), None, true, window, cx);
+}
+
+/// Shows the graph of changes matched by a user-provided revset.
+pub struct ChangeLog {
+    jj_store: Entity<JujutsuStore>,
+    repository: Arc<dyn JujutsuRepository>,
+    workspace: WeakEntity<Workspace>,
+    focus_handle: FocusHandle,
+    revset_editor: Entity<Editor>,
+    scroll_handle: UniformListScrollHandle,
+    changes: Vec<Change>,
+    rows: Vec<GraphRow>,
+    selected_ix: Option<usize>,
+    error: Option<SharedString>,
+    _load_task: Task<()>,
+    _operation_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ChangeLog {
+    pub fn new(
+        jj_store: Entity<JujutsuStore>,
+        repository: Arc<dyn JujutsuRepository>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let revset_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text(DEFAULT_LOG_REVSET, window, cx);
+            editor
+        });
+
+        let subscriptions =
+            vec![
+                cx.subscribe(&revset_editor, |this, _, event: &EditorEvent, cx| {
+                    if let EditorEvent::BufferEdited = event {
+                        this.reload(true, cx);
+                    }
+                }),
+            ];
+
+        let mut this = Self {
+            jj_store,
+            repository,
+            workspace,
+            focus_handle: cx.focus_handle(),
+            revset_editor,
+            scroll_handle: UniformListScrollHandle::new(),
+            changes: Vec::new(),
+            rows: Vec::new(),
+            selected_ix: None,
+            error: None,
+            _load_task: Task::ready(()),
+            _operation_task: Task::ready(()),
+            _subscriptions: subscriptions,
+        };
+        this.reload(false, cx);
+        this
+    }
+
+    fn revset(&self, cx: &App) -> String {
+        let revset = self.revset_editor.read(cx).text(cx);
+        if revset.trim().is_empty() {
+            DEFAULT_LOG_REVSET.to_string()
+        } else {
+            revset
+        }
+    }
+
+    fn reload(&mut self, debounce: bool, cx: &mut Context<Self>) {
+        let revset = self.revset(cx);
+        let repository = self.repository.clone();
+
+        self._load_task = cx.spawn(async move |this, cx| {
+            if debounce {
+                cx.background_executor().timer(REVSET_DEBOUNCE).await;
+            }
+
+            let result = cx
+                .background_spawn(async move { repository.log(&revset, LOG_LIMIT) })
+                .await;
+
+            this.update(cx, |this, cx| {
+                match result {
+                    Ok(changes) => {
+                        let selected_change_id = this
+                            .selected_ix
+                            .and_then(|ix| this.changes.get(ix))
+                            .map(|change| change.change_id.clone());
+                        this.rows = layout_graph(&changes);
+                        this.changes = changes;
+                        this.error = None;
+                        this.selected_ix = selected_change_id
+                            .and_then(|change_id| {
+                                this.changes
+                                    .iter()
+                                    .position(|change| change.change_id == change_id)
+                            })
+                            .or_else(|| {
+                                this.changes
+                                    .iter()
+                                    .position(|change| change.is_working_copy)
+                            })
+                            .or_else(|| (!this.changes.is_empty()).then_some(0));
+                    }
+                    Err(error) => {
+                        this.error = Some(format!("{error:#}").into());
+                    }
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn open_change(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(change) = self.changes.get(ix).cloned() else {
+            return;
+        };
+        let repository = self.repository.clone();
+        let workspace = self.workspace.clone();
+
+        cx.spawn_in(window, async move |_, cx| {
+            let commit_id = change.commit_id.clone();
+            let diff = cx
+                .background_spawn(async move { repository.load_change_diff(&commit_id) })
+                .await?;
+
+            workspace.update_in(cx, |workspace, window, cx| {
+                CommitView::open_with_diff(
+                    commit_details(&change),
+                    commit_diff(diff),
+                    workspace,
+                    window,
+                    cx,
+                );
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn selected_change(&self) -> Option<&Change> {
+        self.selected_ix.and_then(|ix| self.changes.get(ix))
+    }
+
+    /// Runs a mutating operation against the repository in the background and
+    /// reloads the log once it completes.
+    fn run_operation(
+        &mut self,
+        operation: impl FnOnce(&dyn JujutsuRepository) -> Result<()> + Send + 'static,
+        cx: &mut Context<Self>,
+    ) {
+        let repository = self.repository.clone();
+        self._operation_task = cx.spawn(async move |this, cx| {
+            let result = cx
+                .background_spawn(async move { operation(repository.as_ref()) })
+                .await;
+
+            this.update(cx, |this, cx| match result {
+                Ok(()) => {
+                    this.jj_store
+                        .update(cx, |jj_store, cx| jj_store.refresh(cx));
+                    this.reload(false, cx);
+                }
+                Err(error) => {
+                    this.error = Some(format!("{error:#}").into());
+                    cx.notify();
+                }
+            })
+            .ok();
+        });
+    }
+
+    /// Prompts for a single line of text and runs `operation` with it.
+    fn prompt_for_operation(
+        &mut self,
+        label: impl Into<SharedString>,
+        initial_text: &str,
+        operation: impl FnOnce(&dyn JujutsuRepository, String) -> Result<()> + Send + 'static,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let this = cx.entity().downgrade();
+        let label = label.into();
+        let initial_text = initial_text.to_string();
+        workspace.update(cx, |workspace, cx| {
+            workspace.toggle_modal(window, cx, move |window, cx| {
+                let on_confirm = move |text: String, _: &mut Window, cx: &mut App| {
+                    this.update(cx, |this, cx| {
+                        this.run_operation(move |repository| operation(repository, text), cx);
+                    })
+                    .ok();
+                };
+                ChangeInput::new(label, &initial_text, on_confirm, window, cx)
+            });
+        });
+    }
+
+    fn new_change(
+        &mut self,
+        _: &zed_actions::jj::NewChange,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(change) = self.selected_change() else {
+            return;
+        };
+        let parent_commit_ids = [change.commit_id.clone()];
+        self.run_operation(
+            move |repository| repository.new_change(&parent_commit_ids),
+            cx,
+        );
+    }
+
+    fn describe_change(
+        &mut self,
+        _: &zed_actions::jj::DescribeChange,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(change) = self.selected_change() else {
+            return;
+        };
+        let commit_id = change.commit_id.clone();
+        let label = format!("Describe change {}", change.short_change_id());
+        let description = change.description.trim_end().to_string();
+        self.prompt_for_operation(
+            label,
+            &description,
+            move |repository, description| repository.describe(&commit_id, &description),
+            window,
+            cx,
+        );
+    }
+
+    fn squash_change(
+        &mut self,
+        _: &zed_actions::jj::SquashChange,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(change) = self.selected_change() else {
+            return;
+        };
+        let commit_id = change.commit_id.clone();
+        self.run_operation(move |repository| repository.squash(&commit_id, None), cx);
+    }
+
+    fn squash_change_paths(
+        &mut self,
+        _: &zed_actions::jj::SquashChangePaths,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(change) = self.selected_change() else {
+            return;
+        };
+        let commit_id = change.commit_id.clone();
+        let label = format!(
+            "Squash paths of change {} into its parent",
+            change.short_change_id()
+        );
+        self.prompt_for_operation(
+            label,
+            "",
+            move |repository, paths| {
+                let paths = paths
+                    .split_whitespace()
+                    .map(|path| Arc::from(Path::new(path)))
+                    .collect::<Vec<_>>();
+                anyhow::ensure!(!paths.is_empty(), "no paths given");
+                repository.squash(&commit_id, Some(&paths))
+            },
+            window,
+            cx,
+        );
+    }
+
+    fn edit_change(
+        &mut self,
+        _: &zed_actions::jj::EditChange,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(change) = self.selected_change() else {
+            return;
+        };
+        let commit_id = change.commit_id.clone();
+        self.run_operation(move |repository| repository.edit(&commit_id), cx);
+    }
+
+    fn abandon_change(
+        &mut self,
+        _: &zed_actions::jj::AbandonChange,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(change) = self.selected_change() else {
+            return;
+        };
+        let commit_id = change.commit_id.clone();
+        self.run_operation(move |repository| repository.abandon(&commit_id), cx);
+    }
+
+    fn update_bookmark(
+        &mut self,
+        update: BookmarkUpdate,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(change) = self.selected_change() else {
+            return;
+        };
+        let commit_id = change.commit_id.clone();
+        let label = match update {
+            BookmarkUpdate::Create => "Create bookmark at",
+            BookmarkUpdate::Set => "Set bookmark to",
+            BookmarkUpdate::Move => "Move bookmark to",
+        };
+        let label = format!("{label} {}", change.short_change_id());
+        let initial_name = change
+            .bookmarks
+            .first()
+            .map(|bookmark| bookmark.to_string())
+            .unwrap_or_default();
+        self.prompt_for_operation(
+            label,
+            &initial_name,
+            move |repository, name| {
+                let name = name.trim();
+                anyhow::ensure!(!name.is_empty(), "bookmark name must not be empty");
+                repository.update_bookmark(name, &commit_id, update)
+            },
+            window,
+            cx,
+        );
+    }
+
+    fn create_bookmark(
+        &mut self,
+        _: &zed_actions::jj::CreateBookmark,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_bookmark(BookmarkUpdate::Create, window, cx);
+    }
+
+    fn set_bookmark(
+        &mut self,
+        _: &zed_actions::jj::SetBookmark,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_bookmark(BookmarkUpdate::Set, window, cx);
+    }
+
+    fn move_bookmark(
+        &mut self,
+        _: &zed_actions::jj::MoveBookmark,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_bookmark(BookmarkUpdate::Move, window, cx);
+    }
+
+    fn undo(&mut self, _: &zed_actions::jj::Undo, _window: &mut Window, cx: &mut Context<Self>) {
+        self.run_operation(|repository| repository.undo(), cx);
+    }
+
+    fn select_ix(&mut self, ix: Option<usize>, cx: &mut Context<Self>) {
+        self.selected_ix = ix;
+        if let Some(ix) = ix {
+            self.scroll_handle
+                .scroll_to_item(ix, ScrollStrategy::Center);
+        }
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, _window: &mut Window, cx: &mut Context<Self>) {
+        let ix = match self.selected_ix {
+            _ if self.changes.is_empty() => None,
+            None => Some(0),
+            Some(ix) => Some((ix + 1).min(self.changes.len() - 1)),
+        };
+        self.select_ix(ix, cx);
+    }
+
+    fn select_previous(
+        &mut self,
+        _: &menu::SelectPrevious,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let ix = match self.selected_ix {
+            _ if self.changes.is_empty() => None,
+            None => Some(0),
+            Some(ix) => Some(ix.saturating_sub(1)),
+        };
+        self.select_ix(ix, cx);
+    }
+
+    fn select_first(
+        &mut self,
+        _: &menu::SelectFirst,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let ix = (!self.changes.is_empty()).then_some(0);
+        self.select_ix(ix, cx);
+    }
+
+    fn select_last(&mut self, _: &menu::SelectLast, _window: &mut Window, cx: &mut Context<Self>) {
+        let ix = self.changes.len().checked_sub(1);
+        self.select_ix(ix, cx);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.selected_ix {
+            self.open_change(ix, window, cx);
+        }
+    }
+
+    fn dispatch_context(&self, window: &Window, cx: &App) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("JujutsuLog");
+        if !self.revset_editor.focus_handle(cx).is_focused(window) {
+            dispatch_context.add("menu");
+        }
+        dispatch_context
+    }
+
+    fn render_entry(&self, ix: usize, cx: &mut Context<Self>) -> AnyElement {
+        let change = &self.changes[ix];
+        let row = &self.rows[ix];
+
+        let node = if change.is_working_copy {
+            "@"
+        } else if change.has_conflict {
+            "×"
+        } else {
+            "○"
+        };
+        let graph_color = if change.has_conflict {
+            Color::Conflict
+        } else if change.is_working_copy {
+            Color::Accent
+        } else {
+            Color::Muted
+        };
+
+        let description = if change.description.trim().is_empty() {
+            Label::new("(no description set)").color(Color::Muted)
+        } else {
+            Label::new(change.subject().to_string())
+        };
+
+        ListItem::new(ix)
+            .spacing(ListItemSpacing::Dense)
+            .toggle_state(Some(ix) == self.selected_ix)
+            .on_click(cx.listener(move |this, _, window, cx| {
+                this.select_ix(Some(ix), cx);
+                this.open_change(ix, window, cx);
+            }))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Label::new(row.to_text(node))
+                            .buffer_font(cx)
+                            .color(graph_color),
+                    )
+                    .child(
+                        Label::new(change.short_change_id())
+                            .buffer_font(cx)
+                            .color(Color::Accent),
+                    )
+                    .children(change.bookmarks.iter().map(|bookmark| {
+                        Label::new(bookmark.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Info)
+                    }))
+                    .when(change.has_conflict, |this| {
+                        this.child(Label::new("conflict").color(Color::Conflict))
+                    })
+                    .when(change.is_empty, |this| {
+                        this.child(Label::new("(empty)").color(Color::Muted))
+                    })
+                    .child(description.single_line().truncate())
+                    .child(
+                        Label::new(change.author_name.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        Label::new(change.short_commit_id())
+                            .buffer_font(cx)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .into_any_element()
+    }
+
+    fn render_list(&mut self, cx: &mut Context<Self>) -> impl IntoElement {
+        uniform_list(
+            "jj-change-log",
+            self.changes.len(),
+            cx.processor(|this, range: Range<usize>, _window, cx| {
+                range.map(|ix| this.render_entry(ix, cx)).collect()
+            }),
+        )
+        .track_scroll(self.scroll_handle.clone())
+        .size_full()
+    }
+}
+
+fn commit_details(change: &Change) -> CommitDetails {
+    CommitDetails {
+        sha: change.commit_id.clone(),
+        message: change.description.clone(),
+        commit_timestamp: change.timestamp,
+        author_email: change.author_email.clone(),
+        author_name: change.author_name.clone(),
+    }
+}
+
+fn commit_diff(diff: ChangeDiff) -> CommitDiff {
+    CommitDiff {
+        files: diff
+            .files
+            .into_iter()
+            .map(|file| CommitFile {
+                path: RepoPath(file.path),
+                old_text: file.old_text,
+                new_text: file.new_text,
+            })
+            .collect(),
+    }
+}
+
+impl EventEmitter<()> for ChangeLog {}
+
+impl Focusable for ChangeLog {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for ChangeLog {
+    type Event = ();
+
+    fn to_item_events(_: &Self::Event, _: impl FnMut(ItemEvent)) {}
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        "Jujutsu Log".into()
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch).color(Color::Muted))
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+impl Render for ChangeLog {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context(self.dispatch_context(window, cx))
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::new_change))
+            .on_action(cx.listener(Self::describe_change))
+            .on_action(cx.listener(Self::squash_change))
+            .on_action(cx.listener(Self::squash_change_paths))
+            .on_action(cx.listener(Self::edit_change))
+            .on_action(cx.listener(Self::abandon_change))
+            .on_action(cx.listener(Self::create_bookmark))
+            .on_action(cx.listener(Self::set_bookmark))
+            .on_action(cx.listener(Self::move_bookmark))
+            .on_action(cx.listener(Self::undo))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .px_2()
+                    .py_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Label::new("Revset").color(Color::Muted))
+                    .child(div().flex_1().child(self.revset_editor.clone())),
+            )
+            .when_some(self.error.clone(), |this, error| {
+                this.child(
+                    h_flex()
+                        .px_2()
+                        .py_1()
+                        .child(Label::new(error).color(Color::Error)),
+                )
+            })
+            .child(
+                div()
+                    .flex_1()
+                    .size_full()
+                    .p_1()
+                    .child(self.render_list(cx))
+                    .vertical_scrollbar_for(self.scroll_handle.clone(), window, cx),
+            )
+    }
+}
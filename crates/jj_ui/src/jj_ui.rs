@@ -1,22 +1,47 @@
 mod bookmark_picker;
+mod change_graph;
+mod change_input;
+mod change_log;
 
 use command_palette_hooks::CommandPaletteFilter;
 use feature_flags::FeatureFlagAppExt as _;
-use gpui::App;
-use jj::JujutsuStore;
-use workspace::Workspace;
+use gpui::{App, Context};
+use jj::JujutsuRepository;
+use std::sync::Arc;
+use workspace::{Toast, Workspace, notifications::NotificationId};
 
 pub fn init(cx: &mut App) {
-    JujutsuStore::init_global(cx);
-
     cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
         bookmark_picker::register(workspace);
+        change_log::register(workspace);
     })
     .detach();
 
     feature_gate_jj_ui_actions(cx);
 }
 
+/// Returns the jj repository of the workspace's project, telling the user why
+/// there is none when the project is remote.
+fn active_repository(
+    workspace: &mut Workspace,
+    cx: &mut Context<Workspace>,
+) -> Option<Arc<dyn JujutsuRepository>> {
+    let jj_store = workspace.project().read(cx).jj_store(cx);
+    if !jj_store.read(cx).is_local() {
+        struct RemoteJujutsuToast;
+
+        workspace.show_toast(
+            Toast::new(
+                NotificationId::unique::<RemoteJujutsuToast>(),
+                "Jujutsu changes and bookmarks are not available in remote projects yet",
+            ),
+            cx,
+        );
+        return None;
+    }
+    jj_store.read(cx).active_repository(cx)
+}
+
 fn feature_gate_jj_ui_actions(cx: &mut App) {
     const JJ_ACTION_NAMESPACE: &str = "jj";
 
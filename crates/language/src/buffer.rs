@@ -539,6 +539,15 @@ pub enum CharKind {
     Word,
 }
 
+/// A kind of text that is told apart from code by the syntax highlighting.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SyntaxTextKind {
+    /// A comment.
+    Comment,
+    /// A string literal.
+    String,
+}
+
 /// A runnable is a set of data about a region that could be resolved into a task
 pub struct Runnable {
     pub tags: SmallVec<[RunnableTag; 1]>,
@@ -2795,6 +2804,27 @@ impl Buffer {
         redone
     }
 
+    /// Moves the buffer to the given state of its undo tree, switching to undone branches of the
+    /// history if needed. `None` is the text the history starts from.
+    pub fn go_to_undo_tree_state(
+        &mut self,
+        target: Option<TransactionId>,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let was_dirty = self.is_dirty();
+        let old_version = self.version.clone();
+
+        let operations = self.text.go_to_undo_tree_state(target);
+        let changed = !operations.is_empty();
+        for operation in operations {
+            self.send_operation(Operation::Buffer(operation), true, cx);
+        }
+        if changed {
+            self.did_edit(&old_version, was_dirty, cx)
+        }
+        changed
+    }
+
     /// Override current completion triggers with the user-provided completion triggers.
     pub fn set_completion_triggers(
         &mut self,
@@ -3256,6 +3286,50 @@ impl BufferSnapshot {
         (captures, highlight_maps)
     }
 
+    /// Returns the ranges of the comments and string literals that intersect the given range,
+    /// across all syntax layers, including injected ones. Those are the nodes that the layers'
+    /// highlights queries capture with a `comment` or `string` capture name.
+    pub fn comment_and_string_ranges(
+        &self,
+        range: Range<usize>,
+    ) -> Vec<(Range<usize>, SyntaxTextKind)> {
+        let mut captures = self.syntax.captures(range, &self.text, |grammar| {
+            grammar.highlights_query.as_ref()
+        });
+        let kinds_by_grammar = captures
+            .grammars()
+            .iter()
+            .map(|grammar| {
+                let Some(query) = grammar.highlights_query.as_ref() else {
+                    return Vec::new();
+                };
+                query
+                    .capture_names()
+                    .iter()
+                    .map(|name| {
+                        let kind = name.split('.').next()?;
+                        match kind {
+                            "comment" => Some(SyntaxTextKind::Comment),
+                            "string" => Some(SyntaxTextKind::String),
+                            _ => None,
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut ranges = Vec::new();
+        while let Some(capture) = captures.peek() {
+            if let Some(Some(kind)) =
+                kinds_by_grammar[capture.grammar_index].get(capture.index as usize)
+            {
+                ranges.push((capture.node.byte_range(), *kind));
+            }
+            captures.advance();
+        }
+        ranges
+    }
+
     /// Iterates over chunks of text in the given range of the buffer. Text is chunked
     /// in an arbitrary way due to being stored in a [`Rope`](text::Rope). The text is also
     /// returned in chunks where each chunk has a single syntax highlighting style and
@@ -146,6 +146,13 @@ pub struct LanguageSettings {
     pub code_actions_on_format: HashMap<String, bool>,
     /// Whether to perform linked edits
     pub linked_edits: bool,
+    /// Whether to highlight the buffer with semantic tokens from language servers,
+    /// on top of the tree-sitter highlights.
+    pub semantic_tokens: bool,
+    /// Which source to compute foldable ranges from.
+    pub fold_provider: FoldProvider,
+    /// Which source to expand and shrink selections with.
+    pub selection_range_provider: SelectionRangeProvider,
     /// Task configuration for this language.
     pub tasks: LanguageTaskConfig,
     /// Whether to pop the completions menu while typing in an editor without
@@ -627,6 +634,25 @@ pub struct LanguageSettingsContent {
     ///
     /// Default: true
     pub linked_edits: Option<bool>,
+    /// Whether to highlight the buffer with semantic tokens from language servers,
+    /// on top of the tree-sitter highlights. Token types and modifiers are mapped
+    /// to syntax theme keys, e.g. `type`, `variable.mutable` or `function.macro`.
+    ///
+    /// Default: false
+    pub semantic_tokens: Option<bool>,
+    /// Which source to compute foldable ranges from: the language server's
+    /// folding ranges, the tree-sitter bracket pairs, or the indentation.
+    ///
+    /// Default: indent
+    pub fold_provider: Option<FoldProvider>,
+    /// Which source `editor::SelectLargerSyntaxNode` expands selections with: the language
+    /// server's selection ranges, or the tree-sitter syntax tree. Selections that the language
+    /// server cannot expand any further fall back to the syntax tree. Vim text objects, such as
+    /// `af`, always use the syntax tree, as selection ranges don't tell what kind of node they
+    /// enclose.
+    ///
+    /// Default: tree_sitter
+    pub selection_range_provider: Option<SelectionRangeProvider>,
     /// Whether indentation should be adjusted based on the context whilst typing.
     ///
     /// Default: true
@@ -658,6 +684,36 @@ pub struct LanguageSettingsContent {
     pub debuggers: Option<Vec<String>>,
 }
 
+/// The source of foldable ranges in the editor.
+#[derive(
+    Debug, PartialEq, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, SettingsUi,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum FoldProvider {
+    /// Use `textDocument/foldingRange` results from the language server,
+    /// falling back to indentation when no server provides them.
+    Lsp,
+    /// Fold bracket pairs found by the tree-sitter grammar,
+    /// falling back to indentation for languages without one.
+    TreeSitter,
+    /// Fold by indentation.
+    #[default]
+    Indent,
+}
+
+/// The source of the ranges that selections are expanded to.
+#[derive(
+    Debug, PartialEq, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, SettingsUi,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionRangeProvider {
+    /// Use `textDocument/selectionRange` results from the language server.
+    Lsp,
+    /// Use the enclosing tree-sitter syntax nodes.
+    #[default]
+    TreeSitter,
+}
+
 /// The behavior of `editor::Rewrap`.
 #[derive(
     Debug, PartialEq, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, SettingsUi,
@@ -1555,6 +1611,15 @@ impl settings::Settings for AllLanguageSettings {
                 _ => None,
             },
         );
+        vscode.enum_setting(
+            "editor.foldingStrategy",
+            &mut d.fold_provider,
+            |s| match s {
+                "auto" => Some(FoldProvider::Lsp),
+                "indentation" => Some(FoldProvider::Indent),
+                _ => None,
+            },
+        );
         vscode.bool_setting("editor.formatOnType", &mut d.use_on_type_format);
         vscode.bool_setting("editor.linkedEditing", &mut d.linked_edits);
         vscode.bool_setting("editor.formatOnPaste", &mut d.auto_indent_on_paste);
@@ -1648,6 +1713,12 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         src.code_actions_on_format.clone(),
     );
     merge(&mut settings.linked_edits, src.linked_edits);
+    merge(&mut settings.semantic_tokens, src.semantic_tokens);
+    merge(&mut settings.fold_provider, src.fold_provider);
+    merge(
+        &mut settings.selection_range_provider,
+        src.selection_range_provider,
+    );
     merge(&mut settings.tasks, src.tasks.clone());
 
     merge(
@@ -120,6 +120,29 @@ pub fn text_diff_with_options(
     edits
 }
 
+/// Computes a word-based diff between two strings, returning the byte ranges of the words that
+/// were removed from the old text and those of the words that were inserted into the new text.
+pub fn word_diff(
+    old_text: &str,
+    new_text: &str,
+    language_scope: Option<LanguageScope>,
+) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let mut old_ranges = Vec::new();
+    let mut new_ranges = Vec::new();
+    let mut input = InternedInput::default();
+    input.update_before(tokenize(old_text, language_scope.clone()));
+    input.update_after(tokenize(new_text, language_scope));
+    diff_internal(&input, |old_byte_range, new_byte_range, _, _| {
+        if !old_byte_range.is_empty() {
+            old_ranges.push(old_byte_range);
+        }
+        if !new_byte_range.is_empty() {
+            new_ranges.push(new_byte_range);
+        }
+    });
+    (old_ranges, new_ranges)
+}
+
 pub fn apply_diff_patch(base_text: &str, patch: &str) -> Result<String, anyhow::Error> {
     let patch = diffy::Patch::from_str(patch).context("Failed to parse patch")?;
     let result = diffy::apply(base_text, &patch);
@@ -278,6 +301,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_word_diff() {
+        let old_text = "let value = compute(one, two);\n";
+        let new_text = "let result = compute(one, three);\n";
+        assert_eq!(
+            word_diff(old_text, new_text, None),
+            (vec![4..9, 25..28], vec![4..10, 26..31])
+        );
+
+        let old_text = "one two";
+        let new_text = "one two three";
+        assert_eq!(word_diff(old_text, new_text, None), (vec![], vec![7..13]));
+    }
+
     #[test]
     fn test_apply_diff_patch() {
         let old_text = "one two\nthree four five\nsix seven eight nine\nten\n";
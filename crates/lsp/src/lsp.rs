@@ -300,6 +300,35 @@ where
     }
 }
 
+/// The key under [`ServerCapabilities::experimental`] that holds the server's
+/// `typeHierarchyProvider` capability, which [`ServerCapabilities`] has no field for.
+pub const TYPE_HIERARCHY_PROVIDER: &str = "typeHierarchyProvider";
+
+/// The `initialize` request, returning the server's response as JSON to read the
+/// capabilities that [`ServerCapabilities`] drops when it is deserialized.
+enum InitializeWithRawCapabilities {}
+
+impl request::Request for InitializeWithRawCapabilities {
+    type Params = InitializeParams;
+    type Result = Value;
+    const METHOD: &'static str = request::Initialize::METHOD;
+}
+
+fn record_experimental_capability(capabilities: &mut ServerCapabilities, key: &str, value: Value) {
+    match capabilities
+        .experimental
+        .get_or_insert_with(|| json!({}))
+        .as_object_mut()
+    {
+        Some(experimental) => {
+            experimental.entry(key).or_insert(value);
+        }
+        None => {
+            log::warn!("cannot record the {key} capability in non-object experimental capabilities")
+        }
+    }
+}
+
 /// Combined capabilities of the server and the adapter.
 #[derive(Debug)]
 pub struct AdapterServerCapabilities {
@@ -846,6 +875,73 @@ impl LanguageServer {
                         dynamic_registration: Some(true),
                         ..DocumentSymbolClientCapabilities::default()
                     }),
+                    call_hierarchy: Some(CallHierarchyClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
+                    type_hierarchy: Some(TypeHierarchyClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
+                    selection_range: Some(SelectionRangeClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
+                    document_link: Some(DocumentLinkClientCapabilities {
+                        dynamic_registration: Some(false),
+                        tooltip_support: Some(true),
+                    }),
+                    folding_range: Some(FoldingRangeClientCapabilities {
+                        dynamic_registration: Some(false),
+                        line_folding_only: Some(true),
+                        ..FoldingRangeClientCapabilities::default()
+                    }),
+                    semantic_tokens: Some(SemanticTokensClientCapabilities {
+                        dynamic_registration: Some(false),
+                        requests: SemanticTokensClientCapabilitiesRequests {
+                            range: Some(false),
+                            full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                        },
+                        token_types: vec![
+                            SemanticTokenType::NAMESPACE,
+                            SemanticTokenType::TYPE,
+                            SemanticTokenType::CLASS,
+                            SemanticTokenType::ENUM,
+                            SemanticTokenType::INTERFACE,
+                            SemanticTokenType::STRUCT,
+                            SemanticTokenType::TYPE_PARAMETER,
+                            SemanticTokenType::PARAMETER,
+                            SemanticTokenType::VARIABLE,
+                            SemanticTokenType::PROPERTY,
+                            SemanticTokenType::ENUM_MEMBER,
+                            SemanticTokenType::EVENT,
+                            SemanticTokenType::FUNCTION,
+                            SemanticTokenType::METHOD,
+                            SemanticTokenType::MACRO,
+                            SemanticTokenType::KEYWORD,
+                            SemanticTokenType::MODIFIER,
+                            SemanticTokenType::COMMENT,
+                            SemanticTokenType::STRING,
+                            SemanticTokenType::NUMBER,
+                            SemanticTokenType::REGEXP,
+                            SemanticTokenType::OPERATOR,
+                            SemanticTokenType::DECORATOR,
+                        ],
+                        token_modifiers: vec![
+                            SemanticTokenModifier::DECLARATION,
+                            SemanticTokenModifier::DEFINITION,
+                            SemanticTokenModifier::READONLY,
+                            SemanticTokenModifier::STATIC,
+                            SemanticTokenModifier::DEPRECATED,
+                            SemanticTokenModifier::ABSTRACT,
+                            SemanticTokenModifier::ASYNC,
+                            SemanticTokenModifier::MODIFICATION,
+                            SemanticTokenModifier::DOCUMENTATION,
+                            SemanticTokenModifier::DEFAULT_LIBRARY,
+                        ],
+                        formats: vec![TokenFormat::RELATIVE],
+                        overlapping_token_support: Some(false),
+                        multiline_token_support: Some(false),
+                        server_cancel_support: Some(false),
+                        augments_syntax_tokens: Some(true),
+                    }),
                     diagnostic: Some(DiagnosticClientCapabilities {
                         dynamic_registration: Some(true),
                         related_document_support: Some(true),
@@ -893,7 +989,7 @@ impl LanguageServer {
     ) -> Task<Result<Arc<Self>>> {
         cx.background_spawn(async move {
             let response = self
-                .request::<request::Initialize>(params)
+                .request::<InitializeWithRawCapabilities>(params)
                 .await
                 .into_response()
                 .with_context(|| {
@@ -903,6 +999,19 @@ impl LanguageServer {
                         self.server_id()
                     )
                 })?;
+            let type_hierarchy_provider = response
+                .get("capabilities")
+                .and_then(|capabilities| capabilities.get(TYPE_HIERARCHY_PROVIDER))
+                .cloned();
+            let mut response = serde_json::from_value::<InitializeResult>(response)
+                .context("parsing the initialize response")?;
+            if let Some(provider) = type_hierarchy_provider {
+                record_experimental_capability(
+                    &mut response.capabilities,
+                    TYPE_HIERARCHY_PROVIDER,
+                    provider,
+                );
+            }
             if let Some(info) = response.server_info {
                 self.process_name = info.name.into();
             }
@@ -1910,6 +2019,52 @@ mod tests {
         fake.receive_notification::<notification::Exit>().await;
     }
 
+    #[gpui::test]
+    async fn test_type_hierarchy_capability(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            release_channel::init(SemanticVersion::default(), cx);
+        });
+        let (server, fake) = FakeLanguageServer::new(
+            LanguageServerId(0),
+            LanguageServerBinary {
+                path: "path/to/language-server".into(),
+                arguments: vec![],
+                env: None,
+            },
+            "the-lsp".to_string(),
+            Default::default(),
+            &mut cx.to_async(),
+        );
+        fake.set_request_handler::<InitializeWithRawCapabilities, _, _>(|_, _| async move {
+            Ok(json!({
+                "capabilities": {
+                    "hoverProvider": true,
+                    "typeHierarchyProvider": true,
+                },
+            }))
+        });
+
+        let server = cx
+            .update(|cx| {
+                let params = server.default_initialize_params(false, cx);
+                let configuration = DidChangeConfigurationParams {
+                    settings: Default::default(),
+                };
+                server.initialize(params, configuration.into(), cx)
+            })
+            .await
+            .unwrap();
+        let capabilities = server.capabilities();
+        assert_eq!(
+            capabilities.hover_provider,
+            Some(HoverProviderCapability::Simple(true))
+        );
+        assert_eq!(
+            capabilities.experimental,
+            Some(json!({ "typeHierarchyProvider": true }))
+        );
+    }
+
     #[gpui::test]
     fn test_deserialize_string_digit_id() {
         let json = r#"{"jsonrpc":"2.0","id":"2","method":"workspace/configuration","params":{"items":[{"scopeUri":"file:///Users/mph/Devel/personal/hello-scala/","section":"metals"}]}}"#;
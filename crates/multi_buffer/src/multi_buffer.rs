@@ -9,7 +9,7 @@ pub use position::{TypedOffset, TypedPoint, TypedRow};
 use anyhow::{Result, anyhow};
 use buffer_diff::{
     BufferDiff, BufferDiffEvent, BufferDiffSnapshot, DiffHunkSecondaryStatus, DiffHunkStatus,
-    DiffHunkStatusKind,
+    DiffHunkStatusKind, LazyWordDiffs,
 };
 use clock::ReplicaId;
 use collections::{BTreeMap, Bound, HashMap, HashSet};
@@ -123,7 +123,7 @@ pub enum Event {
 }
 
 /// A diff hunk, representing a range of consequent lines in a multibuffer.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub struct MultiBufferDiffHunk {
     /// The row range in the multibuffer where this diff hunk appears.
     pub row_range: Range<MultiBufferRow>,
@@ -137,8 +137,33 @@ pub struct MultiBufferDiffHunk {
     pub diff_base_byte_range: Range<usize>,
     /// Whether or not this hunk also appears in the 'secondary diff'.
     pub secondary_status: DiffHunkSecondaryStatus,
+    /// The words that changed within the hunk.
+    pub word_diffs: LazyWordDiffs,
+}
+
+// The word diffs are derived from the rest of a hunk, so they are left out when comparing hunks.
+impl PartialEq for MultiBufferDiffHunk {
+    fn eq(&self, other: &Self) -> bool {
+        let MultiBufferDiffHunk {
+            row_range,
+            buffer_id,
+            buffer_range,
+            excerpt_id,
+            diff_base_byte_range,
+            secondary_status,
+            word_diffs: _,
+        } = self;
+        *row_range == other.row_range
+            && *buffer_id == other.buffer_id
+            && *buffer_range == other.buffer_range
+            && *excerpt_id == other.excerpt_id
+            && *diff_base_byte_range == other.diff_base_byte_range
+            && *secondary_status == other.secondary_status
+    }
 }
 
+impl Eq for MultiBufferDiffHunk {}
+
 impl MultiBufferDiffHunk {
     pub fn status(&self) -> DiffHunkStatus {
         let kind = if self.buffer_range.start == self.buffer_range.end {
@@ -3860,10 +3885,52 @@ impl MultiBufferSnapshot {
                 buffer_range: hunk.buffer_range.clone(),
                 diff_base_byte_range: hunk.diff_base_byte_range.clone(),
                 secondary_status: hunk.secondary_status,
+                word_diffs: hunk.word_diffs.clone(),
             })
         })
     }
 
+    /// Returns the ranges of the words that changed within a diff hunk, along with whether they
+    /// were inserted into the buffer or deleted from its base text. The deleted words are
+    /// anchored within the hunk's deleted text, which is only part of the multibuffer while the
+    /// hunk is expanded.
+    pub fn diff_hunk_word_ranges(
+        &self,
+        hunk: &MultiBufferDiffHunk,
+    ) -> Vec<(Range<Anchor>, DiffHunkStatusKind)> {
+        let Some((diff, buffer)) = self
+            .diffs
+            .get(&hunk.buffer_id)
+            .zip(self.buffer_for_excerpt(hunk.excerpt_id))
+        else {
+            return Vec::new();
+        };
+        let word_diffs =
+            hunk.word_diffs
+                .get(&hunk.buffer_range, &hunk.diff_base_byte_range, diff, buffer);
+
+        let base_text = diff.base_text();
+        let base_anchor = |offset: usize, bias: Bias| Anchor {
+            buffer_id: Some(hunk.buffer_id),
+            excerpt_id: hunk.excerpt_id,
+            text_anchor: hunk.buffer_range.start,
+            diff_base_anchor: Some(base_text.anchor_at(offset, bias)),
+        };
+        let deleted = word_diffs.base_byte_ranges.iter().map(|range| {
+            (
+                base_anchor(range.start, Bias::Right)..base_anchor(range.end, Bias::Left),
+                DiffHunkStatusKind::Deleted,
+            )
+        });
+        let inserted = word_diffs.buffer_ranges.iter().map(|range| {
+            (
+                Anchor::range_in_buffer(hunk.excerpt_id, hunk.buffer_id, range.clone()),
+                DiffHunkStatusKind::Added,
+            )
+        });
+        deleted.chain(inserted).collect()
+    }
+
     pub fn excerpt_ids_for_range<T: ToOffset>(
         &self,
         range: Range<T>,
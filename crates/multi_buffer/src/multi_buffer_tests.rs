@@ -469,6 +469,47 @@ fn test_diff_hunks_in_range(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+fn test_diff_hunk_word_ranges(cx: &mut TestAppContext) {
+    let base_text = "one\nlet value = compute(two);\nthree\n";
+    let text = "one\nlet result = compute(two);\nthree\n";
+    let buffer = cx.new(|cx| Buffer::local(text, cx));
+    let diff = cx.new(|cx| BufferDiff::new_with_base_text(base_text, &buffer, cx));
+    let multibuffer = cx.new(|cx| MultiBuffer::singleton(buffer, cx));
+    multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.add_diff(diff, cx);
+        multibuffer.set_all_diff_hunks_expanded(cx);
+    });
+    cx.run_until_parked();
+
+    let snapshot = multibuffer.read_with(cx, |multibuffer, cx| multibuffer.snapshot(cx));
+    let hunk = snapshot.diff_hunks().next().unwrap();
+    let word_ranges = snapshot
+        .diff_hunk_word_ranges(&hunk)
+        .into_iter()
+        .map(|(range, kind)| {
+            let point_range = range.to_point(&snapshot);
+            let text = snapshot.text_for_range(range).collect::<String>();
+            (point_range, text, kind)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        word_ranges,
+        [
+            (
+                Point::new(1, 4)..Point::new(1, 9),
+                "value".to_string(),
+                DiffHunkStatusKind::Deleted
+            ),
+            (
+                Point::new(2, 4)..Point::new(2, 10),
+                "result".to_string(),
+                DiffHunkStatusKind::Added
+            ),
+        ]
+    );
+}
+
 #[gpui::test]
 fn test_editing_text_in_diff_hunks(cx: &mut TestAppContext) {
     let base_text = "one\ntwo\nfour\nfive\nsix\nseven\n";
@@ -1,5 +1,6 @@
 mod conflict_set;
 pub mod git_traversal;
+pub mod jj_store;
 
 use crate::{
     ProjectEnvironment, ProjectItem, ProjectPath,
@@ -25,8 +26,9 @@ use git::{
     parse_git_remote_url,
     repository::{
         Branch, CommitDetails, CommitDiff, CommitFile, CommitOptions, DiffType, FetchOptions,
-        GitRepository, GitRepositoryCheckpoint, PushOptions, Remote, RemoteCommandOutput, RepoPath,
-        ResetMode, UpstreamTrackingStatus,
+        GitRepository, GitRepositoryCheckpoint, GitWorktree, LogEntry, LogOptions, MergeOptions,
+        PushOptions, RebaseAction, RebaseControl, RebaseProgress, RebaseTodoEntry, Remote,
+        RemoteCommandOutput, RepoPath, ResetMode, UpstreamTrackingStatus,
     },
     stash::{GitStash, StashEntry},
     status::{
@@ -37,6 +39,7 @@ use gpui::{
     App, AppContext, AsyncApp, Context, Entity, EventEmitter, SharedString, Subscription, Task,
     WeakEntity,
 };
+use jj_store::{JujutsuStore, JujutsuStoreEvent, file_change_kind_to_git_status};
 use language::{
     Buffer, BufferEvent, Language, LanguageRegistry,
     proto::{deserialize_version, serialize_version},
@@ -45,7 +48,10 @@ use parking_lot::Mutex;
 use postage::stream::Stream as _;
 use rpc::{
     AnyProtoClient, TypedEnvelope,
-    proto::{self, FromProto, ToProto, git_reset, split_repository_update},
+    proto::{
+        self, FromProto, ToProto, git_control_rebase, git_rebase_todo_entry, git_reset,
+        split_repository_update,
+    },
 };
 use serde::Deserialize;
 use std::{
@@ -66,13 +72,14 @@ use text::{Bias, BufferId};
 use util::{ResultExt, debug_panic, paths::SanitizedPath, post_inc};
 use worktree::{
     File, PathChange, PathKey, PathProgress, PathSummary, PathTarget, ProjectEntryId,
-    UpdatedGitRepositoriesSet, UpdatedGitRepository, Worktree,
+    UpdatedGitRepositoriesSet, UpdatedGitRepository, Worktree, WorktreeId,
 };
 
 pub struct GitStore {
     state: GitStoreState,
     buffer_store: Entity<BufferStore>,
     worktree_store: Entity<WorktreeStore>,
+    jj_store: Entity<JujutsuStore>,
     repositories: HashMap<RepositoryId, Entity<Repository>>,
     active_repo_id: Option<RepositoryId>,
     #[allow(clippy::type_complexity)]
@@ -246,6 +253,7 @@ pub struct RepositorySnapshot {
     pub remote_origin_url: Option<String>,
     pub remote_upstream_url: Option<String>,
     pub stash_entries: GitStash,
+    pub rebase: Option<RebaseProgress>,
 }
 
 type JobId = u64;
@@ -374,15 +382,31 @@ impl GitStore {
         state: GitStoreState,
         cx: &mut Context<Self>,
     ) -> Self {
+        let jj_store = cx.new(|cx| match &state {
+            GitStoreState::Local { .. } => JujutsuStore::local(&worktree_store, cx),
+            GitStoreState::Remote {
+                upstream_client,
+                upstream_project_id,
+                ..
+            } => JujutsuStore::remote(
+                &worktree_store,
+                upstream_client.clone(),
+                *upstream_project_id,
+                cx,
+            ),
+        });
+
         let _subscriptions = vec![
             cx.subscribe(&worktree_store, Self::on_worktree_store_event),
             cx.subscribe(&buffer_store, Self::on_buffer_store_event),
+            cx.subscribe(&jj_store, Self::on_jj_store_event),
         ];
 
         GitStore {
             state,
             buffer_store,
             worktree_store,
+            jj_store,
             repositories: HashMap::default(),
             active_repo_id: None,
             _subscriptions,
@@ -411,6 +435,15 @@ impl GitStore {
         client.add_entity_request_handler(Self::handle_commit);
         client.add_entity_request_handler(Self::handle_reset);
         client.add_entity_request_handler(Self::handle_show);
+        client.add_entity_request_handler(Self::handle_log);
+        client.add_entity_request_handler(Self::handle_rebase);
+        client.add_entity_request_handler(Self::handle_control_rebase);
+        client.add_entity_request_handler(Self::handle_cherry_pick);
+        client.add_entity_request_handler(Self::handle_revert);
+        client.add_entity_request_handler(Self::handle_merge);
+        client.add_entity_request_handler(Self::handle_get_worktrees);
+        client.add_entity_request_handler(Self::handle_create_worktree);
+        client.add_entity_request_handler(Self::handle_remove_worktree);
         client.add_entity_request_handler(Self::handle_load_commit_diff);
         client.add_entity_request_handler(Self::handle_checkout_files);
         client.add_entity_request_handler(Self::handle_open_commit_message_buffer);
@@ -426,6 +459,8 @@ impl GitStore {
         client.add_entity_message_handler(Self::handle_update_repository);
         client.add_entity_message_handler(Self::handle_remove_repository);
         client.add_entity_request_handler(Self::handle_git_clone);
+        client.add_entity_request_handler(Self::handle_get_jujutsu_status);
+        client.add_entity_request_handler(Self::handle_load_jujutsu_parent_text);
     }
 
     pub fn is_local(&self) -> bool {
@@ -535,6 +570,10 @@ impl GitStore {
         self.shared_diffs.remove(peer_id);
     }
 
+    pub fn jj_store(&self) -> &Entity<JujutsuStore> {
+        &self.jj_store
+    }
+
     pub fn active_repository(&self) -> Option<Entity<Repository>> {
         self.active_repo_id
             .as_ref()
@@ -568,7 +607,7 @@ impl GitStore {
         let Some((repo, repo_path)) =
             self.repository_and_path_for_buffer_id(buffer.read(cx).remote_id(), cx)
         else {
-            return Task::ready(Err(anyhow!("failed to find git repository for buffer")));
+            return self.open_jujutsu_diff(buffer, DiffKind::Unstaged, cx);
         };
 
         let task = self
@@ -624,7 +663,7 @@ impl GitStore {
         let Some((repo, repo_path)) =
             self.repository_and_path_for_buffer_id(buffer.read(cx).remote_id(), cx)
         else {
-            return Task::ready(Err(anyhow!("failed to find git repository for buffer")));
+            return self.open_jujutsu_diff(buffer, DiffKind::Uncommitted, cx);
         };
 
         let task = self
@@ -647,6 +686,45 @@ impl GitStore {
         cx.background_spawn(async move { task.await.map_err(|e| anyhow!("{e}")) })
     }
 
+    /// Opens a diff against the parents of the working-copy change, for buffers
+    /// in a jj workspace that isn't colocated with a git repository. jj has no
+    /// index, so both kinds of diff use the same base text.
+    fn open_jujutsu_diff(
+        &mut self,
+        buffer: Entity<Buffer>,
+        kind: DiffKind,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Entity<BufferDiff>>> {
+        let buffer_id = buffer.read(cx).remote_id();
+        let parent_text = buffer.read(cx).project_path(cx).and_then(|project_path| {
+            self.jj_store.update(cx, |jj_store, cx| {
+                jj_store.load_parent_text(&project_path, cx)
+            })
+        });
+        let Some(parent_text) = parent_text else {
+            return Task::ready(Err(anyhow!("failed to find git repository for buffer")));
+        };
+
+        let task = self
+            .loading_diffs
+            .entry((buffer_id, kind))
+            .or_insert_with(|| {
+                cx.spawn(async move |this, cx| {
+                    let diff_bases_change = parent_text.await.map(|text| match kind {
+                        DiffKind::Unstaged => DiffBasesChange::SetIndex(text),
+                        DiffKind::Uncommitted => DiffBasesChange::SetBoth(text),
+                    });
+                    Self::open_diff_internal(this, kind, diff_bases_change, buffer, cx)
+                        .await
+                        .map_err(Arc::new)
+                })
+                .shared()
+            })
+            .clone();
+
+        cx.background_spawn(async move { task.await.map_err(|e| anyhow!("{e}")) })
+    }
+
     async fn open_diff_internal(
         this: WeakEntity<Self>,
         kind: DiffKind,
@@ -785,8 +863,15 @@ impl GitStore {
         project_path: &ProjectPath,
         cx: &App,
     ) -> Option<FileStatus> {
-        let (repo, repo_path) = self.repository_and_path_for_project_path(project_path, cx)?;
-        Some(repo.read(cx).status_for_path(&repo_path)?.status)
+        if let Some((repo, repo_path)) = self.repository_and_path_for_project_path(project_path, cx)
+        {
+            return Some(repo.read(cx).status_for_path(&repo_path)?.status);
+        }
+        let kind = self
+            .jj_store
+            .read(cx)
+            .status_for_project_path(project_path, cx)?;
+        Some(file_change_kind_to_git_status(kind))
     }
 
     pub fn checkpoint(&self, cx: &mut App) -> Task<Result<GitStoreCheckpoint>> {
@@ -1148,6 +1233,70 @@ impl GitStore {
         ))
     }
 
+    fn on_jj_store_event(
+        &mut self,
+        _: Entity<JujutsuStore>,
+        event: &JujutsuStoreEvent,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            JujutsuStoreEvent::RepositoryUpdated(worktree_id) => {
+                self.reload_jujutsu_diff_bases(*worktree_id, cx);
+            }
+            JujutsuStoreEvent::RepositoryRemoved(_) => {}
+        }
+    }
+
+    /// Reloads the diff bases of open buffers in the given worktree that are
+    /// diffed against a jj workspace rather than a git repository.
+    fn reload_jujutsu_diff_bases(&mut self, worktree_id: WorktreeId, cx: &mut Context<Self>) {
+        let mut loads = Vec::new();
+        for (buffer_id, diff_state) in &self.diffs {
+            let Some(buffer) = self.buffer_store.read(cx).get(*buffer_id) else {
+                continue;
+            };
+            let Some(project_path) = buffer.read(cx).project_path(cx) else {
+                continue;
+            };
+            if project_path.worktree_id != worktree_id
+                || self
+                    .repository_and_path_for_project_path(&project_path, cx)
+                    .is_some()
+            {
+                continue;
+            }
+            let Some(parent_text) = self.jj_store.update(cx, |jj_store, cx| {
+                jj_store.load_parent_text(&project_path, cx)
+            }) else {
+                continue;
+            };
+            loads.push((buffer, diff_state.clone(), parent_text));
+        }
+
+        cx.spawn(async move |this, cx| {
+            for (buffer, diff_state, parent_text) in loads {
+                let Some(parent_text) = parent_text.await.log_err() else {
+                    continue;
+                };
+                this.update(cx, |_, cx| {
+                    let buffer_snapshot = buffer.read(cx).text_snapshot();
+                    diff_state.update(cx, |diff_state, cx| {
+                        if diff_state.head_text.as_deref() == parent_text.as_ref() {
+                            return;
+                        }
+                        diff_state.diff_bases_changed(
+                            buffer_snapshot,
+                            Some(DiffBasesChange::SetBoth(parent_text)),
+                            cx,
+                        );
+                    });
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn on_jobs_updated(&mut self, _: Entity<Repository>, _: &JobsUpdated, cx: &mut Context<Self>) {
         cx.emit(GitStoreEvent::JobsUpdated)
     }
@@ -1363,9 +1512,17 @@ impl GitStore {
     }
 
     pub fn status_for_buffer_id(&self, buffer_id: BufferId, cx: &App) -> Option<FileStatus> {
-        let (repo, path) = self.repository_and_path_for_buffer_id(buffer_id, cx)?;
-        let status = repo.read(cx).snapshot.status_for_path(&path)?;
-        Some(status.status)
+        if let Some((repo, path)) = self.repository_and_path_for_buffer_id(buffer_id, cx) {
+            let status = repo.read(cx).snapshot.status_for_path(&path)?;
+            return Some(status.status);
+        }
+        let buffer = self.buffer_store.read(cx).get(buffer_id)?;
+        let project_path = buffer.read(cx).project_path(cx)?;
+        let kind = self
+            .jj_store
+            .read(cx)
+            .status_for_project_path(&project_path, cx)?;
+        Some(file_change_kind_to_git_status(kind))
     }
 
     pub fn repository_and_path_for_buffer_id(
@@ -1891,6 +2048,24 @@ impl GitStore {
                 .collect::<Vec<_>>(),
         })
     }
+    async fn handle_get_jujutsu_status(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetJujutsuStatus>,
+        cx: AsyncApp,
+    ) -> Result<proto::GetJujutsuStatusResponse> {
+        let jj_store = this.read_with(&cx, |this, _| this.jj_store.clone())?;
+        JujutsuStore::handle_get_status(jj_store, envelope, cx).await
+    }
+
+    async fn handle_load_jujutsu_parent_text(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::LoadJujutsuParentText>,
+        cx: AsyncApp,
+    ) -> Result<proto::LoadJujutsuParentTextResponse> {
+        let jj_store = this.read_with(&cx, |this, _| this.jj_store.clone())?;
+        JujutsuStore::handle_load_parent_text(jj_store, envelope, cx).await
+    }
+
     async fn handle_get_default_branch(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::GetDefaultBranch>,
@@ -1966,6 +2141,49 @@ impl GitStore {
         })
     }
 
+    async fn handle_log(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitLog>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitLogResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let payload = envelope.payload;
+        let options = LogOptions {
+            revision_range: payload.revision_range,
+            path: payload
+                .path
+                .map(|path| RepoPath(Arc::<Path>::from_proto(path))),
+            line_range: payload
+                .line_range_start
+                .zip(payload.line_range_end)
+                .map(|(start, end)| start..end),
+            author: payload.author,
+            skip: payload.skip as usize,
+            limit: payload.limit.map(|limit| limit as usize),
+        };
+
+        let entries = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.log(options)
+            })?
+            .await??;
+        Ok(proto::GitLogResponse {
+            entries: entries
+                .into_iter()
+                .map(|entry| proto::GitLogEntry {
+                    sha: entry.sha.into(),
+                    parents: entry.parents.into_iter().map(Into::into).collect(),
+                    subject: entry.subject.into(),
+                    commit_timestamp: entry.commit_timestamp,
+                    author_name: entry.author_name.into(),
+                    author_email: entry.author_email.into(),
+                    refs: entry.refs.into_iter().map(Into::into).collect(),
+                })
+                .collect(),
+        })
+    }
+
     async fn handle_load_commit_diff(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::LoadCommitDiff>,
@@ -1992,6 +2210,168 @@ impl GitStore {
         })
     }
 
+    async fn handle_rebase(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitRebase>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let todo = envelope
+            .payload
+            .todo
+            .into_iter()
+            .map(|entry| RebaseTodoEntry {
+                action: match entry.action() {
+                    git_rebase_todo_entry::Action::Pick => RebaseAction::Pick,
+                    git_rebase_todo_entry::Action::Reword => RebaseAction::Reword,
+                    git_rebase_todo_entry::Action::Edit => RebaseAction::Edit,
+                    git_rebase_todo_entry::Action::Squash => RebaseAction::Squash,
+                    git_rebase_todo_entry::Action::Fixup => RebaseAction::Fixup,
+                    git_rebase_todo_entry::Action::Drop => RebaseAction::Drop,
+                },
+                sha: entry.sha.into(),
+                message: entry.message,
+            })
+            .collect();
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.rebase(envelope.payload.base, todo)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_control_rebase(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitControlRebase>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let control = match envelope.payload.control() {
+            git_control_rebase::Control::Continue => RebaseControl::Continue,
+            git_control_rebase::Control::Skip => RebaseControl::Skip,
+            git_control_rebase::Control::Abort => RebaseControl::Abort,
+        };
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.control_rebase(control)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_cherry_pick(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitCherryPick>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.cherry_pick(envelope.payload.commit)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_revert(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitRevert>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.revert(envelope.payload.commit)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_merge(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitMerge>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let options = MergeOptions {
+            no_ff: envelope.payload.no_ff,
+            squash: envelope.payload.squash,
+        };
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.merge(envelope.payload.branch, options)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_get_worktrees(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitGetWorktrees>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitWorktreesResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        let worktrees = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.worktrees()
+            })?
+            .await??;
+
+        Ok(proto::GitWorktreesResponse {
+            worktrees: worktrees.iter().map(worktree_to_proto).collect(),
+        })
+    }
+
+    async fn handle_create_worktree(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitCreateWorktree>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.create_worktree(
+                    PathBuf::from(envelope.payload.path),
+                    envelope.payload.branch,
+                    envelope.payload.new_branch,
+                )
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_remove_worktree(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitRemoveWorktree>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle
+                    .remove_worktree(PathBuf::from(envelope.payload.path), envelope.payload.force)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
     async fn handle_reset(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::GitReset>,
@@ -2752,6 +3132,7 @@ impl RepositorySnapshot {
             remote_origin_url: None,
             remote_upstream_url: None,
             stash_entries: Default::default(),
+            rebase: None,
         }
     }
 
@@ -2784,6 +3165,7 @@ impl RepositorySnapshot {
                 .iter()
                 .map(stash_to_proto)
                 .collect(),
+            rebase: self.rebase.as_ref().map(rebase_progress_to_proto),
         }
     }
 
@@ -2853,6 +3235,7 @@ impl RepositorySnapshot {
                 .iter()
                 .map(stash_to_proto)
                 .collect(),
+            rebase: self.rebase.as_ref().map(rebase_progress_to_proto),
         }
     }
 
@@ -2919,6 +3302,26 @@ pub fn stash_to_proto(entry: &StashEntry) -> proto::StashEntry {
     }
 }
 
+pub fn rebase_progress_to_proto(progress: &RebaseProgress) -> proto::GitRebaseProgress {
+    proto::GitRebaseProgress {
+        current: progress.current as u64,
+        total: progress.total as u64,
+        onto: progress.onto.as_ref().map(|onto| onto.to_string()),
+        head_name: progress.head_name.as_ref().map(|name| name.to_string()),
+        stopped_at: progress.stopped_at.as_ref().map(|sha| sha.to_string()),
+    }
+}
+
+pub fn proto_to_rebase_progress(progress: &proto::GitRebaseProgress) -> RebaseProgress {
+    RebaseProgress {
+        current: progress.current as usize,
+        total: progress.total as usize,
+        onto: progress.onto.clone().map(SharedString::from),
+        head_name: progress.head_name.clone().map(SharedString::from),
+        stopped_at: progress.stopped_at.clone().map(SharedString::from),
+    }
+}
+
 pub fn proto_to_stash(entry: &proto::StashEntry) -> Result<StashEntry> {
     Ok(StashEntry {
         oid: Oid::from_bytes(&entry.oid)?,
@@ -3514,6 +3917,301 @@ impl Repository {
         })
     }
 
+    pub fn log(&mut self, options: LogOptions) -> oneshot::Receiver<Result<Vec<LogEntry>>> {
+        let id = self.id;
+        self.send_job(None, move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => backend.log(options).await,
+                RepositoryState::Remote { project_id, client } => {
+                    let line_range = options.line_range;
+                    let response = client
+                        .request(proto::GitLog {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            revision_range: options.revision_range,
+                            path: options.path.map(|path| path.as_ref().to_proto()),
+                            line_range_start: line_range.as_ref().map(|range| range.start),
+                            line_range_end: line_range.as_ref().map(|range| range.end),
+                            author: options.author,
+                            skip: options.skip as u64,
+                            limit: options.limit.map(|limit| limit as u64),
+                        })
+                        .await?;
+                    Ok(response
+                        .entries
+                        .into_iter()
+                        .map(|entry| LogEntry {
+                            sha: entry.sha.into(),
+                            parents: entry.parents.into_iter().map(Into::into).collect(),
+                            subject: entry.subject.into(),
+                            commit_timestamp: entry.commit_timestamp,
+                            author_name: entry.author_name.into(),
+                            author_email: entry.author_email.into(),
+                            refs: entry.refs.into_iter().map(Into::into).collect(),
+                        })
+                        .collect())
+                }
+            }
+        })
+    }
+
+    /// Rebases the commits after `base` following the todo list, ordered from the oldest commit to
+    /// the newest. See [`GitRepository::rebase`].
+    pub fn rebase(
+        &mut self,
+        base: String,
+        todo: Vec<RebaseTodoEntry>,
+    ) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(Some("git rebase".into()), move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local {
+                    backend,
+                    environment,
+                    ..
+                } => backend.rebase(base, todo, environment).await,
+                RepositoryState::Remote { project_id, client } => {
+                    client
+                        .request(proto::GitRebase {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            base,
+                            todo: todo
+                                .into_iter()
+                                .map(|entry| proto::GitRebaseTodoEntry {
+                                    action: match entry.action {
+                                        RebaseAction::Pick => git_rebase_todo_entry::Action::Pick,
+                                        RebaseAction::Reword => {
+                                            git_rebase_todo_entry::Action::Reword
+                                        }
+                                        RebaseAction::Edit => git_rebase_todo_entry::Action::Edit,
+                                        RebaseAction::Squash => {
+                                            git_rebase_todo_entry::Action::Squash
+                                        }
+                                        RebaseAction::Fixup => git_rebase_todo_entry::Action::Fixup,
+                                        RebaseAction::Drop => git_rebase_todo_entry::Action::Drop,
+                                    }
+                                    .into(),
+                                    sha: entry.sha.into(),
+                                    message: entry.message,
+                                })
+                                .collect(),
+                        })
+                        .await?;
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    /// Continues, skips the current commit of, or aborts the rebase in progress.
+    pub fn control_rebase(&mut self, control: RebaseControl) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        let message = match control {
+            RebaseControl::Continue => "git rebase --continue",
+            RebaseControl::Skip => "git rebase --skip",
+            RebaseControl::Abort => "git rebase --abort",
+        };
+        self.send_job(Some(message.into()), move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local {
+                    backend,
+                    environment,
+                    ..
+                } => backend.control_rebase(control, environment).await,
+                RepositoryState::Remote { project_id, client } => {
+                    client
+                        .request(proto::GitControlRebase {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            control: match control {
+                                RebaseControl::Continue => git_control_rebase::Control::Continue,
+                                RebaseControl::Skip => git_control_rebase::Control::Skip,
+                                RebaseControl::Abort => git_control_rebase::Control::Abort,
+                            }
+                            .into(),
+                        })
+                        .await?;
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    /// Applies the changes of a commit on top of the current branch. Conflicts are left to be
+    /// resolved, as for merges.
+    pub fn cherry_pick(&mut self, commit: String) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git cherry-pick {commit}").into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.cherry_pick(commit, environment).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitCherryPick {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                commit,
+                            })
+                            .await?;
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    /// Commits the reverse of the changes of a commit. Conflicts are left to be resolved, as for
+    /// merges.
+    pub fn revert(&mut self, commit: String) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git revert {commit}").into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.revert(commit, environment).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitRevert {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                commit,
+                            })
+                            .await?;
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    /// Merges a branch into the current one, leaving any conflicts to be resolved.
+    pub fn merge(
+        &mut self,
+        branch: String,
+        options: MergeOptions,
+    ) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git merge {branch}").into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.merge(branch, options, environment).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitMerge {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                branch,
+                                no_ff: options.no_ff,
+                                squash: options.squash,
+                            })
+                            .await?;
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn worktrees(&mut self) -> oneshot::Receiver<Result<Vec<GitWorktree>>> {
+        let id = self.id;
+        self.send_job(None, move |repo, _| async move {
+            match repo {
+                RepositoryState::Local { backend, .. } => backend.worktrees().await,
+                RepositoryState::Remote { project_id, client } => {
+                    let response = client
+                        .request(proto::GitGetWorktrees {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                        })
+                        .await?;
+
+                    Ok(response.worktrees.iter().map(proto_to_worktree).collect())
+                }
+            }
+        })
+    }
+
+    /// Checks out a branch in a new linked worktree at the given path, creating the branch from
+    /// `HEAD` first if `new_branch` is set.
+    pub fn create_worktree(
+        &mut self,
+        path: PathBuf,
+        branch: String,
+        new_branch: bool,
+    ) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git worktree add {}", path.display()).into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => {
+                        backend
+                            .create_worktree(path, branch, new_branch, environment)
+                            .await
+                    }
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitCreateWorktree {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                path: path.to_string_lossy().to_string(),
+                                branch,
+                                new_branch,
+                            })
+                            .await?;
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn remove_worktree(&mut self, path: PathBuf, force: bool) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git worktree remove {}", path.display()).into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.remove_worktree(path, force, environment).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitRemoveWorktree {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                path: path.to_string_lossy().to_string(),
+                                force,
+                            })
+                            .await?;
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
     pub fn load_commit_diff(&mut self, commit: String) -> oneshot::Receiver<Result<CommitDiff>> {
         let id = self.id;
         self.send_job(None, move |git_repo, cx| async move {
@@ -4402,6 +5100,7 @@ impl Repository {
                 .filter_map(|entry| proto_to_stash(entry).ok())
                 .collect(),
         };
+        self.snapshot.rebase = update.rebase.as_ref().map(proto_to_rebase_progress);
 
         let edits = update
             .removed_statuses
@@ -4977,6 +5676,31 @@ fn proto_to_branch(proto: &proto::Branch) -> git::repository::Branch {
     }
 }
 
+fn worktree_to_proto(worktree: &GitWorktree) -> proto::GitWorktree {
+    proto::GitWorktree {
+        path: worktree.path.to_string_lossy().to_string(),
+        sha: worktree.sha.as_ref().map(|sha| sha.to_string()),
+        ref_name: worktree
+            .ref_name
+            .as_ref()
+            .map(|ref_name| ref_name.to_string()),
+        is_main: worktree.is_main,
+        is_locked: worktree.is_locked,
+        is_prunable: worktree.is_prunable,
+    }
+}
+
+fn proto_to_worktree(proto: &proto::GitWorktree) -> GitWorktree {
+    GitWorktree {
+        path: PathBuf::from(&proto.path),
+        sha: proto.sha.clone().map(Into::into),
+        ref_name: proto.ref_name.clone().map(Into::into),
+        is_main: proto.is_main,
+        is_locked: proto.is_locked,
+        is_prunable: proto.is_prunable,
+    }
+}
+
 fn commit_details_to_proto(commit: &CommitDetails) -> proto::GitCommitDetails {
     proto::GitCommitDetails {
         sha: commit.sha.to_string(),
@@ -5010,6 +5734,7 @@ async fn compute_snapshot(
         .status(std::slice::from_ref(&WORK_DIRECTORY_REPO_PATH))
         .await?;
     let stash_entries = backend.stash_entries().await?;
+    let rebase = backend.rebase_progress().await;
     let statuses_by_path = SumTree::from_iter(
         statuses
             .entries
@@ -5027,6 +5752,7 @@ async fn compute_snapshot(
     if merge_heads_changed
         || branch != prev_snapshot.branch
         || statuses_by_path != prev_snapshot.statuses_by_path
+        || rebase != prev_snapshot.rebase
     {
         events.push(RepositoryEvent::Updated {
             full_scan: true,
@@ -5061,6 +5787,7 @@ async fn compute_snapshot(
         remote_origin_url,
         remote_upstream_url,
         stash_entries,
+        rebase,
     };
 
     Ok((snapshot, events))
@@ -0,0 +1,573 @@
+use crate::{
+    ProjectPath,
+    worktree_store::{WorktreeStore, WorktreeStoreEvent},
+};
+use anyhow::{Context as _, Result, anyhow};
+use collections::HashMap;
+use futures::{FutureExt as _, future::Shared};
+use git::status::{FileStatus, StatusCode, UnmergedStatus, UnmergedStatusCode};
+use gpui::{App, AppContext as _, AsyncApp, Context, Entity, EventEmitter, Subscription, Task};
+use jj::{Change, FileChangeKind, JujutsuRepository};
+use rpc::{
+    AnyProtoClient, TypedEnvelope,
+    proto::{self, FromProto, ToProto},
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use util::ResultExt as _;
+use worktree::{Worktree, WorktreeId};
+
+const STATUS_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Tracks the Jujutsu workspace containing each visible worktree, along with the
+/// status of its working-copy change.
+///
+/// Unlike git repositories, which are discovered by the worktree scanner, jj
+/// workspaces are discovered by looking for a `.jj` directory in the ancestors
+/// of each worktree root.
+pub struct JujutsuStore {
+    state: JujutsuStoreState,
+    worktree_store: Entity<WorktreeStore>,
+    repositories: HashMap<WorktreeId, JujutsuWorktreeRepository>,
+    /// In-flight workspace discovery for local worktrees, so that downstream
+    /// status requests don't race with it and report no workspace.
+    discovery_tasks: HashMap<WorktreeId, Shared<Task<()>>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+enum JujutsuStoreState {
+    Local,
+    Remote {
+        upstream_client: AnyProtoClient,
+        upstream_project_id: u64,
+    },
+}
+
+pub struct JujutsuWorktreeRepository {
+    /// The root of the jj workspace, which may be an ancestor of the worktree root.
+    pub workspace_root: Arc<Path>,
+    /// Only present for local projects; remote projects go through the upstream client.
+    backend: Option<Arc<dyn JujutsuRepository>>,
+    pub status: JujutsuStatus,
+    _refresh_task: Task<()>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct JujutsuStatus {
+    /// Files changed by the working-copy change, keyed by their path relative
+    /// to the workspace root.
+    pub changes: HashMap<Arc<Path>, FileChangeKind>,
+    pub working_copy_change: Option<Change>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RefreshKind {
+    /// The workspace was just discovered.
+    Discovery,
+    /// Files in the worktree changed on disk.
+    FilesChanged,
+    /// An operation rewrote the working-copy change.
+    Operation,
+}
+
+pub enum JujutsuStoreEvent {
+    RepositoryUpdated(WorktreeId),
+    RepositoryRemoved(WorktreeId),
+}
+
+impl EventEmitter<JujutsuStoreEvent> for JujutsuStore {}
+
+impl JujutsuStore {
+    pub fn local(worktree_store: &Entity<WorktreeStore>, cx: &mut Context<Self>) -> Self {
+        Self::new(worktree_store.clone(), JujutsuStoreState::Local, cx)
+    }
+
+    pub fn remote(
+        worktree_store: &Entity<WorktreeStore>,
+        upstream_client: AnyProtoClient,
+        upstream_project_id: u64,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        Self::new(
+            worktree_store.clone(),
+            JujutsuStoreState::Remote {
+                upstream_client,
+                upstream_project_id,
+            },
+            cx,
+        )
+    }
+
+    fn new(
+        worktree_store: Entity<WorktreeStore>,
+        state: JujutsuStoreState,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let _subscriptions = vec![cx.subscribe(&worktree_store, Self::on_worktree_store_event)];
+        let mut this = Self {
+            state,
+            worktree_store: worktree_store.clone(),
+            repositories: HashMap::default(),
+            discovery_tasks: HashMap::default(),
+            _subscriptions,
+        };
+        for worktree in worktree_store.read(cx).worktrees().collect::<Vec<_>>() {
+            this.discover_repository(&worktree, cx);
+        }
+        this
+    }
+
+    pub fn repository_for_worktree(
+        &self,
+        worktree_id: WorktreeId,
+    ) -> Option<&JujutsuWorktreeRepository> {
+        self.repositories.get(&worktree_id)
+    }
+
+    /// Whether jj operations beyond status (the change log, bookmarks and
+    /// rewriting changes) are available, which is only the case for local
+    /// projects.
+    pub fn is_local(&self) -> bool {
+        matches!(self.state, JujutsuStoreState::Local)
+    }
+
+    /// Returns the repository backing the first visible worktree that belongs
+    /// to a jj workspace, if the project is local.
+    pub fn active_repository(&self, cx: &App) -> Option<Arc<dyn JujutsuRepository>> {
+        self.worktree_store
+            .read(cx)
+            .visible_worktrees(cx)
+            .find_map(|worktree| {
+                self.repositories
+                    .get(&worktree.read(cx).id())?
+                    .backend
+                    .clone()
+            })
+    }
+
+    /// Returns the working-copy change of the first visible worktree that
+    /// belongs to a jj workspace.
+    pub fn working_copy_change(&self, cx: &App) -> Option<&Change> {
+        self.worktree_store
+            .read(cx)
+            .visible_worktrees(cx)
+            .find_map(|worktree| {
+                self.repositories
+                    .get(&worktree.read(cx).id())?
+                    .status
+                    .working_copy_change
+                    .as_ref()
+            })
+    }
+
+    pub fn status_for_project_path(
+        &self,
+        project_path: &ProjectPath,
+        cx: &App,
+    ) -> Option<FileChangeKind> {
+        let (repository, repo_path) =
+            self.repository_and_path_for_project_path(project_path, cx)?;
+        repository.status.changes.get(repo_path.as_path()).copied()
+    }
+
+    /// Loads the contents of the given path in the parents of the working-copy
+    /// change, which is what jj diffs the working copy against.
+    pub fn load_parent_text(
+        &self,
+        project_path: &ProjectPath,
+        cx: &mut Context<Self>,
+    ) -> Option<Task<Result<Option<String>>>> {
+        let (repository, repo_path) =
+            self.repository_and_path_for_project_path(project_path, cx)?;
+        let task = match &self.state {
+            JujutsuStoreState::Local => {
+                let backend = repository.backend.clone()?;
+                cx.background_spawn(async move { backend.load_parent_text(&repo_path) })
+            }
+            JujutsuStoreState::Remote {
+                upstream_client,
+                upstream_project_id,
+            } => {
+                let request = upstream_client.request(proto::LoadJujutsuParentText {
+                    project_id: *upstream_project_id,
+                    worktree_id: project_path.worktree_id.to_proto(),
+                    path: repo_path.as_path().to_proto(),
+                });
+                cx.background_spawn(async move { Ok(request.await?.text) })
+            }
+        };
+        Some(task)
+    }
+
+    /// Refreshes the status of every repository, e.g. after running an operation
+    /// that rewrote the working-copy change.
+    pub fn refresh(&mut self, cx: &mut Context<Self>) {
+        let worktree_ids = self.repositories.keys().copied().collect::<Vec<_>>();
+        for worktree_id in worktree_ids {
+            self.schedule_refresh(worktree_id, RefreshKind::Operation, cx);
+        }
+    }
+
+    fn repository_and_path_for_project_path(
+        &self,
+        project_path: &ProjectPath,
+        cx: &App,
+    ) -> Option<(&JujutsuWorktreeRepository, PathBuf)> {
+        let repository = self.repositories.get(&project_path.worktree_id)?;
+        let abs_path = self.worktree_store.read(cx).absolutize(project_path, cx)?;
+        let repo_path = abs_path
+            .strip_prefix(&repository.workspace_root)
+            .ok()?
+            .to_path_buf();
+        Some((repository, repo_path))
+    }
+
+    fn on_worktree_store_event(
+        &mut self,
+        _: Entity<WorktreeStore>,
+        event: &WorktreeStoreEvent,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            WorktreeStoreEvent::WorktreeAdded(worktree) => {
+                self.discover_repository(worktree, cx);
+            }
+            WorktreeStoreEvent::WorktreeRemoved(_, worktree_id) => {
+                self.discovery_tasks.remove(worktree_id);
+                if self.repositories.remove(worktree_id).is_some() {
+                    cx.emit(JujutsuStoreEvent::RepositoryRemoved(*worktree_id));
+                    cx.notify();
+                }
+            }
+            WorktreeStoreEvent::WorktreeUpdatedEntries(worktree_id, updated_entries) => {
+                // Snapshotting the working copy writes to `.jj`, so ignore
+                // those changes to avoid refreshing in a loop.
+                let only_jj_metadata_changed = updated_entries
+                    .iter()
+                    .all(|(path, _, _)| path.starts_with(".jj"));
+                if !only_jj_metadata_changed {
+                    self.schedule_refresh(*worktree_id, RefreshKind::FilesChanged, cx);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn discover_repository(&mut self, worktree: &Entity<Worktree>, cx: &mut Context<Self>) {
+        let worktree = worktree.read(cx);
+        if !worktree.is_visible() {
+            return;
+        }
+        let worktree_id = worktree.id();
+        let worktree_abs_path = worktree.abs_path();
+
+        match &self.state {
+            JujutsuStoreState::Local => {
+                let Some(fs) = self.worktree_store.read(cx).fs() else {
+                    return;
+                };
+                let task = cx.background_spawn(async move {
+                    let mut workspace_root = None;
+                    for ancestor in worktree_abs_path.ancestors() {
+                        if fs.is_dir(&ancestor.join(".jj")).await {
+                            workspace_root = Some(Arc::<Path>::from(ancestor));
+                            break;
+                        }
+                    }
+                    let workspace_root = workspace_root?;
+                    let backend = fs.open_jj_repo(&workspace_root)?;
+                    Some((workspace_root, backend))
+                });
+                let discovery = cx.spawn(async move |this, cx| {
+                    let discovered = task.await;
+                    this.update(cx, |this, cx| {
+                        this.discovery_tasks.remove(&worktree_id);
+                        let Some((workspace_root, backend)) = discovered else {
+                            return;
+                        };
+                        this.repositories.insert(
+                            worktree_id,
+                            JujutsuWorktreeRepository {
+                                workspace_root,
+                                backend: Some(backend),
+                                status: JujutsuStatus::default(),
+                                _refresh_task: Task::ready(()),
+                            },
+                        );
+                        this.schedule_refresh(worktree_id, RefreshKind::Discovery, cx);
+                    })
+                    .ok();
+                });
+                self.discovery_tasks.insert(worktree_id, discovery.shared());
+            }
+            JujutsuStoreState::Remote {
+                upstream_client,
+                upstream_project_id,
+            } => {
+                // Collab hosts don't forward jj requests.
+                if upstream_client.is_via_collab() {
+                    return;
+                }
+                let request = upstream_client.request(proto::GetJujutsuStatus {
+                    project_id: *upstream_project_id,
+                    worktree_id: worktree_id.to_proto(),
+                });
+                cx.spawn(async move |this, cx| {
+                    let response = request.await?;
+                    let Some((workspace_root, status)) = status_from_proto(response) else {
+                        return Ok(());
+                    };
+                    this.update(cx, |this, cx| {
+                        this.repositories.insert(
+                            worktree_id,
+                            JujutsuWorktreeRepository {
+                                workspace_root,
+                                backend: None,
+                                status,
+                                _refresh_task: Task::ready(()),
+                            },
+                        );
+                        cx.emit(JujutsuStoreEvent::RepositoryUpdated(worktree_id));
+                        cx.notify();
+                    })
+                })
+                .detach_and_log_err(cx);
+            }
+        }
+    }
+
+    fn schedule_refresh(
+        &mut self,
+        worktree_id: WorktreeId,
+        kind: RefreshKind,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(repository) = self.repositories.get_mut(&worktree_id) else {
+            return;
+        };
+        repository._refresh_task = cx.spawn(async move |this, cx| {
+            if kind == RefreshKind::FilesChanged {
+                cx.background_executor().timer(STATUS_DEBOUNCE).await;
+            }
+            // Operations snapshot the working copy before rewriting changes, so only the
+            // files changing on disk since then need a new snapshot.
+            let snapshot = kind != RefreshKind::Operation;
+            let Ok(status) =
+                this.update(cx, |this, cx| this.load_status(worktree_id, snapshot, cx))
+            else {
+                return;
+            };
+            let Some(status) = status.await.log_err() else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                if let Some(repository) = this.repositories.get_mut(&worktree_id) {
+                    repository.status = status;
+                    cx.emit(JujutsuStoreEvent::RepositoryUpdated(worktree_id));
+                    cx.notify();
+                }
+            })
+            .ok();
+        });
+    }
+
+    fn load_status(
+        &self,
+        worktree_id: WorktreeId,
+        snapshot: bool,
+        cx: &App,
+    ) -> Task<Result<JujutsuStatus>> {
+        match &self.state {
+            JujutsuStoreState::Local => {
+                let Some(backend) = self
+                    .repositories
+                    .get(&worktree_id)
+                    .and_then(|repository| repository.backend.clone())
+                else {
+                    return Task::ready(Err(anyhow!("no jj repository for worktree")));
+                };
+                cx.background_spawn(async move { load_local_status(backend.as_ref(), snapshot) })
+            }
+            JujutsuStoreState::Remote {
+                upstream_client,
+                upstream_project_id,
+            } => {
+                let request = upstream_client.request(proto::GetJujutsuStatus {
+                    project_id: *upstream_project_id,
+                    worktree_id: worktree_id.to_proto(),
+                });
+                cx.background_spawn(async move {
+                    let (_, status) =
+                        status_from_proto(request.await?).context("jj repository was removed")?;
+                    Ok(status)
+                })
+            }
+        }
+    }
+
+    pub(super) async fn handle_get_status(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetJujutsuStatus>,
+        cx: AsyncApp,
+    ) -> Result<proto::GetJujutsuStatusResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let discovery = this.read_with(&cx, |this, _| {
+            this.discovery_tasks.get(&worktree_id).cloned()
+        })?;
+        if let Some(discovery) = discovery {
+            discovery.await;
+        }
+        let repository = this.read_with(&cx, |this, _| {
+            let repository = this.repositories.get(&worktree_id)?;
+            Some((
+                repository.workspace_root.clone(),
+                repository.backend.clone()?,
+            ))
+        })?;
+        let Some((workspace_root, backend)) = repository else {
+            return Ok(proto::GetJujutsuStatusResponse::default());
+        };
+
+        // Guests only request the status when the workspace is discovered or its files change.
+        let status = cx
+            .background_spawn(async move { load_local_status(backend.as_ref(), true) })
+            .await?;
+        Ok(status_to_proto(&workspace_root, &status))
+    }
+
+    pub(super) async fn handle_load_parent_text(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::LoadJujutsuParentText>,
+        cx: AsyncApp,
+    ) -> Result<proto::LoadJujutsuParentTextResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let backend = this
+            .read_with(&cx, |this, _| {
+                this.repositories
+                    .get(&worktree_id)
+                    .and_then(|repository| repository.backend.clone())
+            })?
+            .context("no jj repository for worktree")?;
+        let path = PathBuf::from_proto(envelope.payload.path);
+
+        let text = cx
+            .background_spawn(async move { backend.load_parent_text(&path) })
+            .await?;
+        Ok(proto::LoadJujutsuParentTextResponse { text })
+    }
+}
+
+fn load_local_status(backend: &dyn JujutsuRepository, snapshot: bool) -> Result<JujutsuStatus> {
+    if snapshot {
+        backend.snapshot_working_copy()?;
+    }
+    let changes = backend
+        .status()?
+        .into_iter()
+        .map(|change| (change.path, change.kind))
+        .collect();
+    let working_copy_change = backend.working_copy_change()?;
+    Ok(JujutsuStatus {
+        changes,
+        working_copy_change,
+    })
+}
+
+/// Maps a jj file change onto the closest git status, so that jj workspaces
+/// are decorated the same way as git repositories.
+pub fn file_change_kind_to_git_status(kind: FileChangeKind) -> FileStatus {
+    match kind {
+        FileChangeKind::Added => FileStatus::worktree(StatusCode::Added),
+        FileChangeKind::Modified => FileStatus::worktree(StatusCode::Modified),
+        FileChangeKind::Deleted => FileStatus::worktree(StatusCode::Deleted),
+        FileChangeKind::Conflicted => FileStatus::Unmerged(UnmergedStatus {
+            first_head: UnmergedStatusCode::Updated,
+            second_head: UnmergedStatusCode::Updated,
+        }),
+    }
+}
+
+fn status_to_proto(
+    workspace_root: &Path,
+    status: &JujutsuStatus,
+) -> proto::GetJujutsuStatusResponse {
+    use proto::jujutsu_file_change::Kind;
+
+    proto::GetJujutsuStatusResponse {
+        workspace_root: Some(workspace_root.to_proto()),
+        changes: status
+            .changes
+            .iter()
+            .map(|(path, kind)| proto::JujutsuFileChange {
+                path: path.as_ref().to_proto(),
+                kind: match kind {
+                    FileChangeKind::Added => Kind::Added,
+                    FileChangeKind::Modified => Kind::Modified,
+                    FileChangeKind::Deleted => Kind::Deleted,
+                    FileChangeKind::Conflicted => Kind::Conflicted,
+                } as i32,
+            })
+            .collect(),
+        working_copy_change: status.working_copy_change.as_ref().map(|change| {
+            proto::JujutsuChange {
+                change_id: change.change_id.to_string(),
+                commit_id: change.commit_id.to_string(),
+                description: change.description.to_string(),
+                author_name: change.author_name.to_string(),
+                author_email: change.author_email.to_string(),
+                timestamp: change.timestamp,
+                bookmarks: change
+                    .bookmarks
+                    .iter()
+                    .map(|bookmark| bookmark.to_string())
+                    .collect(),
+                is_empty: change.is_empty,
+                has_conflict: change.has_conflict,
+            }
+        }),
+    }
+}
+
+fn status_from_proto(
+    response: proto::GetJujutsuStatusResponse,
+) -> Option<(Arc<Path>, JujutsuStatus)> {
+    use proto::jujutsu_file_change::Kind;
+
+    let workspace_root = Arc::<Path>::from_proto(response.workspace_root?);
+    let changes = response
+        .changes
+        .into_iter()
+        .filter_map(|change| {
+            let kind = match Kind::from_i32(change.kind)? {
+                Kind::Added => FileChangeKind::Added,
+                Kind::Modified => FileChangeKind::Modified,
+                Kind::Deleted => FileChangeKind::Deleted,
+                Kind::Conflicted => FileChangeKind::Conflicted,
+            };
+            Some((Arc::<Path>::from_proto(change.path), kind))
+        })
+        .collect();
+    let working_copy_change = response.working_copy_change.map(|change| Change {
+        change_id: change.change_id.into(),
+        commit_id: change.commit_id.into(),
+        description: change.description.into(),
+        author_name: change.author_name.into(),
+        author_email: change.author_email.into(),
+        timestamp: change.timestamp,
+        bookmarks: change.bookmarks.into_iter().map(Into::into).collect(),
+        is_working_copy: true,
+        is_empty: change.is_empty,
+        has_conflict: change.has_conflict,
+        parents: Vec::new(),
+    });
+    Some((
+        workspace_root,
+        JujutsuStatus {
+            changes,
+            working_copy_change,
+        },
+    ))
+}
@@ -14,12 +14,13 @@ pub mod json_language_server_ext;
 pub mod log_store;
 pub mod lsp_ext_command;
 pub mod rust_analyzer_ext;
+pub mod semantic_tokens;
 
 use crate::{
-    CodeAction, ColorPresentation, Completion, CompletionDisplayOptions, CompletionResponse,
-    CompletionSource, CoreCompletion, DocumentColor, Hover, InlayHint, LocationLink, LspAction,
-    LspPullDiagnostics, ManifestProvidersStore, Project, ProjectItem, ProjectPath,
-    ProjectTransaction, PulledDiagnostics, ResolveState, Symbol,
+    CallHierarchyItem, CodeAction, ColorPresentation, Completion, CompletionDisplayOptions,
+    CompletionResponse, CompletionSource, CoreCompletion, DocumentColor, Hover, InlayHint,
+    LocationLink, LspAction, LspPullDiagnostics, ManifestProvidersStore, Project, ProjectItem,
+    ProjectPath, ProjectTransaction, PulledDiagnostics, ResolveState, Symbol, TypeHierarchyItem,
     buffer_store::{BufferStore, BufferStoreEvent},
     environment::ProjectEnvironment,
     lsp_command::{self, *},
@@ -112,6 +113,7 @@ use std::{
     time::{Duration, Instant},
 };
 use sum_tree::Dimensions;
+use task::{Problem, ProblemSeverity};
 use text::{Anchor, BufferId, LineEnding, OffsetRangeExt, ToPoint as _};
 
 use util::{
@@ -222,6 +224,18 @@ pub struct LocalLspStore {
     registered_buffers: HashMap<BufferId, usize>,
     buffers_opened_in_servers: HashMap<BufferId, HashSet<LanguageServerId>>,
     buffer_pull_diagnostics_result_ids: HashMap<LanguageServerId, HashMap<PathBuf, Option<String>>>,
+    task_diagnostics: TaskDiagnostics,
+}
+
+/// The diagnostics reported by the problem matchers of tasks.
+///
+/// No language server produces them, so they are stored under a source id reserved for them in
+/// the language registry, which no language server can be assigned, and are marked with
+/// [`DiagnosticSourceKind::Other`].
+struct TaskDiagnostics {
+    source_id: LanguageServerId,
+    diagnostics_by_path:
+        HashMap<PathBuf, HashMap<String, Vec<DiagnosticEntry<Unclipped<PointUtf16>>>>>,
 }
 
 impl LocalLspStore {
@@ -3487,6 +3501,7 @@ pub struct LspStore {
     pub lsp_server_capabilities: HashMap<LanguageServerId, lsp::ServerCapabilities>,
     lsp_document_colors: HashMap<BufferId, DocumentColorData>,
     lsp_code_lens: HashMap<BufferId, CodeLensData>,
+    lsp_semantic_tokens: HashMap<BufferId, semantic_tokens::SemanticTokensData>,
     running_lsp_requests: HashMap<TypeId, (Global, HashMap<LspRequestId, Task<()>>)>,
 }
 
@@ -3595,6 +3610,7 @@ impl LspStore {
         client.add_entity_request_handler(Self::handle_get_project_symbols);
         client.add_entity_request_handler(Self::handle_resolve_inlay_hint);
         client.add_entity_request_handler(Self::handle_get_color_presentation);
+        client.add_entity_request_handler(Self::handle_get_semantic_tokens);
         client.add_entity_request_handler(Self::handle_open_buffer_for_symbol);
         client.add_entity_request_handler(Self::handle_refresh_inlay_hints);
         client.add_entity_request_handler(Self::handle_refresh_code_lens);
@@ -3606,6 +3622,15 @@ impl LspStore {
         client.add_entity_request_handler(Self::handle_lsp_command::<GetCompletions>);
         client.add_entity_request_handler(Self::handle_lsp_command::<GetDocumentHighlights>);
         client.add_entity_request_handler(Self::handle_lsp_command::<GetDocumentSymbols>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<PrepareCallHierarchy>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetIncomingCalls>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetOutgoingCalls>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<PrepareTypeHierarchy>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetSupertypes>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetSubtypes>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetFoldingRanges>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetSelectionRanges>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetDocumentLinks>);
         client.add_entity_request_handler(Self::handle_lsp_command::<PrepareRename>);
         client.add_entity_request_handler(Self::handle_lsp_command::<PerformRename>);
         client.add_entity_request_handler(Self::handle_lsp_command::<LinkedEditingRange>);
@@ -3724,6 +3749,10 @@ impl LspStore {
                 yarn,
                 next_diagnostic_group_id: Default::default(),
                 diagnostics: Default::default(),
+                task_diagnostics: TaskDiagnostics {
+                    source_id: languages.next_language_server_id(),
+                    diagnostics_by_path: HashMap::default(),
+                },
                 _subscription: cx.on_app_quit(|this, cx| {
                     this.as_local_mut()
                         .unwrap()
@@ -3752,6 +3781,7 @@ impl LspStore {
             lsp_server_capabilities: HashMap::default(),
             lsp_document_colors: HashMap::default(),
             lsp_code_lens: HashMap::default(),
+            lsp_semantic_tokens: HashMap::default(),
             running_lsp_requests: HashMap::default(),
             active_entry: None,
             _maintain_workspace_config,
@@ -3813,6 +3843,7 @@ impl LspStore {
             lsp_server_capabilities: HashMap::default(),
             lsp_document_colors: HashMap::default(),
             lsp_code_lens: HashMap::default(),
+            lsp_semantic_tokens: HashMap::default(),
             running_lsp_requests: HashMap::default(),
             active_entry: None,
 
@@ -4106,6 +4137,7 @@ impl LspStore {
                     if refcount == 0 {
                         lsp_store.lsp_document_colors.remove(&buffer_id);
                         lsp_store.lsp_code_lens.remove(&buffer_id);
+                        lsp_store.lsp_semantic_tokens.remove(&buffer_id);
                         let local = lsp_store.as_local_mut().unwrap();
                         local.registered_buffers.remove(&buffer_id);
                         local.buffers_opened_in_servers.remove(&buffer_id);
@@ -5279,6 +5311,129 @@ impl LspStore {
         }
     }
 
+    /// Prepares the call hierarchy of the symbol at `position` with every
+    /// capable language server, each item remembering the server it came from.
+    pub fn prepare_call_hierarchy(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        position: PointUtf16,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<CallHierarchyItem>>> {
+        if let Some((upstream_client, project_id)) = self.upstream_client() {
+            let request = PrepareCallHierarchy { position };
+            if !self.is_capable_for_proto_request(buffer, &request, cx) {
+                return Task::ready(Ok(Vec::new()));
+            }
+            let request_task = upstream_client.request_lsp(
+                project_id,
+                LSP_REQUEST_TIMEOUT,
+                cx.background_executor().clone(),
+                request.to_proto(project_id, buffer.read(cx)),
+            );
+            let buffer = buffer.clone();
+            cx.spawn(async move |weak_project, cx| {
+                let Some(project) = weak_project.upgrade() else {
+                    return Ok(Vec::new());
+                };
+                let Some(responses) = request_task.await? else {
+                    return Ok(Vec::new());
+                };
+                let items = join_all(responses.payload.into_iter().map(|response| {
+                    PrepareCallHierarchy { position }.response_from_proto(
+                        response.response,
+                        project.clone(),
+                        buffer.clone(),
+                        cx.clone(),
+                    )
+                }))
+                .await;
+
+                Ok(items
+                    .into_iter()
+                    .collect::<Result<Vec<Vec<_>>>>()?
+                    .into_iter()
+                    .flatten()
+                    .collect())
+            })
+        } else {
+            let items_task = self.request_multiple_lsp_locally(
+                buffer,
+                Some(position),
+                PrepareCallHierarchy { position },
+                cx,
+            );
+            cx.background_spawn(async move {
+                Ok(items_task
+                    .await
+                    .into_iter()
+                    .flat_map(|(_, items)| items)
+                    .collect())
+            })
+        }
+    }
+
+    /// Prepares the type hierarchy of the symbol at `position` with every
+    /// language server, since servers can't advertise support for it, each
+    /// item remembering the server it came from.
+    pub fn prepare_type_hierarchy(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        position: PointUtf16,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        if let Some((upstream_client, project_id)) = self.upstream_client() {
+            let request = PrepareTypeHierarchy { position };
+            if !self.is_capable_for_proto_request(buffer, &request, cx) {
+                return Task::ready(Ok(Vec::new()));
+            }
+            let request_task = upstream_client.request_lsp(
+                project_id,
+                LSP_REQUEST_TIMEOUT,
+                cx.background_executor().clone(),
+                request.to_proto(project_id, buffer.read(cx)),
+            );
+            let buffer = buffer.clone();
+            cx.spawn(async move |weak_project, cx| {
+                let Some(project) = weak_project.upgrade() else {
+                    return Ok(Vec::new());
+                };
+                let Some(responses) = request_task.await? else {
+                    return Ok(Vec::new());
+                };
+                let items = join_all(responses.payload.into_iter().map(|response| {
+                    PrepareTypeHierarchy { position }.response_from_proto(
+                        response.response,
+                        project.clone(),
+                        buffer.clone(),
+                        cx.clone(),
+                    )
+                }))
+                .await;
+
+                Ok(items
+                    .into_iter()
+                    .collect::<Result<Vec<Vec<_>>>>()?
+                    .into_iter()
+                    .flatten()
+                    .collect())
+            })
+        } else {
+            let items_task = self.request_multiple_lsp_locally(
+                buffer,
+                Some(position),
+                PrepareTypeHierarchy { position },
+                cx,
+            );
+            cx.background_spawn(async move {
+                Ok(items_task
+                    .await
+                    .into_iter()
+                    .flat_map(|(_, items)| items)
+                    .collect())
+            })
+        }
+    }
+
     pub fn declarations(
         &mut self,
         buffer: &Entity<Buffer>,
@@ -7730,6 +7885,113 @@ impl LspStore {
         Ok(())
     }
 
+    /// Replaces the diagnostics reported by the task with the given label with the problems given,
+    /// whose paths must be absolute. Problems outside of the project's worktrees are ignored.
+    pub fn update_task_diagnostics(
+        &mut self,
+        task_label: &str,
+        problems: Vec<Problem>,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        let local = self
+            .as_local_mut()
+            .context("cannot report task diagnostics in a remote project")?;
+        let mut new_diagnostics =
+            HashMap::<PathBuf, Vec<DiagnosticEntry<Unclipped<PointUtf16>>>>::default();
+        for problem in problems {
+            let start = PointUtf16::new(
+                problem.line.saturating_sub(1),
+                problem.column.unwrap_or(1).saturating_sub(1),
+            );
+            let end = if problem.end_line.is_some() || problem.end_column.is_some() {
+                PointUtf16::new(
+                    problem
+                        .end_line
+                        .map_or(start.row, |row| row.saturating_sub(1)),
+                    problem
+                        .end_column
+                        .map_or(start.column, |column| column.saturating_sub(1)),
+                )
+            } else {
+                start
+            };
+            let severity = match problem.severity {
+                ProblemSeverity::Error => DiagnosticSeverity::ERROR,
+                ProblemSeverity::Warning => DiagnosticSeverity::WARNING,
+                ProblemSeverity::Info => DiagnosticSeverity::INFORMATION,
+                ProblemSeverity::Hint => DiagnosticSeverity::HINT,
+            };
+            new_diagnostics
+                .entry(problem.path)
+                .or_default()
+                .push(DiagnosticEntry {
+                    range: Unclipped(start)..Unclipped(end),
+                    diagnostic: Diagnostic {
+                        source: Some(task_label.to_string()),
+                        code: problem.code.map(lsp::NumberOrString::String),
+                        severity,
+                        message: problem.message,
+                        group_id: post_inc(&mut local.next_diagnostic_group_id),
+                        is_primary: true,
+                        is_disk_based: true,
+                        source_kind: DiagnosticSourceKind::Other,
+                        ..Diagnostic::default()
+                    },
+                });
+        }
+
+        let task_diagnostics = &mut local.task_diagnostics;
+        let mut updated_paths = Vec::new();
+        task_diagnostics
+            .diagnostics_by_path
+            .retain(|abs_path, diagnostics_by_task| {
+                if diagnostics_by_task.remove(task_label).is_some() {
+                    updated_paths.push(abs_path.clone());
+                }
+                !diagnostics_by_task.is_empty()
+            });
+        for (abs_path, diagnostics) in new_diagnostics {
+            updated_paths.push(abs_path.clone());
+            task_diagnostics
+                .diagnostics_by_path
+                .entry(abs_path)
+                .or_default()
+                .insert(task_label.to_string(), diagnostics);
+        }
+        updated_paths.sort();
+        updated_paths.dedup();
+
+        let mut updates = updated_paths
+            .into_iter()
+            .map(|abs_path| {
+                let diagnostics = task_diagnostics
+                    .diagnostics_by_path
+                    .get(&abs_path)
+                    .into_iter()
+                    .flat_map(|diagnostics_by_task| diagnostics_by_task.values().flatten())
+                    .cloned()
+                    .collect();
+                DocumentDiagnosticsUpdate {
+                    diagnostics: DocumentDiagnostics {
+                        diagnostics,
+                        document_abs_path: abs_path,
+                        version: None,
+                    },
+                    result_id: None,
+                    server_id: task_diagnostics.source_id,
+                    disk_based_sources: Cow::Borrowed(&[]),
+                }
+            })
+            .collect::<Vec<_>>();
+        updates.retain(|update| {
+            self.worktree_store
+                .read(cx)
+                .find_worktree(&update.diagnostics.document_abs_path, cx)
+                .is_some()
+        });
+        self.merge_diagnostic_entries(updates, |_, _, _| false, cx)
+    }
+
     fn update_worktree_diagnostics(
         &mut self,
         worktree_id: WorktreeId,
@@ -8026,7 +8288,7 @@ impl LspStore {
             .update(&mut cx, |this, cx| {
                 this.request_lsp(
                     buffer_handle.clone(),
-                    LanguageServerToQuery::FirstCapable,
+                    request.language_server_to_query(),
                     request,
                     cx,
                 )
@@ -8182,6 +8444,36 @@ impl LspStore {
                 )
                 .await?;
             }
+            Request::PrepareCallHierarchy(prepare_call_hierarchy) => {
+                let position = prepare_call_hierarchy
+                    .position
+                    .clone()
+                    .and_then(deserialize_anchor);
+                Self::query_lsp_locally::<PrepareCallHierarchy>(
+                    lsp_store,
+                    sender_id,
+                    lsp_request_id,
+                    prepare_call_hierarchy,
+                    position,
+                    cx.clone(),
+                )
+                .await?;
+            }
+            Request::PrepareTypeHierarchy(prepare_type_hierarchy) => {
+                let position = prepare_type_hierarchy
+                    .position
+                    .clone()
+                    .and_then(deserialize_anchor);
+                Self::query_lsp_locally::<PrepareTypeHierarchy>(
+                    lsp_store,
+                    sender_id,
+                    lsp_request_id,
+                    prepare_type_hierarchy,
+                    position,
+                    cx.clone(),
+                )
+                .await?;
+            }
             // Diagnostics pull synchronizes internally via the buffer state, and cannot be handled generically as the other requests.
             Request::GetDocumentDiagnostics(get_document_diagnostics) => {
                 let buffer_id = BufferId::new(get_document_diagnostics.buffer_id())?;
@@ -11110,6 +11402,8 @@ impl LspStore {
         for buffer_lens in self.lsp_code_lens.values_mut() {
             buffer_lens.lens.remove(&for_server);
         }
+        self.lsp_semantic_tokens
+            .retain(|_, tokens| tokens.server_id != Some(for_server));
         if let Some(local) = self.as_local_mut() {
             local.buffer_pull_diagnostics_result_ids.remove(&for_server);
             for buffer_servers in local.buffers_opened_in_servers.values_mut() {
@@ -12550,7 +12844,7 @@ impl From<lsp::Documentation> for CompletionDocumentation {
     }
 }
 
-fn glob_literal_prefix(glob: &Path) -> PathBuf {
+pub(crate) fn glob_literal_prefix(glob: &Path) -> PathBuf {
     glob.components()
         .take_while(|component| match component {
             path::Component::Normal(part) => !part.to_string_lossy().contains(['*', '?', '{', '}']),
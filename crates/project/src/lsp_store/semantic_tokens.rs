@@ -0,0 +1,545 @@
+//! Support for `textDocument/semanticTokens/full` and `textDocument/semanticTokens/full/delta`.
+//!
+//! The raw, relative token data reported by the language server is cached per buffer, so that
+//! subsequent requests can ask for a delta against the previous result instead of the whole
+//! token set.
+
+use std::{cmp::Reverse, ops::Range};
+
+use anyhow::{Context as _, Result};
+use client::{TypedEnvelope, proto};
+use clock::Global;
+use futures::{FutureExt as _, future::LocalBoxFuture};
+use gpui::{AsyncApp, Context, Entity, Task};
+use language::{
+    Anchor, Bias, Buffer, PointUtf16, Unclipped,
+    proto::{deserialize_version, serialize_version},
+};
+use lsp::LanguageServerId;
+use text::BufferId;
+
+use crate::{File, lsp_command::file_path_to_lsp_url, lsp_store::LspStore};
+
+/// Semantic tokens of a buffer, resolved to buffer anchors.
+#[derive(Clone, Debug, Default)]
+pub struct BufferSemanticTokens {
+    pub legend: SemanticTokensLegend,
+    pub tokens: Vec<SemanticToken>,
+}
+
+/// Token type and modifier names, as declared by the language server.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SemanticTokensLegend {
+    pub token_types: Vec<String>,
+    pub token_modifiers: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SemanticToken {
+    pub range: Range<Anchor>,
+    pub token_type: u32,
+    pub token_modifiers: u32,
+}
+
+impl BufferSemanticTokens {
+    pub fn token_type(&self, token: &SemanticToken) -> Option<&str> {
+        self.legend
+            .token_types
+            .get(token.token_type as usize)
+            .map(String::as_str)
+    }
+
+    pub fn token_modifiers<'a>(&'a self, token: &SemanticToken) -> impl Iterator<Item = &'a str> {
+        let bitset = token.token_modifiers;
+        self.legend
+            .token_modifiers
+            .iter()
+            .enumerate()
+            .filter(move |(ix, _)| *ix < 32 && bitset & (1 << ix) != 0)
+            .map(|(_, modifier)| modifier.as_str())
+    }
+}
+
+/// The last semantic tokens result received for a buffer.
+#[derive(Debug, Default)]
+pub(super) struct SemanticTokensData {
+    pub(super) server_id: Option<LanguageServerId>,
+    result_id: Option<String>,
+    data: Vec<u32>,
+    /// The buffer version that `data` describes.
+    version: Global,
+}
+
+struct RawSemanticTokens {
+    legend: SemanticTokensLegend,
+    data: Vec<u32>,
+    version: Global,
+}
+
+enum SemanticTokensUpdate {
+    Full(Vec<u32>),
+    Edits(Vec<lsp::SemanticTokensEdit>),
+}
+
+impl LspStore {
+    /// Queries the first language server of the buffer that provides semantic tokens.
+    ///
+    /// Returns `None` if no such server is running for the buffer. The tokens are mapped
+    /// through the edits made to the buffer while they were requested.
+    pub fn semantic_tokens(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Option<BufferSemanticTokens>>> {
+        let raw_tokens = self.fetch_semantic_tokens(buffer, true, cx);
+        let buffer = buffer.clone();
+        cx.spawn(async move |_, cx| {
+            let Some(raw_tokens) = raw_tokens.await? else {
+                return Ok(None);
+            };
+            let snapshot = buffer.read_with(cx, |buffer, _| buffer.text_snapshot())?;
+            let tokens = cx
+                .background_spawn(async move { resolve_semantic_tokens(raw_tokens, &snapshot) })
+                .await;
+            Ok(Some(tokens))
+        })
+    }
+
+    /// Requests the raw tokens of the buffer, as a delta against the last result if `allow_delta`
+    /// is set and the server supports it.
+    fn fetch_semantic_tokens(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        allow_delta: bool,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Option<RawSemanticTokens>>> {
+        if let Some((client, project_id)) = self.upstream_client() {
+            let request = proto::GetSemanticTokens {
+                project_id,
+                buffer_id: buffer.read(cx).remote_id().into(),
+                version: serialize_version(&buffer.read(cx).version()),
+            };
+            let buffer = buffer.clone();
+            return cx.spawn(async move |_, cx| {
+                let response = client.request(request).await?;
+                let Some(tokens) = response.tokens else {
+                    return Ok(None);
+                };
+                let version = deserialize_version(&tokens.version);
+                buffer
+                    .update(cx, |buffer, _| buffer.wait_for_version(version.clone()))?
+                    .await?;
+                Ok(Some(RawSemanticTokens {
+                    legend: SemanticTokensLegend {
+                        token_types: tokens.token_types,
+                        token_modifiers: tokens.token_modifiers,
+                    },
+                    data: tokens.data,
+                    version,
+                }))
+            });
+        }
+
+        let Some(abs_path) = File::from_dyn(buffer.read(cx).file())
+            .and_then(|file| file.as_local().map(|file| file.abs_path(cx)))
+        else {
+            return Task::ready(Ok(None));
+        };
+        let server = buffer.update(cx, |buffer, cx| {
+            self.language_servers_for_local_buffer(buffer, cx)
+                .find_map(|(_, server)| {
+                    let options = semantic_tokens_options(&server.capabilities())?;
+                    Some((server.clone(), options))
+                })
+        });
+        let Some((server, options)) = server else {
+            return Task::ready(Ok(None));
+        };
+        let supports_full = match options.full {
+            Some(lsp::SemanticTokensFullOptions::Bool(full)) => full,
+            Some(lsp::SemanticTokensFullOptions::Delta { .. }) => true,
+            None => false,
+        };
+        if !supports_full {
+            return Task::ready(Ok(None));
+        }
+        let supports_delta = matches!(
+            options.full,
+            Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) })
+        );
+
+        let server_id = server.server_id();
+        let buffer_id = buffer.read(cx).remote_id();
+        let version = buffer.read(cx).version();
+        let previous_result_id = self
+            .lsp_semantic_tokens
+            .get(&buffer_id)
+            .filter(|data| allow_delta && supports_delta && data.server_id == Some(server_id))
+            .and_then(|data| data.result_id.clone());
+        let legend = SemanticTokensLegend {
+            token_types: options
+                .legend
+                .token_types
+                .iter()
+                .map(|token_type| token_type.as_str().to_string())
+                .collect(),
+            token_modifiers: options
+                .legend
+                .token_modifiers
+                .iter()
+                .map(|modifier| modifier.as_str().to_string())
+                .collect(),
+        };
+
+        let text_document = match file_path_to_lsp_url(&abs_path) {
+            Ok(uri) => lsp::TextDocumentIdentifier { uri },
+            Err(error) => return Task::ready(Err(error)),
+        };
+        // Send the request right away, so that it's answered for the buffer at `version`
+        // rather than after edits made before this task is first polled.
+        let response: LocalBoxFuture<Result<(Option<String>, SemanticTokensUpdate)>> =
+            match previous_result_id.clone() {
+                Some(previous_result_id) => {
+                    let request = server.request::<lsp::request::SemanticTokensFullDeltaRequest>(
+                        lsp::SemanticTokensDeltaParams {
+                            text_document,
+                            previous_result_id,
+                            work_done_progress_params: Default::default(),
+                            partial_result_params: Default::default(),
+                        },
+                    );
+                    async move {
+                        let response = request
+                            .await
+                            .into_response()
+                            .context("semantic tokens delta request")?;
+                        Ok(delta_response_to_update(response))
+                    }
+                    .boxed_local()
+                }
+                None => {
+                    let request = server.request::<lsp::request::SemanticTokensFullRequest>(
+                        lsp::SemanticTokensParams {
+                            text_document,
+                            work_done_progress_params: Default::default(),
+                            partial_result_params: Default::default(),
+                        },
+                    );
+                    async move {
+                        let response = request
+                            .await
+                            .into_response()
+                            .context("semantic tokens request")?;
+                        Ok(full_response_to_update(response))
+                    }
+                    .boxed_local()
+                }
+            };
+
+        let buffer = buffer.clone();
+        cx.spawn(async move |lsp_store, cx| {
+            let (result_id, update) = response.await?;
+            let data = lsp_store.update(cx, |lsp_store, _| {
+                let cached = lsp_store.lsp_semantic_tokens.entry(buffer_id).or_default();
+                let data = match update {
+                    SemanticTokensUpdate::Full(data) => data,
+                    SemanticTokensUpdate::Edits(edits) => {
+                        // Another request for the buffer may have been answered while this one
+                        // was in flight, in which case the edits no longer apply to the cached
+                        // data.
+                        if cached.server_id != Some(server_id)
+                            || cached.result_id != previous_result_id
+                        {
+                            return None;
+                        }
+                        let mut data = cached.data.clone();
+                        apply_semantic_token_edits(&mut data, edits);
+                        data
+                    }
+                };
+                // Only cache results at least as new as the cached one, so that later deltas are
+                // requested against the newest result.
+                if cached.server_id != Some(server_id) || version.observed_all(&cached.version) {
+                    cached.server_id = Some(server_id);
+                    cached.result_id = result_id;
+                    cached.data = data.clone();
+                    cached.version = version.clone();
+                }
+                Some(data)
+            })?;
+            match data {
+                Some(data) => Ok(Some(RawSemanticTokens {
+                    legend,
+                    data,
+                    version,
+                })),
+                None => {
+                    lsp_store
+                        .update(cx, |lsp_store, cx| {
+                            lsp_store.fetch_semantic_tokens(&buffer, false, cx)
+                        })?
+                        .await
+                }
+            }
+        })
+    }
+
+    pub(super) async fn handle_get_semantic_tokens(
+        lsp_store: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetSemanticTokens>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GetSemanticTokensResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let buffer = lsp_store.update(&mut cx, |lsp_store, cx| {
+            lsp_store.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&envelope.payload.version))
+            })?
+            .await?;
+        let tokens = lsp_store
+            .update(&mut cx, |lsp_store, cx| {
+                lsp_store.fetch_semantic_tokens(&buffer, true, cx)
+            })?
+            .await?;
+        // The tokens may describe a version newer than the one the guest asked for, which the
+        // guest waits for and maps its tokens from.
+        Ok(proto::GetSemanticTokensResponse {
+            tokens: tokens.map(|tokens| proto::SemanticTokens {
+                token_types: tokens.legend.token_types,
+                token_modifiers: tokens.legend.token_modifiers,
+                data: tokens.data,
+                version: serialize_version(&tokens.version),
+            }),
+        })
+    }
+}
+
+fn full_response_to_update(
+    response: Option<lsp::SemanticTokensResult>,
+) -> (Option<String>, SemanticTokensUpdate) {
+    match response {
+        Some(lsp::SemanticTokensResult::Tokens(tokens)) => (
+            tokens.result_id,
+            SemanticTokensUpdate::Full(flatten_semantic_tokens(tokens.data)),
+        ),
+        Some(lsp::SemanticTokensResult::Partial(partial)) => (
+            None,
+            SemanticTokensUpdate::Full(flatten_semantic_tokens(partial.data)),
+        ),
+        None => (None, SemanticTokensUpdate::Full(Vec::new())),
+    }
+}
+
+fn delta_response_to_update(
+    response: Option<lsp::SemanticTokensFullDeltaResult>,
+) -> (Option<String>, SemanticTokensUpdate) {
+    match response {
+        Some(lsp::SemanticTokensFullDeltaResult::Tokens(tokens)) => (
+            tokens.result_id,
+            SemanticTokensUpdate::Full(flatten_semantic_tokens(tokens.data)),
+        ),
+        Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(delta)) => {
+            (delta.result_id, SemanticTokensUpdate::Edits(delta.edits))
+        }
+        Some(lsp::SemanticTokensFullDeltaResult::PartialTokensDelta { edits }) => {
+            (None, SemanticTokensUpdate::Edits(edits))
+        }
+        None => (None, SemanticTokensUpdate::Full(Vec::new())),
+    }
+}
+
+fn semantic_tokens_options(
+    capabilities: &lsp::ServerCapabilities,
+) -> Option<lsp::SemanticTokensOptions> {
+    match capabilities.semantic_tokens_provider.as_ref()? {
+        lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(options) => {
+            Some(options.clone())
+        }
+        lsp::SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(options) => {
+            Some(options.semantic_tokens_options.clone())
+        }
+    }
+}
+
+/// Anchors the tokens in the buffer, mapping them through the edits made since the version they
+/// describe. Tokens whose text was edited are dropped.
+fn resolve_semantic_tokens(
+    raw_tokens: RawSemanticTokens,
+    buffer: &text::BufferSnapshot,
+) -> BufferSemanticTokens {
+    let mut edits = buffer
+        .edits_since::<PointUtf16>(&raw_tokens.version)
+        .peekable();
+    let mut last_edit = None;
+    let tokens = decode_semantic_tokens(&raw_tokens.data)
+        .filter_map(|token| {
+            let start = PointUtf16::new(token.line, token.start);
+            let end = PointUtf16::new(token.line, token.start + token.length);
+            while let Some(edit) = edits.next_if(|edit| edit.old.end <= start) {
+                last_edit = Some(edit);
+            }
+            if edits.peek().is_some_and(|edit| edit.old.start < end) {
+                return None;
+            }
+            let start = buffer.clip_point_utf16(
+                Unclipped(map_point_through_edit(start, last_edit.as_ref())),
+                Bias::Left,
+            );
+            let end = buffer.clip_point_utf16(
+                Unclipped(map_point_through_edit(end, last_edit.as_ref())),
+                Bias::Right,
+            );
+            Some(SemanticToken {
+                range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                token_type: token.token_type,
+                token_modifiers: token.token_modifiers,
+            })
+        })
+        .collect();
+    BufferSemanticTokens {
+        legend: raw_tokens.legend,
+        tokens,
+    }
+}
+
+/// Maps a point following the given edit, the last one before it, to the edited buffer.
+fn map_point_through_edit(point: PointUtf16, edit: Option<&text::Edit<PointUtf16>>) -> PointUtf16 {
+    match edit {
+        None => point,
+        Some(edit) if point.row == edit.old.end.row => PointUtf16::new(
+            edit.new.end.row,
+            edit.new.end.column + point.column - edit.old.end.column,
+        ),
+        Some(edit) => PointUtf16::new(
+            point.row - edit.old.end.row + edit.new.end.row,
+            point.column,
+        ),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct AbsoluteSemanticToken {
+    line: u32,
+    start: u32,
+    length: u32,
+    token_type: u32,
+    token_modifiers: u32,
+}
+
+/// Converts the relative token encoding of the protocol into absolute positions.
+fn decode_semantic_tokens(data: &[u32]) -> impl Iterator<Item = AbsoluteSemanticToken> + '_ {
+    let mut line = 0;
+    let mut start = 0;
+    data.chunks_exact(5).map(move |token| {
+        if token[0] > 0 {
+            line += token[0];
+            start = token[1];
+        } else {
+            start += token[1];
+        }
+        AbsoluteSemanticToken {
+            line,
+            start,
+            length: token[2],
+            token_type: token[3],
+            token_modifiers: token[4],
+        }
+    })
+}
+
+fn flatten_semantic_tokens(tokens: Vec<lsp::SemanticToken>) -> Vec<u32> {
+    tokens
+        .into_iter()
+        .flat_map(|token| {
+            [
+                token.delta_line,
+                token.delta_start,
+                token.length,
+                token.token_type,
+                token.token_modifiers_bitset,
+            ]
+        })
+        .collect()
+}
+
+/// Applies delta edits to the previous token data.
+///
+/// Edit offsets refer to the previous data, so the edits are applied back to front.
+fn apply_semantic_token_edits(data: &mut Vec<u32>, mut edits: Vec<lsp::SemanticTokensEdit>) {
+    edits.sort_by_key(|edit| Reverse(edit.start));
+    for edit in edits {
+        let start = (edit.start as usize).min(data.len());
+        let end = (start + edit.delete_count as usize).min(data.len());
+        data.splice(
+            start..end,
+            flatten_semantic_tokens(edit.data.unwrap_or_default()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(
+        delta_line: u32,
+        delta_start: u32,
+        length: u32,
+        token_type: u32,
+    ) -> lsp::SemanticToken {
+        lsp::SemanticToken {
+            delta_line,
+            delta_start,
+            length,
+            token_type,
+            token_modifiers_bitset: 0,
+        }
+    }
+
+    #[test]
+    fn test_decode_semantic_tokens() {
+        let data = flatten_semantic_tokens(vec![
+            token(2, 5, 3, 0),
+            token(0, 5, 4, 1),
+            token(3, 2, 7, 2),
+        ]);
+        let tokens = decode_semantic_tokens(&data)
+            .map(|token| (token.line, token.start, token.length, token.token_type))
+            .collect::<Vec<_>>();
+        assert_eq!(tokens, vec![(2, 5, 3, 0), (2, 10, 4, 1), (5, 2, 7, 2)]);
+    }
+
+    #[test]
+    fn test_apply_semantic_token_edits() {
+        let mut data = flatten_semantic_tokens(vec![
+            token(0, 0, 3, 0),
+            token(1, 0, 3, 1),
+            token(1, 0, 3, 2),
+        ]);
+        apply_semantic_token_edits(
+            &mut data,
+            vec![
+                lsp::SemanticTokensEdit {
+                    start: 0,
+                    delete_count: 5,
+                    data: None,
+                },
+                lsp::SemanticTokensEdit {
+                    start: 10,
+                    delete_count: 0,
+                    data: Some(vec![token(1, 4, 2, 3)]),
+                },
+            ],
+        );
+        assert_eq!(
+            data,
+            flatten_semantic_tokens(vec![
+                token(1, 0, 3, 1),
+                token(1, 0, 3, 2),
+                token(1, 4, 2, 3),
+            ])
+        );
+    }
+}
@@ -1,7 +1,11 @@
 #![allow(clippy::format_collect)]
 
 use crate::{
-    Event, git_store::StatusEntry, task_inventory::TaskContexts, task_store::TaskSettingsLocation,
+    Event,
+    git_store::StatusEntry,
+    search::SyntaxScope,
+    task_inventory::TaskContexts,
+    task_store::{TaskSettingsLocation, TaskStoreEvent},
     *,
 };
 use async_trait::async_trait;
@@ -14,11 +18,12 @@ use futures::{StreamExt, future};
 use git::{
     GitHostingProviderRegistry,
     repository::RepoPath,
-    status::{StatusCode, TrackedStatus},
+    status::{StatusCode, TrackedStatus, UnmergedStatus, UnmergedStatusCode},
 };
 use git2::RepositoryInitOptions;
 use gpui::{App, BackgroundExecutor, SemanticVersion, UpdateGlobal};
 use itertools::Itertools;
+use jj::JujutsuRepository as _;
 use language::{
     Diagnostic, DiagnosticEntry, DiagnosticSet, DiagnosticSourceKind, DiskState, FakeLspAdapter,
     LanguageConfig, LanguageMatcher, LanguageName, LineEnding, ManifestName, ManifestProvider,
@@ -38,7 +43,17 @@ use rand::{Rng as _, rngs::StdRng};
 use serde_json::json;
 #[cfg(not(windows))]
 use std::os;
-use std::{env, mem, num::NonZeroU32, ops::Range, str::FromStr, sync::OnceLock, task::Poll};
+use std::{
+    env, mem,
+    num::NonZeroU32,
+    ops::Range,
+    str::FromStr,
+    sync::{
+        OnceLock,
+        atomic::{AtomicUsize, Ordering::SeqCst},
+    },
+    task::Poll,
+};
 use task::{ResolvedTask, ShellKind, TaskContext};
 use unindent::Unindent as _;
 use util::{
@@ -597,6 +612,266 @@ async fn test_fallback_to_single_worktree_tasks(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_watched_tasks_rerun_on_file_changes(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "src": { "main.rs": "fn main() {}" },
+            "README.md": "",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    cx.executor().run_until_parked();
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
+
+    let task = task::TaskTemplate {
+        label: "test".to_string(),
+        command: "cargo test".to_string(),
+        watch: Some(task::TaskWatch {
+            patterns: vec!["src/**/*.rs".to_string()],
+            debounce_ms: 100,
+        }),
+        ..task::TaskTemplate::default()
+    }
+    .resolve_task("test", &TaskContext::default())
+    .unwrap();
+    let task_store = project.read_with(cx, |project, _| project.task_store().clone());
+    let reruns = Arc::new(Mutex::new(Vec::new()));
+    let _subscription = cx.update(|cx| {
+        let reruns = reruns.clone();
+        cx.subscribe(&task_store, move |_, event: &TaskStoreEvent, _| {
+            let TaskStoreEvent::WatchedTaskChanged { task, .. } = event;
+            reruns.lock().push(task.id.clone());
+        })
+    });
+    task_store
+        .update(cx, |task_store, cx| {
+            task_store.watch_task(
+                TaskSourceKind::Worktree {
+                    id: worktree_id,
+                    directory_in_worktree: PathBuf::from(".zed"),
+                    id_base: "local worktree tasks".into(),
+                },
+                task.clone(),
+                cx,
+            )
+        })
+        .unwrap();
+
+    fs.save(
+        path!("/dir/README.md").as_ref(),
+        &"docs".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    cx.executor().advance_clock(Duration::from_millis(200));
+    cx.executor().run_until_parked();
+    assert!(
+        reruns.lock().is_empty(),
+        "Changes of files not matching the patterns should not rerun the task"
+    );
+
+    for contents in ["fn main() { a() }", "fn main() { b() }"] {
+        fs.save(
+            path!("/dir/src/main.rs").as_ref(),
+            &contents.into(),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+        cx.executor().run_until_parked();
+    }
+    cx.executor().advance_clock(Duration::from_millis(200));
+    cx.executor().run_until_parked();
+    assert_eq!(
+        *reruns.lock(),
+        vec![task.id.clone()],
+        "Changes within the debounce interval should rerun the task once"
+    );
+
+    task_store.update(cx, |task_store, cx| task_store.unwatch_task(&task.id, cx));
+    fs.save(
+        path!("/dir/src/main.rs").as_ref(),
+        &"fn main() { c() }".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    cx.executor().advance_clock(Duration::from_millis(200));
+    cx.executor().run_until_parked();
+    assert_eq!(reruns.lock().len(), 1, "Unwatched tasks should not rerun");
+}
+
+#[gpui::test]
+async fn test_watched_tasks_rerun_on_gitignored_file_changes(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            ".gitignore": "generated/",
+            "generated": { "schema": { "api.json": "{}" } },
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    cx.executor().run_until_parked();
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
+
+    let task = task::TaskTemplate {
+        label: "codegen".to_string(),
+        command: "make codegen".to_string(),
+        watch: Some(task::TaskWatch {
+            patterns: vec!["generated/**/*.json".to_string()],
+            debounce_ms: 100,
+        }),
+        ..task::TaskTemplate::default()
+    }
+    .resolve_task("codegen", &TaskContext::default())
+    .unwrap();
+    let task_store = project.read_with(cx, |project, _| project.task_store().clone());
+    let reruns = Arc::new(Mutex::new(Vec::new()));
+    let _subscription = cx.update(|cx| {
+        let reruns = reruns.clone();
+        cx.subscribe(&task_store, move |_, event: &TaskStoreEvent, _| {
+            let TaskStoreEvent::WatchedTaskChanged { task, .. } = event;
+            reruns.lock().push(task.id.clone());
+        })
+    });
+    task_store
+        .update(cx, |task_store, cx| {
+            task_store.watch_task(
+                TaskSourceKind::Worktree {
+                    id: worktree_id,
+                    directory_in_worktree: PathBuf::from(".zed"),
+                    id_base: "local worktree tasks".into(),
+                },
+                task.clone(),
+                cx,
+            )
+        })
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    fs.save(
+        path!("/dir/generated/schema/api.json").as_ref(),
+        &"{\"version\": 2}".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    cx.executor().advance_clock(Duration::from_millis(200));
+    cx.executor().run_until_parked();
+    assert_eq!(
+        *reruns.lock(),
+        vec![task.id.clone()],
+        "Changes of gitignored files matching the patterns should rerun the task"
+    );
+}
+
+#[gpui::test]
+async fn test_watched_tasks_skip_gitignored_directories(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            ".gitignore": "target/",
+            "src": { "main.rs": "fn main() {}" },
+            "target": { "debug": { "build.rs": "" } },
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    cx.executor().run_until_parked();
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
+
+    let task = task::TaskTemplate {
+        label: "test".to_string(),
+        command: "cargo test".to_string(),
+        watch: Some(task::TaskWatch {
+            patterns: vec!["**/*.rs".to_string()],
+            debounce_ms: 100,
+        }),
+        ..task::TaskTemplate::default()
+    }
+    .resolve_task("test", &TaskContext::default())
+    .unwrap();
+    let task_store = project.read_with(cx, |project, _| project.task_store().clone());
+    let reruns = Arc::new(Mutex::new(Vec::new()));
+    let _subscription = cx.update(|cx| {
+        let reruns = reruns.clone();
+        cx.subscribe(&task_store, move |_, event: &TaskStoreEvent, _| {
+            let TaskStoreEvent::WatchedTaskChanged { task, .. } = event;
+            reruns.lock().push(task.id.clone());
+        })
+    });
+    task_store
+        .update(cx, |task_store, cx| {
+            task_store.watch_task(
+                TaskSourceKind::Worktree {
+                    id: worktree_id,
+                    directory_in_worktree: PathBuf::from(".zed"),
+                    id_base: "local worktree tasks".into(),
+                },
+                task.clone(),
+                cx,
+            )
+        })
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    // The task's own output lands in the gitignored directory, and should not rerun it.
+    fs.save(
+        path!("/dir/target/debug/build.rs").as_ref(),
+        &"fn main() {}".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    fs.create_dir(path!("/dir/target/release").as_ref())
+        .await
+        .unwrap();
+    fs.save(
+        path!("/dir/target/release/build.rs").as_ref(),
+        &"fn main() {}".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    cx.executor().advance_clock(Duration::from_millis(200));
+    cx.executor().run_until_parked();
+    assert!(
+        reruns.lock().is_empty(),
+        "Changes in gitignored directories matched by wildcards should not rerun the task"
+    );
+
+    fs.save(
+        path!("/dir/src/main.rs").as_ref(),
+        &"fn main() { a() }".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    cx.executor().advance_clock(Duration::from_millis(200));
+    cx.executor().run_until_parked();
+    assert_eq!(*reruns.lock(), vec![task.id.clone()]);
+}
+
 #[gpui::test]
 async fn test_running_multiple_instances_of_a_single_server_in_one_worktree(
     cx: &mut gpui::TestAppContext,
@@ -3249,28 +3524,700 @@ async fn test_definition(cx: &mut gpui::TestAppContext) {
             ],
         );
 
-        drop(definition);
-    });
-    cx.update(|cx| {
-        assert_eq!(
-            list_worktrees(&project, cx),
-            [(path!("/dir/b.rs").as_ref(), true)]
-        );
+        drop(definition);
+    });
+    cx.update(|cx| {
+        assert_eq!(
+            list_worktrees(&project, cx),
+            [(path!("/dir/b.rs").as_ref(), true)]
+        );
+    });
+
+    fn list_worktrees<'a>(project: &'a Entity<Project>, cx: &'a App) -> Vec<(&'a Path, bool)> {
+        project
+            .read(cx)
+            .worktrees(cx)
+            .map(|worktree| {
+                let worktree = worktree.read(cx);
+                (
+                    worktree.as_local().unwrap().abs_path().as_ref(),
+                    worktree.is_visible(),
+                )
+            })
+            .collect::<Vec<_>>()
+    }
+}
+
+#[gpui::test]
+async fn test_call_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.rs": "fn a() { b() }\nfn c() { a() }",
+            "b.rs": "fn b() {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/a.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    fn lsp_item(name: &str, path: &str, line: u32) -> lsp::CallHierarchyItem {
+        lsp::CallHierarchyItem {
+            name: name.to_string(),
+            kind: lsp::SymbolKind::FUNCTION,
+            tags: None,
+            detail: Some(format!("fn {name}()")),
+            uri: lsp::Uri::from_file_path(path).unwrap(),
+            range: lsp::Range::new(lsp::Position::new(line, 0), lsp::Position::new(line, 14)),
+            selection_range: lsp::Range::new(
+                lsp::Position::new(line, 3),
+                lsp::Position::new(line, 4),
+            ),
+            data: Some(json!({ "name": name })),
+        }
+    }
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.set_request_handler::<lsp::request::CallHierarchyPrepare, _, _>(
+        |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(0, 3)
+            );
+            Ok(Some(vec![lsp_item("a", path!("/dir/a.rs"), 0)]))
+        },
+    );
+    fake_server.set_request_handler::<lsp::request::CallHierarchyIncomingCalls, _, _>(
+        |params, _| async move {
+            assert_eq!(params.item.data, Some(json!({ "name": "a" })));
+            Ok(Some(vec![lsp::CallHierarchyIncomingCall {
+                from: lsp_item("c", path!("/dir/a.rs"), 1),
+                from_ranges: vec![lsp::Range::new(
+                    lsp::Position::new(1, 9),
+                    lsp::Position::new(1, 10),
+                )],
+            }]))
+        },
+    );
+    fake_server.set_request_handler::<lsp::request::CallHierarchyOutgoingCalls, _, _>(
+        |params, _| async move {
+            assert_eq!(params.item.name, "a");
+            Ok(Some(vec![lsp::CallHierarchyOutgoingCall {
+                to: lsp_item("b", path!("/dir/b.rs"), 0),
+                from_ranges: vec![lsp::Range::new(
+                    lsp::Position::new(0, 9),
+                    lsp::Position::new(0, 10),
+                )],
+            }]))
+        },
+    );
+
+    let items = project
+        .update(cx, |project, cx| {
+            project.prepare_call_hierarchy(&buffer, 3, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    let item = items[0].clone();
+    assert_eq!(item.name, "a");
+    assert_eq!(item.location.buffer, buffer);
+    cx.update(|cx| {
+        assert_eq!(item.location.range.to_offset(buffer.read(cx)), 3..4);
+    });
+
+    let incoming = project
+        .update(cx, |project, cx| project.incoming_calls(&item, cx))
+        .await
+        .unwrap();
+    assert_eq!(incoming.len(), 1);
+    assert_eq!(incoming[0].item.name, "c");
+    assert_eq!(incoming[0].call_sites.len(), 1);
+    cx.update(|cx| {
+        let call_site = &incoming[0].call_sites[0];
+        assert_eq!(call_site.buffer, buffer);
+        assert_eq!(call_site.range.to_offset(buffer.read(cx)), 24..25);
+    });
+
+    let outgoing = project
+        .update(cx, |project, cx| project.outgoing_calls(&item, cx))
+        .await
+        .unwrap();
+    assert_eq!(outgoing.len(), 1);
+    assert_eq!(outgoing[0].item.name, "b");
+    cx.update(|cx| {
+        let callee_buffer = outgoing[0].item.location.buffer.read(cx);
+        assert_eq!(
+            callee_buffer.file().unwrap().full_path(cx),
+            Path::new("dir/b.rs")
+        );
+        // Outgoing call sites are located in the caller.
+        let call_site = &outgoing[0].call_sites[0];
+        assert_eq!(call_site.buffer, buffer);
+        assert_eq!(call_site.range.to_offset(buffer.read(cx)), 9..10);
+    });
+}
+
+#[gpui::test]
+async fn test_call_hierarchy_with_multiple_servers(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(path!("/dir"), json!({ "a.tsx": "function a() {}" }))
+        .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(tsx_lang());
+    let language_server_names = ["TypeScriptServer", "ESLintServer"];
+    let mut language_servers = language_server_names.map(|name| {
+        language_registry.register_fake_lsp(
+            "tsx",
+            FakeLspAdapter {
+                name,
+                capabilities: lsp::ServerCapabilities {
+                    call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+                    ..lsp::ServerCapabilities::default()
+                },
+                ..FakeLspAdapter::default()
+            },
+        )
+    });
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/a.tsx"), cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    fn lsp_item(name: &str) -> lsp::CallHierarchyItem {
+        lsp::CallHierarchyItem {
+            name: name.to_string(),
+            kind: lsp::SymbolKind::FUNCTION,
+            tags: None,
+            detail: None,
+            uri: lsp::Uri::from_file_path(path!("/dir/a.tsx")).unwrap(),
+            range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, 15)),
+            selection_range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 10)),
+            data: None,
+        }
+    }
+
+    let mut handlers = Vec::new();
+    for language_server in &mut language_servers {
+        let server = language_server.next().await.unwrap();
+        let server_name = server.server.name().to_string();
+        server.set_request_handler::<lsp::request::CallHierarchyPrepare, _, _>({
+            let server_name = server_name.clone();
+            move |_, _| {
+                let server_name = server_name.clone();
+                async move { Ok(Some(vec![lsp_item(&server_name)])) }
+            }
+        });
+        // Each server must only be asked for the calls of the items it returned.
+        handlers.push(
+            server.set_request_handler::<lsp::request::CallHierarchyIncomingCalls, _, _>(
+                move |params, _| {
+                    let server_name = server_name.clone();
+                    async move {
+                        assert_eq!(params.item.name, server_name);
+                        Ok(Some(vec![lsp::CallHierarchyIncomingCall {
+                            from: lsp_item(&format!("{server_name} caller")),
+                            from_ranges: Vec::new(),
+                        }]))
+                    }
+                },
+            ),
+        );
+    }
+
+    let items = project
+        .update(cx, |project, cx| {
+            project.prepare_call_hierarchy(&buffer, 9, cx)
+        })
+        .await
+        .unwrap();
+    let mut item_names = items
+        .iter()
+        .map(|item| item.name.clone())
+        .collect::<Vec<_>>();
+    item_names.sort();
+    assert_eq!(item_names, vec!["ESLintServer", "TypeScriptServer"]);
+
+    for item in &items {
+        let incoming = project
+            .update(cx, |project, cx| project.incoming_calls(item, cx))
+            .await
+            .unwrap();
+        assert_eq!(incoming.len(), 1);
+        assert_eq!(incoming[0].item.name, format!("{} caller", item.name));
+        assert_eq!(incoming[0].item.server_id, item.server_id);
+    }
+    for mut handler in handlers {
+        handler.next().await.unwrap();
+    }
+}
+
+#[gpui::test]
+async fn test_type_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.rs": "trait Shape {}\nstruct Circle;\nimpl Shape for Circle {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                experimental: Some(json!({ "typeHierarchyProvider": true })),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/a.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    fn lsp_item(name: &str, line: u32, start: u32, end: u32) -> lsp::TypeHierarchyItem {
+        lsp::TypeHierarchyItem {
+            name: name.to_string(),
+            kind: lsp::SymbolKind::STRUCT,
+            tags: None,
+            detail: None,
+            uri: lsp::Uri::from_file_path(path!("/dir/a.rs")).unwrap(),
+            range: lsp::Range::new(lsp::Position::new(line, 0), lsp::Position::new(line, end)),
+            selection_range: lsp::Range::new(
+                lsp::Position::new(line, start),
+                lsp::Position::new(line, end),
+            ),
+            data: None,
+        }
+    }
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.set_request_handler::<lsp::request::TypeHierarchyPrepare, _, _>(
+        |_, _| async move { Ok(Some(vec![lsp_item("Circle", 1, 7, 13)])) },
+    );
+    fake_server.set_request_handler::<lsp::request::TypeHierarchySupertypes, _, _>(
+        |params, _| async move {
+            assert_eq!(params.item.name, "Circle");
+            Ok(Some(vec![lsp_item("Shape", 0, 6, 11)]))
+        },
+    );
+    fake_server.set_request_handler::<lsp::request::TypeHierarchySubtypes, _, _>(
+        |_, _| async move { Ok(Some(Vec::new())) },
+    );
+
+    let items = project
+        .update(cx, |project, cx| {
+            project.prepare_type_hierarchy(&buffer, 22, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    cx.update(|cx| {
+        assert_eq!(items[0].location.range.to_offset(buffer.read(cx)), 22..28);
+    });
+
+    let supertypes = project
+        .update(cx, |project, cx| project.supertypes(&items[0], cx))
+        .await
+        .unwrap();
+    assert_eq!(supertypes.len(), 1);
+    assert_eq!(supertypes[0].name, "Shape");
+    cx.update(|cx| {
+        assert_eq!(supertypes[0].location.buffer, buffer);
+        assert_eq!(
+            supertypes[0].location.range.to_offset(buffer.read(cx)),
+            6..11
+        );
+    });
+
+    let subtypes = project
+        .update(cx, |project, cx| project.subtypes(&items[0], cx))
+        .await
+        .unwrap();
+    assert!(subtypes.is_empty());
+}
+
+#[gpui::test]
+async fn test_type_hierarchy_with_multiple_servers(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(path!("/dir"), json!({ "a.tsx": "class Circle {}" }))
+        .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(tsx_lang());
+    let mut unsupported_servers = language_registry.register_fake_lsp(
+        "tsx",
+        FakeLspAdapter {
+            name: "ESLintServer",
+            ..FakeLspAdapter::default()
+        },
+    );
+    let mut supported_servers = language_registry.register_fake_lsp(
+        "tsx",
+        FakeLspAdapter {
+            name: "TypeScriptServer",
+            capabilities: lsp::ServerCapabilities {
+                experimental: Some(json!({ "typeHierarchyProvider": true })),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/a.tsx"), cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    fn lsp_item(name: &str) -> lsp::TypeHierarchyItem {
+        lsp::TypeHierarchyItem {
+            name: name.to_string(),
+            kind: lsp::SymbolKind::CLASS,
+            tags: None,
+            detail: None,
+            uri: lsp::Uri::from_file_path(path!("/dir/a.tsx")).unwrap(),
+            range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, 15)),
+            selection_range: lsp::Range::new(lsp::Position::new(0, 6), lsp::Position::new(0, 12)),
+            data: None,
+        }
+    }
+
+    let unsupported_server = unsupported_servers.next().await.unwrap();
+    unsupported_server.set_request_handler::<lsp::request::TypeHierarchyPrepare, _, _>(
+        |_, _| async move { panic!("the server has no type hierarchy capability") },
+    );
+    unsupported_server.set_request_handler::<lsp::request::TypeHierarchySupertypes, _, _>(
+        |_, _| async move { panic!("supertypes should be requested from the item's server") },
+    );
+    let supported_server = supported_servers.next().await.unwrap();
+    supported_server.set_request_handler::<lsp::request::TypeHierarchyPrepare, _, _>(
+        |_, _| async move { Ok(Some(vec![lsp_item("Circle")])) },
+    );
+    supported_server.set_request_handler::<lsp::request::TypeHierarchySupertypes, _, _>(
+        |params, _| async move {
+            assert_eq!(params.item.name, "Circle");
+            Ok(Some(vec![lsp_item("Shape")]))
+        },
+    );
+
+    let items = project
+        .update(cx, |project, cx| {
+            project.prepare_type_hierarchy(&buffer, 6, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].name, "Circle");
+    assert_eq!(items[0].server_id, supported_server.server.server_id());
+
+    let supertypes = project
+        .update(cx, |project, cx| project.supertypes(&items[0], cx))
+        .await
+        .unwrap();
+    assert_eq!(supertypes.len(), 1);
+    assert_eq!(supertypes[0].name, "Shape");
+}
+
+#[gpui::test]
+async fn test_semantic_tokens(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(path!("/dir"), json!({ "a.rs": "fn a() {}\nfn b() {}" }))
+        .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                semantic_tokens_provider: Some(
+                    lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        lsp::SemanticTokensOptions {
+                            legend: lsp::SemanticTokensLegend {
+                                token_types: vec![lsp::SemanticTokenType::FUNCTION],
+                                token_modifiers: Vec::new(),
+                            },
+                            full: Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                            ..lsp::SemanticTokensOptions::default()
+                        },
+                    ),
+                ),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/a.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    fn token(delta_line: u32, delta_start: u32, length: u32) -> lsp::SemanticToken {
+        lsp::SemanticToken {
+            delta_line,
+            delta_start,
+            length,
+            token_type: 0,
+            token_modifiers_bitset: 0,
+        }
+    }
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.set_request_handler::<lsp::request::SemanticTokensFullRequest, _, _>(
+        |_, _| async move {
+            Ok(Some(lsp::SemanticTokensResult::Tokens(
+                lsp::SemanticTokens {
+                    result_id: Some("1".into()),
+                    data: vec![token(0, 3, 1), token(1, 3, 1)],
+                },
+            )))
+        },
+    );
+    fake_server.set_request_handler::<lsp::request::SemanticTokensFullDeltaRequest, _, _>(
+        |params, _| async move {
+            let delta = match params.previous_result_id.as_str() {
+                // Replaces the second token with one covering `fn`.
+                "1" => lsp::SemanticTokensDelta {
+                    result_id: Some("2".into()),
+                    edits: vec![lsp::SemanticTokensEdit {
+                        start: 5,
+                        delete_count: 5,
+                        data: Some(vec![token(1, 0, 2)]),
+                    }],
+                },
+                "2" => lsp::SemanticTokensDelta {
+                    result_id: Some("3".into()),
+                    edits: Vec::new(),
+                },
+                previous_result_id => panic!("unexpected result id {previous_result_id}"),
+            };
+            Ok(Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(delta)))
+        },
+    );
+
+    fn token_ranges(
+        tokens: &crate::lsp_store::semantic_tokens::BufferSemanticTokens,
+        buffer: &Buffer,
+    ) -> Vec<Range<usize>> {
+        tokens
+            .tokens
+            .iter()
+            .map(|token| token.range.to_offset(buffer))
+            .collect()
+    }
+
+    let lsp_store = project.read_with(cx, |project, _| project.lsp_store());
+    let tokens = lsp_store
+        .update(cx, |lsp_store, cx| lsp_store.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(tokens.token_type(&tokens.tokens[0]), Some("function"));
+    cx.update(|cx| assert_eq!(token_ranges(&tokens, buffer.read(cx)), vec![3..4, 13..14]));
+
+    let tokens = lsp_store
+        .update(cx, |lsp_store, cx| lsp_store.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap()
+        .unwrap();
+    cx.update(|cx| assert_eq!(token_ranges(&tokens, buffer.read(cx)), vec![3..4, 10..12]));
+
+    // Tokens describe the buffer as it was when they were requested, so edits made
+    // while the request is in flight shift them along with the text.
+    let tokens_task = lsp_store.update(cx, |lsp_store, cx| lsp_store.semantic_tokens(&buffer, cx));
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "// \n")], None, cx));
+    let tokens = tokens_task.await.unwrap().unwrap();
+    cx.update(|cx| assert_eq!(token_ranges(&tokens, buffer.read(cx)), vec![7..8, 14..16]));
+}
+
+#[gpui::test]
+async fn test_overlapping_semantic_token_deltas(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(path!("/dir"), json!({ "a.rs": "fn a() {}\nfn b() {}" }))
+        .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                semantic_tokens_provider: Some(
+                    lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        lsp::SemanticTokensOptions {
+                            legend: lsp::SemanticTokensLegend {
+                                token_types: vec![lsp::SemanticTokenType::FUNCTION],
+                                token_modifiers: Vec::new(),
+                            },
+                            full: Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                            ..lsp::SemanticTokensOptions::default()
+                        },
+                    ),
+                ),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/a.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    fn token(delta_line: u32, delta_start: u32, length: u32) -> lsp::SemanticToken {
+        lsp::SemanticToken {
+            delta_line,
+            delta_start,
+            length,
+            token_type: 0,
+            token_modifiers_bitset: 0,
+        }
+    }
+
+    // The server first reports the token of `a`, then adds the token of `b` in its next result,
+    // which is `2`.
+    let full_requests = Arc::new(AtomicUsize::new(0));
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.set_request_handler::<lsp::request::SemanticTokensFullRequest, _, _>({
+        let full_requests = full_requests.clone();
+        move |_, _| {
+            let tokens = if full_requests.fetch_add(1, SeqCst) == 0 {
+                lsp::SemanticTokens {
+                    result_id: Some("1".into()),
+                    data: vec![token(0, 3, 1)],
+                }
+            } else {
+                lsp::SemanticTokens {
+                    result_id: Some("2".into()),
+                    data: vec![token(0, 3, 1), token(1, 3, 1)],
+                }
+            };
+            async move { Ok(Some(lsp::SemanticTokensResult::Tokens(tokens))) }
+        }
     });
+    fake_server.set_request_handler::<lsp::request::SemanticTokensFullDeltaRequest, _, _>(
+        |params, _| async move {
+            let delta = match params.previous_result_id.as_str() {
+                "1" => lsp::SemanticTokensDelta {
+                    result_id: Some("2".into()),
+                    edits: vec![lsp::SemanticTokensEdit {
+                        start: 5,
+                        delete_count: 0,
+                        data: Some(vec![token(1, 3, 1)]),
+                    }],
+                },
+                "2" => lsp::SemanticTokensDelta {
+                    result_id: Some("2".into()),
+                    edits: Vec::new(),
+                },
+                previous_result_id => panic!("unexpected result id {previous_result_id}"),
+            };
+            Ok(Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(delta)))
+        },
+    );
 
-    fn list_worktrees<'a>(project: &'a Entity<Project>, cx: &'a App) -> Vec<(&'a Path, bool)> {
-        project
-            .read(cx)
-            .worktrees(cx)
-            .map(|worktree| {
-                let worktree = worktree.read(cx);
-                (
-                    worktree.as_local().unwrap().abs_path().as_ref(),
-                    worktree.is_visible(),
-                )
-            })
-            .collect::<Vec<_>>()
+    fn token_ranges(
+        tokens: &crate::lsp_store::semantic_tokens::BufferSemanticTokens,
+        buffer: &Buffer,
+    ) -> Vec<Range<usize>> {
+        tokens
+            .tokens
+            .iter()
+            .map(|token| token.range.to_offset(buffer))
+            .collect()
+    }
+
+    let lsp_store = project.read_with(cx, |project, _| project.lsp_store());
+    let tokens = lsp_store
+        .update(cx, |lsp_store, cx| lsp_store.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap()
+        .unwrap();
+    cx.update(|cx| assert_eq!(token_ranges(&tokens, buffer.read(cx)), vec![3..4]));
+
+    // Both requests ask for a delta against `1`. Once the first delta is applied, the second one
+    // no longer applies to the cached tokens, so they are requested in full instead.
+    let first_tokens = lsp_store.update(cx, |lsp_store, cx| lsp_store.semantic_tokens(&buffer, cx));
+    let second_tokens =
+        lsp_store.update(cx, |lsp_store, cx| lsp_store.semantic_tokens(&buffer, cx));
+    let (first_tokens, second_tokens) = futures::join!(first_tokens, second_tokens);
+    for tokens in [first_tokens, second_tokens] {
+        let tokens = tokens.unwrap().unwrap();
+        cx.update(|cx| assert_eq!(token_ranges(&tokens, buffer.read(cx)), vec![3..4, 13..14]));
     }
+    assert_eq!(full_requests.load(SeqCst), 2);
+
+    let tokens = lsp_store
+        .update(cx, |lsp_store, cx| lsp_store.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap()
+        .unwrap();
+    cx.update(|cx| assert_eq!(token_ranges(&tokens, buffer.read(cx)), vec![3..4, 13..14]));
 }
 
 #[gpui::test]
@@ -6109,6 +7056,149 @@ async fn test_search_with_unicode(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_search_with_syntax_scope(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "one.rs": "// TODO: x\nconst TODO: &str = \"TODO\";\n",
+            "two.txt": "TODO",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(Arc::new(
+        Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        )
+        .with_highlights_query("(line_comment) @comment (string_literal) @string")
+        .unwrap(),
+    ));
+
+    let query = |syntax_scope| {
+        SearchQuery::text(
+            "TODO",
+            false,
+            true,
+            false,
+            Default::default(),
+            Default::default(),
+            false,
+            None,
+        )
+        .unwrap()
+        .with_syntax_scope(syntax_scope)
+    };
+
+    assert_eq!(
+        search(&project, query(SyntaxScope::COMMENTS), cx)
+            .await
+            .unwrap(),
+        HashMap::from_iter([(path!("dir/one.rs").to_string(), vec![3..7])])
+    );
+    assert_eq!(
+        search(&project, query(SyntaxScope::STRINGS), cx)
+            .await
+            .unwrap(),
+        HashMap::from_iter([(path!("dir/one.rs").to_string(), vec![31..35])])
+    );
+    assert_eq!(
+        search(&project, query(SyntaxScope::CODE), cx)
+            .await
+            .unwrap(),
+        HashMap::from_iter([
+            (path!("dir/one.rs").to_string(), vec![17..21]),
+            (path!("dir/two.txt").to_string(), vec![0..4]),
+        ])
+    );
+    assert_eq!(
+        search(
+            &project,
+            query(SyntaxScope::COMMENTS | SyntaxScope::STRINGS),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([(path!("dir/one.rs").to_string(), vec![3..7, 31..35])])
+    );
+}
+
+#[gpui::test]
+async fn test_structural_search(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "one.rs": "fn main() {\n    foo(bar(1, 2), \"a, b\");\n    foo (x,y);\n    afoo(1, 2);\n}\n",
+            "two.rs": "fn two() { foo(); }",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+
+    let query = SearchQuery::structural(
+        "foo($a, $b)",
+        false,
+        Default::default(),
+        Default::default(),
+        false,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        search(&project, query.clone(), cx).await.unwrap(),
+        HashMap::from_iter([(path!("dir/one.rs").to_string(), vec![16..38, 44..53])])
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/dir/one.rs"), cx)
+        })
+        .await
+        .unwrap();
+    let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+    let query = query.with_replacement("foo($b, $a)".to_string());
+    assert_eq!(
+        query.replacement_in_buffer(&snapshot, 16..38).as_deref(),
+        Some("foo(\"a, b\", bar(1, 2))")
+    );
+    assert_eq!(
+        query.replacement_in_buffer(&snapshot, 44..53).as_deref(),
+        Some("foo(y, x)")
+    );
+
+    let query = SearchQuery::structural(
+        "foo($a)",
+        false,
+        Default::default(),
+        Default::default(),
+        false,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        search(&project, query, cx).await.unwrap(),
+        HashMap::from_iter([(path!("dir/one.rs").to_string(), vec![16..38, 44..53])]),
+        "Placeholders should match at least one syntax node"
+    );
+}
+
 #[gpui::test]
 async fn test_create_entry(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -7998,6 +9088,114 @@ async fn test_home_dir_as_git_repository(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_jj_workspace_status_and_diff_bases(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        path!("/root"),
+        json!({
+            "project": {
+                "a.txt": "ONE\n",
+                "b.txt": "two\n",
+                "c.txt": "three\n",
+            },
+        }),
+    )
+    .await;
+    // The workspace is an ancestor of the worktree, so paths in it are prefixed with `project`.
+    let repository = fs.insert_jj_repository(path!("/root").as_ref()).await;
+    repository.set_working_copy_files([("project/a.txt", "one\n"), ("project/b.txt", "two\n")]);
+    repository
+        .describe(&repository.working_copy_commit_id(), "initial files")
+        .unwrap();
+    repository
+        .new_change(&[repository.working_copy_commit_id()])
+        .unwrap();
+    repository.set_working_copy_files([
+        ("project/a.txt", "ONE\n"),
+        ("project/b.txt", "two\n"),
+        ("project/c.txt", "three\n"),
+    ]);
+
+    let project = Project::test(fs.clone(), [path!("/root/project").as_ref()], cx).await;
+    cx.executor().run_until_parked();
+    let tree_id = project.read_with(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
+    let jj_store = project.read_with(cx, |project, cx| project.jj_store(cx));
+
+    project.read_with(cx, |project, cx| {
+        let jj_store = jj_store.read(cx);
+        assert_eq!(
+            jj_store
+                .repository_for_worktree(tree_id)
+                .unwrap()
+                .workspace_root
+                .as_ref(),
+            Path::new(path!("/root"))
+        );
+        let git_store = project.git_store().read(cx);
+        assert_eq!(
+            git_store.project_path_git_status(&(tree_id, "a.txt").into(), cx),
+            Some(FileStatus::worktree(StatusCode::Modified))
+        );
+        assert_eq!(
+            git_store.project_path_git_status(&(tree_id, "b.txt").into(), cx),
+            None
+        );
+        assert_eq!(
+            git_store.project_path_git_status(&(tree_id, "c.txt").into(), cx),
+            Some(FileStatus::worktree(StatusCode::Added))
+        );
+        // The title bar shows the working-copy change.
+        let change = jj_store.working_copy_change(cx).unwrap();
+        assert_eq!(
+            change.commit_id.as_ref(),
+            repository.working_copy_commit_id().as_ref()
+        );
+        assert!(change.description.is_empty());
+    });
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/root/project/a.txt"), cx)
+        })
+        .await
+        .unwrap();
+    let diff = project
+        .update(cx, |project, cx| {
+            project.open_uncommitted_diff(buffer.clone(), cx)
+        })
+        .await
+        .unwrap();
+    diff.read_with(cx, |diff, _| {
+        assert_eq!(diff.base_text_string().unwrap(), "one\n");
+    });
+
+    // Squashing the working-copy change into its parent moves the edits into the diff base.
+    repository
+        .describe(&repository.working_copy_commit_id(), "edit files")
+        .unwrap();
+    repository
+        .squash(&repository.working_copy_commit_id(), None)
+        .unwrap();
+    jj_store.update(cx, |jj_store, cx| jj_store.refresh(cx));
+    cx.executor().run_until_parked();
+    diff.read_with(cx, |diff, _| {
+        assert_eq!(diff.base_text_string().unwrap(), "ONE\n");
+    });
+    project.read_with(cx, |project, cx| {
+        assert_eq!(
+            project
+                .git_store()
+                .read(cx)
+                .project_path_git_status(&(tree_id, "a.txt").into(), cx),
+            None
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_git_repository_status(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -9097,6 +10295,65 @@ async fn test_repository_deduplication(cx: &mut gpui::TestAppContext) {
     pretty_assertions::assert_eq!(repos, [Path::new(path!("/root/project")).into()]);
 }
 
+#[gpui::test]
+async fn test_cherry_pick_conflicts(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        path!("/project"),
+        json!({
+            ".git": {},
+            "a.txt": "a",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+    project
+        .update(cx, |project, cx| project.git_scans_complete(cx))
+        .await;
+    cx.executor().run_until_parked();
+    let repository = project.read_with(cx, |project, cx| {
+        project.repositories(cx).values().next().unwrap().clone()
+    });
+
+    repository
+        .update(cx, |repository, _| repository.cherry_pick("abc".into()))
+        .await
+        .unwrap()
+        .unwrap();
+    cx.executor().run_until_parked();
+    repository.read_with(cx, |repository, _| {
+        assert!(repository.merge.heads.iter().all(Option::is_none));
+        assert!(repository.merge.conflicted_paths.is_empty());
+    });
+
+    // Conflicts are tracked like those of a merge, until the operation is committed.
+    fs.set_unmerged_paths_for_repo(
+        path!("/project/.git").as_ref(),
+        &[(
+            "a.txt".into(),
+            UnmergedStatus {
+                first_head: UnmergedStatusCode::Updated,
+                second_head: UnmergedStatusCode::Updated,
+            },
+        )],
+    );
+    repository
+        .update(cx, |repository, _| repository.cherry_pick("def".into()))
+        .await
+        .unwrap()
+        .unwrap();
+    cx.executor().run_until_parked();
+    repository.read_with(cx, |repository, _| {
+        assert!(repository.merge.heads.contains(&Some("def".into())));
+        assert_eq!(
+            repository.merge.conflicted_paths.iter().collect::<Vec<_>>(),
+            [&RepoPath::from("a.txt")]
+        );
+    });
+}
+
 async fn search(
     project: &Entity<Project>,
     query: SearchQuery,
@@ -1,9 +1,10 @@
 use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
 use anyhow::Result;
+use bitflags::bitflags;
 use client::proto;
 use fancy_regex::{Captures, Regex, RegexBuilder};
 use gpui::Entity;
-use language::{Buffer, BufferSnapshot, CharKind};
+use language::{Buffer, BufferSnapshot, CharKind, SyntaxTextKind};
 use smol::future::yield_now;
 use std::{
     borrow::Cow,
@@ -15,6 +16,10 @@ use std::{
 use text::Anchor;
 use util::paths::PathMatcher;
 
+mod structural;
+
+pub use structural::StructuralPattern;
+
 #[derive(Debug)]
 pub enum SearchResult {
     Buffer {
@@ -31,6 +36,20 @@ pub enum SearchInputKind {
     Exclude,
 }
 
+bitflags! {
+    /// The syntactic parts of the buffers that a search matches in, as told by their syntax
+    /// highlighting. A search with no scope matches anywhere.
+    #[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+    pub struct SyntaxScope: u8 {
+        /// Inside of comments.
+        const COMMENTS = 1 << 0;
+        /// Inside of string literals.
+        const STRINGS = 1 << 1;
+        /// Outside of both comments and string literals.
+        const CODE = 1 << 2;
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct SearchInputs {
     query: Arc<str>,
@@ -38,6 +57,7 @@ pub struct SearchInputs {
     files_to_exclude: PathMatcher,
     match_full_paths: bool,
     buffers: Option<Vec<Entity<Buffer>>>,
+    syntax_scope: SyntaxScope,
 }
 
 impl SearchInputs {
@@ -75,6 +95,13 @@ pub enum SearchQuery {
         one_match_per_line: bool,
         inner: SearchInputs,
     },
+
+    Structural {
+        pattern: StructuralPattern,
+        replacement: Option<String>,
+        include_ignored: bool,
+        inner: SearchInputs,
+    },
 }
 
 static WORD_MATCH_TEST: LazyLock<Regex> = LazyLock::new(|| {
@@ -124,6 +151,7 @@ impl SearchQuery {
             files_to_include,
             match_full_paths,
             buffers,
+            syntax_scope: SyntaxScope::empty(),
         };
         Ok(Self::Text {
             search,
@@ -187,6 +215,7 @@ impl SearchQuery {
             files_to_include,
             match_full_paths,
             buffers,
+            syntax_scope: SyntaxScope::empty(),
         };
         Ok(Self::Regex {
             regex,
@@ -200,6 +229,36 @@ impl SearchQuery {
         })
     }
 
+    /// Create a structural query, matching code against a template whose `$name` placeholders
+    /// stand for runs of complete syntax nodes.
+    ///
+    /// Include/exclude patterns are matched the same way as for [`SearchQuery::text`].
+    pub fn structural(
+        query: impl ToString,
+        include_ignored: bool,
+        files_to_include: PathMatcher,
+        files_to_exclude: PathMatcher,
+        match_full_paths: bool,
+        buffers: Option<Vec<Entity<Buffer>>>,
+    ) -> Result<Self> {
+        let query = query.to_string();
+        let pattern = StructuralPattern::new(&query)?;
+        let inner = SearchInputs {
+            query: query.into(),
+            files_to_exclude,
+            files_to_include,
+            match_full_paths,
+            buffers,
+            syntax_scope: SyntaxScope::empty(),
+        };
+        Ok(Self::Structural {
+            pattern,
+            replacement: None,
+            include_ignored,
+            inner,
+        })
+    }
+
     /// Extracts case sensitivity settings from pattern items in the provided
     /// query and returns the same query, with the pattern items removed.
     ///
@@ -264,7 +323,17 @@ impl SearchQuery {
             message.files_to_exclude
         };
 
-        if message.regex {
+        let syntax_scope = SyntaxScope::from_bits_truncate(message.syntax_scope as u8);
+        let query = if message.structural {
+            Self::structural(
+                message.query,
+                message.include_ignored,
+                PathMatcher::new(files_to_include)?,
+                PathMatcher::new(files_to_exclude)?,
+                message.match_full_paths,
+                None, // search opened only don't need search remote
+            )
+        } else if message.regex {
             Self::regex(
                 message.query,
                 message.whole_word,
@@ -287,7 +356,8 @@ impl SearchQuery {
                 false,
                 None, // search opened only don't need search remote
             )
-        }
+        };
+        Ok(query?.with_syntax_scope(syntax_scope))
     }
 
     pub fn with_replacement(mut self, new_replacement: String) -> Self {
@@ -299,6 +369,10 @@ impl SearchQuery {
             | Self::Regex {
                 ref mut replacement,
                 ..
+            }
+            | Self::Structural {
+                ref mut replacement,
+                ..
             } => {
                 *replacement = Some(new_replacement);
                 self
@@ -306,18 +380,32 @@ impl SearchQuery {
         }
     }
 
+    /// Restricts the matches of this query to the given parts of the buffers' syntax.
+    pub fn with_syntax_scope(mut self, syntax_scope: SyntaxScope) -> Self {
+        match self {
+            Self::Text { ref mut inner, .. }
+            | Self::Regex { ref mut inner, .. }
+            | Self::Structural { ref mut inner, .. } => {
+                inner.syntax_scope = syntax_scope;
+                self
+            }
+        }
+    }
+
     pub fn to_proto(&self) -> proto::SearchQuery {
         let files_to_include = self.files_to_include().sources().to_vec();
         let files_to_exclude = self.files_to_exclude().sources().to_vec();
         proto::SearchQuery {
             query: self.as_str().to_string(),
             regex: self.is_regex(),
+            structural: self.is_structural(),
             whole_word: self.whole_word(),
             case_sensitive: self.case_sensitive(),
             include_ignored: self.include_ignored(),
             files_to_include: files_to_include.clone(),
             files_to_exclude: files_to_exclude.clone(),
             match_full_paths: self.match_full_paths(),
+            syntax_scope: self.syntax_scope().bits() as u32,
             // Populate legacy fields for backwards compatibility
             files_to_include_legacy: files_to_include.join(","),
             files_to_exclude_legacy: files_to_exclude.join(","),
@@ -361,19 +449,26 @@ impl SearchQuery {
                     Ok(false)
                 }
             }
+            Self::Structural { pattern, .. } => {
+                let mut text = String::new();
+                reader.read_to_string(&mut text)?;
+                Ok(pattern.might_match(&text))
+            }
         }
     }
     /// Returns the replacement text for this `SearchQuery`.
     pub fn replacement(&self) -> Option<&str> {
         match self {
-            SearchQuery::Text { replacement, .. } | SearchQuery::Regex { replacement, .. } => {
-                replacement.as_deref()
-            }
+            SearchQuery::Text { replacement, .. }
+            | SearchQuery::Regex { replacement, .. }
+            | SearchQuery::Structural { replacement, .. } => replacement.as_deref(),
         }
     }
     /// Replaces search hits if replacement is set. `text` is assumed to be a string that matches this `SearchQuery` exactly, without any leftovers on either side.
+    /// Structural queries need the buffer of their matches, see [`SearchQuery::replacement_in_buffer`].
     pub fn replacement_for<'a>(&self, text: &'a str) -> Option<Cow<'a, str>> {
         match self {
+            SearchQuery::Structural { .. } => None,
             SearchQuery::Text { replacement, .. } => replacement.clone().map(Cow::from),
             SearchQuery::Regex {
                 regex, replacement, ..
@@ -398,6 +493,29 @@ impl SearchQuery {
         }
     }
 
+    /// Replaces the search hit in the given range of the buffer if replacement is set, also
+    /// supporting structural queries, whose placeholders get the text they matched in the buffer.
+    pub fn replacement_in_buffer(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Option<Cow<'static, str>> {
+        match self {
+            SearchQuery::Structural {
+                pattern,
+                replacement,
+                ..
+            } => pattern
+                .replacement_for(buffer, range, replacement.as_deref()?)
+                .map(Cow::from),
+            SearchQuery::Text { .. } | SearchQuery::Regex { .. } => {
+                let text = buffer.text_for_range(range).collect::<String>();
+                self.replacement_for(&text)
+                    .map(|replacement| Cow::from(replacement.into_owned()))
+            }
+        }
+    }
+
     pub async fn search(
         &self,
         buffer: &BufferSnapshot,
@@ -410,7 +528,7 @@ impl SearchQuery {
         }
 
         let range_offset = subrange.as_ref().map(|r| r.start).unwrap_or(0);
-        let rope = if let Some(range) = subrange {
+        let rope = if let Some(range) = subrange.clone() {
             buffer.as_rope().slice(range)
         } else {
             buffer.as_rope().clone()
@@ -493,6 +611,27 @@ impl SearchQuery {
                     }
                 }
             }
+
+            Self::Structural { pattern, .. } => {
+                let range = range_offset..range_offset + rope.len();
+                matches.extend(
+                    pattern
+                        .find_matches(buffer, range.clone())
+                        .into_iter()
+                        .filter(|mat| mat.end <= range.end)
+                        .map(|mat| mat.start - range_offset..mat.end - range_offset),
+                );
+            }
+        }
+
+        let syntax_scope = self.syntax_scope();
+        if !syntax_scope.is_empty() && !matches.is_empty() {
+            let syntax_scope_ranges =
+                SyntaxScopeRanges::new(buffer, subrange.unwrap_or(0..buffer.len()));
+            matches.retain(|range| {
+                let range = range_offset + range.start..range_offset + range.end;
+                syntax_scope_ranges.contains(syntax_scope, &range)
+            });
         }
 
         matches
@@ -510,6 +649,7 @@ impl SearchQuery {
         match self {
             Self::Text { whole_word, .. } => *whole_word,
             Self::Regex { whole_word, .. } => *whole_word,
+            Self::Structural { .. } => false,
         }
     }
 
@@ -517,6 +657,7 @@ impl SearchQuery {
         match self {
             Self::Text { case_sensitive, .. } => *case_sensitive,
             Self::Regex { case_sensitive, .. } => *case_sensitive,
+            Self::Structural { .. } => true,
         }
     }
 
@@ -528,6 +669,9 @@ impl SearchQuery {
             Self::Regex {
                 include_ignored, ..
             } => *include_ignored,
+            Self::Structural {
+                include_ignored, ..
+            } => *include_ignored,
         }
     }
 
@@ -535,6 +679,15 @@ impl SearchQuery {
         matches!(self, Self::Regex { .. })
     }
 
+    pub fn is_structural(&self) -> bool {
+        matches!(self, Self::Structural { .. })
+    }
+
+    /// Whether searching needs the buffers to be parsed first.
+    pub fn requires_syntax(&self) -> bool {
+        self.is_structural() || !self.syntax_scope().is_empty()
+    }
+
     pub fn files_to_include(&self) -> &PathMatcher {
         self.as_inner().files_to_include()
     }
@@ -560,6 +713,10 @@ impl SearchQuery {
         self.as_inner().match_full_paths
     }
 
+    pub fn syntax_scope(&self) -> SyntaxScope {
+        self.as_inner().syntax_scope
+    }
+
     /// Check match full paths to determine whether you're required to pass a fully qualified
     /// project path (starts with a project root).
     pub fn match_path(&self, file_path: &Path) -> bool {
@@ -578,7 +735,9 @@ impl SearchQuery {
     }
     pub fn as_inner(&self) -> &SearchInputs {
         match self {
-            Self::Regex { inner, .. } | Self::Text { inner, .. } => inner,
+            Self::Regex { inner, .. }
+            | Self::Text { inner, .. }
+            | Self::Structural { inner, .. } => inner,
         }
     }
 
@@ -591,9 +750,67 @@ impl SearchQuery {
             Self::Regex {
                 one_match_per_line, ..
             } => Some(*one_match_per_line),
-            Self::Text { .. } => None,
+            Self::Text { .. } | Self::Structural { .. } => None,
+        }
+    }
+}
+
+/// The comments and string literals of a buffer range, each merged into sorted, disjoint ranges.
+struct SyntaxScopeRanges {
+    comments: Vec<Range<usize>>,
+    strings: Vec<Range<usize>>,
+    comments_and_strings: Vec<Range<usize>>,
+}
+
+impl SyntaxScopeRanges {
+    fn new(buffer: &BufferSnapshot, range: Range<usize>) -> Self {
+        let mut comments = Vec::new();
+        let mut strings = Vec::new();
+        for (range, kind) in buffer.comment_and_string_ranges(range) {
+            match kind {
+                SyntaxTextKind::Comment => comments.push(range),
+                SyntaxTextKind::String => strings.push(range),
+            }
+        }
+        let comments_and_strings = merge_ranges(comments.iter().chain(&strings).cloned().collect());
+        Self {
+            comments: merge_ranges(comments),
+            strings: merge_ranges(strings),
+            comments_and_strings,
         }
     }
+
+    /// Whether a match in the given range lies within any part of the scope.
+    fn contains(&self, scope: SyntaxScope, range: &Range<usize>) -> bool {
+        (scope.contains(SyntaxScope::COMMENTS) && contains_range(&self.comments, range))
+            || (scope.contains(SyntaxScope::STRINGS) && contains_range(&self.strings, range))
+            || (scope.contains(SyntaxScope::CODE)
+                && !intersects_range(&self.comments_and_strings, range))
+    }
+}
+
+fn merge_ranges(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    ranges.sort_unstable_by_key(|range| range.start);
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+fn contains_range(ranges: &[Range<usize>], range: &Range<usize>) -> bool {
+    let ix = ranges.partition_point(|candidate| candidate.start <= range.start);
+    ix > 0 && ranges[ix - 1].end >= range.end
+}
+
+fn intersects_range(ranges: &[Range<usize>], range: &Range<usize>) -> bool {
+    let ix = ranges.partition_point(|candidate| candidate.end <= range.start);
+    ranges
+        .get(ix)
+        .is_some_and(|candidate| candidate.start < range.end)
 }
 
 #[cfg(test)]
@@ -644,6 +861,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_structural_templates() {
+        let structural = |template| {
+            SearchQuery::structural(
+                template,
+                false,
+                Default::default(),
+                Default::default(),
+                false,
+                None,
+            )
+        };
+        assert!(structural("foo($a, $b)").is_ok());
+        assert!(structural("$a.unwrap()").is_ok());
+        assert!(
+            structural("foo($a$b)").is_err(),
+            "Adjacent placeholders should be rejected"
+        );
+        assert!(
+            structural("$a").is_err(),
+            "Templates made of placeholders only should be rejected"
+        );
+    }
+
     #[test]
     fn test_case_sensitive_pattern_items() {
         let case_sensitive = false;
@@ -0,0 +1,391 @@
+//! Structural search matches code against templates like `foo($a, $b)`, where every `$name`
+//! placeholder stands for a run of one or more complete syntax nodes of the buffer's tree-sitter
+//! trees, and the rest of the template is matched as text, ignoring whitespace between tokens.
+
+use std::{ops::Range, sync::Arc};
+
+use anyhow::{Result, bail};
+use clock::Global;
+use collections::HashMap;
+use language::{BufferSnapshot, Node};
+use parking_lot::Mutex;
+use text::BufferId;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Part {
+    /// Text to match, without whitespace.
+    Text(String),
+    /// Whitespace, which matches any amount of whitespace.
+    Whitespace,
+    /// A placeholder, which matches a run of sibling syntax nodes.
+    Placeholder(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct StructuralPattern {
+    parts: Vec<Part>,
+    /// The matches found in each buffer, so that replacing them reuses what their placeholders
+    /// matched.
+    found_matches: Arc<Mutex<HashMap<BufferId, FoundMatches>>>,
+}
+
+#[derive(Debug)]
+struct FoundMatches {
+    version: Global,
+    /// Sorted by their ranges.
+    matches: Vec<StructuralMatch>,
+}
+
+#[derive(Clone, Debug)]
+struct StructuralMatch {
+    range: Range<usize>,
+    /// The ranges matched by each placeholder.
+    captures: Vec<(String, Range<usize>)>,
+}
+
+impl StructuralPattern {
+    pub fn new(template: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut chars = template.trim().chars().peekable();
+        while let Some(c) = chars.next() {
+            if c.is_whitespace() {
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                parts.push(Part::Whitespace);
+                continue;
+            }
+            if c == '$' && chars.peek().is_some_and(|c| is_word_char(*c)) {
+                let mut name = String::new();
+                while let Some(c) = chars.next_if(|c| is_word_char(*c)) {
+                    name.push(c);
+                }
+                if let Some(Part::Placeholder(previous)) = parts.last() {
+                    bail!(
+                        "placeholders `${previous}` and `${name}` must be separated by some text"
+                    );
+                }
+                parts.push(Part::Placeholder(name));
+                continue;
+            }
+            if c == '$' {
+                chars.next_if_eq(&'$');
+            }
+            match parts.last_mut() {
+                Some(Part::Text(text)) => text.push(c),
+                _ => parts.push(Part::Text(c.to_string())),
+            }
+        }
+
+        if !parts.iter().any(|part| matches!(part, Part::Text(_))) {
+            bail!("structural search templates must contain some text besides placeholders");
+        }
+        Ok(Self {
+            parts,
+            found_matches: Arc::default(),
+        })
+    }
+
+    /// Whether the given text might contain matches, judging by the longest word of the template.
+    pub(crate) fn might_match(&self, text: &str) -> bool {
+        let longest_word = self
+            .parts
+            .iter()
+            .filter_map(|part| match part {
+                Part::Text(text) => Some(text),
+                _ => None,
+            })
+            .flat_map(|text| text.split(|c| !is_word_char(c)))
+            .max_by_key(|word| word.len());
+        longest_word.is_none_or(|word| text.contains(word))
+    }
+
+    /// Finds the non-overlapping matches of the pattern that start within the given range.
+    pub(crate) fn find_matches(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Vec<Range<usize>> {
+        let mut matches = Vec::new();
+        for layer in buffer.syntax_layers() {
+            let matcher = Matcher {
+                parts: &self.parts,
+                buffer,
+                root: layer.node(),
+            };
+            let mut last_match_end = range.start;
+            for start in node_starts(layer.node(), &range) {
+                if start < last_match_end {
+                    continue;
+                }
+                if let Some(found) = matcher.match_at(start) {
+                    last_match_end = found.range.end;
+                    matches.push(found);
+                }
+            }
+        }
+
+        matches.sort_unstable_by_key(|found| (found.range.start, found.range.end));
+        let mut last_match_end = 0;
+        matches.retain(|found| {
+            let overlaps = found.range.start < last_match_end;
+            if !overlaps {
+                last_match_end = found.range.end;
+            }
+            !overlaps
+        });
+        let ranges = matches.iter().map(|found| found.range.clone()).collect();
+
+        // Searches in several ranges of a buffer, like the selections, add up.
+        let mut found_matches = self.found_matches.lock();
+        let found_in_buffer =
+            found_matches
+                .entry(buffer.remote_id())
+                .or_insert_with(|| FoundMatches {
+                    version: buffer.version().clone(),
+                    matches: Vec::new(),
+                });
+        if found_in_buffer.version != *buffer.version() {
+            found_in_buffer.version = buffer.version().clone();
+            found_in_buffer.matches.clear();
+        }
+        found_in_buffer.matches.extend(matches);
+        found_in_buffer
+            .matches
+            .sort_unstable_by_key(|found| (found.range.start, found.range.end));
+        found_in_buffer
+            .matches
+            .dedup_by_key(|found| found.range.clone());
+        ranges
+    }
+
+    /// Renders the replacement template for the match in the given range, substituting the
+    /// placeholders with the text they matched.
+    pub(crate) fn replacement_for(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+        replacement: &str,
+    ) -> Option<String> {
+        let found = self.found_match(buffer, &range)?;
+        let mut result = String::new();
+        let mut chars = replacement.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '$' {
+                result.push(c);
+                continue;
+            }
+            if chars.next_if_eq(&'$').is_some() {
+                result.push('$');
+                continue;
+            }
+            let mut name = String::new();
+            while let Some(c) = chars.next_if(|c| is_word_char(*c)) {
+                name.push(c);
+            }
+            match found
+                .captures
+                .iter()
+                .find(|(placeholder, _)| *placeholder == name)
+            {
+                Some((_, range)) => result.extend(buffer.text_for_range(range.clone())),
+                None => {
+                    result.push('$');
+                    result.push_str(&name);
+                }
+            }
+        }
+        Some(result)
+    }
+
+    /// Returns the match found in the given range of the buffer. Matches the pattern again only
+    /// if the buffer was not searched in its current version, e.g. when searching on a remote host.
+    fn found_match(
+        &self,
+        buffer: &BufferSnapshot,
+        range: &Range<usize>,
+    ) -> Option<StructuralMatch> {
+        if let Some(found_in_buffer) = self.found_matches.lock().get(&buffer.remote_id())
+            && found_in_buffer.version == *buffer.version()
+        {
+            let ix = found_in_buffer
+                .matches
+                .binary_search_by_key(&(range.start, range.end), |found| {
+                    (found.range.start, found.range.end)
+                })
+                .ok()?;
+            return Some(found_in_buffer.matches[ix].clone());
+        }
+        buffer.syntax_layers().find_map(|layer| {
+            let matcher = Matcher {
+                parts: &self.parts,
+                buffer,
+                root: layer.node(),
+            };
+            matcher
+                .match_at(range.start)
+                .filter(|found| found.range.end == range.end)
+        })
+    }
+}
+
+struct Matcher<'a> {
+    parts: &'a [Part],
+    buffer: &'a BufferSnapshot,
+    root: Node<'a>,
+}
+
+impl<'a> Matcher<'a> {
+    /// Matches the pattern at the start of a syntax node.
+    fn match_at(&self, start: usize) -> Option<StructuralMatch> {
+        let match_ends = self.node_ends(start);
+        let mut placeholders = Vec::new();
+        let end = self.match_parts(self.parts, start, &match_ends, &mut placeholders)?;
+        Some(StructuralMatch {
+            range: start..end,
+            captures: placeholders
+                .into_iter()
+                .map(|(name, range)| (name.to_string(), range))
+                .collect(),
+        })
+    }
+
+    fn match_parts(
+        &self,
+        parts: &'a [Part],
+        position: usize,
+        match_ends: &[usize],
+        placeholders: &mut Vec<(&'a str, Range<usize>)>,
+    ) -> Option<usize> {
+        let Some((part, rest)) = parts.split_first() else {
+            return match_ends
+                .binary_search(&position)
+                .is_ok()
+                .then_some(position);
+        };
+        match part {
+            Part::Whitespace => {
+                let position = self.skip_whitespace(position);
+                self.match_parts(rest, position, match_ends, placeholders)
+            }
+            Part::Text(text) => {
+                let position = self.match_text(text, position)?;
+                self.match_parts(rest, position, match_ends, placeholders)
+            }
+            Part::Placeholder(name) => {
+                let start = self.skip_whitespace(position);
+                let previous_match = placeholders
+                    .iter()
+                    .find(|(placeholder, _)| *placeholder == name.as_str())
+                    .map(|(_, range)| range.clone());
+                let ends = match previous_match {
+                    // A placeholder used several times matches the same text every time.
+                    Some(previous_match) => {
+                        let end = start + previous_match.len();
+                        if !self.node_ends(start).contains(&end)
+                            || !self.same_text(previous_match, start..end)
+                        {
+                            return None;
+                        }
+                        vec![end]
+                    }
+                    None => self.node_ends(start),
+                };
+                for end in ends {
+                    placeholders.push((name.as_str(), start..end));
+                    if let Some(end) = self.match_parts(rest, end, match_ends, placeholders) {
+                        return Some(end);
+                    }
+                    placeholders.pop();
+                }
+                None
+            }
+        }
+    }
+
+    /// Matches text, allowing whitespace before every character that is not part of a word
+    /// together with the previous one.
+    fn match_text(&self, text: &str, mut position: usize) -> Option<usize> {
+        let mut previous = None;
+        for c in text.chars() {
+            if !previous.is_some_and(|previous| is_word_char(previous) && is_word_char(c)) {
+                position = self.skip_whitespace(position);
+            }
+            if self.buffer.chars_at(position).next() != Some(c) {
+                return None;
+            }
+            position += c.len_utf8();
+            previous = Some(c);
+        }
+        Some(position)
+    }
+
+    fn skip_whitespace(&self, position: usize) -> usize {
+        position
+            + self
+                .buffer
+                .chars_at(position)
+                .take_while(|c| c.is_whitespace())
+                .map(char::len_utf8)
+                .sum::<usize>()
+    }
+
+    fn same_text(&self, a: Range<usize>, b: Range<usize>) -> bool {
+        let a = self.buffer.text_for_range(a).flat_map(str::chars);
+        let b = self.buffer.text_for_range(b).flat_map(str::chars);
+        a.eq(b)
+    }
+
+    /// Returns the sorted ends of the runs of sibling nodes that start at the given position.
+    fn node_ends(&self, start: usize) -> Vec<usize> {
+        let mut ends = Vec::new();
+        let Some(mut node) = self.root.descendant_for_byte_range(start, start + 1) else {
+            return ends;
+        };
+        if node.start_byte() != start {
+            return ends;
+        }
+        loop {
+            let mut sibling = Some(node);
+            while let Some(current) = sibling {
+                ends.push(current.end_byte());
+                sibling = current.next_sibling();
+            }
+            match node.parent() {
+                Some(parent) if parent.start_byte() == start => node = parent,
+                _ => break,
+            }
+        }
+        ends.sort_unstable();
+        ends.dedup();
+        ends.retain(|end| *end > start);
+        ends
+    }
+}
+
+/// Returns the sorted positions within the range where syntax nodes start.
+fn node_starts(root: Node, range: &Range<usize>) -> Vec<usize> {
+    let mut starts = Vec::new();
+    let mut cursor = root.walk();
+    'outer: loop {
+        let node = cursor.node();
+        if node.end_byte() > range.start && node.start_byte() < range.end {
+            if node.start_byte() >= range.start {
+                starts.push(node.start_byte());
+            }
+            if cursor.goto_first_child() {
+                continue;
+            }
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                break 'outer;
+            }
+        }
+    }
+    starts.sort_unstable();
+    starts.dedup();
+    starts
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
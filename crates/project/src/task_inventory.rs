@@ -8,7 +8,7 @@ use std::{
     sync::Arc,
 };
 
-use anyhow::Result;
+use anyhow::{Context as _, Result, bail};
 use collections::{HashMap, HashSet, VecDeque};
 use dap::DapRegistry;
 use gpui::{App, AppContext as _, Context, Entity, SharedString, Task, WeakEntity};
@@ -21,8 +21,8 @@ use lsp::{LanguageServerId, LanguageServerName};
 use paths::{debug_task_file_name, task_file_name};
 use settings::{InvalidSettingsError, parse_json_with_comments};
 use task::{
-    DebugScenario, ResolvedTask, TaskContext, TaskId, TaskTemplate, TaskTemplates, TaskVariables,
-    VariableName,
+    DebugScenario, DependsOrder, ResolvedTask, TaskContext, TaskId, TaskTemplate, TaskTemplates,
+    TaskVariables, VariableName,
 };
 use text::{BufferId, Point, ToPoint};
 use util::{NumericPrefixWithSuffix, ResultExt as _, paths::PathExt as _, post_inc};
@@ -43,6 +43,8 @@ pub struct Inventory {
     last_scheduled_scenarios: VecDeque<(DebugScenario, DebugScenarioContext)>,
     templates_from_settings: InventoryFor<TaskTemplate>,
     scenarios_from_settings: InventoryFor<DebugScenario>,
+    /// The values last entered for the inputs of each task, by task template label and input id.
+    last_task_inputs: HashMap<String, HashMap<String, String>>,
 }
 
 impl std::fmt::Debug for Inventory {
@@ -52,10 +54,27 @@ impl std::fmt::Debug for Inventory {
             .field("last_scheduled_scenarios", &self.last_scheduled_scenarios)
             .field("templates_from_settings", &self.templates_from_settings)
             .field("scenarios_from_settings", &self.scenarios_from_settings)
+            .field("last_task_inputs", &self.last_task_inputs)
             .finish()
     }
 }
 
+/// A task to run together with the tasks it depends on, all resolved with the same [`TaskContext`].
+#[derive(Clone, Debug)]
+pub struct ResolvedTaskChain {
+    pub task_source_kind: TaskSourceKind,
+    pub task: ResolvedTask,
+    /// Chains of the tasks from the task's `depends_on`, in the order they are listed.
+    pub dependencies: Vec<ResolvedTaskChain>,
+}
+
+impl ResolvedTaskChain {
+    /// How the dependencies of the task should be run.
+    pub fn depends_order(&self) -> DependsOrder {
+        self.task.original_task().depends_order
+    }
+}
+
 // Helper trait for better error messages in [InventoryFor]
 trait InventoryContents: Clone {
     const GLOBAL_SOURCE_FILE: &'static str;
@@ -246,6 +265,7 @@ impl Inventory {
             last_scheduled_scenarios: VecDeque::default(),
             templates_from_settings: InventoryFor::default(),
             scenarios_from_settings: InventoryFor::default(),
+            last_task_inputs: HashMap::default(),
         })
     }
 
@@ -608,6 +628,53 @@ impl Inventory {
         self.last_scheduled_tasks.retain(|(_, task)| &task.id != id);
     }
 
+    /// Returns the value last entered for the given input of the task template.
+    pub fn last_task_input(&self, template: &TaskTemplate, input_id: &str) -> Option<&str> {
+        self.last_task_inputs
+            .get(&template.label)?
+            .get(input_id)
+            .map(String::as_str)
+    }
+
+    /// Remembers the values entered for the inputs of the task template, to suggest them next time.
+    pub fn task_inputs_entered(
+        &mut self,
+        template: &TaskTemplate,
+        inputs: impl IntoIterator<Item = (String, String)>,
+    ) {
+        self.last_task_inputs
+            .entry(template.label.clone())
+            .or_default()
+            .extend(inputs);
+    }
+
+    /// Resolves the tasks the given task depends on, recursively, with the task's [`TaskContext`].
+    /// Dependencies are looked up by their labels among the tasks of the task's worktree first, and global tasks next.
+    /// Fails if any dependency is missing or cannot be resolved, or if the dependencies form a cycle.
+    /// A task that several tasks of the chain depend on is resolved once, with the same [`TaskId`].
+    pub fn resolve_task_chain(
+        &self,
+        task_source_kind: TaskSourceKind,
+        resolved_task: ResolvedTask,
+    ) -> Result<ResolvedTaskChain> {
+        let worktree = match &task_source_kind {
+            TaskSourceKind::Worktree { id, .. } => Some(*id),
+            _ => None,
+        };
+        let templates = worktree
+            .into_iter()
+            .flat_map(|worktree| self.worktree_templates_from_settings(worktree))
+            .chain(self.global_templates_from_settings())
+            .collect::<Vec<_>>();
+        resolve_task_chain(
+            task_source_kind,
+            resolved_task,
+            &templates,
+            &mut Vec::new(),
+            &mut HashMap::default(),
+        )
+    }
+
     fn global_templates_from_settings(
         &self,
     ) -> impl '_ + Iterator<Item = (TaskSourceKind, TaskTemplate)> {
@@ -799,6 +866,61 @@ impl Inventory {
     }
 }
 
+fn resolve_task_chain(
+    task_source_kind: TaskSourceKind,
+    task: ResolvedTask,
+    templates: &[(TaskSourceKind, TaskTemplate)],
+    labels_in_chain: &mut Vec<String>,
+    resolved_chains: &mut HashMap<String, ResolvedTaskChain>,
+) -> Result<ResolvedTaskChain> {
+    let label = task.original_task().label.clone();
+    if labels_in_chain.contains(&label) {
+        bail!(
+            "Task dependency cycle: {} -> {label}",
+            labels_in_chain.join(" -> ")
+        );
+    }
+
+    labels_in_chain.push(label.clone());
+    let dependencies = task
+        .original_task()
+        .depends_on
+        .iter()
+        .unique()
+        .map(|dependency_label| {
+            // Tasks that several others depend on are resolved once and shared.
+            if let Some(chain) = resolved_chains.get(dependency_label) {
+                return Ok(chain.clone());
+            }
+            let (source_kind, template) = templates
+                .iter()
+                .find(|(_, template)| &template.label == dependency_label)
+                .with_context(|| {
+                    format!("Task `{label}` depends on an unknown task `{dependency_label}`")
+                })?;
+            let dependency = template
+                .resolve_task(&source_kind.to_id_base(), task.task_context())
+                .with_context(|| format!("Failed to resolve task `{dependency_label}`"))?;
+            let chain = resolve_task_chain(
+                source_kind.clone(),
+                dependency,
+                templates,
+                labels_in_chain,
+                resolved_chains,
+            )?;
+            resolved_chains.insert(dependency_label.clone(), chain.clone());
+            Ok(chain)
+        })
+        .collect::<Result<Vec<_>>>();
+    labels_in_chain.pop();
+
+    Ok(ResolvedTaskChain {
+        task_source_kind,
+        task,
+        dependencies: dependencies?,
+    })
+}
+
 fn task_lru_comparator(
     (kind_a, task_a, lru_score_a): &(TaskSourceKind, ResolvedTask, u32),
     (kind_b, task_b, lru_score_b): &(TaskSourceKind, ResolvedTask, u32),
@@ -1541,6 +1663,114 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_resolving_task_chains(cx: &mut TestAppContext) {
+        init_test(cx);
+        let inventory = cx.update(|cx| Inventory::new(cx));
+        inventory.update(cx, |inventory, _| {
+            inventory
+                .update_file_based_tasks(
+                    TaskSettingsLocation::Global(tasks_file()),
+                    Some(
+                        &json!([
+                            {"label": "lint", "command": "cargo", "args": ["clippy"]},
+                            {"label": "test", "command": "cargo", "args": ["test"]},
+                            {
+                                "label": "check",
+                                "depends_on": ["lint", "test"],
+                                "depends_order": "parallel",
+                            },
+                            {
+                                "label": "release",
+                                "command": "cargo",
+                                "args": ["build"],
+                                "depends_on": ["check"],
+                            },
+                            {"label": "missing", "command": "echo", "depends_on": ["unknown"]},
+                            {"label": "cycle 1", "command": "echo", "depends_on": ["cycle 2"]},
+                            {"label": "cycle 2", "command": "echo", "depends_on": ["cycle 1"]},
+                            {"label": "shared", "command": "echo"},
+                            {"label": "left", "command": "echo", "depends_on": ["shared"]},
+                            {"label": "right", "command": "echo", "depends_on": ["shared"]},
+                            {
+                                "label": "diamond",
+                                "command": "echo",
+                                "depends_on": ["left", "right", "left"],
+                            },
+                        ])
+                        .to_string(),
+                    ),
+                )
+                .unwrap();
+        });
+
+        let resolve_chain = |label: &str, cx: &mut TestAppContext| {
+            inventory.update(cx, |inventory, _| {
+                let (source_kind, template) = inventory
+                    .global_templates_from_settings()
+                    .find(|(_, template)| template.label == label)
+                    .unwrap();
+                let task = template
+                    .resolve_task(&source_kind.to_id_base(), &TaskContext::default())
+                    .unwrap();
+                inventory.resolve_task_chain(source_kind, task)
+            })
+        };
+
+        let chain = resolve_chain("release", cx).unwrap();
+        assert_eq!(chain.task.resolved_label, "release");
+        assert_eq!(chain.depends_order(), DependsOrder::Sequence);
+        assert_eq!(chain.dependencies.len(), 1);
+        let check = &chain.dependencies[0];
+        assert_eq!(check.task.resolved_label, "check");
+        assert!(!check.task.original_task().has_command());
+        assert_eq!(check.depends_order(), DependsOrder::Parallel);
+        assert_eq!(
+            check
+                .dependencies
+                .iter()
+                .map(|dependency| dependency.task.resolved_label.as_str())
+                .collect::<Vec<_>>(),
+            vec!["lint", "test"]
+        );
+
+        let error = resolve_chain("missing", cx).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Task `missing` depends on an unknown task `unknown`"
+        );
+        let error = resolve_chain("cycle 1", cx).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Task dependency cycle: cycle 1 -> cycle 2 -> cycle 1"
+        );
+
+        let chain = resolve_chain("diamond", cx).unwrap();
+        assert_eq!(
+            chain
+                .dependencies
+                .iter()
+                .map(|dependency| dependency.task.resolved_label.as_str())
+                .collect::<Vec<_>>(),
+            vec!["left", "right"],
+            "Repeated dependencies should be listed once"
+        );
+        let shared_ids = chain
+            .dependencies
+            .iter()
+            .flat_map(|dependency| &dependency.dependencies)
+            .map(|shared| {
+                assert_eq!(shared.task.resolved_label, "shared");
+                shared.task.id.clone()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(shared_ids.len(), 2);
+        assert_eq!(
+            shared_ids[0], shared_ids[1],
+            "A task that several tasks depend on should be resolved once"
+        );
+    }
+
     fn init_test(_cx: &mut TestAppContext) {
         zlog::init_test();
         TaskStore::init(None);
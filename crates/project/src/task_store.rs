@@ -1,25 +1,31 @@
 use std::{
+    ffi::OsStr,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
 use anyhow::Context as _;
 use collections::HashMap;
-use fs::Fs;
-use gpui::{App, AsyncApp, Context, Entity, EventEmitter, Task, WeakEntity};
+use fs::{Fs, PathEventKind, Watcher};
+use futures::StreamExt as _;
+use gpui::{App, AsyncApp, Context, Entity, EventEmitter, Subscription, Task, WeakEntity};
 use language::{
     ContextLocation, ContextProvider as _, LanguageToolchainStore, Location,
     proto::{deserialize_anchor, serialize_anchor},
 };
 use rpc::{AnyProtoClient, TypedEnvelope, proto};
 use settings::{InvalidSettingsError, SettingsLocation};
-use task::{TaskContext, TaskVariables, VariableName};
+use task::{ResolvedTask, TaskContext, TaskId, TaskVariables, VariableName};
 use text::{BufferId, OffsetRangeExt};
-use util::ResultExt;
+use util::{ResultExt, paths::PathMatcher};
+use worktree::{FS_WATCH_LATENCY, Snapshot, UpdatedEntriesSet, Worktree, WorktreeId};
 
 use crate::{
-    BasicContextProvider, Inventory, ProjectEnvironment, buffer_store::BufferStore,
-    worktree_store::WorktreeStore,
+    BasicContextProvider, Inventory, ProjectEnvironment, TaskSourceKind,
+    buffer_store::BufferStore,
+    lsp_store::glob_literal_prefix,
+    worktree_store::{WorktreeStore, WorktreeStoreEvent},
 };
 
 // platform-dependent warning
@@ -34,6 +40,30 @@ pub struct StoreState {
     buffer_store: WeakEntity<BufferStore>,
     worktree_store: Entity<WorktreeStore>,
     toolchain_store: Arc<dyn LanguageToolchainStore>,
+    watched_tasks: HashMap<TaskId, WatchedTask>,
+    _worktree_store_subscription: Subscription,
+}
+
+/// A task to rerun when the files it watches change.
+struct WatchedTask {
+    task_source_kind: TaskSourceKind,
+    task: ResolvedTask,
+    /// The worktree to watch, or `None` to watch all of them.
+    worktree_id: Option<WorktreeId>,
+    path_matcher: PathMatcher,
+    debounce: Duration,
+    pending_rerun: Option<Task<()>>,
+    /// Watches the task's files with the file system, or `None` in remote projects, where the
+    /// changes the worktrees report are used instead.
+    file_watch: Option<Task<()>>,
+}
+
+pub enum TaskStoreEvent {
+    /// The files watched by the task changed, and the task should be rerun.
+    WatchedTaskChanged {
+        task_source_kind: TaskSourceKind,
+        task: ResolvedTask,
+    },
 }
 
 enum StoreMode {
@@ -48,6 +78,7 @@ enum StoreMode {
 }
 
 impl EventEmitter<crate::Event> for TaskStore {}
+impl EventEmitter<TaskStoreEvent> for TaskStore {}
 
 #[derive(Debug)]
 pub enum TaskSettingsLocation<'a> {
@@ -173,6 +204,9 @@ impl TaskStore {
             task_inventory: Inventory::new(cx),
             buffer_store,
             toolchain_store,
+            watched_tasks: HashMap::default(),
+            _worktree_store_subscription: cx
+                .subscribe(&worktree_store, Self::on_worktree_store_event),
             worktree_store,
         })
     }
@@ -193,6 +227,9 @@ impl TaskStore {
             task_inventory: Inventory::new(cx),
             buffer_store,
             toolchain_store,
+            watched_tasks: HashMap::default(),
+            _worktree_store_subscription: cx
+                .subscribe(&worktree_store, Self::on_worktree_store_event),
             worktree_store,
         })
     }
@@ -237,6 +274,246 @@ impl TaskStore {
         }
     }
 
+    /// Starts rerunning the task whenever files matching its `watch` patterns change in the worktrees,
+    /// including the gitignored ones. The task is watched until [`Self::unwatch_task`] is called.
+    pub fn watch_task(
+        &mut self,
+        task_source_kind: TaskSourceKind,
+        task: ResolvedTask,
+        cx: &mut Context<Self>,
+    ) -> anyhow::Result<()> {
+        let TaskStore::Functional(state) = self else {
+            return Ok(());
+        };
+        let Some(watch) = task.original_task().watch.as_ref() else {
+            return Ok(());
+        };
+        let path_matcher = PathMatcher::new(&watch.patterns).with_context(|| {
+            format!(
+                "parsing the watch patterns of task `{}`",
+                task.resolved_label
+            )
+        })?;
+        let worktree_id = match &task_source_kind {
+            TaskSourceKind::Worktree { id, .. } => Some(*id),
+            _ => None,
+        };
+        let file_watch = state.worktree_store.read(cx).fs().map(|fs| {
+            let worktrees = state
+                .worktree_store
+                .read(cx)
+                .visible_worktrees(cx)
+                .filter(|worktree| worktree_id.is_none_or(|id| worktree.read(cx).id() == id))
+                .collect();
+            Self::watch_task_files(fs, task.id.clone(), worktrees, path_matcher.clone(), cx)
+        });
+        state.watched_tasks.insert(
+            task.id.clone(),
+            WatchedTask {
+                debounce: Duration::from_millis(watch.debounce_ms),
+                task_source_kind,
+                task,
+                worktree_id,
+                path_matcher,
+                pending_rerun: None,
+                file_watch,
+            },
+        );
+        cx.notify();
+        Ok(())
+    }
+
+    /// Stops rerunning the task on file changes.
+    pub fn unwatch_task(&mut self, task_id: &TaskId, cx: &mut Context<Self>) {
+        if let TaskStore::Functional(state) = self
+            && state.watched_tasks.remove(task_id).is_some()
+        {
+            cx.notify();
+        }
+    }
+
+    /// Stops rerunning all watched tasks on file changes.
+    pub fn unwatch_all_tasks(&mut self, cx: &mut Context<Self>) {
+        if let TaskStore::Functional(state) = self
+            && !state.watched_tasks.is_empty()
+        {
+            state.watched_tasks.clear();
+            cx.notify();
+        }
+    }
+
+    pub fn is_task_watched(&self, task_id: &TaskId) -> bool {
+        match self {
+            TaskStore::Functional(state) => state.watched_tasks.contains_key(task_id),
+            TaskStore::Noop => false,
+        }
+    }
+
+    fn on_worktree_store_event(
+        &mut self,
+        _: Entity<WorktreeStore>,
+        event: &WorktreeStoreEvent,
+        cx: &mut Context<Self>,
+    ) {
+        if let WorktreeStoreEvent::WorktreeUpdatedEntries(worktree_id, updated_entries) = event {
+            self.worktree_entries_updated(*worktree_id, updated_entries, cx);
+        }
+    }
+
+    fn worktree_entries_updated(
+        &mut self,
+        worktree_id: WorktreeId,
+        updated_entries: &UpdatedEntriesSet,
+        cx: &mut Context<Self>,
+    ) {
+        let TaskStore::Functional(state) = self else {
+            return;
+        };
+        let changed_tasks = state
+            .watched_tasks
+            .iter()
+            .filter(|(_, watched_task)| {
+                watched_task.file_watch.is_none()
+                    && watched_task
+                        .worktree_id
+                        .is_none_or(|watched_worktree_id| watched_worktree_id == worktree_id)
+                    && updated_entries
+                        .iter()
+                        .any(|(path, _, _)| watched_task.path_matcher.is_match(path))
+            })
+            .map(|(task_id, _)| task_id.clone())
+            .collect::<Vec<_>>();
+        for task_id in changed_tasks {
+            self.watched_files_changed(&task_id, cx);
+        }
+    }
+
+    /// Watches the directories the task's patterns can match files in, as the worktrees do not
+    /// report the changes of the gitignored files.
+    ///
+    /// Gitignored directories, such as `target` or `node_modules`, are only watched when a pattern
+    /// points into them, e.g. `target/schema/*.json`, rather than matching them with a wildcard.
+    fn watch_task_files(
+        fs: Arc<dyn Fs>,
+        task_id: TaskId,
+        worktrees: Vec<Entity<Worktree>>,
+        path_matcher: PathMatcher,
+        cx: &mut Context<Self>,
+    ) -> Task<()> {
+        cx.spawn(async move |task_store, cx| {
+            let read_snapshots = |cx: &mut AsyncApp| {
+                cx.update(|cx| {
+                    worktrees
+                        .iter()
+                        .map(|worktree| worktree.read(cx).snapshot())
+                        .collect::<Vec<_>>()
+                })
+            };
+            let Ok(mut worktree_snapshots) = read_snapshots(cx) else {
+                return;
+            };
+            let mut watch_roots = worktree_snapshots
+                .iter()
+                .flat_map(|snapshot| {
+                    path_matcher.sources().iter().map(|pattern| {
+                        snapshot
+                            .abs_path()
+                            .join(glob_literal_prefix(Path::new(pattern)))
+                    })
+                })
+                .collect::<Vec<_>>();
+            watch_roots.sort();
+            watch_roots.dedup();
+
+            let mut watches = Vec::with_capacity(watch_roots.len());
+            for watch_root in watch_roots {
+                let skip_ignored = !is_ignored(&worktree_snapshots, &watch_root);
+                let (events, watcher) = fs.watch(&watch_root, FS_WATCH_LATENCY).await;
+                watch_directories_under(
+                    fs.as_ref(),
+                    watcher.as_ref(),
+                    &watch_root,
+                    skip_ignored.then_some(worktree_snapshots.as_slice()),
+                )
+                .await;
+                watches.push(events.map(move |events| (watcher.clone(), skip_ignored, events)));
+            }
+            let mut events = futures::stream::select_all(watches);
+            while let Some((watcher, skip_ignored, events)) = events.next().await {
+                let Ok(snapshots) = read_snapshots(cx) else {
+                    break;
+                };
+                worktree_snapshots = snapshots;
+                let mut matched = false;
+                for event in events {
+                    // Some file system watchers report the changes of every descendant of the
+                    // watched directory, including the ones in the skipped directories.
+                    if skip_ignored && is_ignored(&worktree_snapshots, &event.path) {
+                        continue;
+                    }
+                    if event.kind == Some(PathEventKind::Created) && fs.is_dir(&event.path).await {
+                        watcher.add(&event.path).log_err();
+                        watch_directories_under(
+                            fs.as_ref(),
+                            watcher.as_ref(),
+                            &event.path,
+                            skip_ignored.then_some(worktree_snapshots.as_slice()),
+                        )
+                        .await;
+                    }
+                    matched |= worktree_snapshots.iter().any(|snapshot| {
+                        event
+                            .path
+                            .strip_prefix(snapshot.abs_path())
+                            .is_ok_and(|path| path_matcher.is_match(path))
+                    });
+                }
+                if matched
+                    && task_store
+                        .update(cx, |task_store, cx| {
+                            task_store.watched_files_changed(&task_id, cx)
+                        })
+                        .is_err()
+                {
+                    break;
+                }
+            }
+        })
+    }
+
+    /// Reruns the watched task once its files stop changing for the debounce interval.
+    fn watched_files_changed(&mut self, task_id: &TaskId, cx: &mut Context<Self>) {
+        let TaskStore::Functional(state) = self else {
+            return;
+        };
+        let Some(watched_task) = state.watched_tasks.get_mut(task_id) else {
+            return;
+        };
+
+        // Replacing the pending rerun restarts the debounce interval.
+        let task_id = task_id.clone();
+        let debounce = watched_task.debounce;
+        watched_task.pending_rerun = Some(cx.spawn(async move |task_store, cx| {
+            cx.background_executor().timer(debounce).await;
+            task_store
+                .update(cx, |task_store, cx| {
+                    let TaskStore::Functional(state) = task_store else {
+                        return;
+                    };
+                    let Some(watched_task) = state.watched_tasks.get_mut(&task_id) else {
+                        return;
+                    };
+                    watched_task.pending_rerun = None;
+                    let event = TaskStoreEvent::WatchedTaskChanged {
+                        task_source_kind: watched_task.task_source_kind.clone(),
+                        task: watched_task.task.clone(),
+                    };
+                    cx.emit(event);
+                })
+                .ok();
+        }));
+    }
+
     pub fn shared(&mut self, remote_id: u64, new_downstream_client: AnyProtoClient, _cx: &mut App) {
         if let Self::Functional(StoreState {
             mode: StoreMode::Local {
@@ -442,6 +719,49 @@ fn worktree_root(
         })
 }
 
+/// Adds the directories under `path` to the watcher, as some file system watchers only report the
+/// changes of the direct children of the watched directories. When worktree snapshots are given,
+/// the directories they consider gitignored are skipped.
+async fn watch_directories_under(
+    fs: &dyn Fs,
+    watcher: &dyn Watcher,
+    path: &Path,
+    skip_ignored_in: Option<&[Snapshot]>,
+) {
+    let mut directories = vec![path.to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        let Ok(mut entries) = fs.read_dir(&directory).await else {
+            continue;
+        };
+        while let Some(entry) = entries.next().await {
+            let Ok(entry) = entry else {
+                continue;
+            };
+            if entry.file_name() != Some(OsStr::new(".git"))
+                && !skip_ignored_in.is_some_and(|snapshots| is_ignored(snapshots, &entry))
+                && fs.is_dir(&entry).await
+            {
+                watcher.add(&entry).log_err();
+                directories.push(entry);
+            }
+        }
+    }
+}
+
+/// Whether the worktree containing `abs_path` considers it gitignored, based on the closest
+/// ancestor it has an entry for, as the worktrees do not scan the gitignored directories.
+fn is_ignored(worktree_snapshots: &[Snapshot], abs_path: &Path) -> bool {
+    worktree_snapshots.iter().any(|snapshot| {
+        abs_path
+            .strip_prefix(snapshot.abs_path())
+            .is_ok_and(|path| {
+                path.ancestors()
+                    .find_map(|path| snapshot.entry_for_path(path))
+                    .is_some_and(|entry| entry.is_ignored)
+            })
+    })
+}
+
 fn combine_task_variables(
     mut captured_variables: TaskVariables,
     fs: Option<Arc<dyn Fs>>,
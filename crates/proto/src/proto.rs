@@ -97,6 +97,22 @@ messages!(
     (GetDocumentSymbolsResponse, Background),
     (GetHover, Background),
     (GetHoverResponse, Background),
+    (GetIncomingCalls, Background),
+    (GetIncomingCallsResponse, Background),
+    (GetOutgoingCalls, Background),
+    (GetOutgoingCallsResponse, Background),
+    (GetSubtypes, Background),
+    (GetSubtypesResponse, Background),
+    (GetSemanticTokens, Background),
+    (GetSemanticTokensResponse, Background),
+    (GetFoldingRanges, Background),
+    (GetFoldingRangesResponse, Background),
+    (GetSelectionRanges, Background),
+    (GetSelectionRangesResponse, Background),
+    (GetDocumentLinks, Background),
+    (GetDocumentLinksResponse, Background),
+    (GetSupertypes, Background),
+    (GetSupertypesResponse, Background),
     (GetNotifications, Foreground),
     (GetNotificationsResponse, Foreground),
     (GetCrashFiles, Background),
@@ -190,6 +206,10 @@ messages!(
     (PerformRename, Background),
     (PerformRenameResponse, Background),
     (Ping, Foreground),
+    (PrepareCallHierarchy, Background),
+    (PrepareCallHierarchyResponse, Background),
+    (PrepareTypeHierarchy, Background),
+    (PrepareTypeHierarchyResponse, Background),
     (PrepareRename, Background),
     (PrepareRenameResponse, Background),
     (ProjectEntryResponse, Foreground),
@@ -289,6 +309,17 @@ messages!(
     (GitCheckoutFiles, Background),
     (GitShow, Background),
     (GitCommitDetails, Background),
+    (GitLog, Background),
+    (GitLogResponse, Background),
+    (GitRebase, Background),
+    (GitControlRebase, Background),
+    (GitCherryPick, Background),
+    (GitRevert, Background),
+    (GitMerge, Background),
+    (GitGetWorktrees, Background),
+    (GitWorktreesResponse, Background),
+    (GitCreateWorktree, Background),
+    (GitRemoveWorktree, Background),
     (SetIndexText, Background),
     (Push, Background),
     (Fetch, Background),
@@ -323,6 +354,10 @@ messages!(
     (ExternalAgentsUpdated, Background),
     (ExternalAgentLoadingStatusUpdated, Background),
     (NewExternalAgentVersionAvailable, Background),
+    (GetJujutsuStatus, Background),
+    (GetJujutsuStatusResponse, Background),
+    (LoadJujutsuParentText, Background),
+    (LoadJujutsuParentTextResponse, Background),
 );
 
 request_messages!(
@@ -358,6 +393,16 @@ request_messages!(
     (GetDocumentHighlights, GetDocumentHighlightsResponse),
     (GetDocumentSymbols, GetDocumentSymbolsResponse),
     (GetHover, GetHoverResponse),
+    (PrepareCallHierarchy, PrepareCallHierarchyResponse),
+    (GetIncomingCalls, GetIncomingCallsResponse),
+    (GetOutgoingCalls, GetOutgoingCallsResponse),
+    (PrepareTypeHierarchy, PrepareTypeHierarchyResponse),
+    (GetSupertypes, GetSupertypesResponse),
+    (GetSubtypes, GetSubtypesResponse),
+    (GetSemanticTokens, GetSemanticTokensResponse),
+    (GetFoldingRanges, GetFoldingRangesResponse),
+    (GetSelectionRanges, GetSelectionRangesResponse),
+    (GetDocumentLinks, GetDocumentLinksResponse),
     (GetNotifications, GetNotificationsResponse),
     (GetProjectSymbols, GetProjectSymbolsResponse),
     (GetReferences, GetReferencesResponse),
@@ -473,6 +518,15 @@ request_messages!(
     (InstallExtension, Ack),
     (RegisterBufferWithLanguageServers, Ack),
     (GitShow, GitCommitDetails),
+    (GitLog, GitLogResponse),
+    (GitRebase, Ack),
+    (GitControlRebase, Ack),
+    (GitCherryPick, Ack),
+    (GitRevert, Ack),
+    (GitMerge, Ack),
+    (GitGetWorktrees, GitWorktreesResponse),
+    (GitCreateWorktree, Ack),
+    (GitRemoveWorktree, Ack),
     (GitReset, Ack),
     (GitCheckoutFiles, Ack),
     (SetIndexText, Ack),
@@ -495,7 +549,9 @@ request_messages!(
     (GitClone, GitCloneResponse),
     (ToggleLspLogs, Ack),
     (GetProcesses, GetProcessesResponse),
-    (GetAgentServerCommand, AgentServerCommand)
+    (GetAgentServerCommand, AgentServerCommand),
+    (GetJujutsuStatus, GetJujutsuStatusResponse),
+    (LoadJujutsuParentText, LoadJujutsuParentTextResponse)
 );
 
 lsp_messages!(
@@ -510,6 +566,8 @@ lsp_messages!(
     (GetDeclaration, GetDeclarationResponse, true),
     (GetTypeDefinition, GetTypeDefinitionResponse, true),
     (GetImplementation, GetImplementationResponse, true),
+    (PrepareCallHierarchy, PrepareCallHierarchyResponse, true),
+    (PrepareTypeHierarchy, PrepareTypeHierarchyResponse, true),
 );
 
 entity_messages!(
@@ -543,6 +601,16 @@ entity_messages!(
     GetDocumentHighlights,
     GetDocumentSymbols,
     GetHover,
+    PrepareCallHierarchy,
+    GetIncomingCalls,
+    GetOutgoingCalls,
+    PrepareTypeHierarchy,
+    GetSupertypes,
+    GetSubtypes,
+    GetSemanticTokens,
+    GetFoldingRanges,
+    GetSelectionRanges,
+    GetDocumentLinks,
     GetProjectSymbols,
     GetReferences,
     GetSignatureHelp,
@@ -626,6 +694,15 @@ entity_messages!(
     CancelLanguageServerWork,
     RegisterBufferWithLanguageServers,
     GitShow,
+    GitLog,
+    GitRebase,
+    GitControlRebase,
+    GitCherryPick,
+    GitRevert,
+    GitMerge,
+    GitGetWorktrees,
+    GitCreateWorktree,
+    GitRemoveWorktree,
     GitReset,
     GitCheckoutFiles,
     SetIndexText,
@@ -654,6 +731,8 @@ entity_messages!(
     ExternalAgentsUpdated,
     ExternalAgentLoadingStatusUpdated,
     NewExternalAgentVersionAvailable,
+    GetJujutsuStatus,
+    LoadJujutsuParentText,
 );
 
 entity_messages!(
@@ -838,6 +917,8 @@ impl LspQuery {
             Some(lsp_query::Request::GetImplementation(_)) => ("GetImplementation", false),
             Some(lsp_query::Request::GetReferences(_)) => ("GetReferences", false),
             Some(lsp_query::Request::GetDocumentColor(_)) => ("GetDocumentColor", false),
+            Some(lsp_query::Request::PrepareCallHierarchy(_)) => ("PrepareCallHierarchy", false),
+            Some(lsp_query::Request::PrepareTypeHierarchy(_)) => ("PrepareTypeHierarchy", false),
             None => ("<unknown>", true),
         }
     }
@@ -12,6 +12,7 @@ use extension::ExtensionHostProxy;
 use fs::{FakeFs, Fs};
 use gpui::{AppContext as _, Entity, SemanticVersion, TestAppContext};
 use http_client::{BlockedHttpClient, FakeHttpClient};
+use jj::{FileChangeKind, JujutsuRepository as _};
 use language::{
     Buffer, FakeLspAdapter, LanguageConfig, LanguageMatcher, LanguageRegistry, LineEnding,
     language_settings::{AllLanguageSettings, language_settings},
@@ -1445,6 +1446,100 @@ async fn test_remote_git_diffs(cx: &mut TestAppContext, server_cx: &mut TestAppC
     });
 }
 
+#[gpui::test]
+async fn test_remote_jj_status_and_diffs(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        "/code",
+        json!({
+            "project1": {
+                "a.txt": "ONE\n",
+                "b.txt": "two\n",
+            },
+        }),
+    )
+    .await;
+    let repository = fs.insert_jj_repository(Path::new("/code/project1")).await;
+    repository.set_working_copy_files([("a.txt", "one\n"), ("b.txt", "two\n")]);
+    repository
+        .new_change(&[repository.working_copy_commit_id()])
+        .unwrap();
+    repository.set_working_copy_files([("a.txt", "ONE\n"), ("b.txt", "two\n")]);
+    repository
+        .describe(&repository.working_copy_commit_id(), "shout")
+        .unwrap();
+
+    let (project, _headless) = init_test(&fs, cx, server_cx).await;
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree("/code/project1", true, cx)
+        })
+        .await
+        .unwrap();
+    let worktree_id = cx.update(|cx| worktree.read(cx).id());
+    cx.executor().run_until_parked();
+
+    let jj_store = project.read_with(cx, |project, cx| project.jj_store(cx));
+    jj_store.read_with(cx, |jj_store, cx| {
+        assert!(!jj_store.is_local());
+        assert_eq!(
+            jj_store
+                .repository_for_worktree(worktree_id)
+                .unwrap()
+                .workspace_root
+                .as_ref(),
+            Path::new("/code/project1")
+        );
+        assert_eq!(
+            jj_store.status_for_project_path(&(worktree_id, "a.txt").into(), cx),
+            Some(FileChangeKind::Modified)
+        );
+        assert_eq!(
+            jj_store.status_for_project_path(&(worktree_id, "b.txt").into(), cx),
+            None
+        );
+        assert_eq!(
+            jj_store
+                .working_copy_change(cx)
+                .unwrap()
+                .description
+                .as_ref(),
+            "shout"
+        );
+    });
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_buffer((worktree_id, Path::new("a.txt")), cx)
+        })
+        .await
+        .unwrap();
+    let diff = project
+        .update(cx, |project, cx| {
+            project.open_uncommitted_diff(buffer.clone(), cx)
+        })
+        .await
+        .unwrap();
+    diff.read_with(cx, |diff, _| {
+        assert_eq!(diff.base_text_string().unwrap(), "one\n");
+    });
+
+    repository
+        .squash(&repository.working_copy_commit_id(), None)
+        .unwrap();
+    jj_store.update(cx, |jj_store, cx| jj_store.refresh(cx));
+    cx.executor().run_until_parked();
+    diff.read_with(cx, |diff, _| {
+        assert_eq!(diff.base_text_string().unwrap(), "ONE\n");
+    });
+    jj_store.read_with(cx, |jj_store, cx| {
+        assert_eq!(
+            jj_store.status_for_project_path(&(worktree_id, "a.txt").into(), cx),
+            None
+        );
+    });
+}
+
 // TODO: this test fails on Windows.
 #[cfg(not(windows))]
 #[gpui::test]
@@ -1,13 +1,19 @@
 #![allow(unused, dead_code)]
-use std::sync::Arc;
+use std::{mem, sync::Arc};
 
+use anyhow::Result;
 use editor::{Editor, EditorMode, MultiBuffer};
+use futures::FutureExt as _;
 use futures::future::Shared;
 use gpui::{
-    App, Entity, Hsla, RetainAllImageCache, Task, TextStyleRefinement, image_cache, prelude::*,
+    App, Entity, Focusable, Hsla, RetainAllImageCache, Task, TextStyleRefinement, image_cache,
+    prelude::*,
 };
 use language::{Buffer, Language, LanguageRegistry};
-use markdown_preview::{markdown_parser::parse_markdown, markdown_renderer::render_markdown_block};
+use markdown_preview::{
+    markdown_elements::ParsedMarkdown, markdown_parser::parse_markdown,
+    markdown_renderer::render_markdown_block,
+};
 use nbformat::v4::{CellId, CellMetadata, CellType};
 use settings::Settings as _;
 use theme::ThemeSettings;
@@ -33,6 +39,8 @@ pub enum CellControlType {
     CellOptions,
     CollapseCell,
     ExpandCell,
+    EditCell,
+    PreviewCell,
 }
 
 impl CellControlType {
@@ -44,6 +52,8 @@ impl CellControlType {
             CellControlType::CellOptions => IconName::Ellipsis,
             CellControlType::CollapseCell => IconName::ChevronDown,
             CellControlType::ExpandCell => IconName::ChevronRight,
+            CellControlType::EditCell => IconName::Pencil,
+            CellControlType::PreviewCell => IconName::Eye,
         }
     }
 }
@@ -112,6 +122,62 @@ fn convert_outputs(
         .collect()
 }
 
+/// Splits a cell's source into nbformat's list of lines, each keeping its line ending.
+pub(crate) fn source_lines(source: &str) -> Vec<String> {
+    source
+        .split_inclusive('\n')
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+/// Creates an auto-height editor for a cell's source, setting the buffer's language
+/// once it has loaded.
+fn cell_editor(
+    source: &str,
+    language: Shared<Task<Option<Arc<Language>>>>,
+    window: &mut Window,
+    cx: &mut App,
+) -> (Entity<Editor>, Task<()>) {
+    let buffer = cx.new(|cx| Buffer::local(source, cx));
+    let multi_buffer = cx.new(|cx| MultiBuffer::singleton(buffer.clone(), cx));
+
+    let editor = cx.new(|cx| {
+        let mut editor = Editor::new(
+            EditorMode::AutoHeight {
+                min_lines: 1,
+                max_lines: Some(1024),
+            },
+            multi_buffer,
+            None,
+            window,
+            cx,
+        );
+
+        let theme = ThemeSettings::get_global(cx);
+
+        let refinement = TextStyleRefinement {
+            font_family: Some(theme.buffer_font.family.clone()),
+            font_size: Some(theme.buffer_font_size(cx).into()),
+            color: Some(cx.theme().colors().editor_foreground),
+            background_color: Some(gpui::transparent_black()),
+            ..Default::default()
+        };
+
+        editor.set_show_gutter(false, cx);
+        editor.set_text_style_refinement(refinement);
+        editor
+    });
+
+    let language_task = window.spawn(cx, async move |cx| {
+        let language = language.await;
+        buffer
+            .update(cx, |buffer, cx| buffer.set_language(language, cx))
+            .log_err();
+    });
+
+    (editor, language_task)
+}
+
 impl Cell {
     pub fn load(
         cell: &nbformat::v4::Cell,
@@ -125,40 +191,36 @@ impl Cell {
                 id,
                 metadata,
                 source,
-                ..
+                attachments,
             } => {
                 let source = source.join("");
+                let markdown_language = {
+                    let languages = languages.clone();
+                    cx.background_spawn(async move {
+                        languages.language_for_name("Markdown").await.ok()
+                    })
+                    .shared()
+                };
+                let (editor, language_task) = cell_editor(&source, markdown_language, window, cx);
 
                 let entity = cx.new(|cx| {
-                    let markdown_parsing_task = {
-                        let languages = languages.clone();
-                        let source = source.clone();
-
-                        cx.spawn_in(window, async move |this, cx| {
-                            let parsed_markdown = cx
-                                .background_spawn(async move {
-                                    parse_markdown(&source, None, Some(languages)).await
-                                })
-                                .await;
-
-                            this.update(cx, |cell: &mut MarkdownCell, _| {
-                                cell.parsed_markdown = Some(parsed_markdown);
-                            })
-                            .log_err();
-                        })
-                    };
-
-                    MarkdownCell {
-                        markdown_parsing_task,
+                    let mut cell = MarkdownCell {
+                        markdown_parsing_task: Task::ready(()),
                         image_cache: RetainAllImageCache::new(cx),
                         languages: languages.clone(),
                         id: id.clone(),
                         metadata: metadata.clone(),
+                        attachments: attachments.clone(),
+                        editor,
+                        editing: source.is_empty(),
                         source: source.clone(),
                         parsed_markdown: None,
                         selected: false,
                         cell_position: None,
-                    }
+                        language_task,
+                    };
+                    cell.parse_markdown(window, cx);
+                    cell
                 });
 
                 Cell::Markdown(entity)
@@ -170,57 +232,17 @@ impl Cell {
                 source,
                 outputs,
             } => Cell::Code(cx.new(|cx| {
-                let text = source.join("");
-
-                let buffer = cx.new(|cx| Buffer::local(text.clone(), cx));
-                let multi_buffer = cx.new(|cx| MultiBuffer::singleton(buffer.clone(), cx));
-
-                let editor_view = cx.new(|cx| {
-                    let mut editor = Editor::new(
-                        EditorMode::AutoHeight {
-                            min_lines: 1,
-                            max_lines: Some(1024),
-                        },
-                        multi_buffer,
-                        None,
-                        window,
-                        cx,
-                    );
-
-                    let theme = ThemeSettings::get_global(cx);
-
-                    let refinement = TextStyleRefinement {
-                        font_family: Some(theme.buffer_font.family.clone()),
-                        font_size: Some(theme.buffer_font_size(cx).into()),
-                        color: Some(cx.theme().colors().editor_foreground),
-                        background_color: Some(gpui::transparent_black()),
-                        ..Default::default()
-                    };
-
-                    editor.set_text(text, window, cx);
-                    editor.set_show_gutter(false, cx);
-                    editor.set_text_style_refinement(refinement);
-
-                    // editor.set_read_only(true);
-                    editor
-                });
-
-                let buffer = buffer.clone();
-                let language_task = cx.spawn_in(window, async move |this, cx| {
-                    let language = notebook_language.await;
-
-                    buffer.update(cx, |buffer, cx| {
-                        buffer.set_language(language.clone(), cx);
-                    });
-                });
+                let source = source.join("");
+                let (editor, language_task) = cell_editor(&source, notebook_language, window, cx);
 
                 CodeCell {
                     id: id.clone(),
                     metadata: metadata.clone(),
                     execution_count: *execution_count,
-                    source: source.join(""),
-                    editor: editor_view,
+                    source,
+                    editor,
                     outputs: convert_outputs(outputs, window, cx),
+                    raw_outputs: outputs.clone(),
                     selected: false,
                     language_task,
                     cell_position: None,
@@ -230,15 +252,171 @@ impl Cell {
                 id,
                 metadata,
                 source,
-            } => Cell::Raw(cx.new(|_| RawCell {
-                id: id.clone(),
-                metadata: metadata.clone(),
-                source: source.join(""),
-                selected: false,
-                cell_position: None,
+            } => Cell::Raw(cx.new(|cx| {
+                let source = source.join("");
+                let (editor, language_task) =
+                    cell_editor(&source, Task::ready(None).shared(), window, cx);
+
+                RawCell {
+                    id: id.clone(),
+                    metadata: metadata.clone(),
+                    source,
+                    editor,
+                    selected: false,
+                    cell_position: None,
+                    language_task,
+                }
             })),
         }
     }
+
+    /// Creates an empty cell of the given type with a fresh id.
+    pub fn new(
+        cell_type: CellType,
+        languages: &Arc<LanguageRegistry>,
+        notebook_language: Shared<Task<Option<Arc<Language>>>>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Result<Self> {
+        let id = CellId::from(uuid::Uuid::new_v4());
+        let metadata: CellMetadata =
+            serde_json::from_value(serde_json::Value::Object(Default::default()))?;
+        let cell = nbformat_cell(cell_type, id, metadata, Vec::new(), None);
+        Ok(Self::load(&cell, languages, notebook_language, window, cx))
+    }
+
+    /// Creates a cell of another type with the same id, metadata and source.
+    ///
+    /// Outputs are dropped when converting a code cell.
+    pub fn convert_to(
+        &self,
+        cell_type: CellType,
+        languages: &Arc<LanguageRegistry>,
+        notebook_language: Shared<Task<Option<Arc<Language>>>>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Self {
+        let (id, metadata, source, attachments) = match self.to_nbformat(cx) {
+            nbformat::v4::Cell::Markdown {
+                id,
+                metadata,
+                source,
+                attachments,
+            } => (id, metadata, source, attachments),
+            nbformat::v4::Cell::Code {
+                id,
+                metadata,
+                source,
+                ..
+            }
+            | nbformat::v4::Cell::Raw {
+                id,
+                metadata,
+                source,
+            } => (id, metadata, source, None),
+        };
+        let cell = nbformat_cell(cell_type, id, metadata, source, attachments);
+        Self::load(&cell, languages, notebook_language, window, cx)
+    }
+
+    pub fn id(&self, cx: &App) -> CellId {
+        match self {
+            Cell::Code(cell) => cell.read(cx).id().clone(),
+            Cell::Markdown(cell) => cell.read(cx).id().clone(),
+            Cell::Raw(cell) => cell.read(cx).id().clone(),
+        }
+    }
+
+    pub fn cell_type(&self) -> CellType {
+        match self {
+            Cell::Code(_) => CellType::Code,
+            Cell::Markdown(_) => CellType::Markdown,
+            Cell::Raw(_) => CellType::Raw,
+        }
+    }
+
+    pub fn editor(&self, cx: &App) -> Entity<Editor> {
+        match self {
+            Cell::Code(cell) => cell.read(cx).editor.clone(),
+            Cell::Markdown(cell) => cell.read(cx).editor.clone(),
+            Cell::Raw(cell) => cell.read(cx).editor.clone(),
+        }
+    }
+
+    /// Whether the cell's source has been edited since the notebook was last saved.
+    pub fn is_dirty(&self, cx: &App) -> bool {
+        self.editor(cx).read(cx).buffer().read(cx).is_dirty(cx)
+    }
+
+    pub fn did_save(&self, cx: &mut App) {
+        let buffers = self.editor(cx).read(cx).buffer().read(cx).all_buffers();
+        for buffer in buffers {
+            buffer.update(cx, |buffer, cx| buffer.did_save(buffer.version(), None, cx));
+        }
+    }
+
+    /// Converts the cell back to nbformat, preserving its metadata and outputs.
+    pub fn to_nbformat(&self, cx: &App) -> nbformat::v4::Cell {
+        let source = source_lines(&self.editor(cx).read(cx).text(cx));
+        match self {
+            Cell::Code(cell) => {
+                let cell = cell.read(cx);
+                nbformat::v4::Cell::Code {
+                    id: cell.id.clone(),
+                    metadata: cell.metadata.clone(),
+                    execution_count: cell.execution_count,
+                    source,
+                    outputs: cell.raw_outputs.clone(),
+                }
+            }
+            Cell::Markdown(cell) => {
+                let cell = cell.read(cx);
+                nbformat::v4::Cell::Markdown {
+                    id: cell.id.clone(),
+                    metadata: cell.metadata.clone(),
+                    source,
+                    attachments: cell.attachments.clone(),
+                }
+            }
+            Cell::Raw(cell) => {
+                let cell = cell.read(cx);
+                nbformat::v4::Cell::Raw {
+                    id: cell.id.clone(),
+                    metadata: cell.metadata.clone(),
+                    source,
+                }
+            }
+        }
+    }
+}
+
+fn nbformat_cell(
+    cell_type: CellType,
+    id: CellId,
+    metadata: CellMetadata,
+    source: Vec<String>,
+    attachments: Option<serde_json::Value>,
+) -> nbformat::v4::Cell {
+    match cell_type {
+        CellType::Code => nbformat::v4::Cell::Code {
+            id,
+            metadata,
+            execution_count: None,
+            source,
+            outputs: Vec::new(),
+        },
+        CellType::Markdown => nbformat::v4::Cell::Markdown {
+            id,
+            metadata,
+            source,
+            attachments,
+        },
+        CellType::Raw => nbformat::v4::Cell::Raw {
+            id,
+            metadata,
+            source,
+        },
+    }
 }
 
 pub trait RenderableCell: Render {
@@ -335,13 +513,54 @@ pub trait RunnableCell: RenderableCell {
 pub struct MarkdownCell {
     id: CellId,
     metadata: CellMetadata,
+    attachments: Option<serde_json::Value>,
     image_cache: Entity<RetainAllImageCache>,
     source: String,
-    parsed_markdown: Option<markdown_preview::markdown_elements::ParsedMarkdown>,
+    editor: Entity<Editor>,
+    /// Whether the cell shows its source in an editor rather than the rendered preview.
+    editing: bool,
+    parsed_markdown: Option<ParsedMarkdown>,
     markdown_parsing_task: Task<()>,
     selected: bool,
     cell_position: Option<CellPosition>,
     languages: Arc<LanguageRegistry>,
+    language_task: Task<()>,
+}
+
+impl MarkdownCell {
+    pub fn editing(&self) -> bool {
+        self.editing
+    }
+
+    /// Switches between editing the source and showing the rendered preview.
+    pub fn toggle_preview(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editing = !self.editing;
+        if self.editing {
+            window.focus(&self.editor.focus_handle(cx));
+        } else {
+            self.parse_markdown(window, cx);
+        }
+        cx.notify();
+    }
+
+    fn parse_markdown(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.source = self.editor.read(cx).text(cx);
+        let languages = self.languages.clone();
+        let source = self.source.clone();
+        self.markdown_parsing_task = cx.spawn_in(window, async move |this, cx| {
+            let parsed_markdown = cx
+                .background_spawn(
+                    async move { parse_markdown(&source, None, Some(languages)).await },
+                )
+                .await;
+
+            this.update(cx, |cell, cx| {
+                cell.parsed_markdown = Some(parsed_markdown);
+                cx.notify();
+            })
+            .log_err();
+        });
+    }
 }
 
 impl RenderableCell for MarkdownCell {
@@ -372,8 +591,16 @@ impl RenderableCell for MarkdownCell {
         self
     }
 
-    fn control(&self, _window: &mut Window, _: &mut Context<Self>) -> Option<CellControl> {
-        None
+    fn control(&self, _window: &mut Window, cx: &mut Context<Self>) -> Option<CellControl> {
+        let control_type = if self.editing {
+            CellControlType::PreviewCell
+        } else {
+            CellControlType::EditCell
+        };
+        Some(
+            CellControl::new("toggle-markdown-preview", control_type)
+                .on_click(cx.listener(move |this, _, window, cx| this.toggle_preview(window, cx))),
+        )
     }
 
     fn cell_position(&self) -> Option<&CellPosition> {
@@ -388,6 +615,38 @@ impl RenderableCell for MarkdownCell {
 
 impl Render for MarkdownCell {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if self.editing {
+            return v_flex()
+                .size_full()
+                .children(self.cell_position_spacer(true, window, cx))
+                .child(
+                    h_flex()
+                        .w_full()
+                        .pr_6()
+                        .rounded_xs()
+                        .items_start()
+                        .gap(DynamicSpacing::Base08.rems(cx))
+                        .bg(self.selected_bg_color(window, cx))
+                        .child(self.gutter(window, cx))
+                        .child(
+                            div().py_1p5().w_full().child(
+                                div()
+                                    .flex()
+                                    .size_full()
+                                    .flex_1()
+                                    .py_3()
+                                    .px_5()
+                                    .rounded_lg()
+                                    .border_1()
+                                    .border_color(cx.theme().colors().border)
+                                    .bg(cx.theme().colors().editor_background)
+                                    .child(div().w_full().child(self.editor.clone())),
+                            ),
+                        ),
+                )
+                .children(self.cell_position_spacer(false, window, cx));
+        }
+
         let Some(parsed) = self.parsed_markdown.as_ref() else {
             return div();
         };
@@ -435,6 +694,8 @@ pub struct CodeCell {
     source: String,
     editor: Entity<editor::Editor>,
     outputs: Vec<Output>,
+    /// The outputs as loaded from the notebook, written back when it is saved.
+    raw_outputs: Vec<nbformat::v4::Output>,
     selected: bool,
     cell_position: Option<CellPosition>,
     language_task: Task<()>,
@@ -448,8 +709,26 @@ impl CodeCell {
         !self.outputs.is_empty()
     }
 
-    pub fn clear_outputs(&mut self) {
+    /// Clears the outputs and execution count, returning the cleared outputs.
+    pub fn clear_outputs(&mut self) -> Vec<nbformat::v4::Output> {
         self.outputs.clear();
+        self.execution_count = None;
+        mem::take(&mut self.raw_outputs)
+    }
+
+    pub fn raw_outputs(&self) -> &[nbformat::v4::Output] {
+        &self.raw_outputs
+    }
+
+    pub fn set_outputs(
+        &mut self,
+        outputs: Vec<nbformat::v4::Output>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.outputs = convert_outputs(&outputs, window, cx);
+        self.raw_outputs = outputs;
+        cx.notify();
     }
 
     fn output_control(&self) -> Option<CellControlType> {
@@ -672,8 +951,10 @@ pub struct RawCell {
     id: CellId,
     metadata: CellMetadata,
     source: String,
+    editor: Entity<Editor>,
     selected: bool,
     cell_position: Option<CellPosition>,
+    language_task: Task<()>,
 }
 
 impl RenderableCell for RawCell {
@@ -735,12 +1016,25 @@ impl Render for RawCell {
                             .size_full()
                             .flex_1()
                             .p_3()
-                            .font_ui(cx)
-                            .text_size(TextSize::Default.rems(cx))
-                            .child(self.source.clone()),
+                            .child(div().w_full().child(self.editor.clone())),
                     ),
             )
             // TODO: Move base cell render into trait impl so we don't have to repeat this
             .children(self.cell_position_spacer(false, window, cx))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_lines() {
+        assert_eq!(source_lines(""), Vec::<String>::new());
+        assert_eq!(source_lines("print(1)"), vec!["print(1)"]);
+        assert_eq!(
+            source_lines("import os\n\nprint(os.getcwd())\n"),
+            vec!["import os\n", "\n", "print(os.getcwd())\n"]
+        );
+    }
+}
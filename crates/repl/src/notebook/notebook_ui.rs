@@ -1,29 +1,31 @@
 #![allow(unused, dead_code)]
 use std::future::Future;
-use std::{path::PathBuf, sync::Arc};
+use std::{mem, path::PathBuf, sync::Arc};
 
-use anyhow::{Context as _, Result};
+use anyhow::{Context as _, Result, anyhow};
 use client::proto::ViewId;
 use collections::HashMap;
+use editor::EditorEvent;
 use feature_flags::{FeatureFlagAppExt as _, NotebookFeatureFlag};
 use futures::FutureExt;
 use futures::future::Shared;
 use gpui::{
     AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable, ListScrollEvent, ListState,
-    Point, Task, actions, list, prelude::*,
+    Point, Subscription, Task, actions, list, prelude::*,
 };
 use language::{Language, LanguageRegistry};
 use project::{Project, ProjectEntryId, ProjectPath};
+use serde::Serialize as _;
 use ui::{Tooltip, prelude::*};
 use workspace::item::{ItemEvent, SaveOptions, TabContentParams};
 use workspace::searchable::SearchableItemHandle;
 use workspace::{Item, ItemHandle, Pane, ProjectItem, ToolbarItemLocation};
 use workspace::{ToolbarItemEvent, ToolbarItemView};
 
-use super::{Cell, CellPosition, RenderableCell};
+use super::{Cell, CellPosition, CodeCell, RenderableCell};
 
-use nbformat::v4::CellId;
 use nbformat::v4::Metadata as NotebookMetadata;
+use nbformat::v4::{CellId, CellType};
 
 actions!(
     notebook,
@@ -42,6 +44,18 @@ actions!(
         AddMarkdownBlock,
         /// Adds a new code cell.
         AddCodeBlock,
+        /// Adds a new raw cell.
+        AddRawBlock,
+        /// Deletes the current cell.
+        DeleteCell,
+        /// Changes the current cell into a code cell.
+        ChangeToCodeCell,
+        /// Changes the current cell into a markdown cell.
+        ChangeToMarkdownCell,
+        /// Changes the current cell into a raw cell.
+        ChangeToRawCell,
+        /// Toggles between editing the current markdown cell and previewing it.
+        ToggleMarkdownPreview,
     ]
 );
 
@@ -77,6 +91,7 @@ pub struct NotebookEditor {
 
     focus_handle: FocusHandle,
     notebook_item: Entity<NotebookItem>,
+    notebook_language: Shared<Task<Option<Arc<Language>>>>,
 
     remote_id: Option<ViewId>,
     cell_list: ListState,
@@ -84,6 +99,35 @@ pub struct NotebookEditor {
     selected_cell_index: usize,
     cell_order: Vec<CellId>,
     cell_map: HashMap<CellId, Cell>,
+
+    /// Structural edits, such as adding, deleting, moving or converting cells, that can be
+    /// undone. Edits to a cell's source are undone by the cell's editor.
+    undo_stack: Vec<NotebookSnapshot>,
+    redo_stack: Vec<NotebookSnapshot>,
+    /// Identifies the current cell structure, so that undoing back to the saved structure
+    /// makes the notebook clean again.
+    structure_version: usize,
+    saved_structure_version: usize,
+    next_structure_version: usize,
+    _cell_subscriptions: Vec<Subscription>,
+}
+
+/// The cell structure of a notebook before a structural edit.
+struct NotebookSnapshot {
+    cell_order: Vec<CellId>,
+    cell_map: HashMap<CellId, Cell>,
+    selected_cell_index: usize,
+    structure_version: usize,
+    /// Outputs to restore, for edits that cleared them.
+    outputs: Vec<(Entity<CodeCell>, Vec<nbformat::v4::Output>)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotebookEditorEvent {
+    /// The notebook's cells or their sources changed.
+    Edited,
+    /// The notebook was saved or reloaded from disk.
+    Saved,
 }
 
 impl NotebookEditor {
@@ -103,44 +147,212 @@ impl NotebookEditor {
             .spawn_in(window, async move |_, _| notebook_language.await)
             .shared();
 
-        let mut cell_order = vec![]; // Vec<CellId>
-        let mut cell_map = HashMap::default(); // HashMap<CellId, Cell>
-
-        for (index, cell) in notebook_item
-            .read(cx)
-            .notebook
-            .clone()
-            .cells
-            .iter()
-            .enumerate()
-        {
-            let cell_id = cell.id();
-            cell_order.push(cell_id.clone());
-            cell_map.insert(
-                cell_id.clone(),
-                Cell::load(cell, &languages, notebook_language.clone(), window, cx),
-            );
-        }
-
-        let notebook_handle = cx.entity().downgrade();
-        let cell_count = cell_order.len();
-
-        let this = cx.entity();
-        let cell_list = ListState::new(cell_count, gpui::ListAlignment::Top, px(1000.));
+        let cell_list = ListState::new(0, gpui::ListAlignment::Top, px(1000.));
 
-        Self {
+        let mut this = Self {
             project,
-            languages: languages.clone(),
+            languages,
             focus_handle,
             notebook_item,
+            notebook_language,
             remote_id: None,
             cell_list,
             selected_cell_index: 0,
-            cell_order: cell_order.clone(),
-            cell_map: cell_map.clone(),
+            cell_order: Vec::new(),
+            cell_map: HashMap::default(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            structure_version: 0,
+            saved_structure_version: 0,
+            next_structure_version: 1,
+            _cell_subscriptions: Vec::new(),
+        };
+        this.load_cells(window, cx);
+        this
+    }
+
+    /// Replaces the cells with the ones of the notebook item, e.g. after reloading it.
+    fn load_cells(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let cells = self.notebook_item.read(cx).notebook.cells.clone();
+        self.cell_order.clear();
+        self.cell_map.clear();
+        self._cell_subscriptions.clear();
+        for cell in &cells {
+            let cell = Cell::load(
+                cell,
+                &self.languages,
+                self.notebook_language.clone(),
+                window,
+                cx,
+            );
+            self.insert_cell_at(self.cell_order.len(), cell, window, cx);
+        }
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.selected_cell_index = 0;
+        self.cell_list.reset(self.cell_order.len());
+        cx.notify();
+    }
+
+    fn insert_cell_at(
+        &mut self,
+        index: usize,
+        cell: Cell,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let cell_id = cell.id(cx);
+        self.subscribe_to_cell(&cell, window, cx);
+        self.cell_order.insert(index, cell_id.clone());
+        self.cell_map.insert(cell_id, cell);
+    }
+
+    fn subscribe_to_cell(&mut self, cell: &Cell, window: &mut Window, cx: &mut Context<Self>) {
+        let cell_id = cell.id(cx);
+        let editor = cell.editor(cx);
+        self._cell_subscriptions.push(cx.subscribe_in(
+            &editor,
+            window,
+            move |this, _, event: &EditorEvent, window, cx| match event {
+                EditorEvent::BufferEdited => {
+                    cx.emit(NotebookEditorEvent::Edited);
+                    cx.notify();
+                }
+                EditorEvent::Focused => {
+                    if let Some(index) = this.cell_order.iter().position(|id| *id == cell_id) {
+                        this.set_selected_index(index, false, window, cx);
+                        cx.notify();
+                    }
+                }
+                _ => {}
+            },
+        ));
+    }
+
+    fn selected_cell(&self) -> Option<&Cell> {
+        self.cell_order
+            .get(self.selected_cell_index)
+            .and_then(|cell_id| self.cell_map.get(cell_id))
+    }
+
+    /// Records the current cell structure so that the next structural edit can be undone.
+    fn push_undo(&mut self, outputs: Vec<(Entity<CodeCell>, Vec<nbformat::v4::Output>)>) {
+        self.undo_stack.push(NotebookSnapshot {
+            cell_order: self.cell_order.clone(),
+            cell_map: self.cell_map.clone(),
+            selected_cell_index: self.selected_cell_index,
+            structure_version: self.structure_version,
+            outputs,
+        });
+        self.redo_stack.clear();
+    }
+
+    fn structure_changed(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.structure_version = self.next_structure_version;
+        self.next_structure_version += 1;
+        self.did_change_cells(window, cx);
+    }
+
+    fn did_change_cells(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.cell_list.reset(self.cell_order.len());
+        self.selected_cell_index = self
+            .selected_cell_index
+            .min(self.cell_order.len().saturating_sub(1));
+        self.jump_to_cell(self.selected_cell_index, window, cx);
+        cx.emit(NotebookEditorEvent::Edited);
+        cx.notify();
+    }
+
+    /// Restores a snapshot, returning the snapshot that reverts it.
+    fn restore(
+        &mut self,
+        snapshot: NotebookSnapshot,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> NotebookSnapshot {
+        let current_outputs = snapshot
+            .outputs
+            .iter()
+            .map(|(cell, _)| (cell.clone(), cell.read(cx).raw_outputs().to_vec()))
+            .collect();
+        let reverse = NotebookSnapshot {
+            cell_order: mem::replace(&mut self.cell_order, snapshot.cell_order),
+            cell_map: mem::replace(&mut self.cell_map, snapshot.cell_map),
+            selected_cell_index: mem::replace(
+                &mut self.selected_cell_index,
+                snapshot.selected_cell_index,
+            ),
+            structure_version: mem::replace(
+                &mut self.structure_version,
+                snapshot.structure_version,
+            ),
+            outputs: current_outputs,
+        };
+        for (cell, outputs) in snapshot.outputs {
+            cell.update(cx, |cell, cx| cell.set_outputs(outputs, window, cx));
+        }
+        self.did_change_cells(window, cx);
+        reverse
+    }
+
+    fn undo(&mut self, _: &editor::actions::Undo, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(snapshot) = self.undo_stack.pop() {
+            let reverse = self.restore(snapshot, window, cx);
+            self.redo_stack.push(reverse);
         }
     }
 
+    fn redo(&mut self, _: &editor::actions::Redo, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(snapshot) = self.redo_stack.pop() {
+            let reverse = self.restore(snapshot, window, cx);
+            self.undo_stack.push(reverse);
+        }
+    }
+
+    /// Builds the notebook to save from the current cells, keeping the notebook's metadata.
+    pub fn to_notebook(&self, cx: &App) -> nbformat::v4::Notebook {
+        let mut notebook = self.notebook_item.read(cx).notebook.clone();
+        notebook.cells = self
+            .cell_order
+            .iter()
+            .filter_map(|cell_id| self.cell_map.get(cell_id))
+            .map(|cell| cell.to_nbformat(cx))
+            .collect();
+        notebook
+    }
+
+    /// Writes the notebook to `abs_path`, and once that succeeds, moves the notebook to
+    /// `project_path` if one is given.
+    fn write_notebook(
+        &mut self,
+        abs_path: PathBuf,
+        project_path: Option<ProjectPath>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let notebook = self.to_notebook(cx);
+        let fs = self.project.read(cx).fs().clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let content = serialize_notebook(&notebook)?;
+            fs.atomic_write(abs_path.clone(), content).await?;
+            this.update(cx, |this, cx| {
+                this.notebook_item.update(cx, |item, _| {
+                    item.notebook = notebook;
+                    if let Some(project_path) = project_path {
+                        item.path = abs_path;
+                        item.project_path = project_path;
+                    }
+                });
+                this.saved_structure_version = this.structure_version;
+                for cell in this.cell_map.values() {
+                    cell.did_save(cx);
+                }
+                cx.emit(NotebookEditorEvent::Saved);
+                cx.notify();
+            })
+        })
+    }
+
     fn has_outputs(&self, window: &mut Window, cx: &mut Context<Self>) -> bool {
         self.cell_map.values().any(|cell| {
             if let Cell::Code(code_cell) = cell {
@@ -152,13 +364,23 @@ impl NotebookEditor {
     }
 
     fn clear_outputs(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let mut cleared_outputs = Vec::new();
         for cell in self.cell_map.values() {
-            if let Cell::Code(code_cell) = cell {
-                code_cell.update(cx, |cell, _cx| {
-                    cell.clear_outputs();
+            if let Cell::Code(code_cell) = cell
+                && code_cell.read(cx).has_outputs()
+            {
+                let outputs = code_cell.update(cx, |cell, cx| {
+                    cx.notify();
+                    cell.clear_outputs()
                 });
+                cleared_outputs.push((code_cell.clone(), outputs));
             }
         }
+        if cleared_outputs.is_empty() {
+            return;
+        }
+        self.push_undo(cleared_outputs);
+        self.structure_changed(window, cx);
     }
 
     fn run_cells(&mut self, window: &mut Window, cx: &mut Context<Self>) {
@@ -170,19 +392,106 @@ impl NotebookEditor {
     }
 
     fn move_cell_up(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Move cell up triggered");
+        let index = self.selected_cell_index;
+        if index == 0 || index >= self.cell_order.len() {
+            return;
+        }
+        self.push_undo(Vec::new());
+        self.cell_order.swap(index - 1, index);
+        self.selected_cell_index = index - 1;
+        self.structure_changed(window, cx);
     }
 
     fn move_cell_down(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Move cell down triggered");
+        let index = self.selected_cell_index;
+        if index + 1 >= self.cell_order.len() {
+            return;
+        }
+        self.push_undo(Vec::new());
+        self.cell_order.swap(index, index + 1);
+        self.selected_cell_index = index + 1;
+        self.structure_changed(window, cx);
     }
 
     fn add_markdown_block(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Add markdown block triggered");
+        self.add_cell(CellType::Markdown, window, cx);
     }
 
     fn add_code_block(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Add code block triggered");
+        self.add_cell(CellType::Code, window, cx);
+    }
+
+    /// Adds an empty cell below the current one and focuses it.
+    fn add_cell(&mut self, cell_type: CellType, window: &mut Window, cx: &mut Context<Self>) {
+        let cell = match Cell::new(
+            cell_type,
+            &self.languages,
+            self.notebook_language.clone(),
+            window,
+            cx,
+        ) {
+            Ok(cell) => cell,
+            Err(error) => {
+                log::error!("failed to create notebook cell: {error:#}");
+                return;
+            }
+        };
+        let editor = cell.editor(cx);
+        let index = if self.cell_order.is_empty() {
+            0
+        } else {
+            self.selected_cell_index + 1
+        };
+        self.push_undo(Vec::new());
+        self.insert_cell_at(index, cell, window, cx);
+        self.selected_cell_index = index;
+        self.structure_changed(window, cx);
+        window.focus(&editor.focus_handle(cx));
+    }
+
+    fn delete_cell(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.selected_cell_index >= self.cell_order.len() {
+            return;
+        }
+        self.push_undo(Vec::new());
+        let cell_id = self.cell_order.remove(self.selected_cell_index);
+        self.cell_map.remove(&cell_id);
+        self.structure_changed(window, cx);
+        window.focus(&self.focus_handle);
+    }
+
+    fn change_cell_type(
+        &mut self,
+        cell_type: CellType,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(cell) = self.selected_cell() else {
+            return;
+        };
+        if cell.cell_type() == cell_type {
+            return;
+        }
+        let cell = cell.convert_to(
+            cell_type,
+            &self.languages,
+            self.notebook_language.clone(),
+            window,
+            cx,
+        );
+        self.push_undo(Vec::new());
+        self.subscribe_to_cell(&cell, window, cx);
+        self.cell_map.insert(cell.id(cx), cell);
+        self.structure_changed(window, cx);
+    }
+
+    fn toggle_markdown_preview(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(Cell::Markdown(cell)) = self.selected_cell() {
+            cell.update(cx, |cell, cx| cell.toggle_preview(window, cx));
+            if !cell.read(cx).editing() {
+                window.focus(&self.focus_handle);
+            }
+        }
     }
 
     fn cell_count(&self) -> usize {
@@ -382,6 +691,20 @@ impl NotebookEditor {
                                 .on_click(|_, window, cx| {
                                     window.dispatch_action(Box::new(MoveCellDown), cx);
                                 }),
+                            )
+                            .child(
+                                Self::render_notebook_control(
+                                    "delete-cell",
+                                    IconName::Trash,
+                                    window,
+                                    cx,
+                                )
+                                .tooltip(move |window, cx| {
+                                    Tooltip::for_action("Delete cell", &DeleteCell, window, cx)
+                                })
+                                .on_click(|_, window, cx| {
+                                    window.dispatch_action(Box::new(DeleteCell), cx);
+                                }),
                             ),
                     )
                     .child(
@@ -505,6 +828,24 @@ impl Render for NotebookEditor {
             .on_action(
                 cx.listener(|this, &AddCodeBlock, window, cx| this.add_code_block(window, cx)),
             )
+            .on_action(cx.listener(|this, &AddRawBlock, window, cx| {
+                this.add_cell(CellType::Raw, window, cx)
+            }))
+            .on_action(cx.listener(|this, &DeleteCell, window, cx| this.delete_cell(window, cx)))
+            .on_action(cx.listener(|this, &ChangeToCodeCell, window, cx| {
+                this.change_cell_type(CellType::Code, window, cx)
+            }))
+            .on_action(cx.listener(|this, &ChangeToMarkdownCell, window, cx| {
+                this.change_cell_type(CellType::Markdown, window, cx)
+            }))
+            .on_action(cx.listener(|this, &ChangeToRawCell, window, cx| {
+                this.change_cell_type(CellType::Raw, window, cx)
+            }))
+            .on_action(cx.listener(|this, &ToggleMarkdownPreview, window, cx| {
+                this.toggle_markdown_preview(window, cx)
+            }))
+            .on_action(cx.listener(Self::undo))
+            .on_action(cx.listener(Self::redo))
             .on_action(cx.listener(Self::select_next))
             .on_action(cx.listener(Self::select_previous))
             .on_action(cx.listener(Self::select_first))
@@ -576,22 +917,7 @@ impl project::ProjectItem for NotebookItem {
 
                 // todo: watch for changes to the file
                 let file_content = fs.load(abs_path.as_path()).await?;
-                let notebook = nbformat::parse_notebook(&file_content);
-
-                let notebook = match notebook {
-                    Ok(nbformat::Notebook::V4(notebook)) => notebook,
-                    // 4.1 - 4.4 are converted to 4.5
-                    Ok(nbformat::Notebook::Legacy(legacy_notebook)) => {
-                        // TODO: Decide if we want to mutate the notebook by including Cell IDs
-                        // and any other conversions
-
-                        nbformat::upgrade_legacy_notebook(legacy_notebook)?
-                    }
-                    // Bad notebooks and notebooks v4.0 and below are not supported
-                    Err(e) => {
-                        anyhow::bail!("Failed to parse notebook: {:?}", e);
-                    }
-                };
+                let notebook = parse_notebook(&file_content)?;
 
                 let id = project
                     .update(cx, |project, cx| {
@@ -625,6 +951,32 @@ impl project::ProjectItem for NotebookItem {
     }
 }
 
+/// Parses an nbformat 4 notebook, upgrading 4.1 - 4.4 notebooks to 4.5.
+pub fn parse_notebook(content: &str) -> Result<nbformat::v4::Notebook> {
+    match nbformat::parse_notebook(content) {
+        Ok(nbformat::Notebook::V4(notebook)) => Ok(notebook),
+        // 4.1 - 4.4 are converted to 4.5, which adds cell ids
+        Ok(nbformat::Notebook::Legacy(legacy_notebook)) => {
+            Ok(nbformat::upgrade_legacy_notebook(legacy_notebook)?)
+        }
+        // Bad notebooks and notebooks v4.0 and below are not supported
+        Err(e) => {
+            anyhow::bail!("Failed to parse notebook: {:?}", e);
+        }
+    }
+}
+
+/// Serializes a notebook the way Jupyter does, with one space indentation and a
+/// trailing newline, so that saving an unchanged notebook produces a minimal diff.
+pub fn serialize_notebook(notebook: &nbformat::v4::Notebook) -> Result<String> {
+    let mut content = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut content, formatter);
+    notebook.serialize(&mut serializer)?;
+    content.push(b'\n');
+    Ok(String::from_utf8(content)?)
+}
+
 impl NotebookItem {
     pub fn language_name(&self) -> Option<String> {
         self.notebook
@@ -654,7 +1006,7 @@ impl NotebookItem {
     }
 }
 
-impl EventEmitter<()> for NotebookEditor {}
+impl EventEmitter<NotebookEditorEvent> for NotebookEditor {}
 
 // pub struct NotebookControls {
 //     pane_focused: bool,
@@ -702,7 +1054,17 @@ impl EventEmitter<()> for NotebookEditor {}
 // }
 
 impl Item for NotebookEditor {
-    type Event = ();
+    type Event = NotebookEditorEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        match event {
+            NotebookEditorEvent::Edited => {
+                f(ItemEvent::Edit);
+                f(ItemEvent::UpdateTab);
+            }
+            NotebookEditorEvent::Saved => f(ItemEvent::UpdateTab),
+        }
+    }
 
     fn clone_on_split(
         &self,
@@ -773,49 +1135,62 @@ impl Item for NotebookEditor {
         // TODO
     }
 
-    // TODO
     fn can_save(&self, _cx: &App) -> bool {
-        false
+        true
     }
-    // TODO
+
+    fn can_save_as(&self, _cx: &App) -> bool {
+        true
+    }
+
     fn save(
         &mut self,
         _options: SaveOptions,
         _project: Entity<Project>,
-        _window: &mut Window,
-        _cx: &mut Context<Self>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("save() must be implemented if can_save() returns true")
+        let abs_path = self.notebook_item.read(cx).path.clone();
+        self.write_notebook(abs_path, None, window, cx)
     }
 
-    // TODO
     fn save_as(
         &mut self,
-        _project: Entity<Project>,
-        _path: ProjectPath,
-        _window: &mut Window,
-        _cx: &mut Context<Self>,
+        project: Entity<Project>,
+        path: ProjectPath,
+        window: &mut Window,
+        cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("save_as() must be implemented if can_save() returns true")
+        let Some(abs_path) = project.read(cx).absolute_path(&path, cx) else {
+            return Task::ready(Err(anyhow!("failed to resolve the path of {path:?}")));
+        };
+        self.write_notebook(abs_path, Some(path), window, cx)
     }
-    // TODO
+
     fn reload(
         &mut self,
-        _project: Entity<Project>,
-        _window: &mut Window,
-        _cx: &mut Context<Self>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("reload() must be implemented if can_save() returns true")
+        let fs = project.read(cx).fs().clone();
+        let abs_path = self.notebook_item.read(cx).path.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let file_content = fs.load(&abs_path).await?;
+            let notebook = parse_notebook(&file_content)?;
+            this.update_in(cx, |this, window, cx| {
+                this.notebook_item
+                    .update(cx, |item, _| item.notebook = notebook);
+                this.load_cells(window, cx);
+                this.saved_structure_version = this.structure_version;
+                cx.emit(NotebookEditorEvent::Saved);
+            })
+        })
     }
 
     fn is_dirty(&self, cx: &App) -> bool {
-        self.cell_map.values().any(|cell| {
-            if let Cell::Code(code_cell) = cell {
-                code_cell.read(cx).is_dirty(cx)
-            } else {
-                false
-            }
-        })
+        self.structure_version != self.saved_structure_version
+            || self.cell_map.values().any(|cell| cell.is_dirty(cx))
     }
 }
 
@@ -838,3 +1213,168 @@ impl ProjectItem for NotebookEditor {
         Self::new(project, item, window, cx)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use indoc::indoc;
+    use project::{FakeFs, Fs as _};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::Path;
+    use util::path;
+
+    const NOTEBOOK: &str = indoc! {r##"
+        {
+         "cells": [
+          {
+           "cell_type": "markdown",
+           "id": "intro",
+           "metadata": {"tags": ["header"]},
+           "source": ["# Title\n", "\n", "Some text"]
+          },
+          {
+           "cell_type": "code",
+           "execution_count": 3,
+           "id": "compute",
+           "metadata": {"collapsed": false},
+           "outputs": [
+            {
+             "name": "stdout",
+             "output_type": "stream",
+             "text": ["42\n"]
+            }
+           ],
+           "source": ["print(6 * 7)"]
+          }
+         ],
+         "metadata": {
+          "custom_tool": {"setting": true},
+          "kernelspec": {"display_name": "Python 3", "language": "python", "name": "python3"}
+         },
+         "nbformat": 4,
+         "nbformat_minor": 5
+        }
+    "##};
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            workspace::init_settings(cx);
+            editor::init(cx);
+        });
+    }
+
+    #[test]
+    fn test_notebook_round_trip() {
+        let notebook = parse_notebook(NOTEBOOK).unwrap();
+        let serialized = serialize_notebook(&notebook).unwrap();
+        assert!(serialized.starts_with("{\n \""));
+        assert!(serialized.ends_with("}\n"));
+
+        let value: serde_json::Value = serde_json::from_str(&serialized).unwrap();
+        let reparsed = parse_notebook(&serialized).unwrap();
+        let reserialized: serde_json::Value =
+            serde_json::from_str(&serialize_notebook(&reparsed).unwrap()).unwrap();
+        assert_eq!(reserialized, value);
+
+        assert_eq!(
+            value["metadata"]["custom_tool"],
+            serde_json::json!({"setting": true})
+        );
+        assert_eq!(
+            value["cells"][0]["metadata"]["tags"],
+            serde_json::json!(["header"])
+        );
+        assert_eq!(
+            value["cells"][0]["source"],
+            serde_json::json!(["# Title\n", "\n", "Some text"])
+        );
+        assert_eq!(value["cells"][1]["execution_count"], 3);
+        assert_eq!(
+            value["cells"][1]["outputs"][0]["text"],
+            serde_json::json!(["42\n"])
+        );
+    }
+
+    #[gpui::test]
+    async fn test_notebook_editor_round_trip(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/dir"), json!({ "notebook.ipynb": NOTEBOOK }))
+            .await;
+        let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+
+        let notebook_item = cx
+            .update(|cx| {
+                <NotebookItem as project::ProjectItem>::try_open(
+                    &project,
+                    &ProjectPath {
+                        worktree_id,
+                        path: Path::new("notebook.ipynb").into(),
+                    },
+                    cx,
+                )
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        let (notebook_editor, cx) = cx.add_window_view(|window, cx| {
+            NotebookEditor::new(project.clone(), notebook_item.clone(), window, cx)
+        });
+        cx.run_until_parked();
+
+        notebook_editor
+            .update_in(cx, |notebook_editor, window, cx| {
+                notebook_editor.save_as(
+                    project.clone(),
+                    ProjectPath {
+                        worktree_id,
+                        path: Path::new("copy.ipynb").into(),
+                    },
+                    window,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        notebook_item.read_with(cx, |notebook_item, _| {
+            assert_eq!(notebook_item.path, Path::new(path!("/dir/copy.ipynb")));
+            assert_eq!(
+                notebook_item.project_path.path.as_ref(),
+                Path::new("copy.ipynb")
+            );
+        });
+
+        let saved = fs.load(path!("/dir/copy.ipynb").as_ref()).await.unwrap();
+        let saved: serde_json::Value = serde_json::from_str(&saved).unwrap();
+        let original: serde_json::Value =
+            serde_json::from_str(&serialize_notebook(&parse_notebook(NOTEBOOK).unwrap()).unwrap())
+                .unwrap();
+        assert_eq!(saved, original);
+        assert_eq!(saved["nbformat"], 4);
+        assert_eq!(saved["nbformat_minor"], 5);
+        assert_eq!(
+            saved["metadata"]["custom_tool"],
+            serde_json::json!({"setting": true})
+        );
+        assert_eq!(
+            saved["cells"][0]["metadata"]["tags"],
+            serde_json::json!(["header"])
+        );
+        assert_eq!(saved["cells"][1]["metadata"]["collapsed"], false);
+        assert_eq!(saved["cells"][1]["execution_count"], 3);
+        assert_eq!(
+            saved["cells"][1]["outputs"][0]["text"],
+            serde_json::json!(["42\n"])
+        );
+    }
+}
@@ -2,7 +2,7 @@ use bitflags::bitflags;
 pub use buffer_search::BufferSearchBar;
 use editor::SearchSettings;
 use gpui::{Action, App, ClickEvent, FocusHandle, IntoElement, actions};
-use project::search::SearchQuery;
+use project::search::{SearchQuery, SyntaxScope};
 pub use project_search::ProjectSearchView;
 use ui::{ButtonStyle, IconButton, IconButtonShape};
 use ui::{Tooltip, prelude::*};
@@ -37,6 +37,14 @@ actions!(
         ToggleIncludeIgnored,
         /// Toggles regular expression mode.
         ToggleRegex,
+        /// Toggles matching inside of comments.
+        ToggleInComments,
+        /// Toggles matching inside of string literals.
+        ToggleInStrings,
+        /// Toggles matching outside of comments and string literals.
+        ToggleInCode,
+        /// Toggles structural search mode, which matches code against templates.
+        ToggleStructural,
         /// Toggles the replace interface.
         ToggleReplace,
         /// Toggles searching within selection only.
@@ -62,7 +70,7 @@ actions!(
 
 bitflags! {
     #[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
-    pub struct SearchOptions: u8 {
+    pub struct SearchOptions: u16 {
         const NONE = 0;
         const WHOLE_WORD = 1 << SearchOption::WholeWord as u8;
         const CASE_SENSITIVE = 1 << SearchOption::CaseSensitive as u8;
@@ -71,6 +79,10 @@ bitflags! {
         const ONE_MATCH_PER_LINE = 1 << SearchOption::OneMatchPerLine as u8;
         /// If set, reverse direction when finding the active match
         const BACKWARDS = 1 << SearchOption::Backwards as u8;
+        const IN_COMMENTS = 1 << SearchOption::InComments as u8;
+        const IN_STRINGS = 1 << SearchOption::InStrings as u8;
+        const IN_CODE = 1 << SearchOption::InCode as u8;
+        const STRUCTURAL = 1 << SearchOption::Structural as u8;
     }
 }
 
@@ -83,6 +95,10 @@ pub enum SearchOption {
     Regex,
     OneMatchPerLine,
     Backwards,
+    InComments,
+    InStrings,
+    InCode,
+    Structural,
 }
 
 pub(crate) enum SearchSource<'a, 'b> {
@@ -103,6 +119,10 @@ impl SearchOption {
             SearchOption::Regex => "Use Regular Expressions",
             SearchOption::OneMatchPerLine => "One Match Per Line",
             SearchOption::Backwards => "Search Backwards",
+            SearchOption::InComments => "Match Inside Comments",
+            SearchOption::InStrings => "Match Inside Strings",
+            SearchOption::InCode => "Match Outside Comments and Strings",
+            SearchOption::Structural => "Use Structural Templates",
         }
     }
 
@@ -112,6 +132,10 @@ impl SearchOption {
             SearchOption::CaseSensitive => ui::IconName::CaseSensitive,
             SearchOption::IncludeIgnored => ui::IconName::Sliders,
             SearchOption::Regex => ui::IconName::Regex,
+            SearchOption::InComments => ui::IconName::Chat,
+            SearchOption::InStrings => ui::IconName::Quote,
+            SearchOption::InCode => ui::IconName::Code,
+            SearchOption::Structural => ui::IconName::ListTree,
             _ => panic!("{self:?} is not a named SearchOption"),
         }
     }
@@ -122,6 +146,10 @@ impl SearchOption {
             SearchOption::CaseSensitive => &ToggleCaseSensitive,
             SearchOption::IncludeIgnored => &ToggleIncludeIgnored,
             SearchOption::Regex => &ToggleRegex,
+            SearchOption::InComments => &ToggleInComments,
+            SearchOption::InStrings => &ToggleInStrings,
+            SearchOption::InCode => &ToggleInCode,
+            SearchOption::Structural => &ToggleStructural,
             _ => panic!("{self:?} is not a toggle action"),
         }
     }
@@ -175,9 +203,38 @@ impl SearchOptions {
         options.set(SearchOptions::CASE_SENSITIVE, query.case_sensitive());
         options.set(SearchOptions::INCLUDE_IGNORED, query.include_ignored());
         options.set(SearchOptions::REGEX, query.is_regex());
+        options.set(SearchOptions::STRUCTURAL, query.is_structural());
+        let syntax_scope = query.syntax_scope();
+        options.set(
+            SearchOptions::IN_COMMENTS,
+            syntax_scope.contains(SyntaxScope::COMMENTS),
+        );
+        options.set(
+            SearchOptions::IN_STRINGS,
+            syntax_scope.contains(SyntaxScope::STRINGS),
+        );
+        options.set(
+            SearchOptions::IN_CODE,
+            syntax_scope.contains(SyntaxScope::CODE),
+        );
         options
     }
 
+    /// The parts of the syntax that matches are restricted to.
+    pub fn syntax_scope(&self) -> SyntaxScope {
+        let mut syntax_scope = SyntaxScope::empty();
+        syntax_scope.set(
+            SyntaxScope::COMMENTS,
+            self.contains(SearchOptions::IN_COMMENTS),
+        );
+        syntax_scope.set(
+            SyntaxScope::STRINGS,
+            self.contains(SearchOptions::IN_STRINGS),
+        );
+        syntax_scope.set(SyntaxScope::CODE, self.contains(SearchOptions::IN_CODE));
+        syntax_scope
+    }
+
     pub fn from_settings(settings: &SearchSettings) -> SearchOptions {
         let mut options = SearchOptions::NONE;
         options.set(SearchOptions::WHOLE_WORD, settings.whole_word);
@@ -0,0 +1,547 @@
+//! Problem matchers, used to extract compiler errors and similar problems from the output of tasks.
+
+use std::{borrow::Cow, path::PathBuf};
+
+use anyhow::Context as _;
+use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::ResultExt as _;
+
+/// A way to extract problems from a task's output, either a built-in one or a custom set of patterns.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemMatcher {
+    /// One of the matchers for the common tools.
+    BuiltIn(BuiltInProblemMatcher),
+    /// A matcher with custom patterns.
+    Custom(CustomProblemMatcher),
+}
+
+/// Problem matchers for the common tools.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BuiltInProblemMatcher {
+    /// Errors and warnings reported by `rustc` and `cargo`.
+    #[serde(alias = "$rustc", alias = "cargo")]
+    Rustc,
+    /// Errors reported by the TypeScript compiler.
+    #[serde(alias = "$tsc")]
+    Tsc,
+    /// Errors and warnings reported by `gcc` and `clang`.
+    #[serde(alias = "$gcc")]
+    Gcc,
+    /// Problems reported by ESLint with its default `stylish` formatter.
+    #[serde(alias = "$eslint-stylish")]
+    Eslint,
+}
+
+/// A problem matcher defined by regular expressions.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CustomProblemMatcher {
+    /// Patterns to match against consecutive lines of the output, a problem is reported when all of them match.
+    /// Problems spanning a single line need a single pattern.
+    pub patterns: Vec<ProblemPattern>,
+    /// Severity of the problems for which the patterns do not capture one.
+    #[serde(default)]
+    pub severity: ProblemSeverity,
+}
+
+/// A regular expression to match an output line with, along with the indices of its capture groups
+/// that contain the problem's details.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemPattern {
+    /// The regular expression to match the line with.
+    pub regexp: String,
+    /// The group with the path of the file, relative to the task's working directory or absolute.
+    #[serde(default)]
+    pub file: Option<usize>,
+    /// The group with the 1-based line of the problem.
+    #[serde(default)]
+    pub line: Option<usize>,
+    /// The group with the 1-based column of the problem.
+    #[serde(default)]
+    pub column: Option<usize>,
+    /// The group with the 1-based line the problem ends at.
+    #[serde(default)]
+    pub end_line: Option<usize>,
+    /// The group with the 1-based column the problem ends at.
+    #[serde(default)]
+    pub end_column: Option<usize>,
+    /// The group with the severity of the problem, e.g. `error` or `warning`.
+    #[serde(default)]
+    pub severity: Option<usize>,
+    /// The group with the code of the problem.
+    #[serde(default)]
+    pub code: Option<usize>,
+    /// The group with the message of the problem.
+    #[serde(default)]
+    pub message: Option<usize>,
+    /// Whether the last pattern keeps matching the following lines, reporting a problem for each of them,
+    /// e.g. for tools that list all problems of a file under its path.
+    #[serde(default)]
+    pub r#loop: bool,
+}
+
+/// Severity of a problem reported by a task.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemSeverity {
+    #[default]
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+impl ProblemSeverity {
+    fn parse(severity: &str) -> Option<Self> {
+        let severity = severity.trim().to_lowercase();
+        if severity.starts_with("err") || severity == "fatal" {
+            Some(Self::Error)
+        } else if severity.starts_with("warn") {
+            Some(Self::Warning)
+        } else if severity.starts_with("info") || severity == "note" || severity == "help" {
+            Some(Self::Info)
+        } else if severity == "hint" {
+            Some(Self::Hint)
+        } else {
+            None
+        }
+    }
+}
+
+/// A problem found in a task's output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    /// Path of the file, as printed by the task.
+    pub path: PathBuf,
+    /// 1-based line of the problem.
+    pub line: u32,
+    /// 1-based column of the problem, if known.
+    pub column: Option<u32>,
+    /// 1-based line the problem ends at, if known.
+    pub end_line: Option<u32>,
+    /// 1-based column the problem ends at, if known.
+    pub end_column: Option<u32>,
+    pub severity: ProblemSeverity,
+    pub code: Option<String>,
+    pub message: String,
+}
+
+impl ProblemMatcher {
+    fn definition(&self) -> Cow<'_, CustomProblemMatcher> {
+        match self {
+            Self::BuiltIn(built_in) => Cow::Owned(built_in.definition()),
+            Self::Custom(custom) => Cow::Borrowed(custom),
+        }
+    }
+
+    /// Finds all problems in the given task output.
+    /// Fails if any of the matcher's patterns is not a valid regular expression.
+    pub fn match_output(&self, output: &str) -> anyhow::Result<Vec<Problem>> {
+        let definition = self.definition();
+        let patterns = definition
+            .patterns
+            .iter()
+            .map(|pattern| {
+                let regex = Regex::new(&pattern.regexp).with_context(|| {
+                    format!("invalid problem matcher pattern {:?}", pattern.regexp)
+                })?;
+                anyhow::Ok((regex, pattern))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let Some(last_pattern_ix) = patterns.len().checked_sub(1) else {
+            return Ok(Vec::new());
+        };
+
+        let mut problems = Vec::new();
+        let mut matched = MatchedProblem::default();
+        let mut next_pattern_ix = 0;
+        for line in output.lines() {
+            if next_pattern_ix > 0 {
+                let (regex, pattern) = &patterns[next_pattern_ix];
+                if let Some(captures) = regex.captures(line) {
+                    let mut problem = matched.clone();
+                    problem.extend(pattern, &captures);
+                    if next_pattern_ix < last_pattern_ix {
+                        matched = problem;
+                        next_pattern_ix += 1;
+                    } else {
+                        problems.extend(problem.finish(definition.severity));
+                        // Looping patterns keep the details matched by the previous patterns.
+                        if !pattern.r#loop {
+                            matched = MatchedProblem::default();
+                            next_pattern_ix = 0;
+                        }
+                    }
+                    continue;
+                }
+                matched = MatchedProblem::default();
+                next_pattern_ix = 0;
+            }
+
+            let (regex, pattern) = &patterns[0];
+            if let Some(captures) = regex.captures(line) {
+                let mut problem = MatchedProblem::default();
+                problem.extend(pattern, &captures);
+                if last_pattern_ix == 0 {
+                    problems.extend(problem.finish(definition.severity));
+                } else {
+                    matched = problem;
+                    next_pattern_ix = 1;
+                }
+            }
+        }
+        Ok(problems)
+    }
+}
+
+/// Finds the problems in the given task output with all matchers given, skipping the invalid ones.
+pub fn match_problems(problem_matchers: &[ProblemMatcher], output: &str) -> Vec<Problem> {
+    problem_matchers
+        .iter()
+        .filter_map(|problem_matcher| problem_matcher.match_output(output).log_err())
+        .flatten()
+        .collect()
+}
+
+impl BuiltInProblemMatcher {
+    fn definition(self) -> CustomProblemMatcher {
+        let patterns = match self {
+            Self::Rustc => vec![
+                ProblemPattern {
+                    regexp: r"^(warning|error)(?:\[(\w+)\])?: (.*)$".to_string(),
+                    severity: Some(1),
+                    code: Some(2),
+                    message: Some(3),
+                    ..ProblemPattern::default()
+                },
+                ProblemPattern {
+                    regexp: r"^\s*--> (.*?):(\d+):(\d+)$".to_string(),
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    ..ProblemPattern::default()
+                },
+            ],
+            Self::Tsc => vec![ProblemPattern {
+                regexp: r"^([^\s].*)[\(:](\d+)[,:](\d+)(?:\):\s+|\s+-\s+)(error|warning|info)\s+(TS\d+)\s*:\s*(.*)$"
+                    .to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                code: Some(5),
+                message: Some(6),
+                ..ProblemPattern::default()
+            }],
+            Self::Gcc => vec![ProblemPattern {
+                regexp: r"^(.*?):(\d+):(\d*):?\s+(?:fatal\s+)?(warning|error):\s+(.*)$".to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                message: Some(5),
+                ..ProblemPattern::default()
+            }],
+            Self::Eslint => vec![
+                ProblemPattern {
+                    regexp: r"^([^\s].*)$".to_string(),
+                    file: Some(1),
+                    ..ProblemPattern::default()
+                },
+                ProblemPattern {
+                    regexp: r"^\s+(\d+):(\d+)\s+(error|warning|info)\s+(.*?)(?:\s\s+(\S+))?$"
+                        .to_string(),
+                    line: Some(1),
+                    column: Some(2),
+                    severity: Some(3),
+                    message: Some(4),
+                    code: Some(5),
+                    r#loop: true,
+                    ..ProblemPattern::default()
+                },
+            ],
+        };
+        CustomProblemMatcher {
+            patterns,
+            severity: ProblemSeverity::Error,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct MatchedProblem {
+    path: Option<String>,
+    line: Option<u32>,
+    column: Option<u32>,
+    end_line: Option<u32>,
+    end_column: Option<u32>,
+    severity: Option<ProblemSeverity>,
+    code: Option<String>,
+    message: Option<String>,
+}
+
+impl MatchedProblem {
+    fn extend(&mut self, pattern: &ProblemPattern, captures: &Captures) {
+        let group = |ix: Option<usize>| {
+            captures
+                .get(ix?)
+                .map(|group| group.as_str().trim())
+                .filter(|group| !group.is_empty())
+        };
+        let number = |ix: Option<usize>| group(ix)?.parse::<u32>().ok();
+
+        if let Some(path) = group(pattern.file) {
+            self.path = Some(path.to_string());
+        }
+        if let Some(line) = number(pattern.line) {
+            self.line = Some(line);
+        }
+        if let Some(column) = number(pattern.column) {
+            self.column = Some(column);
+        }
+        if let Some(end_line) = number(pattern.end_line) {
+            self.end_line = Some(end_line);
+        }
+        if let Some(end_column) = number(pattern.end_column) {
+            self.end_column = Some(end_column);
+        }
+        if let Some(severity) = group(pattern.severity).and_then(ProblemSeverity::parse) {
+            self.severity = Some(severity);
+        }
+        if let Some(code) = group(pattern.code) {
+            self.code = Some(code.to_string());
+        }
+        if let Some(message) = group(pattern.message) {
+            self.message = Some(message.to_string());
+        }
+    }
+
+    fn finish(self, default_severity: ProblemSeverity) -> Option<Problem> {
+        Some(Problem {
+            path: PathBuf::from(self.path?),
+            line: self.line.unwrap_or(1),
+            column: self.column,
+            end_line: self.end_line,
+            end_column: self.end_column,
+            severity: self.severity.unwrap_or(default_severity),
+            code: self.code,
+            message: self.message?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn problem(
+        path: &str,
+        line: u32,
+        column: u32,
+        severity: ProblemSeverity,
+        code: Option<&str>,
+        message: &str,
+    ) -> Problem {
+        Problem {
+            path: PathBuf::from(path),
+            line,
+            column: Some(column),
+            end_line: None,
+            end_column: None,
+            severity,
+            code: code.map(ToString::to_string),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_rustc_problems() {
+        let output = r#"
+   Compiling example v0.1.0 (/work/example)
+warning: unused variable: `x`
+ --> src/main.rs:2:9
+  |
+2 |     let x = 5;
+  |         ^ help: if this is intentional, prefix it with an underscore: `_x`
+  |
+error[E0425]: cannot find value `y` in this scope
+  --> src/lib.rs:10:5
+   |
+10 |     y
+   |     ^ not found in this scope
+warning: `example` (bin "example") generated 1 warning
+error: could not compile `example` (bin "example") due to 1 previous error
+"#;
+        assert_eq!(
+            ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Rustc)
+                .match_output(output)
+                .unwrap(),
+            vec![
+                problem(
+                    "src/main.rs",
+                    2,
+                    9,
+                    ProblemSeverity::Warning,
+                    None,
+                    "unused variable: `x`"
+                ),
+                problem(
+                    "src/lib.rs",
+                    10,
+                    5,
+                    ProblemSeverity::Error,
+                    Some("E0425"),
+                    "cannot find value `y` in this scope"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tsc_and_gcc_problems() {
+        let output = "src/index.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.\n\
+            src/app.ts:12:1 - error TS1005: ';' expected.\n";
+        assert_eq!(
+            ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc)
+                .match_output(output)
+                .unwrap(),
+            vec![
+                problem(
+                    "src/index.ts",
+                    3,
+                    7,
+                    ProblemSeverity::Error,
+                    Some("TS2322"),
+                    "Type 'string' is not assignable to type 'number'."
+                ),
+                problem(
+                    "src/app.ts",
+                    12,
+                    1,
+                    ProblemSeverity::Error,
+                    Some("TS1005"),
+                    "';' expected."
+                ),
+            ]
+        );
+
+        let output = "main.c: In function 'main':\n\
+            main.c:4:5: warning: implicit declaration of function 'foo'\n\
+            main.c:6:12: fatal error: bar.h: No such file or directory\n";
+        assert_eq!(
+            ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Gcc)
+                .match_output(output)
+                .unwrap(),
+            vec![
+                problem(
+                    "main.c",
+                    4,
+                    5,
+                    ProblemSeverity::Warning,
+                    None,
+                    "implicit declaration of function 'foo'"
+                ),
+                problem(
+                    "main.c",
+                    6,
+                    12,
+                    ProblemSeverity::Error,
+                    None,
+                    "bar.h: No such file or directory"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_looping_eslint_problems() {
+        let output = r#"
+/work/src/a.js
+  1:10  error    'foo' is defined but never used  no-unused-vars
+  3:1   warning  Unexpected console statement     no-console
+
+/work/src/b.js
+  7:3  error  Missing semicolon  semi
+
+✖ 3 problems (2 errors, 1 warning)
+"#;
+        assert_eq!(
+            ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Eslint)
+                .match_output(output)
+                .unwrap(),
+            vec![
+                problem(
+                    "/work/src/a.js",
+                    1,
+                    10,
+                    ProblemSeverity::Error,
+                    Some("no-unused-vars"),
+                    "'foo' is defined but never used"
+                ),
+                problem(
+                    "/work/src/a.js",
+                    3,
+                    1,
+                    ProblemSeverity::Warning,
+                    Some("no-console"),
+                    "Unexpected console statement"
+                ),
+                problem(
+                    "/work/src/b.js",
+                    7,
+                    3,
+                    ProblemSeverity::Error,
+                    Some("semi"),
+                    "Missing semicolon"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_custom_problem_matchers() {
+        let problem_matchers: Vec<ProblemMatcher> = serde_json::from_value(serde_json::json!([
+            "$gcc",
+            {
+                "patterns": [{
+                    "regexp": "^LINT (\\S+) (\\d+)-(\\d+): (.*)$",
+                    "file": 1,
+                    "line": 2,
+                    "end_line": 3,
+                    "message": 4,
+                }],
+                "severity": "warning",
+            },
+        ]))
+        .unwrap();
+        assert_eq!(
+            problem_matchers[0],
+            ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Gcc)
+        );
+
+        let output = "LINT docs/README.md 3-5: Line is too long\nmain.c:1:1: error: oops\n";
+        assert_eq!(
+            match_problems(&problem_matchers, output),
+            vec![
+                problem("main.c", 1, 1, ProblemSeverity::Error, None, "oops"),
+                Problem {
+                    path: PathBuf::from("docs/README.md"),
+                    line: 3,
+                    column: None,
+                    end_line: Some(5),
+                    end_column: None,
+                    severity: ProblemSeverity::Warning,
+                    code: None,
+                    message: "Line is too long".to_string(),
+                },
+            ]
+        );
+    }
+}
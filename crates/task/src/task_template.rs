@@ -9,8 +9,8 @@ use util::serde::default_true;
 use util::{ResultExt, truncate_and_remove_front};
 
 use crate::{
-    AttachRequest, ResolvedTask, RevealTarget, Shell, SpawnInTerminal, TaskContext, TaskId,
-    VariableName, ZED_VARIABLE_NAME_PREFIX, serde_helpers::non_empty_string_vec,
+    AttachRequest, ProblemMatcher, ResolvedTask, RevealTarget, Shell, SpawnInTerminal, TaskContext,
+    TaskId, VariableName, ZED_VARIABLE_NAME_PREFIX, serde_helpers::non_empty_string_vec,
 };
 
 /// A template definition of a Zed task to run.
@@ -25,6 +25,7 @@ pub struct TaskTemplate {
     /// Human readable name of the task to display in the UI.
     pub label: String,
     /// Executable command to spawn.
+    #[serde(default)]
     pub command: String,
     /// Arguments to the command.
     #[serde(default)]
@@ -72,6 +73,78 @@ pub struct TaskTemplate {
     /// Whether to show the command line in the task output.
     #[serde(default = "default_true")]
     pub show_command: bool,
+    /// Labels of the tasks to run before this one.
+    /// The task is not run if any of them fails, and may omit `command` to only run its dependencies.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// How to run the tasks from `depends_on`:
+    /// * `sequence` — one after another, in the order they are listed (default)
+    /// * `parallel` — all at once
+    #[serde(default)]
+    pub depends_order: DependsOrder,
+    /// Problem matchers to report the problems from the task output as diagnostics with,
+    /// either built-in ones (`rustc`, `tsc`, `gcc` or `eslint`) or custom patterns.
+    #[serde(default)]
+    pub problem_matcher: Vec<ProblemMatcher>,
+    /// Values to ask for before spawning the task, available in the task as `ZED_INPUT_<id>` variables.
+    #[serde(default)]
+    pub inputs: Vec<TaskInput>,
+    /// Files to watch, rerunning the task in its terminal when any of them changes.
+    #[serde(default)]
+    pub watch: Option<TaskWatch>,
+}
+
+/// Files to rerun a task on changes of.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TaskWatch {
+    /// Glob patterns of the files to watch, relative to the worktree root.
+    pub patterns: Vec<String>,
+    /// How long to wait for further changes before rerunning the task, in milliseconds.
+    #[serde(default = "default_watch_debounce_ms")]
+    pub debounce_ms: u64,
+}
+
+fn default_watch_debounce_ms() -> u64 {
+    200
+}
+
+/// A value to ask for before spawning a task.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TaskInput {
+    /// The id of the input, the value is available in the task as the `ZED_INPUT_<id>` variable.
+    pub id: String,
+    /// A description of the value to show when asking for it.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// The value to suggest, unless a value was entered for the task before.
+    #[serde(default)]
+    pub default: Option<String>,
+    /// How to ask for the value.
+    #[serde(flatten)]
+    pub kind: TaskInputKind,
+}
+
+/// How to ask for the value of a task's input.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TaskInputKind {
+    /// Ask to type in any value.
+    PromptString,
+    /// Ask to pick one of the options given.
+    PickString {
+        /// The options to pick from.
+        options: Vec<String>,
+    },
+    /// Ask to pick one of the lines that a shell command prints, run in the task's working directory.
+    Command {
+        /// The command to run.
+        command: String,
+        /// Arguments to the command.
+        #[serde(default)]
+        args: Vec<String>,
+    },
 }
 
 #[derive(Deserialize, Eq, PartialEq, Clone, Debug)]
@@ -109,6 +182,17 @@ pub enum HideStrategy {
     OnSuccess,
 }
 
+/// How to run the tasks a task depends on.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DependsOrder {
+    /// Run the dependencies one after another, stopping at the first failure.
+    #[default]
+    Sequence,
+    /// Run all dependencies at once.
+    Parallel,
+}
+
 /// A group of Tasks defined in a JSON file.
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct TaskTemplates(pub Vec<TaskTemplate>);
@@ -133,7 +217,7 @@ impl TaskTemplate {
     /// Every [`ResolvedTask`] gets a [`TaskId`], based on the `id_base` (to avoid collision with various task sources),
     /// and hashes of its template and [`TaskContext`], see [`ResolvedTask`] fields' documentation for more details.
     pub fn resolve_task(&self, id_base: &str, cx: &TaskContext) -> Option<ResolvedTask> {
-        if self.label.trim().is_empty() || self.command.trim().is_empty() {
+        if self.label.trim().is_empty() || (!self.has_command() && self.depends_on.is_empty()) {
             return None;
         }
 
@@ -245,6 +329,7 @@ impl TaskTemplate {
             id: id.clone(),
             substituted_variables,
             original_task: self.clone(),
+            task_context: cx.clone(),
             resolved_label: full_label.clone(),
             resolved: SpawnInTerminal {
                 id,
@@ -271,9 +356,15 @@ impl TaskTemplate {
                 show_summary: self.show_summary,
                 show_command: self.show_command,
                 show_rerun: true,
+                problem_matcher: self.problem_matcher.clone(),
             },
         })
     }
+
+    /// Whether the task spawns a command of its own, rather than only running its dependencies.
+    pub fn has_command(&self) -> bool {
+        !self.command.trim().is_empty()
+    }
 }
 
 const MAX_DISPLAY_VARIABLE_LENGTH: usize = 15;
@@ -335,6 +426,9 @@ fn substitute_all_template_variables_in_str<A: AsRef<str>>(
             }
             // Got a task variable hit - use the variable value, ignore default
             return Ok(Some(name.as_ref().to_owned()));
+        } else if let Ok(VariableName::Input(_)) = variable_name.parse() {
+            // Inputs that have not been asked for yet, keep them verbatim to substitute later.
+            return Ok(Some(format!("${{{var}}}")));
         } else if variable_name.starts_with(ZED_VARIABLE_NAME_PREFIX) {
             // Unknown ZED variable - use default if available
             if !default.is_empty() {
@@ -469,6 +563,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_resolving_templates_with_dependencies_only() {
+        let task = TaskTemplate {
+            label: "build all".to_string(),
+            depends_on: vec!["build server".to_string(), "build client".to_string()],
+            depends_order: DependsOrder::Parallel,
+            ..TaskTemplate::default()
+        };
+        let resolved_task = task
+            .resolve_task(TEST_ID_BASE, &TaskContext::default())
+            .expect("should resolve a task that only runs its dependencies");
+        assert!(!resolved_task.original_task().has_command());
+        assert_eq!(resolved_task.resolved_label, "build all");
+
+        let task: TaskTemplate = serde_json::from_value(serde_json::json!({
+            "label": "build all",
+            "depends_on": ["build server", "build client"],
+            "depends_order": "parallel",
+        }))
+        .unwrap();
+        assert_eq!(task.depends_on, vec!["build server", "build client"]);
+        assert_eq!(task.depends_order, DependsOrder::Parallel);
+    }
+
     #[test]
     fn test_template_cwd_resolution() {
         let task_without_cwd = TaskTemplate {
@@ -734,6 +852,90 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_resolving_templates_with_inputs() {
+        let task = TaskTemplate {
+            label: "Deploy to ${ZED_INPUT_environment}".into(),
+            command: "deploy".into(),
+            args: vec!["${ZED_INPUT_environment}".into()],
+            inputs: vec![TaskInput {
+                id: "environment".into(),
+                description: None,
+                default: None,
+                kind: TaskInputKind::PickString {
+                    options: vec!["staging".into(), "production".into()],
+                },
+            }],
+            ..TaskTemplate::default()
+        };
+
+        let unanswered = task
+            .resolve_task(TEST_ID_BASE, &TaskContext::default())
+            .expect("Inputs that were not asked for yet should not prevent resolving the task");
+        assert_eq!(
+            unanswered.resolved.label,
+            "Deploy to ${ZED_INPUT_environment}"
+        );
+        assert_eq!(unanswered.resolved.args, vec!["${ZED_INPUT_environment}"]);
+        assert!(unanswered.has_unanswered_inputs());
+
+        let context = TaskContext {
+            task_variables: TaskVariables::from_iter([(
+                VariableName::Input("environment".into()),
+                "staging".to_string(),
+            )]),
+            ..TaskContext::default()
+        };
+        let answered = task.resolve_task(TEST_ID_BASE, &context).unwrap();
+        assert_eq!(answered.resolved.label, "Deploy to staging");
+        assert_eq!(answered.resolved.args, vec!["staging"]);
+        assert!(!answered.has_unanswered_inputs());
+        assert_substituted_variables(&answered, vec![VariableName::Input("environment".into())]);
+    }
+
+    #[test]
+    fn test_input_deserialization() {
+        let inputs: Vec<TaskInput> = serde_json::from_value(serde_json::json!([
+            { "id": "name", "type": "prompt_string", "default": "world" },
+            { "id": "target", "type": "pick_string", "options": ["debug", "release"] },
+            { "id": "branch", "type": "command", "command": "git", "args": ["branch"] },
+        ]))
+        .unwrap();
+        assert_eq!(
+            inputs
+                .into_iter()
+                .map(|input| input.kind)
+                .collect::<Vec<_>>(),
+            vec![
+                TaskInputKind::PromptString,
+                TaskInputKind::PickString {
+                    options: vec!["debug".into(), "release".into()],
+                },
+                TaskInputKind::Command {
+                    command: "git".into(),
+                    args: vec!["branch".into()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_watch_deserialization() {
+        let template: TaskTemplate = serde_json::from_value(serde_json::json!({
+            "label": "test",
+            "command": "cargo test",
+            "watch": { "patterns": ["src/**/*.rs", "Cargo.toml"] }
+        }))
+        .unwrap();
+        assert_eq!(
+            template.watch,
+            Some(TaskWatch {
+                patterns: vec!["src/**/*.rs".into(), "Cargo.toml".into()],
+                debounce_ms: 200,
+            })
+        );
+    }
+
     #[test]
     fn test_symbol_dependent_tasks() {
         let task_with_all_properties = TaskTemplate {
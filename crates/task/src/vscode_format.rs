@@ -1,9 +1,13 @@
 use anyhow::bail;
 use collections::HashMap;
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
 use util::ResultExt;
 
-use crate::{EnvVariableReplacer, TaskTemplate, TaskTemplates, VariableName};
+use crate::{
+    BuiltInProblemMatcher, CustomProblemMatcher, DependsOrder, EnvVariableReplacer, ProblemMatcher,
+    ProblemPattern, ProblemSeverity, TaskInput, TaskInputKind, TaskTemplate, TaskTemplates,
+    VariableName,
+};
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -19,6 +23,9 @@ struct VsCodeTaskDefinition {
     label: String,
     #[serde(flatten)]
     command: Option<Command>,
+    #[serde(default, deserialize_with = "task_references")]
+    depends_on: Vec<VsCodeTaskReference>,
+    depends_order: Option<DependsOrder>,
     #[serde(flatten)]
     other_attributes: HashMap<String, serde_json_lenient::Value>,
     options: Option<TaskOptions>,
@@ -41,44 +48,271 @@ enum Command {
     },
 }
 
+/// A task in `dependsOn`, referenced either by its label or by its definition, such as
+/// `{"type": "npm", "script": "build"}`.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeTaskReference {
+    Label(String),
+    Definition(Command),
+    Unsupported(serde_json_lenient::Value),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum VsCodeTaskReferences {
+    One(VsCodeTaskReference),
+    Many(Vec<VsCodeTaskReference>),
+}
+
+fn task_references<'de, D>(deserializer: D) -> Result<Vec<VsCodeTaskReference>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match VsCodeTaskReferences::deserialize(deserializer)? {
+        VsCodeTaskReferences::One(reference) => vec![reference],
+        VsCodeTaskReferences::Many(references) => references,
+    })
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeProblemMatchers {
+    One(VsCodeProblemMatcher),
+    Many(Vec<VsCodeProblemMatcher>),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeProblemMatcher {
+    Named(String),
+    #[serde(rename_all = "camelCase")]
+    Custom {
+        base: Option<String>,
+        severity: Option<ProblemSeverity>,
+        pattern: Option<VsCodeProblemPatterns>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeProblemPatterns {
+    One(VsCodeProblemPattern),
+    Many(Vec<VsCodeProblemPattern>),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeProblemPattern {
+    regexp: String,
+    file: Option<usize>,
+    line: Option<usize>,
+    column: Option<usize>,
+    end_line: Option<usize>,
+    end_column: Option<usize>,
+    severity: Option<usize>,
+    code: Option<usize>,
+    message: Option<usize>,
+    #[serde(default)]
+    r#loop: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeInput {
+    id: String,
+    #[serde(flatten)]
+    kind: VsCodeInputKind,
+    description: Option<String>,
+    default: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum VsCodeInputKind {
+    PromptString,
+    PickString { options: Vec<VsCodeInputOption> },
+    Command { command: String },
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeInputOption {
+    Value(String),
+    Labeled { value: String },
+}
+
+impl VsCodeInput {
+    fn into_zed_format(self) -> Option<TaskInput> {
+        let kind = match self.kind {
+            VsCodeInputKind::PromptString => TaskInputKind::PromptString,
+            VsCodeInputKind::PickString { options } => TaskInputKind::PickString {
+                options: options
+                    .into_iter()
+                    .map(|option| match option {
+                        VsCodeInputOption::Value(value) | VsCodeInputOption::Labeled { value } => {
+                            value
+                        }
+                    })
+                    .collect(),
+            },
+            // These run editor commands of VS Code, not shell ones.
+            VsCodeInputKind::Command { command } => {
+                log::warn!(
+                    "Skipping the input `{}` using the command `{command}`",
+                    self.id
+                );
+                return None;
+            }
+        };
+        Some(TaskInput {
+            id: self.id,
+            description: self.description,
+            default: self.default,
+            kind,
+        })
+    }
+}
+
+impl VsCodeProblemMatcher {
+    fn into_zed_format(self) -> Option<ProblemMatcher> {
+        let built_in = |name: &str| {
+            let built_in = serde_json::from_value::<BuiltInProblemMatcher>(name.into()).ok();
+            if built_in.is_none() {
+                log::warn!("Skipping the unsupported problem matcher `{name}`");
+            }
+            built_in.map(ProblemMatcher::BuiltIn)
+        };
+        match self {
+            Self::Named(name) => built_in(&name),
+            Self::Custom {
+                severity,
+                pattern: Some(patterns),
+                ..
+            } => {
+                let patterns = match patterns {
+                    VsCodeProblemPatterns::One(pattern) => vec![pattern],
+                    VsCodeProblemPatterns::Many(patterns) => patterns,
+                };
+                Some(ProblemMatcher::Custom(CustomProblemMatcher {
+                    patterns: patterns
+                        .into_iter()
+                        .map(|pattern| ProblemPattern {
+                            regexp: pattern.regexp,
+                            file: pattern.file,
+                            line: pattern.line,
+                            column: pattern.column,
+                            end_line: pattern.end_line,
+                            end_column: pattern.end_column,
+                            severity: pattern.severity,
+                            code: pattern.code,
+                            message: pattern.message,
+                            r#loop: pattern.r#loop,
+                        })
+                        .collect(),
+                    severity: severity.unwrap_or_default(),
+                }))
+            }
+            Self::Custom {
+                base: Some(base), ..
+            } => built_in(&base),
+            Self::Custom { .. } => None,
+        }
+    }
+}
+
 impl VsCodeTaskDefinition {
     fn into_zed_format(
         self,
         replacer: &EnvVariableReplacer,
+        inputs: &[TaskInput],
+        labels_by_definition: &[(Command, String)],
     ) -> anyhow::Result<Option<TaskTemplate>> {
-        if self.other_attributes.contains_key("dependsOn") {
-            log::warn!(
-                "Skipping deserializing of a task `{}` with the unsupported `dependsOn` key",
-                self.label
-            );
-            return Ok(None);
-        }
-        // `type` might not be set in e.g. tasks that use `dependsOn`; we still want to deserialize the whole object though (hence command is an Option),
-        // as that way we can provide more specific description of why deserialization failed.
-        // E.g. if the command is missing due to `dependsOn` presence, we can check other_attributes first before doing this (and provide nice error message)
-        // catch-all if on value.command presence.
-        let Some(command) = self.command else {
-            bail!("Missing `type` field in task");
-        };
-
-        let (command, args) = match command {
-            Command::Npm { script } => ("npm".to_owned(), vec!["run".to_string(), script]),
-            Command::Shell { command, args } => (command, args),
-            Command::Gulp { task } => ("gulp".to_owned(), vec![task]),
+        let depends_on = self
+            .depends_on
+            .into_iter()
+            .filter_map(|reference| match reference {
+                VsCodeTaskReference::Label(label) => Some(label),
+                VsCodeTaskReference::Definition(definition) => {
+                    let label = labels_by_definition
+                        .iter()
+                        .find(|(command, _)| command == &definition)
+                        .map(|(_, label)| label.clone());
+                    if label.is_none() {
+                        log::warn!(
+                            "Skipping the dependency {definition:?} of task `{}`, \
+                            which is not defined in the file",
+                            self.label
+                        );
+                    }
+                    label
+                }
+                VsCodeTaskReference::Unsupported(reference) => {
+                    log::warn!(
+                        "Skipping the unsupported dependency `{reference}` of task `{}`",
+                        self.label
+                    );
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        // `type` might not be set in tasks that only run their `dependsOn` tasks.
+        let (command, args) = match self.command {
+            Some(Command::Npm { script }) => ("npm".to_owned(), vec!["run".to_string(), script]),
+            Some(Command::Shell { command, args }) => (command, args),
+            Some(Command::Gulp { task }) => ("gulp".to_owned(), vec![task]),
+            None if !depends_on.is_empty() => (String::new(), Vec::new()),
+            None => bail!("Missing `type` field in task"),
         };
         // Per VSC docs, only `command`, `args` and `options` support variable substitution.
         let command = replacer.replace(&command);
         let args = args.into_iter().map(|arg| replacer.replace(&arg)).collect();
+        let problem_matcher = match self.other_attributes.get("problemMatcher") {
+            Some(problem_matcher) => {
+                match serde_json_lenient::from_value::<VsCodeProblemMatchers>(
+                    problem_matcher.clone(),
+                ) {
+                    Ok(VsCodeProblemMatchers::One(problem_matcher)) => vec![problem_matcher],
+                    Ok(VsCodeProblemMatchers::Many(problem_matchers)) => problem_matchers,
+                    Err(e) => {
+                        log::warn!(
+                            "Skipping the problem matchers of task `{}`: {e}",
+                            self.label
+                        );
+                        Vec::new()
+                    }
+                }
+            }
+            None => Vec::new(),
+        };
         let mut template = TaskTemplate {
             label: self.label,
             command,
             args,
+            depends_on,
+            // Unlike Zed, VS Code runs the dependencies in parallel by default.
+            depends_order: self.depends_order.unwrap_or(DependsOrder::Parallel),
+            problem_matcher: problem_matcher
+                .into_iter()
+                .filter_map(VsCodeProblemMatcher::into_zed_format)
+                .collect(),
             ..TaskTemplate::default()
         };
         if let Some(options) = self.options {
             template.cwd = options.cwd.map(|cwd| replacer.replace(&cwd));
             template.env = options.env;
         }
+        // Inputs are declared once per file, but only the ones a task uses should be asked for.
+        template.inputs = inputs
+            .iter()
+            .filter(|input| {
+                let variable = format!("${{{}}}", VariableName::Input(input.id.clone().into()));
+                template.command.contains(&variable)
+                    || template.args.iter().any(|arg| arg.contains(&variable))
+                    || template.cwd.iter().any(|cwd| cwd.contains(&variable))
+            })
+            .cloned()
+            .collect();
         Ok(Some(template))
     }
 }
@@ -87,6 +321,8 @@ impl VsCodeTaskDefinition {
 #[derive(Debug, Deserialize, PartialEq)]
 pub struct VsCodeTaskFile {
     tasks: Vec<VsCodeTaskDefinition>,
+    #[serde(default)]
+    inputs: Vec<VsCodeInput>,
 }
 
 impl TryFrom<VsCodeTaskFile> for TaskTemplates {
@@ -105,12 +341,22 @@ impl TryFrom<VsCodeTaskFile> for TaskTemplates {
                 VariableName::SelectedText.to_string(),
             ),
         ]));
+        let inputs = value
+            .inputs
+            .into_iter()
+            .filter_map(VsCodeInput::into_zed_format)
+            .collect::<Vec<_>>();
+        let labels_by_definition = value
+            .tasks
+            .iter()
+            .filter_map(|task| Some((task.command.clone()?, task.label.clone())))
+            .collect::<Vec<_>>();
         let templates = value
             .tasks
             .into_iter()
             .filter_map(|vscode_definition| {
                 vscode_definition
-                    .into_zed_format(&replacer)
+                    .into_zed_format(&replacer, &inputs, &labels_by_definition)
                     .log_err()
                     .flatten()
             })
@@ -124,7 +370,8 @@ mod tests {
     use std::collections::HashMap;
 
     use crate::{
-        TaskTemplate, TaskTemplates, VsCodeTaskFile,
+        BuiltInProblemMatcher, CustomProblemMatcher, DependsOrder, ProblemMatcher, ProblemPattern,
+        ProblemSeverity, TaskInput, TaskInputKind, TaskTemplate, TaskTemplates, VsCodeTaskFile,
         vscode_format::{Command, VsCodeTaskDefinition},
     };
 
@@ -182,6 +429,8 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                depends_on: Vec::new(),
+                depends_order: None,
             },
             VsCodeTaskDefinition {
                 label: "tsc: watch ./src".to_string(),
@@ -196,6 +445,8 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                depends_on: Vec::new(),
+                depends_order: None,
             },
             VsCodeTaskDefinition {
                 label: "npm: build:compiler".to_string(),
@@ -204,6 +455,8 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                depends_on: Vec::new(),
+                depends_order: None,
             },
             VsCodeTaskDefinition {
                 label: "npm: build:tests".to_string(),
@@ -212,6 +465,8 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                depends_on: Vec::new(),
+                depends_order: None,
             },
         ];
 
@@ -227,6 +482,7 @@ mod tests {
                 label: "gulp: tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matcher: vec![ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc)],
                 ..Default::default()
             },
             TaskTemplate {
@@ -244,12 +500,14 @@ mod tests {
                 label: "npm: build:compiler".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:compiler".to_string()],
+                problem_matcher: vec![ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc)],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matcher: vec![ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc)],
                 ..Default::default()
             },
         ];
@@ -270,6 +528,8 @@ mod tests {
                     script: "watch".to_string(),
                 }),
                 options: None,
+                depends_on: Vec::new(),
+                depends_order: None,
                 other_attributes: Default::default(),
             },
             VsCodeTaskDefinition {
@@ -278,6 +538,8 @@ mod tests {
                     script: "build".to_string(),
                 }),
                 options: None,
+                depends_on: Vec::new(),
+                depends_order: None,
                 other_attributes: Default::default(),
             },
             VsCodeTaskDefinition {
@@ -287,6 +549,8 @@ mod tests {
                     args: Default::default(),
                 }),
                 options: None,
+                depends_on: Vec::new(),
+                depends_order: None,
                 other_attributes: Default::default(),
             },
             VsCodeTaskDefinition {
@@ -296,6 +560,8 @@ mod tests {
                     args: Default::default(),
                 }),
                 options: None,
+                depends_on: Vec::new(),
+                depends_order: None,
                 other_attributes: Default::default(),
             },
             VsCodeTaskDefinition {
@@ -304,18 +570,30 @@ mod tests {
                     script: "pretest".to_string(),
                 }),
                 options: None,
+                depends_on: Vec::new(),
+                depends_order: None,
                 other_attributes: Default::default(),
             },
             VsCodeTaskDefinition {
                 label: "Build Server and Extension".to_string(),
                 command: None,
                 options: None,
+                depends_on: vec![
+                    VsCodeTaskReference::Label("Build Server".to_string()),
+                    VsCodeTaskReference::Label("Build Extension".to_string()),
+                ],
+                depends_order: None,
                 other_attributes: Default::default(),
             },
             VsCodeTaskDefinition {
                 label: "Build Server (Release) and Extension".to_string(),
                 command: None,
                 options: None,
+                depends_on: vec![
+                    VsCodeTaskReference::Label("Build Server (Release)".to_string()),
+                    VsCodeTaskReference::Label("Build Extension".to_string()),
+                ],
+                depends_order: None,
                 other_attributes: Default::default(),
             },
         ];
@@ -336,26 +614,228 @@ mod tests {
                 label: "Build Extension".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build".to_string()],
+                problem_matcher: vec![ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc)],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server".to_string(),
                 command: "cargo build --package rust-analyzer".to_string(),
+                problem_matcher: vec![ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Rustc)],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server (Release)".to_string(),
                 command: "cargo build --release --package rust-analyzer".to_string(),
+                problem_matcher: vec![ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Rustc)],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Pretest".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "pretest".to_string()],
+                problem_matcher: vec![ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc)],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server and Extension".to_string(),
+                depends_on: vec!["Build Server".to_string(), "Build Extension".to_string()],
+                depends_order: DependsOrder::Parallel,
+                problem_matcher: vec![ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Rustc)],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server (Release) and Extension".to_string(),
+                depends_on: vec![
+                    "Build Server (Release)".to_string(),
+                    "Build Extension".to_string(),
+                ],
+                depends_order: DependsOrder::Parallel,
+                problem_matcher: vec![ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Rustc)],
                 ..Default::default()
             },
         ];
         let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
         assert_eq!(tasks.0, expected);
     }
+
+    #[test]
+    fn can_deserialize_depends_on_task_definitions() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "tasks": [
+                    {"label": "compile", "type": "npm", "script": "compile"},
+                    {"label": "bundle", "type": "gulp", "task": "bundle"},
+                    {
+                        "label": "build",
+                        "dependsOn": [
+                            {"type": "npm", "script": "compile"},
+                            {"type": "gulp", "task": "bundle"},
+                            {"type": "npm", "script": "missing"},
+                            {"type": "unknown", "task": "other"},
+                            "compile"
+                        ],
+                        "dependsOrder": "sequence"
+                    },
+                    {"label": "single", "dependsOn": {"type": "gulp", "task": "bundle"}}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(
+            tasks
+                .0
+                .iter()
+                .map(|task| (task.label.as_str(), task.depends_on.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("compile", Vec::new()),
+                ("bundle", Vec::new()),
+                (
+                    "build",
+                    vec![
+                        "compile".to_string(),
+                        "bundle".to_string(),
+                        "compile".to_string()
+                    ]
+                ),
+                ("single", vec!["bundle".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn can_deserialize_custom_problem_matchers() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "tasks": [{
+                    "label": "lint",
+                    "type": "shell",
+                    "command": "lint",
+                    "problemMatcher": [
+                        "$gcc",
+                        "$unknown",
+                        {
+                            "owner": "lint",
+                            "severity": "warning",
+                            "pattern": {
+                                "regexp": "^(.*):(\\d+): (.*)$",
+                                "file": 1,
+                                "line": 2,
+                                "message": 3
+                            }
+                        }
+                    ]
+                }]
+            }"#,
+        )
+        .unwrap();
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(
+            tasks.0,
+            vec![TaskTemplate {
+                label: "lint".to_string(),
+                command: "lint".to_string(),
+                depends_order: DependsOrder::Parallel,
+                problem_matcher: vec![
+                    ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Gcc),
+                    ProblemMatcher::Custom(CustomProblemMatcher {
+                        patterns: vec![ProblemPattern {
+                            regexp: r"^(.*):(\d+): (.*)$".to_string(),
+                            file: Some(1),
+                            line: Some(2),
+                            message: Some(3),
+                            ..ProblemPattern::default()
+                        }],
+                        severity: ProblemSeverity::Warning,
+                    }),
+                ],
+                ..Default::default()
+            }]
+        );
+    }
+
+    #[test]
+    fn can_deserialize_inputs() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "tasks": [
+                    {
+                        "label": "deploy",
+                        "type": "shell",
+                        "command": "deploy",
+                        "args": ["--env", "${input:environment}", "--tag", "${input:tag}"]
+                    },
+                    {
+                        "label": "build",
+                        "type": "shell",
+                        "command": "make"
+                    }
+                ],
+                "inputs": [
+                    {
+                        "id": "environment",
+                        "type": "pickString",
+                        "description": "Where to deploy",
+                        "options": ["staging", { "label": "Production", "value": "prod" }],
+                        "default": "staging"
+                    },
+                    {
+                        "id": "tag",
+                        "type": "promptString"
+                    },
+                    {
+                        "id": "unused",
+                        "type": "promptString"
+                    },
+                    {
+                        "id": "file",
+                        "type": "command",
+                        "command": "extension.pickFile"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(
+            tasks.0,
+            vec![
+                TaskTemplate {
+                    label: "deploy".to_string(),
+                    command: "deploy".to_string(),
+                    args: vec![
+                        "--env".to_string(),
+                        "${ZED_INPUT_environment}".to_string(),
+                        "--tag".to_string(),
+                        "${ZED_INPUT_tag}".to_string(),
+                    ],
+                    depends_order: DependsOrder::Parallel,
+                    inputs: vec![
+                        TaskInput {
+                            id: "environment".to_string(),
+                            description: Some("Where to deploy".to_string()),
+                            default: Some("staging".to_string()),
+                            kind: TaskInputKind::PickString {
+                                options: vec!["staging".to_string(), "prod".to_string()],
+                            },
+                        },
+                        TaskInput {
+                            id: "tag".to_string(),
+                            description: None,
+                            default: None,
+                            kind: TaskInputKind::PromptString,
+                        },
+                    ],
+                    ..Default::default()
+                },
+                TaskTemplate {
+                    label: "build".to_string(),
+                    command: "make".to_string(),
+                    depends_order: DependsOrder::Parallel,
+                    ..Default::default()
+                },
+            ]
+        );
+    }
 }
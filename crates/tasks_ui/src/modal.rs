@@ -20,7 +20,7 @@ use ui::{
 
 use util::{ResultExt, truncate_and_trailoff};
 use workspace::{ModalView, Workspace};
-pub use zed_actions::{Rerun, Spawn};
+pub use zed_actions::{Rerun, Spawn, StopWatching};
 
 /// A modal used to spawn new tasks.
 pub struct TasksModalDelegate {
@@ -409,11 +409,13 @@ impl PickerDelegate for TasksModalDelegate {
         let Some((task_source_kind, mut task)) = task else {
             return;
         };
-        if let Some(TaskOverrides {
-            reveal_target: Some(reveal_target),
-        }) = &self.task_overrides
-        {
-            task.resolved.reveal_target = *reveal_target;
+        let reveal_target_override = self
+            .task_overrides
+            .as_ref()
+            .and_then(|overrides| overrides.reveal_target);
+
+        if let Some(reveal_target) = reveal_target_override {
+            task.resolved.reveal_target = reveal_target;
         }
 
         self.workspace
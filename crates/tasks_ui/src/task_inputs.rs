@@ -0,0 +1,365 @@
+//! Asks for the values of the task inputs before spawning the tasks that declare them.
+
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use futures::channel::oneshot;
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{
+    App, AsyncWindowContext, Context, DismissEvent, Entity, EventEmitter, Focusable,
+    InteractiveElement, ParentElement, Render, SharedString, Styled, Subscription, Task,
+    WeakEntity, Window, rems,
+};
+use picker::{Picker, PickerDelegate, highlighted_match_with_paths::HighlightedMatch};
+use project::TaskSourceKind;
+use task::{
+    ResolvedTask, Shell, ShellBuilder, TaskContext, TaskInput, TaskInputKind, VariableName,
+};
+use ui::{ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt as _;
+use workspace::{ModalView, TaskInputProvider, Workspace};
+
+/// Asks for the values of the task inputs in modals, for every task the workspace schedules.
+pub(crate) struct TaskInputModalProvider;
+
+impl TaskInputProvider for TaskInputModalProvider {
+    fn resolve_inputs(
+        &self,
+        task_source_kind: TaskSourceKind,
+        task: ResolvedTask,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Task<Option<ResolvedTask>> {
+        cx.spawn_in(window, async move |workspace, cx| {
+            resolve_task_inputs(&task_source_kind, &task, workspace, cx).await
+        })
+    }
+}
+
+/// Asks for the values of the task's inputs one by one, and resolves the task again with them.
+/// Returns `None` if any of the inputs got dismissed, or if the task cannot be resolved.
+async fn resolve_task_inputs(
+    task_source_kind: &TaskSourceKind,
+    task: &ResolvedTask,
+    workspace: WeakEntity<Workspace>,
+    cx: &mut AsyncWindowContext,
+) -> Option<ResolvedTask> {
+    let template = task.original_task().clone();
+    let mut task_context = task.task_context().clone();
+    let (inventory, is_local) = workspace
+        .read_with(cx, |workspace, cx| {
+            let project = workspace.project().read(cx);
+            (
+                project.task_store().read(cx).task_inventory().cloned(),
+                project.is_local(),
+            )
+        })
+        .ok()?;
+
+    let mut entered_inputs = Vec::new();
+    for input in &template.inputs {
+        let variable = input_variable(input);
+        if task_context.task_variables.get(&variable).is_some() {
+            continue;
+        }
+        let last_value = inventory.as_ref().and_then(|inventory| {
+            inventory
+                .read_with(cx, |inventory, _| {
+                    inventory
+                        .last_task_input(&template, &input.id)
+                        .map(ToOwned::to_owned)
+                })
+                .ok()
+                .flatten()
+        });
+        let options = match &input.kind {
+            TaskInputKind::PromptString => None,
+            TaskInputKind::PickString { options } => Some(options.clone()),
+            TaskInputKind::Command { command, args } => {
+                let options = if is_local {
+                    command_output_lines(command, args, &task_context).await
+                } else {
+                    Err(anyhow::anyhow!(
+                        "Command inputs are not supported in remote projects"
+                    ))
+                };
+                match options {
+                    Ok(options) => Some(options),
+                    Err(e) => {
+                        log::error!(
+                            "Failed to get the options of task input `{}`: {e:#}",
+                            input.id
+                        );
+                        workspace
+                            .update(cx, |workspace, cx| workspace.show_error(&e, cx))
+                            .ok();
+                        return None;
+                    }
+                }
+            }
+        };
+
+        let (answer_tx, answer_rx) = oneshot::channel();
+        workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.toggle_modal(window, cx, |window, cx| {
+                    TaskInputModal::new(
+                        input,
+                        options,
+                        last_value.or_else(|| input.default.clone()),
+                        answer_tx,
+                        window,
+                        cx,
+                    )
+                })
+            })
+            .ok()?;
+        let value = answer_rx.await.ok()?;
+        task_context.task_variables.insert(variable, value.clone());
+        entered_inputs.push((input.id.clone(), value));
+    }
+
+    if let Some(inventory) = inventory {
+        inventory
+            .update(cx, |inventory, _| {
+                inventory.task_inputs_entered(&template, entered_inputs)
+            })
+            .log_err();
+    }
+    let mut resolved_task = template.resolve_task(&task_source_kind.to_id_base(), &task_context)?;
+    // Keep the overrides the task was scheduled with, e.g. the reveal strategy of a runnable.
+    resolved_task.resolved.reveal = task.resolved.reveal;
+    resolved_task.resolved.reveal_target = task.resolved.reveal_target;
+    resolved_task.resolved.allow_concurrent_runs = task.resolved.allow_concurrent_runs;
+    resolved_task.resolved.use_new_terminal = task.resolved.use_new_terminal;
+    Some(resolved_task)
+}
+
+fn input_variable(input: &TaskInput) -> VariableName {
+    VariableName::Input(input.id.clone().into())
+}
+
+async fn command_output_lines(
+    command: &str,
+    args: &[String],
+    task_context: &TaskContext,
+) -> anyhow::Result<Vec<String>> {
+    let (program, shell_args) = ShellBuilder::new(None, &Shell::System)
+        .non_interactive()
+        .build(Some(command.to_owned()), args);
+    let mut process = util::command::new_smol_command(program);
+    process.args(shell_args).envs(&task_context.project_env);
+    if let Some(cwd) = &task_context.cwd {
+        process.current_dir(cwd);
+    }
+    let output = process
+        .output()
+        .await
+        .with_context(|| format!("running `{command}`"))?;
+    anyhow::ensure!(
+        output.status.success(),
+        "`{command}` failed with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr).trim()
+    );
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(ToOwned::to_owned)
+        .collect())
+}
+
+/// A modal used to enter the value of a single task input.
+pub struct TaskInputModal {
+    picker: Entity<Picker<TaskInputDelegate>>,
+    _subscription: Subscription,
+}
+
+impl TaskInputModal {
+    fn new(
+        input: &TaskInput,
+        options: Option<Vec<String>>,
+        suggested_value: Option<String>,
+        answer_tx: oneshot::Sender<String>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let placeholder_text = match (&input.description, &options) {
+            (Some(description), _) => Arc::from(description.as_str()),
+            (None, Some(_)) => Arc::from(format!("Pick a value for `{}`", input.id)),
+            (None, None) => Arc::from(format!("Enter a value for `{}`", input.id)),
+        };
+        let prefilled_query = suggested_value.clone().filter(|_| options.is_none());
+        let delegate = TaskInputDelegate {
+            options,
+            suggested_value,
+            matches: Vec::new(),
+            selected_index: 0,
+            query: String::new(),
+            placeholder_text,
+            answer_tx: Some(answer_tx),
+        };
+        let picker = cx.new(|cx| {
+            let picker = Picker::uniform_list(delegate, window, cx).modal(true);
+            if let Some(query) = prefilled_query {
+                picker.set_query(query, window, cx);
+            }
+            picker
+        });
+        let _subscription = cx.subscribe(&picker, |_, _, _: &DismissEvent, cx| {
+            cx.emit(DismissEvent);
+        });
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+}
+
+impl Render for TaskInputModal {
+    fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("TaskInputModal")
+            .w(rems(34.))
+            .child(self.picker.clone())
+    }
+}
+
+impl EventEmitter<DismissEvent> for TaskInputModal {}
+
+impl Focusable for TaskInputModal {
+    fn focus_handle(&self, cx: &App) -> gpui::FocusHandle {
+        self.picker.read(cx).focus_handle(cx)
+    }
+}
+
+impl ModalView for TaskInputModal {}
+
+pub struct TaskInputDelegate {
+    /// The values to pick from, or `None` if any value can be typed in.
+    options: Option<Vec<String>>,
+    suggested_value: Option<String>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    query: String,
+    placeholder_text: Arc<str>,
+    answer_tx: Option<oneshot::Sender<String>>,
+}
+
+impl PickerDelegate for TaskInputDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut Window, _: &mut Context<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _: &mut Window, _: &mut App) -> Arc<str> {
+        self.placeholder_text.clone()
+    }
+
+    fn no_matches_text(&self, _: &mut Window, _: &mut App) -> Option<SharedString> {
+        self.options.as_ref().map(|_| "No matches".into())
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        self.query = query.clone();
+        let Some(options) = &self.options else {
+            return Task::ready(());
+        };
+        let candidates = options
+            .iter()
+            .enumerate()
+            .map(|(id, option)| StringMatchCandidate::new(id, option))
+            .collect::<Vec<_>>();
+        cx.spawn_in(window, async move |picker, cx| {
+            let matches = fuzzy::match_strings(
+                &candidates,
+                &query,
+                true,
+                true,
+                1000,
+                &Default::default(),
+                cx.background_executor().clone(),
+            )
+            .await;
+            picker
+                .update(cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.selected_index = if query.is_empty() {
+                        delegate
+                            .suggested_value
+                            .as_ref()
+                            .and_then(|suggested| {
+                                matches
+                                    .iter()
+                                    .position(|string_match| &string_match.string == suggested)
+                            })
+                            .unwrap_or(0)
+                    } else {
+                        0
+                    };
+                    delegate.matches = matches;
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let answer = match &self.options {
+            Some(options) => self
+                .matches
+                .get(self.selected_index)
+                .and_then(|string_match| options.get(string_match.candidate_id))
+                .cloned(),
+            None => Some(self.query.clone()),
+        };
+        let Some(answer) = answer else {
+            return;
+        };
+        if let Some(answer_tx) = self.answer_tx.take() {
+            answer_tx.send(answer).ok();
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let string_match = self.matches.get(ix)?;
+        let highlighted_option = HighlightedMatch {
+            text: string_match.string.clone(),
+            highlight_positions: string_match.positions.clone(),
+            char_count: string_match.string.chars().count(),
+            color: Color::Default,
+        };
+        Some(
+            ListItem::new(SharedString::from(format!("task-input-{ix}")))
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(highlighted_option.render(window, cx)),
+        )
+    }
+}
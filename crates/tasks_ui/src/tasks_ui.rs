@@ -8,14 +8,23 @@ use task::{RevealTarget, TaskContext, TaskId, TaskTemplate, TaskVariables, Varia
 use workspace::Workspace;
 
 mod modal;
+mod task_inputs;
 
-pub use modal::{Rerun, ShowAttachModal, Spawn, TaskOverrides, TasksModal};
+pub use modal::{Rerun, ShowAttachModal, Spawn, StopWatching, TaskOverrides, TasksModal};
 
 pub fn init(cx: &mut App) {
     cx.observe_new(
         |workspace: &mut Workspace, _: Option<&mut Window>, _: &mut Context<Workspace>| {
+            workspace.set_task_input_provider(task_inputs::TaskInputModalProvider);
             workspace
                 .register_action(spawn_task_or_modal)
+                .register_action(|workspace, _: &StopWatching, _, cx| {
+                    workspace.project().update(cx, |project, cx| {
+                        project.task_store().update(cx, |task_store, cx| {
+                            task_store.unwatch_all_tasks(cx);
+                        })
+                    });
+                })
                 .register_action(move |workspace, action: &modal::Rerun, window, cx| {
                     if let Some((task_source_kind, mut last_scheduled_task)) = workspace
                         .project()
@@ -390,17 +399,17 @@ fn worktree_context(worktree_abs_path: &Path) -> TaskContext {
 
 #[cfg(test)]
 mod tests {
-    use std::{collections::HashMap, sync::Arc};
+    use std::{cell::RefCell, collections::HashMap, process::ExitStatus, rc::Rc, sync::Arc};
 
     use editor::{Editor, SelectionEffects};
-    use gpui::TestAppContext;
+    use gpui::{App, Task, TestAppContext, Window};
     use language::{Language, LanguageConfig};
     use project::{BasicContextProvider, FakeFs, Project, task_store::TaskStore};
     use serde_json::json;
-    use task::{TaskContext, TaskVariables, VariableName};
+    use task::{SpawnInTerminal, TaskContext, TaskVariables, VariableName};
     use ui::VisualContext;
     use util::path;
-    use workspace::{AppState, Workspace};
+    use workspace::{AppState, TerminalProvider, Workspace};
 
     use crate::task_contexts;
 
@@ -598,6 +607,89 @@ mod tests {
         );
     }
 
+    struct RecordingTerminalProvider {
+        spawned_labels: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl TerminalProvider for RecordingTerminalProvider {
+        fn spawn(
+            &self,
+            task: SpawnInTerminal,
+            _: &mut Window,
+            _: &mut App,
+        ) -> Task<Option<anyhow::Result<ExitStatus>>> {
+            self.spawned_labels.borrow_mut().push(task.label);
+            Task::ready(Some(Ok(ExitStatus::default())))
+        }
+    }
+
+    #[gpui::test]
+    async fn test_task_chain_runs_shared_dependency_once(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/dir"),
+            json!({
+                ".zed": {
+                    "tasks.json": r#"[
+                        {"label": "A", "command": "echo", "depends_on": ["B", "C"]},
+                        {"label": "B", "command": "echo", "depends_on": ["D"]},
+                        {"label": "C", "command": "echo", "depends_on": ["D"]},
+                        {"label": "D", "command": "echo"},
+                    ]"#,
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let spawned_labels = Rc::new(RefCell::new(Vec::new()));
+        workspace.update(cx, |workspace, _| {
+            workspace.set_terminal_provider(RecordingTerminalProvider {
+                spawned_labels: spawned_labels.clone(),
+            })
+        });
+        cx.run_until_parked();
+
+        let inventory = project.read_with(cx, |project, cx| {
+            project
+                .task_store()
+                .read(cx)
+                .task_inventory()
+                .cloned()
+                .unwrap()
+        });
+        let (source_kind, template) = inventory
+            .update(cx, |inventory, cx| {
+                inventory.list_tasks(None, None, Some(worktree_id), cx)
+            })
+            .await
+            .into_iter()
+            .find(|(_, template)| template.label == "A")
+            .unwrap();
+        workspace.update_in(cx, |workspace, window, cx| {
+            workspace.schedule_task(
+                source_kind,
+                &template,
+                &TaskContext::default(),
+                false,
+                window,
+                cx,
+            )
+        });
+        cx.run_until_parked();
+
+        assert_eq!(
+            *spawned_labels.borrow(),
+            vec!["D", "B", "C", "A"],
+            "A dependency shared by several tasks of the chain should run once"
+        );
+    }
+
     pub(crate) fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
         cx.update(|cx| {
             let state = AppState::test(cx);
@@ -0,0 +1,165 @@
+use std::{collections::VecDeque, io, sync::Arc};
+
+use alacritty_terminal::{
+    event::{OnResize, WindowSize},
+    tty::{ChildEvent, EventedPty, EventedReadWrite, Pty},
+    vte::{Parser, Perform},
+};
+use parking_lot::Mutex;
+use polling::{Event, PollMode, Poller};
+
+/// Tasks printing more than this only keep the end of their output.
+const MAX_TASK_OUTPUT_BYTES: usize = 64 * 1024 * 1024;
+
+/// Everything a task printed to its terminal, as read from the pty, so that it can be inspected
+/// regardless of how the terminal wrapped it or how much of it the scrollback kept.
+#[derive(Clone, Default)]
+pub struct TaskOutput(Arc<Mutex<VecDeque<u8>>>);
+
+impl TaskOutput {
+    fn record(&self, bytes: &[u8]) {
+        let mut output = self.0.lock();
+        output.extend(bytes);
+        let excess = output.len().saturating_sub(MAX_TASK_OUTPUT_BYTES);
+        output.drain(..excess);
+    }
+
+    /// Returns the printed text without escape sequences, with the lines that carriage returns
+    /// overwrote, such as progress bars, replaced by their last version.
+    pub fn text(&self) -> String {
+        let mut output = self.0.lock();
+        plain_text(output.make_contiguous())
+    }
+}
+
+/// A pty that records what the process writes to it.
+pub(crate) struct RecordingPty {
+    pty: Pty,
+    output: Option<TaskOutput>,
+}
+
+impl RecordingPty {
+    pub(crate) fn new(pty: Pty, output: Option<TaskOutput>) -> Self {
+        Self { pty, output }
+    }
+}
+
+impl io::Read for RecordingPty {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.pty.reader().read(buf)?;
+        if let Some(output) = &self.output {
+            output.record(&buf[..read]);
+        }
+        Ok(read)
+    }
+}
+
+impl EventedReadWrite for RecordingPty {
+    type Reader = Self;
+    type Writer = <Pty as EventedReadWrite>::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: Event,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        unsafe { self.pty.register(poll, interest, mode) }
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: Event,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.reregister(poll, interest, mode)
+    }
+
+    fn deregister(&mut self, poll: &Arc<Poller>) -> io::Result<()> {
+        self.pty.deregister(poll)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        self
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        self.pty.writer()
+    }
+}
+
+impl EventedPty for RecordingPty {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.pty.next_child_event()
+    }
+}
+
+impl OnResize for RecordingPty {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.pty.on_resize(window_size)
+    }
+}
+
+fn plain_text(output: &[u8]) -> String {
+    let mut text = PlainText::default();
+    Parser::new().advance(&mut text, output);
+    text.text
+}
+
+#[derive(Default)]
+struct PlainText {
+    text: String,
+    line_start: usize,
+    carriage_return: bool,
+}
+
+impl Perform for PlainText {
+    fn print(&mut self, c: char) {
+        if self.carriage_return {
+            self.carriage_return = false;
+            self.text.truncate(self.line_start);
+        }
+        self.text.push(c);
+    }
+
+    fn execute(&mut self, byte: u8) {
+        match byte {
+            b'\n' => {
+                self.carriage_return = false;
+                self.text.push('\n');
+                self.line_start = self.text.len();
+            }
+            // The pty turns every `\n` into `\r\n`, so only text printed after a carriage return
+            // overwrites the line.
+            b'\r' => self.carriage_return = true,
+            b'\t' => self.print('\t'),
+            0x08 => {
+                if self.text.len() > self.line_start {
+                    self.text.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text() {
+        let colored =
+            b"\x1b[1m\x1b[31merror[E0425]\x1b[0m: cannot find value\r\n  --> a.rs:2:5\r\n";
+        assert_eq!(
+            plain_text(colored),
+            "error[E0425]: cannot find value\n  --> a.rs:2:5\n"
+        );
+        assert_eq!(
+            plain_text(b"Building [=>  ] 1/3\rBuilding [==> ] 2/3\rFinished\r\ndone\r\n"),
+            "Finished\ndone\n"
+        );
+        assert_eq!(plain_text(b"ab\x08c\tx\x1b]0;title\x07"), "ac\tx");
+    }
+}
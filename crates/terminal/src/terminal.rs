@@ -3,6 +3,7 @@ pub mod mappings;
 pub use alacritty_terminal;
 
 mod pty_info;
+mod task_output;
 mod terminal_hyperlinks;
 pub mod terminal_settings;
 
@@ -45,6 +46,8 @@ use serde::{Deserialize, Serialize};
 use settings::Settings;
 use smol::channel::{Receiver, Sender};
 use task::{HideStrategy, Shell, TaskId};
+use task_output::RecordingPty;
+pub use task_output::TaskOutput;
 use terminal_hyperlinks::RegexSearches;
 use terminal_settings::{AlternateScroll, CursorShape, TerminalSettings};
 use theme::{ActiveTheme, Theme};
@@ -478,12 +481,13 @@ impl TerminalBuilder {
         };
 
         let pty_info = PtyProcessInfo::new(&pty);
+        let task_output = task.is_some().then(TaskOutput::default);
 
         //And connect them together
         let event_loop = EventLoop::new(
             term.clone(),
             ZedListener(events_tx),
-            pty,
+            RecordingPty::new(pty, task_output.clone()),
             pty_options.drain_on_exit,
             false,
         )?;
@@ -496,6 +500,7 @@ impl TerminalBuilder {
 
         let mut terminal = Terminal {
             task,
+            task_output,
             pty_tx: Notifier(pty_tx),
             completion_tx,
             term,
@@ -718,6 +723,7 @@ pub struct Terminal {
     selection_phase: SelectionPhase,
     hyperlink_regex_searches: RegexSearches,
     task: Option<TaskState>,
+    task_output: Option<TaskOutput>,
     vi_mode_enabled: bool,
     is_ssh_terminal: bool,
     last_mouse_move_time: Instant,
@@ -1894,6 +1900,11 @@ impl Terminal {
         self.task.as_ref()
     }
 
+    /// The output of the terminal's task, as printed by the task process.
+    pub fn task_output(&self) -> Option<&TaskOutput> {
+        self.task_output.as_ref()
+    }
+
     pub fn wait_for_completed_task(&self, cx: &App) -> Task<Option<ExitStatus>> {
         if let Some(task) = self.task() {
             if task.status == TaskStatus::Running {
@@ -346,6 +346,51 @@ pub(crate) struct SerializedPane {
     pub pinned_count: usize,
 }
 
+/// The layout of the splits inside a single terminal tab.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct SerializedTerminalSplits {
+    pub layout: SerializedSplitNode,
+    /// The index of the active split among the splits of the layout, in depth-first order.
+    pub active: usize,
+    pub zoomed: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) enum SerializedSplitNode {
+    /// The terminal of the tab itself, which is serialized as the item.
+    Root,
+    Terminal {
+        working_directory: Option<PathBuf>,
+    },
+    Split {
+        axis: SerializedAxis,
+        members: Vec<SerializedSplitNode>,
+    },
+}
+
+impl SerializedSplitNode {
+    /// The working directories of the split terminals, in depth-first order.
+    pub fn working_directories(&self) -> Vec<Option<PathBuf>> {
+        let mut working_directories = Vec::new();
+        self.collect_working_directories(&mut working_directories);
+        working_directories
+    }
+
+    fn collect_working_directories(&self, working_directories: &mut Vec<Option<PathBuf>>) {
+        match self {
+            SerializedSplitNode::Root => {}
+            SerializedSplitNode::Terminal { working_directory } => {
+                working_directories.push(working_directory.clone())
+            }
+            SerializedSplitNode::Split { members, .. } => {
+                for member in members {
+                    member.collect_working_directories(working_directories);
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct SerializedAxis(pub Axis);
 
@@ -417,6 +462,9 @@ impl Domain for TerminalDb {
             ALTER TABLE terminals ADD COLUMN working_directory_path TEXT;
             UPDATE terminals SET working_directory_path = CAST(working_directory AS TEXT);
         ),
+        sql!(
+            ALTER TABLE terminals ADD COLUMN splits TEXT;
+        ),
     ];
 }
 
@@ -471,4 +519,24 @@ impl TerminalDb {
             WHERE item_id = ? AND workspace_id = ?
         }
     }
+
+    query! {
+        pub async fn save_splits(
+            splits: Option<String>,
+            item_id: ItemId,
+            workspace_id: WorkspaceId
+        ) -> Result<()> {
+            UPDATE terminals
+            SET splits = ?
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
+
+    query! {
+        pub fn get_splits(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<String>> {
+            SELECT splits
+            FROM terminals
+            WHERE item_id = ? AND workspace_id = ? AND splits IS NOT NULL
+        }
+    }
 }
@@ -19,7 +19,7 @@ use itertools::Itertools;
 use project::{Fs, Project, ProjectEntryId};
 use search::{BufferSearchBar, buffer_search::DivRegistrar};
 use settings::Settings;
-use task::{RevealStrategy, RevealTarget, SpawnInTerminal, TaskId};
+use task::{ProblemMatcher, RevealStrategy, RevealTarget, SpawnInTerminal, TaskId, match_problems};
 use terminal::{
     Terminal,
     terminal_settings::{TerminalDockPosition, TerminalSettings},
@@ -33,15 +33,17 @@ use workspace::{
     ActivateNextPane, ActivatePane, ActivatePaneDown, ActivatePaneLeft, ActivatePaneRight,
     ActivatePaneUp, ActivatePreviousPane, DraggedSelection, DraggedTab, ItemId, MoveItemToPane,
     MoveItemToPaneInDirection, NewTerminal, Pane, PaneGroup, SplitDirection, SplitDown, SplitLeft,
-    SplitRight, SplitUp, SwapPaneDown, SwapPaneLeft, SwapPaneRight, SwapPaneUp, ToggleZoom,
+    SplitRight, SplitUp, SwapPaneDown, SwapPaneLeft, SwapPaneRight, SwapPaneUp, Toast, ToggleZoom,
     Workspace,
     dock::{DockPosition, Panel, PanelEvent, PanelHandle},
     item::SerializableItem,
-    move_active_item, move_item, pane,
+    move_active_item, move_item,
+    notifications::NotificationId,
+    pane,
     ui::IconName,
 };
 
-use anyhow::{Result, anyhow};
+use anyhow::{Context as _, Result, anyhow};
 use zed_actions::assistant::InlineAssist;
 
 const TERMINAL_PANEL_KEY: &str = "TerminalPanel";
@@ -1555,6 +1557,50 @@ impl workspace::TerminalProvider for TerminalProvider {
         cx: &mut App,
     ) -> Task<Option<Result<ExitStatus>>> {
         let terminal_panel = self.0.clone();
+        let workspace = self
+            .0
+            .read(cx)
+            .workspace
+            .upgrade()
+            .filter(|_| !task.problem_matcher.is_empty());
+        let mut problems_project = None;
+        if let Some(workspace) = workspace {
+            let project = workspace.read(cx).project().clone();
+            if project.read(cx).is_local() {
+                problems_project = Some(project);
+            } else {
+                // Task diagnostics are stored by the local language server store only.
+                workspace.update(cx, |workspace, cx| {
+                    struct RemoteTaskProblems;
+
+                    workspace.show_toast(
+                        Toast::new(
+                            NotificationId::composite::<RemoteTaskProblems>(task.label.clone()),
+                            format!(
+                                "Problems reported by task `{}` are not shown, \
+                                as problem matchers are not supported in remote projects yet",
+                                task.label
+                            ),
+                        )
+                        .autohide(),
+                        cx,
+                    );
+                });
+            }
+        }
+        // Problems of the previous run get cleared when the task is rerun.
+        if let Some(project) = &problems_project {
+            project.update(cx, |project, cx| {
+                project.lsp_store().update(cx, |lsp_store, cx| {
+                    lsp_store
+                        .update_task_diagnostics(&task.label, Vec::new(), cx)
+                        .log_err();
+                })
+            });
+        }
+        let task_label = task.label.clone();
+        let task_cwd = task.cwd.clone();
+        let problem_matcher = task.problem_matcher.clone();
         window.spawn(cx, async move |cx| {
             let terminal = terminal_panel
                 .update_in(cx, |terminal_panel, window, cx| {
@@ -1568,6 +1614,18 @@ impl workspace::TerminalProvider for TerminalProvider {
                         .read_with(cx, |terminal, cx| terminal.wait_for_completed_task(cx))
                         .ok()?
                         .await?;
+                    if let Some(project) = problems_project {
+                        report_task_problems(
+                            project,
+                            &terminal,
+                            task_label,
+                            task_cwd,
+                            problem_matcher,
+                            cx,
+                        )
+                        .await
+                        .log_err();
+                    }
                     Some(Ok(exit_status))
                 }
                 Err(e) => Some(Err(e)),
@@ -1576,6 +1634,40 @@ impl workspace::TerminalProvider for TerminalProvider {
     }
 }
 
+/// Reports the problems that the task's problem matchers found in its output as project diagnostics.
+/// The whole output of the task process is matched, rather than the terminal's content, which is
+/// soft-wrapped and limited by the scrollback.
+async fn report_task_problems(
+    project: Entity<Project>,
+    terminal: &Entity<Terminal>,
+    task_label: String,
+    task_cwd: Option<PathBuf>,
+    problem_matcher: Vec<ProblemMatcher>,
+    cx: &mut AsyncWindowContext,
+) -> Result<()> {
+    let output = terminal
+        .read_with(cx, |terminal, _| terminal.task_output().cloned())?
+        .context("the task terminal did not record its output")?;
+    let problems = cx
+        .background_spawn(async move {
+            match_problems(&problem_matcher, &output.text())
+                .into_iter()
+                .map(|mut problem| {
+                    if let Some(cwd) = task_cwd.as_ref().filter(|_| problem.path.is_relative()) {
+                        problem.path = cwd.join(&problem.path);
+                    }
+                    problem
+                })
+                .collect::<Vec<_>>()
+        })
+        .await;
+    project.update(cx, |project, cx| {
+        project.lsp_store().update(cx, |lsp_store, cx| {
+            lsp_store.update_task_diagnostics(&task_label, problems, cx)
+        })
+    })?
+}
+
 struct InlineAssistTabBarButton {
     focus_handle: FocusHandle,
 }
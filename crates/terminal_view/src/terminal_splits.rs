@@ -0,0 +1,760 @@
+//! Splits inside a single terminal tab, laid out like tmux panes. The tab's own terminal is one
+//! of the splits, and every other split is a `TerminalView` running its own terminal.
+
+use collections::HashMap;
+use gpui::{AnyElement, App, Axis, Context, Entity, EntityId, Focusable, Subscription, Window};
+use terminal::Terminal;
+use ui::prelude::*;
+use workspace::item::ItemEvent;
+
+use crate::{
+    ActivateNextSplit, ActivatePreviousSplit, CloseSplit, SplitDown, SplitRight, TerminalView,
+    ToggleSplitZoom,
+    persistence::{SerializedAxis, SerializedSplitNode, SerializedTerminalSplits},
+    terminal_scrollbar::TerminalScrollHandle,
+};
+
+#[derive(Clone, PartialEq)]
+pub(crate) enum SplitLeaf {
+    /// The terminal of the tab itself.
+    Root,
+    Terminal(Entity<TerminalView>),
+}
+
+enum SplitNode {
+    Leaf(SplitLeaf),
+    Axis { axis: Axis, members: Vec<SplitNode> },
+}
+
+/// The layout of a terminal tab with more than one split.
+pub(crate) struct TerminalSplits {
+    layout: SplitNode,
+    active: SplitLeaf,
+    zoomed: bool,
+    subscriptions: HashMap<EntityId, Vec<Subscription>>,
+}
+
+impl TerminalSplits {
+    pub(crate) fn active(&self) -> &SplitLeaf {
+        &self.active
+    }
+
+    /// Renders the splits, using the given element for the tab's own terminal.
+    pub(crate) fn render(&self, root_terminal: AnyElement, cx: &App) -> AnyElement {
+        if self.zoomed {
+            return match &self.active {
+                SplitLeaf::Root => root_terminal,
+                SplitLeaf::Terminal(split) => split.clone().into_any_element(),
+            };
+        }
+        self.layout.render(&mut Some(root_terminal), cx)
+    }
+
+    pub(crate) fn serialize(&self, cx: &App) -> SerializedTerminalSplits {
+        SerializedTerminalSplits {
+            layout: self.layout.serialize(cx),
+            active: self
+                .layout
+                .leaves()
+                .iter()
+                .position(|leaf| *leaf == self.active)
+                .unwrap_or(0),
+            zoomed: self.zoomed,
+        }
+    }
+}
+
+impl SplitNode {
+    /// The leaves of the layout, in depth-first order.
+    fn leaves(&self) -> Vec<SplitLeaf> {
+        let mut leaves = Vec::new();
+        self.collect_leaves(&mut leaves);
+        leaves
+    }
+
+    fn collect_leaves(&self, leaves: &mut Vec<SplitLeaf>) {
+        match self {
+            SplitNode::Leaf(leaf) => leaves.push(leaf.clone()),
+            SplitNode::Axis { members, .. } => {
+                for member in members {
+                    member.collect_leaves(leaves);
+                }
+            }
+        }
+    }
+
+    /// Inserts a new leaf next to the target one along the given axis, returning the new leaf
+    /// back if the target is not in the layout.
+    fn insert_after(
+        &mut self,
+        target: &SplitLeaf,
+        new: SplitLeaf,
+        axis: Axis,
+    ) -> Option<SplitLeaf> {
+        match self {
+            SplitNode::Leaf(leaf) => {
+                if leaf != target {
+                    return Some(new);
+                }
+                let leaf = leaf.clone();
+                *self = SplitNode::Axis {
+                    axis,
+                    members: vec![SplitNode::Leaf(leaf), SplitNode::Leaf(new)],
+                };
+                None
+            }
+            SplitNode::Axis {
+                axis: member_axis,
+                members,
+            } => {
+                let mut new = new;
+                for ix in 0..members.len() {
+                    if *member_axis == axis
+                        && matches!(&members[ix], SplitNode::Leaf(leaf) if leaf == target)
+                    {
+                        members.insert(ix + 1, SplitNode::Leaf(new));
+                        return None;
+                    }
+                    new = members[ix].insert_after(target, new, axis)?;
+                }
+                Some(new)
+            }
+        }
+    }
+
+    /// Removes the leaf from the layout, collapsing the splits that are left with one member.
+    fn remove(&mut self, target: &SplitLeaf) -> bool {
+        let SplitNode::Axis { members, .. } = self else {
+            return false;
+        };
+        let removed = match members
+            .iter()
+            .position(|member| matches!(member, SplitNode::Leaf(leaf) if leaf == target))
+        {
+            Some(ix) => {
+                members.remove(ix);
+                true
+            }
+            None => members.iter_mut().any(|member| member.remove(target)),
+        };
+        if removed && members.len() == 1 {
+            if let Some(member) = members.pop() {
+                *self = member;
+            }
+        }
+        removed
+    }
+
+    fn replace(&mut self, target: &SplitLeaf, new: SplitLeaf) -> bool {
+        match self {
+            SplitNode::Leaf(leaf) => {
+                if leaf == target {
+                    *leaf = new;
+                    true
+                } else {
+                    false
+                }
+            }
+            SplitNode::Axis { members, .. } => members
+                .iter_mut()
+                .any(|member| member.replace(target, new.clone())),
+        }
+    }
+
+    fn render(&self, root_terminal: &mut Option<AnyElement>, cx: &App) -> AnyElement {
+        match self {
+            SplitNode::Leaf(SplitLeaf::Root) => root_terminal
+                .take()
+                .unwrap_or_else(|| div().into_any_element()),
+            SplitNode::Leaf(SplitLeaf::Terminal(split)) => split.clone().into_any_element(),
+            SplitNode::Axis { axis, members } => {
+                let border_color = cx.theme().colors().border;
+                let container = match axis {
+                    Axis::Horizontal => h_flex(),
+                    Axis::Vertical => v_flex(),
+                };
+                container
+                    .size_full()
+                    .children(members.iter().enumerate().map(|(ix, member)| {
+                        div()
+                            .flex_1()
+                            .size_full()
+                            .min_w_0()
+                            .min_h_0()
+                            .overflow_hidden()
+                            .when(ix > 0, |this| match axis {
+                                Axis::Horizontal => this.border_l_1(),
+                                Axis::Vertical => this.border_t_1(),
+                            })
+                            .border_color(border_color)
+                            .child(member.render(root_terminal, cx))
+                    }))
+                    .into_any_element()
+            }
+        }
+    }
+
+    fn serialize(&self, cx: &App) -> SerializedSplitNode {
+        match self {
+            SplitNode::Leaf(SplitLeaf::Root) => SerializedSplitNode::Root,
+            SplitNode::Leaf(SplitLeaf::Terminal(split)) => SerializedSplitNode::Terminal {
+                working_directory: split.read(cx).terminal.read(cx).working_directory(),
+            },
+            SplitNode::Axis { axis, members } => SerializedSplitNode::Split {
+                axis: SerializedAxis(*axis),
+                members: members.iter().map(|member| member.serialize(cx)).collect(),
+            },
+        }
+    }
+}
+
+impl TerminalView {
+    pub(crate) fn split_right(
+        &mut self,
+        _: &SplitRight,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.split(Axis::Horizontal, window, cx);
+    }
+
+    pub(crate) fn split_down(
+        &mut self,
+        _: &SplitDown,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.split(Axis::Vertical, window, cx);
+    }
+
+    /// Opens a new terminal next to the active split, in the same working directory.
+    fn split(&mut self, axis: Axis, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        let active_terminal = match self.splits.as_ref().map(|splits| &splits.active) {
+            Some(SplitLeaf::Terminal(split)) => split.read(cx).terminal.clone(),
+            Some(SplitLeaf::Root) | None => self.terminal.clone(),
+        };
+        let working_directory = active_terminal.read(cx).working_directory();
+        let terminal = project.update(cx, |project, cx| {
+            project.create_terminal_shell(working_directory, cx)
+        });
+        cx.spawn_in(window, async move |this, cx| {
+            let terminal = terminal.await?;
+            this.update_in(cx, |this, window, cx| {
+                let (split, subscriptions) = this.new_split(terminal, window, cx);
+                let splits = this.splits.get_or_insert_with(|| TerminalSplits {
+                    layout: SplitNode::Leaf(SplitLeaf::Root),
+                    active: SplitLeaf::Root,
+                    zoomed: false,
+                    subscriptions: HashMap::default(),
+                });
+                let leaf = SplitLeaf::Terminal(split.clone());
+                if splits
+                    .layout
+                    .insert_after(&splits.active, leaf.clone(), axis)
+                    .is_some()
+                {
+                    return;
+                }
+                splits
+                    .subscriptions
+                    .insert(split.entity_id(), subscriptions);
+                splits.active = leaf;
+                splits.zoomed = false;
+                this.cwd_serialized = false;
+                window.focus(&split.read(cx).focus_handle);
+                cx.emit(ItemEvent::UpdateTab);
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn new_split(
+        &mut self,
+        terminal: Entity<Terminal>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> (Entity<TerminalView>, Vec<Subscription>) {
+        let split = cx.new(|cx| {
+            let mut split = TerminalView::new(
+                terminal,
+                self.workspace.clone(),
+                None,
+                self.project.clone(),
+                window,
+                cx,
+            );
+            split.is_split = true;
+            split
+        });
+        let weak_split = split.downgrade();
+        let subscriptions = vec![
+            cx.subscribe_in(&split, window, Self::handle_split_event),
+            cx.on_focus_in(&split.read(cx).focus_handle, window, move |this, _, cx| {
+                if let Some(split) = weak_split.upgrade() {
+                    this.split_focused(SplitLeaf::Terminal(split), cx);
+                }
+            }),
+        ];
+        (split, subscriptions)
+    }
+
+    fn handle_split_event(
+        &mut self,
+        split: &Entity<TerminalView>,
+        event: &ItemEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            ItemEvent::CloseItem => {
+                self.close_split_leaf(SplitLeaf::Terminal(split.clone()), window, cx)
+            }
+            ItemEvent::UpdateTab => {
+                if !split.read(cx).cwd_serialized {
+                    split.update(cx, |split, _| split.cwd_serialized = true);
+                    self.cwd_serialized = false;
+                    cx.emit(ItemEvent::UpdateTab);
+                }
+            }
+            ItemEvent::UpdateBreadcrumbs | ItemEvent::Edit => {}
+        }
+    }
+
+    pub(crate) fn split_focused(&mut self, leaf: SplitLeaf, cx: &mut Context<Self>) {
+        if let Some(splits) = &mut self.splits {
+            if splits.active != leaf {
+                splits.active = leaf;
+                cx.notify();
+            }
+        }
+    }
+
+    pub(crate) fn activate_next_split(
+        &mut self,
+        _: &ActivateNextSplit,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.activate_adjacent_split(true, window, cx);
+    }
+
+    pub(crate) fn activate_previous_split(
+        &mut self,
+        _: &ActivatePreviousSplit,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.activate_adjacent_split(false, window, cx);
+    }
+
+    fn activate_adjacent_split(&mut self, next: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(splits) = &mut self.splits else {
+            return;
+        };
+        let leaves = splits.layout.leaves();
+        let ix = leaves
+            .iter()
+            .position(|leaf| *leaf == splits.active)
+            .unwrap_or(0);
+        let ix = if next {
+            (ix + 1) % leaves.len()
+        } else {
+            (ix + leaves.len() - 1) % leaves.len()
+        };
+        splits.active = leaves[ix].clone();
+        splits.zoomed = false;
+        window.focus(&self.focus_handle(cx));
+        cx.notify();
+    }
+
+    pub(crate) fn toggle_split_zoom(
+        &mut self,
+        _: &ToggleSplitZoom,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(splits) = &mut self.splits {
+            splits.zoomed = !splits.zoomed;
+            self.cwd_serialized = false;
+            cx.emit(ItemEvent::UpdateTab);
+            cx.notify();
+        }
+    }
+
+    pub(crate) fn close_split(
+        &mut self,
+        _: &CloseSplit,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let active = self
+            .splits
+            .as_ref()
+            .map_or(SplitLeaf::Root, |splits| splits.active.clone());
+        self.close_split_leaf(active, window, cx);
+    }
+
+    /// Closes the given split, or the whole tab if it has no other splits. When the tab's own
+    /// terminal is closed, the terminal of the next split takes its place.
+    pub(crate) fn close_split_leaf(
+        &mut self,
+        leaf: SplitLeaf,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let focused = self.focus_handle(cx).contains_focused(window, cx);
+        let Some(mut splits) = self.splits.take() else {
+            cx.emit(ItemEvent::CloseItem);
+            return;
+        };
+        let leaves = splits.layout.leaves();
+        let Some(ix) = leaves.iter().position(|candidate| *candidate == leaf) else {
+            self.splits = Some(splits);
+            return;
+        };
+        let next_leaf = leaves[(ix + 1) % leaves.len()].clone();
+        if splits.active == leaf {
+            splits.active = next_leaf.clone();
+        }
+
+        match leaf {
+            SplitLeaf::Terminal(split) => {
+                splits.layout.remove(&SplitLeaf::Terminal(split.clone()));
+                splits.subscriptions.remove(&split.entity_id());
+            }
+            SplitLeaf::Root => {
+                let SplitLeaf::Terminal(promoted) = next_leaf else {
+                    self.splits = Some(splits);
+                    return;
+                };
+                let promoted_leaf = SplitLeaf::Terminal(promoted.clone());
+                splits.layout.remove(&SplitLeaf::Root);
+                splits.layout.replace(&promoted_leaf, SplitLeaf::Root);
+                splits.subscriptions.remove(&promoted.entity_id());
+                if splits.active == promoted_leaf {
+                    splits.active = SplitLeaf::Root;
+                }
+                let terminal = promoted.read(cx).terminal.clone();
+                self.scroll_handle = TerminalScrollHandle::new(terminal.read(cx));
+                self.set_terminal(terminal, window, cx);
+            }
+        }
+
+        splits.zoomed = false;
+        if splits.layout.leaves().len() > 1 {
+            self.splits = Some(splits);
+        }
+        if focused {
+            window.focus(&self.focus_handle(cx));
+        }
+        self.cwd_serialized = false;
+        cx.emit(ItemEvent::UpdateTab);
+        cx.notify();
+    }
+
+    /// Recreates the splits of a deserialized tab, using the given terminals for the split
+    /// terminals of the layout, in depth-first order.
+    pub(crate) fn restore_splits(
+        &mut self,
+        serialized: SerializedTerminalSplits,
+        terminals: Vec<Option<Entity<Terminal>>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let mut terminals = terminals.into_iter();
+        let mut subscriptions = HashMap::default();
+        let Some(layout) = self.deserialize_split_node(
+            serialized.layout,
+            &mut terminals,
+            &mut subscriptions,
+            window,
+            cx,
+        ) else {
+            return;
+        };
+        let leaves = layout.leaves();
+        if leaves.len() < 2 || !leaves.contains(&SplitLeaf::Root) {
+            return;
+        }
+        self.splits = Some(TerminalSplits {
+            active: leaves
+                .get(serialized.active)
+                .cloned()
+                .unwrap_or(SplitLeaf::Root),
+            layout,
+            zoomed: serialized.zoomed,
+            subscriptions,
+        });
+    }
+
+    fn deserialize_split_node(
+        &mut self,
+        serialized: SerializedSplitNode,
+        terminals: &mut impl Iterator<Item = Option<Entity<Terminal>>>,
+        subscriptions: &mut HashMap<EntityId, Vec<Subscription>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<SplitNode> {
+        match serialized {
+            SerializedSplitNode::Root => Some(SplitNode::Leaf(SplitLeaf::Root)),
+            SerializedSplitNode::Terminal { .. } => {
+                let terminal = terminals.next().flatten()?;
+                let (split, split_subscriptions) = self.new_split(terminal, window, cx);
+                subscriptions.insert(split.entity_id(), split_subscriptions);
+                Some(SplitNode::Leaf(SplitLeaf::Terminal(split)))
+            }
+            SerializedSplitNode::Split { axis, members } => {
+                let mut deserialized_members = Vec::new();
+                for member in members {
+                    deserialized_members.extend(self.deserialize_split_node(
+                        member,
+                        terminals,
+                        subscriptions,
+                        window,
+                        cx,
+                    ));
+                }
+                if deserialized_members.len() > 1 {
+                    Some(SplitNode::Axis {
+                        axis: axis.0,
+                        members: deserialized_members,
+                    })
+                } else {
+                    deserialized_members.pop()
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::TERMINAL_DB;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::{FakeFs, Project};
+    use workspace::{AppState, WORKSPACE_DB, Workspace, item::SerializableItem};
+
+    #[gpui::test]
+    async fn test_split_and_close(cx: &mut TestAppContext) {
+        let (terminal_view, _, cx) = init_test(cx).await;
+
+        terminal_view.update_in(cx, |view, window, cx| {
+            view.split_right(&SplitRight, window, cx)
+        });
+        cx.run_until_parked();
+        let first = active_split(&terminal_view, cx);
+        terminal_view.update_in(cx, |view, window, cx| {
+            view.split_down(&SplitDown, window, cx)
+        });
+        cx.run_until_parked();
+        let second = active_split(&terminal_view, cx);
+        let splits = [first.clone(), second.clone()];
+        assert_eq!(layout(&terminal_view, &splits, cx), "h(root, v(0, 1))");
+
+        terminal_view.update_in(cx, |view, window, cx| {
+            view.close_split(&CloseSplit, window, cx)
+        });
+        assert_eq!(layout(&terminal_view, &splits, cx), "h(root, 0)");
+        assert!(
+            terminal_view.read_with(cx, |view, _| {
+                view.splits.as_ref().unwrap().active == SplitLeaf::Root
+            }),
+            "Closing the active split should activate the next one"
+        );
+
+        let first_terminal = first.read_with(cx, |first, _| first.terminal.clone());
+        terminal_view.update_in(cx, |view, window, cx| {
+            view.close_split(&CloseSplit, window, cx)
+        });
+        terminal_view.read_with(cx, |view, _| {
+            assert!(
+                view.splits.is_none(),
+                "A tab with a single split left should have no splits"
+            );
+            assert_eq!(view.terminal, first_terminal);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_closing_root_promotes_next_split(cx: &mut TestAppContext) {
+        let (terminal_view, _, cx) = init_test(cx).await;
+
+        terminal_view.update_in(cx, |view, window, cx| {
+            view.split_right(&SplitRight, window, cx)
+        });
+        cx.run_until_parked();
+        let first = active_split(&terminal_view, cx);
+        terminal_view.update_in(cx, |view, window, cx| {
+            view.split_down(&SplitDown, window, cx)
+        });
+        cx.run_until_parked();
+        let second = active_split(&terminal_view, cx);
+        let splits = [first.clone(), second.clone()];
+
+        let first_terminal = first.read_with(cx, |first, _| first.terminal.clone());
+        terminal_view.update_in(cx, |view, window, cx| {
+            view.close_split_leaf(SplitLeaf::Root, window, cx)
+        });
+        assert_eq!(
+            layout(&terminal_view, &splits, cx),
+            "v(root, 1)",
+            "The split after the tab's own terminal should take its place"
+        );
+        terminal_view.read_with(cx, |view, _| {
+            assert_eq!(view.terminal, first_terminal);
+            assert!(view.splits.as_ref().unwrap().active == SplitLeaf::Terminal(second));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_splits_serialization_round_trip(cx: &mut TestAppContext) {
+        let (terminal_view, workspace, cx) = init_test(cx).await;
+
+        terminal_view.update_in(cx, |view, window, cx| {
+            view.split_right(&SplitRight, window, cx)
+        });
+        cx.run_until_parked();
+        terminal_view.update_in(cx, |view, window, cx| {
+            view.split_down(&SplitDown, window, cx)
+        });
+        cx.run_until_parked();
+        terminal_view.update_in(cx, |view, window, cx| {
+            view.split_right(&SplitRight, window, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            layout(&terminal_view, &[], cx),
+            "h(root, v(split, h(split, split)))"
+        );
+        let serialized = terminal_view.read_with(cx, |view, cx| {
+            serde_json::to_string(&view.splits.as_ref().unwrap().serialize(cx)).unwrap()
+        });
+
+        let item_id = 1234;
+        let workspace_id = WORKSPACE_DB.next_id().await.unwrap();
+        let working_directory = std::env::current_dir().unwrap();
+        TERMINAL_DB
+            .save_working_directory(item_id, workspace_id, working_directory)
+            .await
+            .unwrap();
+        TERMINAL_DB
+            .save_splits(Some(serialized.clone()), item_id, workspace_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            TERMINAL_DB.get_splits(item_id, workspace_id).unwrap(),
+            Some(serialized.clone())
+        );
+
+        let project = workspace.read_with(cx, |workspace, _| workspace.project().clone());
+        let restored = cx
+            .update(|window, cx| {
+                TerminalView::deserialize(
+                    project,
+                    workspace.downgrade(),
+                    workspace_id,
+                    item_id,
+                    window,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            layout(&restored, &[], cx),
+            "h(root, v(split, h(split, split)))"
+        );
+        restored.read_with(cx, |restored, cx| {
+            let splits = restored.splits.as_ref().unwrap();
+            assert_eq!(splits.serialize(cx).active, 3);
+            assert!(!splits.zoomed);
+        });
+    }
+
+    async fn init_test(
+        cx: &mut TestAppContext,
+    ) -> (
+        Entity<TerminalView>,
+        Entity<Workspace>,
+        &mut VisualTestContext,
+    ) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            terminal::init(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            Project::init_settings(cx);
+            language::init(cx);
+            editor::init(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let working_directory = std::env::current_dir().unwrap();
+        let terminal = project
+            .update(cx, |project, cx| {
+                project.create_terminal_shell(Some(working_directory), cx)
+            })
+            .await
+            .unwrap();
+        let terminal_view = cx.new_window_entity(|window, cx| {
+            TerminalView::new(
+                terminal,
+                workspace.downgrade(),
+                None,
+                project.downgrade(),
+                window,
+                cx,
+            )
+        });
+        (terminal_view, workspace, cx)
+    }
+
+    fn active_split(
+        terminal_view: &Entity<TerminalView>,
+        cx: &mut VisualTestContext,
+    ) -> Entity<TerminalView> {
+        terminal_view.read_with(cx, |view, _| match view.splits.as_ref().unwrap().active() {
+            SplitLeaf::Terminal(split) => split.clone(),
+            SplitLeaf::Root => panic!("The new split should be active"),
+        })
+    }
+
+    /// Describes the layout of the splits, naming the split terminals by their index in `splits`.
+    fn layout(
+        terminal_view: &Entity<TerminalView>,
+        splits: &[Entity<TerminalView>],
+        cx: &mut VisualTestContext,
+    ) -> String {
+        fn describe(node: &SplitNode, splits: &[Entity<TerminalView>]) -> String {
+            match node {
+                SplitNode::Leaf(SplitLeaf::Root) => "root".to_string(),
+                SplitNode::Leaf(SplitLeaf::Terminal(split)) => splits
+                    .iter()
+                    .position(|candidate| candidate == split)
+                    .map_or_else(|| "split".to_string(), |ix| ix.to_string()),
+                SplitNode::Axis { axis, members } => {
+                    let axis = match axis {
+                        Axis::Horizontal => "h",
+                        Axis::Vertical => "v",
+                    };
+                    let members = members
+                        .iter()
+                        .map(|member| describe(member, splits))
+                        .collect::<Vec<_>>();
+                    format!("{axis}({})", members.join(", "))
+                }
+            }
+        }
+
+        terminal_view.read_with(cx, |view, _| match &view.splits {
+            Some(terminal_splits) => describe(&terminal_splits.layout, splits),
+            None => "root".to_string(),
+        })
+    }
+}
@@ -4,6 +4,7 @@ pub mod terminal_panel;
 mod terminal_path_like_target;
 pub mod terminal_scrollbar;
 mod terminal_slash_command;
+mod terminal_splits;
 pub mod terminal_tab_tooltip;
 
 use assistant_slash_command::SlashCommandRegistry;
@@ -13,7 +14,7 @@ use gpui::{
     KeyContext, KeyDownEvent, Keystroke, MouseButton, MouseDownEvent, Pixels, Render,
     ScrollWheelEvent, Styled, Subscription, Task, WeakEntity, actions, anchored, deferred, div,
 };
-use persistence::TERMINAL_DB;
+use persistence::{SerializedTerminalSplits, TERMINAL_DB};
 use project::{Project, search::SearchQuery};
 use schemars::JsonSchema;
 use task::TaskId;
@@ -32,6 +33,7 @@ use terminal_panel::TerminalPanel;
 use terminal_path_like_target::{hover_path_like_target, open_path_like_target};
 use terminal_scrollbar::TerminalScrollHandle;
 use terminal_slash_command::TerminalSlashCommand;
+use terminal_splits::{SplitLeaf, TerminalSplits};
 use terminal_tab_tooltip::TerminalTooltip;
 use ui::{
     ContextMenu, Icon, IconName, Label, ScrollAxes, Scrollbars, Tooltip, WithScrollbar, h_flex,
@@ -88,7 +90,19 @@ actions!(
     terminal,
     [
         /// Reruns the last executed task in the terminal.
-        RerunTask
+        RerunTask,
+        /// Splits the active terminal split, opening a new terminal to its right.
+        SplitRight,
+        /// Splits the active terminal split, opening a new terminal below it.
+        SplitDown,
+        /// Moves focus to the next split of the terminal tab.
+        ActivateNextSplit,
+        /// Moves focus to the previous split of the terminal tab.
+        ActivatePreviousSplit,
+        /// Toggles showing only the active split of the terminal tab.
+        ToggleSplitZoom,
+        /// Closes the active split of the terminal tab, or the tab if it has no other splits.
+        CloseSplit
     ]
 );
 
@@ -141,6 +155,10 @@ pub struct TerminalView {
     scroll_top: Pixels,
     scroll_handle: TerminalScrollHandle,
     ime_state: Option<ImeState>,
+    /// The other terminals shown inside this terminal's tab, if it was split.
+    splits: Option<TerminalSplits>,
+    /// Whether this is one of the splits of another terminal's tab.
+    is_split: bool,
     _subscriptions: Vec<Subscription>,
     _terminal_subscriptions: Vec<Subscription>,
 }
@@ -191,8 +209,11 @@ impl EventEmitter<ItemEvent> for TerminalView {}
 impl EventEmitter<SearchEvent> for TerminalView {}
 
 impl Focusable for TerminalView {
-    fn focus_handle(&self, _cx: &App) -> FocusHandle {
-        self.focus_handle.clone()
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        match self.splits.as_ref().map(TerminalSplits::active) {
+            Some(SplitLeaf::Terminal(split)) => split.read(cx).focus_handle.clone(),
+            Some(SplitLeaf::Root) | None => self.focus_handle.clone(),
+        }
     }
 }
 
@@ -262,10 +283,13 @@ impl TerminalView {
             scroll_handle,
             cwd_serialized: false,
             ime_state: None,
+            splits: None,
+            is_split: false,
             _subscriptions: vec![
                 focus_in,
                 focus_out,
                 cx.observe_global::<SettingsStore>(Self::settings_changed),
+                cx.on_release(Self::unwatch_task),
             ],
             _terminal_subscriptions: terminal_subscriptions,
         }
@@ -385,6 +409,8 @@ impl TerminalView {
         let context_menu = ContextMenu::build(window, cx, |menu, _, _| {
             menu.context(self.focus_handle.clone())
                 .action("New Terminal", Box::new(NewTerminal))
+                .action("Split Right", Box::new(SplitRight))
+                .action("Split Down", Box::new(SplitDown))
                 .separator()
                 .action("Copy", Box::new(Copy))
                 .action("Paste", Box::new(Paste))
@@ -666,6 +692,34 @@ impl TerminalView {
         &self.terminal
     }
 
+    /// Stops rerunning the terminal's task on file changes once the terminal is closed,
+    /// unless the task still runs in another terminal.
+    fn unwatch_task(&mut self, cx: &mut App) {
+        let Some(task_id) = self.terminal.read(cx).task().map(|task| task.id.clone()) else {
+            return;
+        };
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        let terminal_id = self.terminal.entity_id();
+        let runs_elsewhere = project
+            .read(cx)
+            .local_terminal_handles()
+            .iter()
+            .filter_map(|terminal| terminal.upgrade())
+            .filter(|terminal| terminal.entity_id() != terminal_id)
+            .any(|terminal| {
+                terminal
+                    .read(cx)
+                    .task()
+                    .is_some_and(|task| task.id == task_id)
+            });
+        if !runs_elsewhere {
+            let task_store = project.read(cx).task_store().clone();
+            task_store.update(cx, |task_store, cx| task_store.unwatch_task(&task_id, cx));
+        }
+    }
+
     pub fn set_block_below_cursor(
         &mut self,
         block: BlockProperties,
@@ -961,7 +1015,7 @@ fn subscribe_for_terminal_events(
                     ),
                 },
                 Event::BreadcrumbsChanged => cx.emit(ItemEvent::UpdateBreadcrumbs),
-                Event::CloseTerminal => cx.emit(ItemEvent::CloseItem),
+                Event::CloseTerminal => terminal_view.close_split_leaf(SplitLeaf::Root, window, cx),
                 Event::SelectionsChanged => {
                     window.invalidate_character_coordinates();
                     cx.emit(SearchEvent::ActiveMatchChanged)
@@ -1024,6 +1078,7 @@ impl TerminalView {
     }
 
     fn focus_in(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.split_focused(SplitLeaf::Root, cx);
         self.terminal.update(cx, |terminal, _| {
             terminal.set_cursor_shape(self.cursor_shape);
             terminal.focus_in();
@@ -1044,6 +1099,30 @@ impl TerminalView {
 
 impl Render for TerminalView {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let terminal = self.render_terminal(window, cx).into_any_element();
+        if self.is_split {
+            return terminal;
+        }
+        let content = match &self.splits {
+            Some(splits) => splits.render(terminal, cx),
+            None => terminal,
+        };
+        div()
+            .id("terminal-splits")
+            .size_full()
+            .on_action(cx.listener(TerminalView::split_right))
+            .on_action(cx.listener(TerminalView::split_down))
+            .on_action(cx.listener(TerminalView::activate_next_split))
+            .on_action(cx.listener(TerminalView::activate_previous_split))
+            .on_action(cx.listener(TerminalView::toggle_split_zoom))
+            .on_action(cx.listener(TerminalView::close_split))
+            .child(content)
+            .into_any_element()
+    }
+}
+
+impl TerminalView {
+    fn render_terminal(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         // TODO: this should be moved out of render
         self.scroll_handle.update(self.terminal.read(cx));
 
@@ -1067,7 +1146,7 @@ impl Render for TerminalView {
             .id("terminal-view")
             .size_full()
             .relative()
-            .track_focus(&self.focus_handle(cx))
+            .track_focus(&self.focus_handle)
             .key_context(self.dispatch_context(cx))
             .on_action(cx.listener(TerminalView::send_text))
             .on_action(cx.listener(TerminalView::send_keystroke))
@@ -1332,6 +1411,10 @@ impl SerializableItem for TerminalView {
         _: &mut Window,
         cx: &mut Context<Self>,
     ) -> Option<Task<anyhow::Result<()>>> {
+        let splits = self
+            .splits
+            .as_ref()
+            .and_then(|splits| serde_json::to_string(&splits.serialize(cx)).log_err());
         let terminal = self.terminal().read(cx);
         if terminal.task().is_some() {
             return None;
@@ -1342,7 +1425,8 @@ impl SerializableItem for TerminalView {
             Some(cx.background_spawn(async move {
                 TERMINAL_DB
                     .save_working_directory(item_id, workspace_id, cwd)
-                    .await
+                    .await?;
+                TERMINAL_DB.save_splits(splits, item_id, workspace_id).await
             }))
         } else {
             None
@@ -1381,20 +1465,44 @@ impl SerializableItem for TerminalView {
                 })
                 .ok()
                 .flatten();
+            let splits = TERMINAL_DB
+                .get_splits(item_id, workspace_id)
+                .log_err()
+                .flatten()
+                .and_then(|splits| {
+                    serde_json::from_str::<SerializedTerminalSplits>(&splits).log_err()
+                });
 
             let terminal = project
                 .update(cx, |project, cx| project.create_terminal_shell(cwd, cx))?
                 .await?;
+            let mut split_terminals = Vec::new();
+            for working_directory in splits
+                .iter()
+                .flat_map(|splits| splits.layout.working_directories())
+            {
+                let split_terminal = project
+                    .update(cx, |project, cx| {
+                        project.create_terminal_shell(working_directory, cx)
+                    })?
+                    .await
+                    .log_err();
+                split_terminals.push(split_terminal);
+            }
             cx.update(|window, cx| {
                 cx.new(|cx| {
-                    TerminalView::new(
+                    let mut terminal_view = TerminalView::new(
                         terminal,
                         workspace,
                         Some(workspace_id),
                         project.downgrade(),
                         window,
                         cx,
-                    )
+                    );
+                    if let Some(splits) = splits {
+                        terminal_view.restore_splits(splits, split_terminals, window, cx);
+                    }
+                    terminal_view
                 })
             })
         })
@@ -658,6 +658,87 @@ fn test_finalize_last_transaction() {
     assert_eq!(buffer.text(), "ab2cde6");
 }
 
+#[test]
+fn test_undo_tree() {
+    let now = Instant::now();
+    let mut buffer = Buffer::new(0, BufferId::new(1).unwrap(), "abc");
+    buffer.set_group_interval(Duration::from_millis(300));
+
+    let edit = |buffer: &mut Buffer, offset: usize, text: &str, secs: u64| {
+        let now = now + Duration::from_secs(secs);
+        let transaction_id = buffer.start_transaction_at(now).unwrap();
+        buffer.edit([(offset..offset, text)]);
+        buffer.end_transaction_at(now);
+        transaction_id
+    };
+
+    let transaction_1 = edit(&mut buffer, 3, "d", 0);
+    let transaction_2 = edit(&mut buffer, 4, "e", 1);
+    buffer.undo();
+    // Editing after undoing keeps the undone transaction as a branch of the tree.
+    let transaction_3 = edit(&mut buffer, 4, "f", 2);
+    assert_eq!(buffer.text(), "abcdf");
+    assert!(buffer.redo().is_none());
+    assert_eq!(
+        buffer
+            .undo_tree()
+            .iter()
+            .map(|node| (node.transaction_id, node.parent))
+            .collect::<Vec<_>>(),
+        [
+            (transaction_1, None),
+            (transaction_2, Some(transaction_1)),
+            (transaction_3, Some(transaction_1)),
+        ]
+    );
+
+    buffer.go_to_undo_tree_state(Some(transaction_2));
+    assert_eq!(buffer.text(), "abcde");
+    assert_eq!(buffer.undo_tree_state(), Some(transaction_2));
+    buffer.undo();
+    assert_eq!(buffer.text(), "abcd");
+    buffer.redo();
+    assert_eq!(buffer.text(), "abcde");
+
+    buffer.go_to_undo_tree_state(None);
+    assert_eq!(buffer.text(), "abc");
+    buffer.go_to_undo_tree_state(Some(transaction_3));
+    assert_eq!(buffer.text(), "abcdf");
+
+    // States can be found in the order they were created, regardless of their branch.
+    assert_eq!(buffer.undo_tree_state_by_changes(-1), Some(transaction_2));
+    assert_eq!(buffer.undo_tree_state_by_changes(-2), Some(transaction_1));
+    assert_eq!(buffer.undo_tree_state_by_changes(-5), None);
+    assert_eq!(buffer.undo_tree_state_by_changes(1), Some(transaction_3));
+    assert_eq!(
+        buffer.undo_tree_state_by_time(Duration::from_secs(1), true),
+        Some(transaction_2)
+    );
+    assert_eq!(
+        buffer.undo_tree_state_by_time(Duration::from_millis(1500), true),
+        Some(transaction_1)
+    );
+
+    // Any number of branches can start from the same state.
+    buffer.go_to_undo_tree_state(Some(transaction_1));
+    let transaction_4 = edit(&mut buffer, 0, "g", 3);
+    assert_eq!(buffer.text(), "gabcd");
+    buffer.go_to_undo_tree_state(Some(transaction_2));
+    assert_eq!(buffer.text(), "abcde");
+    buffer.go_to_undo_tree_state(Some(transaction_3));
+    assert_eq!(buffer.text(), "abcdf");
+    buffer.go_to_undo_tree_state(Some(transaction_4));
+    assert_eq!(buffer.text(), "gabcd");
+    buffer.undo();
+    buffer.undo();
+    assert_eq!(buffer.text(), "abc");
+    assert_eq!(
+        buffer.undo_tree_state_by_time(Duration::from_millis(500), false),
+        Some(transaction_1)
+    );
+    assert_eq!(buffer.undo_tree().len(), 4);
+}
+
 #[test]
 fn test_edited_ranges_for_transaction() {
     let now = Instant::now();
@@ -28,7 +28,8 @@ use std::{
     cmp::{self, Ordering, Reverse},
     fmt::Display,
     future::Future,
-    iter::Iterator,
+    iter::{self, Iterator},
+    mem,
     num::NonZeroU64,
     ops::{self, Deref, Range, Sub},
     str,
@@ -142,11 +143,32 @@ impl HistoryEntry {
     }
 }
 
+/// A state of the buffer's undo tree, reached by applying a transaction on top of its parent
+/// state.
+#[derive(Clone, Debug)]
+pub struct UndoTreeNode {
+    pub transaction_id: TransactionId,
+    /// The state the transaction was applied to, or `None` for the base text.
+    pub parent: Option<TransactionId>,
+    pub edited_at: Instant,
+}
+
+/// Transactions that were undone before making a new change, kept so that the undo tree can be
+/// navigated back to them.
+#[derive(Clone, Debug)]
+struct UndoBranch {
+    /// The transaction the branch was undone to, or `None` for the base text.
+    parent: Option<TransactionId>,
+    /// The transactions of the branch, from the oldest to the newest one.
+    entries: Vec<HistoryEntry>,
+}
+
 struct History {
     base_text: Rope,
     operations: TreeMap<clock::Lamport, Operation>,
     undo_stack: Vec<HistoryEntry>,
     redo_stack: Vec<HistoryEntry>,
+    branches: Vec<UndoBranch>,
     transaction_depth: usize,
     group_interval: Duration,
 }
@@ -191,6 +213,7 @@ impl History {
             operations: Default::default(),
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
+            branches: Vec::new(),
             transaction_depth: 0,
             // Don't group transactions in tests unless we opt in, because it's a footgun.
             #[cfg(any(test, feature = "test-support"))]
@@ -244,7 +267,16 @@ impl History {
                 self.undo_stack.pop();
                 None
             } else {
-                self.redo_stack.clear();
+                if !self.redo_stack.is_empty() {
+                    // Keep the transaction the redo stack branches off from separate from the
+                    // new one, so that both branches start from the same state.
+                    let parent = self.undo_stack.len().checked_sub(2).map(|parent_ix| {
+                        let parent = &mut self.undo_stack[parent_ix];
+                        parent.suppress_grouping = true;
+                        parent.transaction.id
+                    });
+                    self.archive_redo_stack(parent);
+                }
                 let entry = self.undo_stack.last_mut().unwrap();
                 entry.last_edit_at = now;
                 Some(entry)
@@ -402,21 +434,33 @@ impl History {
 
     fn forget(&mut self, transaction_id: TransactionId) -> Option<Transaction> {
         assert_eq!(self.transaction_depth, 0);
-        if let Some(entry_ix) = self
+        let (transaction, previous_id) = if let Some(entry_ix) = self
             .undo_stack
             .iter()
             .rposition(|entry| entry.transaction.id == transaction_id)
         {
-            Some(self.undo_stack.remove(entry_ix).transaction)
+            let transaction = self.undo_stack.remove(entry_ix).transaction;
+            let previous = entry_ix.checked_sub(1).map(|ix| &self.undo_stack[ix]);
+            (transaction, previous.map(|entry| entry.transaction.id))
         } else if let Some(entry_ix) = self
             .redo_stack
             .iter()
             .rposition(|entry| entry.transaction.id == transaction_id)
         {
-            Some(self.redo_stack.remove(entry_ix).transaction)
+            let transaction = self.redo_stack.remove(entry_ix).transaction;
+            let previous = self.redo_stack[entry_ix..]
+                .first()
+                .or(self.undo_stack.last());
+            (transaction, previous.map(|entry| entry.transaction.id))
         } else {
-            None
+            return None;
+        };
+        for branch in &mut self.branches {
+            if branch.parent == Some(transaction_id) {
+                branch.parent = previous_id;
+            }
         }
+        Some(transaction)
     }
 
     fn transaction(&self, transaction_id: TransactionId) -> Option<&Transaction> {
@@ -477,6 +521,115 @@ impl History {
         }
         &self.undo_stack[undo_stack_start_len..]
     }
+
+    /// Keeps the redo stack as a branch of the undo tree starting from `parent`.
+    fn archive_redo_stack(&mut self, parent: Option<TransactionId>) {
+        if !self.redo_stack.is_empty() {
+            let mut entries = mem::take(&mut self.redo_stack);
+            entries.reverse();
+            self.branches.push(UndoBranch { parent, entries });
+        }
+    }
+
+    fn is_in_undo_or_redo_stack(&self, transaction_id: TransactionId) -> bool {
+        self.undo_stack
+            .iter()
+            .chain(&self.redo_stack)
+            .any(|entry| entry.transaction.id == transaction_id)
+    }
+
+    fn undo_tree(&self) -> Vec<UndoTreeNode> {
+        let mut nodes = Vec::new();
+        let current_line = self
+            .undo_stack
+            .iter()
+            .chain(self.redo_stack.iter().rev())
+            .collect::<Vec<_>>();
+        let lines = iter::once((None, current_line)).chain(
+            self.branches
+                .iter()
+                .map(|branch| (branch.parent, branch.entries.iter().collect())),
+        );
+        for (mut parent, entries) in lines {
+            for entry in entries {
+                nodes.push(UndoTreeNode {
+                    transaction_id: entry.transaction.id,
+                    parent,
+                    edited_at: entry.last_edit_at,
+                });
+                parent = Some(entry.transaction.id);
+            }
+        }
+        nodes.sort_by_key(|node| node.transaction_id);
+        nodes
+    }
+
+    /// Rearranges the history so that the undo stack ends with `target`, switching to the
+    /// branches of the undo tree that lead to it. Returns the transactions that need to be undone
+    /// or redone to get there, or `None` if `target` is not part of the history.
+    fn go_to(&mut self, target: Option<TransactionId>) -> Option<Vec<Transaction>> {
+        assert_eq!(self.transaction_depth, 0);
+
+        let mut branch_path = Vec::new();
+        let mut state = target;
+        while let Some(transaction_id) = state
+            && !self.is_in_undo_or_redo_stack(transaction_id)
+        {
+            let branch = self.branches.iter().find(|branch| {
+                branch
+                    .entries
+                    .iter()
+                    .any(|entry| entry.transaction.id == transaction_id)
+            })?;
+            branch_path.push(transaction_id);
+            state = branch.parent;
+        }
+
+        let mut transactions = Vec::new();
+        for transaction_id in branch_path.into_iter().rev() {
+            let branch_ix = self.branches.iter().position(|branch| {
+                branch
+                    .entries
+                    .iter()
+                    .any(|entry| entry.transaction.id == transaction_id)
+            })?;
+            let branch = self.branches.remove(branch_ix);
+            self.move_to(branch.parent, &mut transactions);
+            self.archive_redo_stack(branch.parent);
+            self.redo_stack = branch.entries.into_iter().rev().collect();
+        }
+        self.move_to(target, &mut transactions);
+        Some(transactions)
+    }
+
+    /// Undoes or redoes transactions until the undo stack ends with `state`, which must be in
+    /// either stack.
+    fn move_to(&mut self, state: Option<TransactionId>, transactions: &mut Vec<Transaction>) {
+        let undo_stack_len = match state {
+            Some(transaction_id) => self
+                .undo_stack
+                .iter()
+                .rposition(|entry| entry.transaction.id == transaction_id)
+                .map(|entry_ix| entry_ix + 1),
+            None => Some(0),
+        };
+        if let Some(undo_stack_len) = undo_stack_len {
+            while self.undo_stack.len() > undo_stack_len {
+                let entry = self.undo_stack.pop().unwrap();
+                transactions.push(entry.transaction.clone());
+                self.redo_stack.push(entry);
+            }
+        } else {
+            while let Some(entry) = self.redo_stack.pop() {
+                let reached_state = Some(entry.transaction.id) == state;
+                transactions.push(entry.transaction.clone());
+                self.undo_stack.push(entry);
+                if reached_state {
+                    break;
+                }
+            }
+        }
+    }
 }
 
 struct Edits<'a, D: TextDimension, F: FnMut(&FragmentSummary) -> bool> {
@@ -1498,6 +1651,70 @@ impl Buffer {
             .collect()
     }
 
+    /// Returns all the states of the undo tree, including the undone branches, in the order they
+    /// were created.
+    pub fn undo_tree(&self) -> Vec<UndoTreeNode> {
+        self.history.undo_tree()
+    }
+
+    /// Returns the current state of the undo tree, or `None` if all transactions are undone.
+    pub fn undo_tree_state(&self) -> Option<TransactionId> {
+        self.history
+            .undo_stack
+            .last()
+            .map(|entry| entry.transaction.id)
+    }
+
+    /// Returns the state of the undo tree created `steps` changes after the current one, or
+    /// before it when negative, regardless of the branch it's on. `None` is the base text.
+    pub fn undo_tree_state_by_changes(&self, steps: isize) -> Option<TransactionId> {
+        let nodes = self.undo_tree();
+        let current_ix = self.undo_tree_state().map_or(0, |transaction_id| {
+            1 + nodes
+                .iter()
+                .position(|node| node.transaction_id == transaction_id)
+                .unwrap_or_default()
+        });
+        let ix = current_ix.saturating_add_signed(steps).min(nodes.len());
+        Some(nodes.get(ix.checked_sub(1)?)?.transaction_id)
+    }
+
+    /// Returns the state of the undo tree that was last edited the closest to `offset` after
+    /// the current state's last edit, or before it when `older` is set. `None` is the base text.
+    pub fn undo_tree_state_by_time(&self, offset: Duration, older: bool) -> Option<TransactionId> {
+        let nodes = self.undo_tree();
+        let current_time = match self.undo_tree_state() {
+            Some(transaction_id) => {
+                nodes
+                    .iter()
+                    .find(|node| node.transaction_id == transaction_id)?
+                    .edited_at
+            }
+            None if older => return None,
+            None => nodes.iter().map(|node| node.edited_at).min()?,
+        };
+        let target_time = if older {
+            current_time.checked_sub(offset)?
+        } else {
+            current_time.checked_add(offset)?
+        };
+        nodes
+            .iter()
+            .filter(|node| node.edited_at <= target_time)
+            .max_by_key(|node| (node.edited_at, node.transaction_id))
+            .map(|node| node.transaction_id)
+    }
+
+    /// Undoes and redoes transactions, switching branches of the undo tree if needed, until the
+    /// buffer is in the given state. `None` is the base text.
+    pub fn go_to_undo_tree_state(&mut self, target: Option<TransactionId>) -> Vec<Operation> {
+        let transactions = self.history.go_to(target).unwrap_or_default();
+        transactions
+            .into_iter()
+            .map(|transaction| self.undo_or_redo(transaction))
+            .collect()
+    }
+
     fn undo_or_redo(&mut self, transaction: Transaction) -> Operation {
         let mut counts = HashMap::default();
         for edit_id in transaction.edit_ids {
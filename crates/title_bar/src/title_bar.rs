@@ -208,8 +208,11 @@ impl Render for TitleBar {
                                         .children(self.render_project_host(cx))
                                         .child(self.render_project_name(cx))
                                 })
-                                .when(title_bar_settings.show_branch_name, |title_bar| {
-                                    title_bar.children(self.render_project_branch(cx))
+                                .when(title_bar_settings.show_branch_name, |title_bar| match self
+                                    .render_jujutsu_change(cx)
+                                {
+                                    Some(change) => title_bar.child(change),
+                                    None => title_bar.children(self.render_project_branch(cx)),
                                 })
                         })
                 })
@@ -313,6 +316,7 @@ impl TitleBar {
             }),
         );
         subscriptions.push(cx.subscribe(&project, |_, _, _: &project::Event, cx| cx.notify()));
+        subscriptions.push(cx.observe(&project.read(cx).jj_store(cx), |_, _, cx| cx.notify()));
         subscriptions.push(cx.observe(&active_call, |this, _, cx| this.active_call_changed(cx)));
         subscriptions.push(cx.observe_window_activation(window, Self::window_activation_changed));
         subscriptions.push(cx.observe(&user_store, |_, _, cx| cx.notify()));
@@ -576,6 +580,48 @@ impl TitleBar {
         )
     }
 
+    /// Shows the working-copy change in place of the git branch when the
+    /// project is a Jujutsu workspace, where the git HEAD is usually detached.
+    pub fn render_jujutsu_change(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let workspace = self.workspace.upgrade()?;
+        let label = {
+            let jj_store = self.project.read(cx).jj_store(cx);
+            let jj_store = jj_store.read(cx);
+            let change = jj_store.working_copy_change(cx)?;
+            let change_id = change.short_change_id();
+            match change.bookmarks.first() {
+                Some(bookmark) => format!("{change_id} {bookmark}"),
+                None => change_id.to_string(),
+            }
+        };
+
+        Some(
+            Button::new("project_jj_change_trigger", label)
+                .color(Color::Muted)
+                .style(ButtonStyle::Subtle)
+                .label_size(LabelSize::Small)
+                .tooltip(move |window, cx| {
+                    Tooltip::for_action("Jujutsu Log", &zed_actions::jj::Log, window, cx)
+                })
+                .on_click(move |_, window, cx| {
+                    let _ = workspace.update(cx, |this, cx| {
+                        window.focus(&this.active_pane().focus_handle(cx));
+                        window.dispatch_action(zed_actions::jj::Log.boxed_clone(), cx);
+                    });
+                })
+                .when(
+                    TitleBarSettings::get_global(cx).show_branch_icon,
+                    |change_button| {
+                        change_button
+                            .icon(IconName::GitBranch)
+                            .icon_position(IconPosition::Start)
+                            .icon_color(Color::Muted)
+                            .icon_size(IconSize::Indicator)
+                    },
+                ),
+        )
+    }
+
     fn window_activation_changed(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         if window.is_window_active() {
             ActiveCall::global(cx)
@@ -7,6 +7,7 @@ mod apca_contrast;
 mod color_contrast;
 mod corner_solver;
 mod format_distance;
+mod graph_layout;
 mod search_input;
 mod with_rem_size;
 
@@ -14,6 +15,7 @@ pub use apca_contrast::*;
 pub use color_contrast::*;
 pub use corner_solver::{CornerSolver, inner_corner_radius};
 pub use format_distance::*;
+pub use graph_layout::*;
 pub use search_input::*;
 pub use with_rem_size::*;
 
@@ -0,0 +1,243 @@
+//! Lays out the lanes of a commit graph, one row per commit, to draw it next to a log that lists
+//! every commit after its children.
+
+use gpui::SharedString;
+
+/// A line of the graph within a row, between a lane at the row's top or bottom edge and a lane
+/// at the row's center, where the node of the row's commit is drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GraphSegment {
+    pub from_lane: usize,
+    pub to_lane: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GraphRow {
+    /// The lane of the row's commit.
+    pub lane: usize,
+    /// The lines from the lanes at the top edge of the row to the lanes at its center.
+    pub incoming: Vec<GraphSegment>,
+    /// The lines from the lanes at the center of the row to the lanes at its bottom edge. Lanes
+    /// passing through the row come first, followed by one line per parent of the commit, in the
+    /// order the parents were given.
+    pub outgoing: Vec<GraphSegment>,
+}
+
+impl GraphRow {
+    /// The number of lanes at the top edge or at the center of the row.
+    pub fn width(&self) -> usize {
+        self.incoming
+            .iter()
+            .map(|segment| segment.from_lane + 1)
+            .fold(self.lane + 1, usize::max)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct GraphLayout {
+    /// The commit that each lane is heading to below the last row, if any.
+    lanes: Vec<Option<SharedString>>,
+    rows: Vec<GraphRow>,
+    lane_count: usize,
+}
+
+impl GraphLayout {
+    pub fn rows(&self) -> &[GraphRow] {
+        &self.rows
+    }
+
+    /// The number of lanes needed to draw every row.
+    pub fn lane_count(&self) -> usize {
+        self.lane_count
+    }
+
+    /// Adds a row for the next commit of the log.
+    ///
+    /// The first parent always continues in the lane of the commit, so lanes heading to the same
+    /// commit only converge on that commit's row.
+    pub fn push(&mut self, id: &SharedString, parents: &[SharedString]) -> &GraphRow {
+        let lane = self.lane_heading_to(id).unwrap_or_else(|| self.free_lane());
+
+        let mut incoming = Vec::new();
+        for (ix, target) in self.lanes.iter_mut().enumerate() {
+            let Some(target_id) = target else {
+                continue;
+            };
+            if target_id == id {
+                incoming.push(GraphSegment {
+                    from_lane: ix,
+                    to_lane: lane,
+                });
+                *target = None;
+            } else {
+                incoming.push(GraphSegment {
+                    from_lane: ix,
+                    to_lane: ix,
+                });
+            }
+        }
+
+        let mut outgoing = self
+            .lanes
+            .iter()
+            .enumerate()
+            .filter(|(_, target)| target.is_some())
+            .map(|(ix, _)| GraphSegment {
+                from_lane: ix,
+                to_lane: ix,
+            })
+            .collect::<Vec<_>>();
+        for (parent_ix, parent) in parents.iter().enumerate() {
+            let parent_lane = if parent_ix == 0 {
+                self.lanes[lane] = Some(parent.clone());
+                lane
+            } else if let Some(parent_lane) = self.lane_heading_to(parent) {
+                parent_lane
+            } else {
+                let parent_lane = self.free_lane();
+                self.lanes[parent_lane] = Some(parent.clone());
+                parent_lane
+            };
+            outgoing.push(GraphSegment {
+                from_lane: lane,
+                to_lane: parent_lane,
+            });
+        }
+
+        self.lane_count = self.lane_count.max(self.lanes.len());
+        while self.lanes.last().is_some_and(Option::is_none) {
+            self.lanes.pop();
+        }
+        self.rows.push(GraphRow {
+            lane,
+            incoming,
+            outgoing,
+        });
+        self.rows.last().unwrap()
+    }
+
+    fn lane_heading_to(&self, id: &SharedString) -> Option<usize> {
+        self.lanes
+            .iter()
+            .position(|target| target.as_ref() == Some(id))
+    }
+
+    fn free_lane(&mut self) -> usize {
+        match self.lanes.iter().position(Option::is_none) {
+            Some(ix) => ix,
+            None => {
+                self.lanes.push(None);
+                self.lanes.len() - 1
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(from_lane: usize, to_lane: usize) -> GraphSegment {
+        GraphSegment { from_lane, to_lane }
+    }
+
+    fn layout(commits: &[(&str, &[&str])]) -> GraphLayout {
+        let mut layout = GraphLayout::default();
+        for (id, parents) in commits {
+            let parents = parents
+                .iter()
+                .map(|parent| SharedString::from(parent.to_string()))
+                .collect::<Vec<_>>();
+            layout.push(&SharedString::from(id.to_string()), &parents);
+        }
+        layout
+    }
+
+    #[test]
+    fn test_linear_history() {
+        let layout = layout(&[("c", &["b"]), ("b", &["a"]), ("a", &[])]);
+        assert_eq!(layout.lane_count(), 1);
+        assert_eq!(
+            layout.rows(),
+            &[
+                GraphRow {
+                    lane: 0,
+                    incoming: vec![],
+                    outgoing: vec![segment(0, 0)],
+                },
+                GraphRow {
+                    lane: 0,
+                    incoming: vec![segment(0, 0)],
+                    outgoing: vec![segment(0, 0)],
+                },
+                GraphRow {
+                    lane: 0,
+                    incoming: vec![segment(0, 0)],
+                    outgoing: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_branch_and_merge() {
+        // m merges the side branch s into the main branch b, which both fork from a.
+        let layout = layout(&[("m", &["b", "s"]), ("s", &["a"]), ("b", &["a"]), ("a", &[])]);
+        assert_eq!(layout.lane_count(), 2);
+        assert_eq!(
+            layout.rows(),
+            &[
+                GraphRow {
+                    lane: 0,
+                    incoming: vec![],
+                    outgoing: vec![segment(0, 0), segment(0, 1)],
+                },
+                GraphRow {
+                    lane: 1,
+                    incoming: vec![segment(0, 0), segment(1, 1)],
+                    outgoing: vec![segment(0, 0), segment(1, 1)],
+                },
+                GraphRow {
+                    lane: 0,
+                    incoming: vec![segment(0, 0), segment(1, 1)],
+                    outgoing: vec![segment(1, 1), segment(0, 0)],
+                },
+                GraphRow {
+                    lane: 0,
+                    incoming: vec![segment(0, 0), segment(1, 0)],
+                    outgoing: vec![],
+                },
+            ]
+        );
+        assert_eq!(layout.rows()[3].width(), 2);
+    }
+
+    #[test]
+    fn test_unrelated_heads() {
+        // Two branches whose tips are both listed before their common parent.
+        let layout = layout(&[("x", &["a"]), ("y", &["a"]), ("a", &[]), ("z", &[])]);
+        assert_eq!(layout.lane_count(), 2);
+        assert_eq!(layout.rows()[1].lane, 1);
+        assert_eq!(
+            layout.rows()[1].outgoing,
+            vec![segment(0, 0), segment(1, 1)]
+        );
+        assert_eq!(
+            layout.rows()[2],
+            GraphRow {
+                lane: 0,
+                incoming: vec![segment(0, 0), segment(1, 0)],
+                outgoing: vec![],
+            }
+        );
+        // Once every lane has converged, unrelated commits start over in the first lane.
+        assert_eq!(
+            layout.rows()[3],
+            GraphRow {
+                lane: 0,
+                incoming: vec![],
+                outgoing: vec![],
+            }
+        );
+    }
+}
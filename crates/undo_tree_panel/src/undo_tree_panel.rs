@@ -0,0 +1,510 @@
+mod undo_tree_panel_settings;
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context as _;
+use collections::HashMap;
+use db::kvp::KEY_VALUE_STORE;
+use editor::{Editor, EditorEvent};
+use fs::Fs;
+use gpui::{
+    Action, App, AsyncWindowContext, Entity, EventEmitter, FocusHandle, Focusable, KeyContext,
+    ListSizingBehavior, Pixels, ScrollStrategy, Subscription, Task, UniformListScrollHandle,
+    WeakEntity, actions, px, uniform_list,
+};
+use language::{Buffer, TransactionId};
+use menu::{Confirm, SelectFirst, SelectLast, SelectNext, SelectPrevious};
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use ui::{ListItem, prelude::*};
+use undo_tree_panel_settings::{UndoTreePanelDockPosition, UndoTreePanelSettings};
+use util::ResultExt;
+use workspace::{
+    Workspace,
+    dock::{DockPosition, Panel, PanelEvent},
+};
+
+actions!(
+    undo_tree_panel,
+    [
+        /// Toggles focus on the undo tree panel.
+        ToggleFocus,
+    ]
+);
+
+const UNDO_TREE_PANEL_KEY: &str = "UndoTreePanel";
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(50);
+
+pub fn init_settings(cx: &mut App) {
+    UndoTreePanelSettings::register(cx);
+}
+
+pub fn init(cx: &mut App) {
+    init_settings(cx);
+
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, window, cx| {
+            workspace.toggle_panel_focus::<UndoTreePanel>(window, cx);
+        });
+    })
+    .detach();
+}
+
+/// A row of the panel, showing a state of the active buffer's undo tree.
+struct UndoTreeEntry {
+    /// The state of the undo tree, or `None` for the text the history starts from.
+    state: Option<TransactionId>,
+    /// The position of the state in the order the states were created, starting from 1.
+    number: usize,
+    depth: usize,
+    edited_at: Option<Instant>,
+}
+
+struct ActiveEditor {
+    editor: WeakEntity<Editor>,
+    _subscription: Subscription,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedUndoTreePanel {
+    width: Option<Pixels>,
+    active: Option<bool>,
+}
+
+/// Shows every state of the active buffer's history, including the changes that were undone
+/// and then edited over, and goes back to any of them.
+pub struct UndoTreePanel {
+    workspace: WeakEntity<Workspace>,
+    fs: Arc<dyn Fs>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    width: Option<Pixels>,
+    active: bool,
+    active_editor: Option<ActiveEditor>,
+    entries: Vec<UndoTreeEntry>,
+    current_state: Option<TransactionId>,
+    selected_index: usize,
+    update_task: Task<()>,
+    pending_serialization: Task<Option<()>>,
+    _workspace_subscription: Subscription,
+}
+
+impl UndoTreePanel {
+    pub async fn load(
+        workspace: WeakEntity<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> anyhow::Result<Entity<Self>> {
+        let serialized_panel = match workspace
+            .read_with(&cx, |workspace, _| {
+                UndoTreePanel::serialization_key(workspace)
+            })
+            .ok()
+            .flatten()
+        {
+            Some(serialization_key) => cx
+                .background_spawn(async move { KEY_VALUE_STORE.read_kvp(&serialization_key) })
+                .await
+                .context("loading undo tree panel")
+                .log_err()
+                .flatten()
+                .map(|panel| serde_json::from_str::<SerializedUndoTreePanel>(&panel))
+                .transpose()
+                .log_err()
+                .flatten(),
+            None => None,
+        };
+
+        workspace.update_in(&mut cx, |workspace, window, cx| {
+            let panel = Self::new(workspace, window, cx);
+            if let Some(serialized_panel) = serialized_panel {
+                panel.update(cx, |panel, cx| {
+                    panel.width = serialized_panel.width.map(|px| px.round());
+                    panel.active = serialized_panel.active.unwrap_or(false);
+                    cx.notify();
+                });
+            }
+            panel
+        })
+    }
+
+    fn new(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Entity<Self> {
+        let workspace_handle = cx.entity();
+        let fs = workspace.app_state().fs.clone();
+        let active_editor = workspace.active_item_as::<Editor>(cx);
+        cx.new(|cx| {
+            let workspace_subscription = cx.subscribe_in(
+                &workspace_handle,
+                window,
+                |this, workspace, event, window, cx| {
+                    if let workspace::Event::ActiveItemChanged = event {
+                        let editor = workspace.read(cx).active_item_as::<Editor>(cx);
+                        this.set_active_editor(editor, window, cx);
+                    }
+                },
+            );
+            let mut this = Self {
+                workspace: workspace_handle.downgrade(),
+                fs,
+                focus_handle: cx.focus_handle(),
+                scroll_handle: UniformListScrollHandle::new(),
+                width: None,
+                active: false,
+                active_editor: None,
+                entries: Vec::new(),
+                current_state: None,
+                selected_index: 0,
+                update_task: Task::ready(()),
+                pending_serialization: Task::ready(None),
+                _workspace_subscription: workspace_subscription,
+            };
+            this.set_active_editor(active_editor, window, cx);
+            this
+        })
+    }
+
+    fn serialization_key(workspace: &Workspace) -> Option<String> {
+        workspace
+            .database_id()
+            .map(|id| i64::from(id).to_string())
+            .or(workspace.session_id())
+            .map(|id| format!("{}-{:?}", UNDO_TREE_PANEL_KEY, id))
+    }
+
+    fn serialize(&mut self, cx: &mut Context<Self>) {
+        let Some(serialization_key) = self
+            .workspace
+            .read_with(cx, |workspace, _| {
+                UndoTreePanel::serialization_key(workspace)
+            })
+            .ok()
+            .flatten()
+        else {
+            return;
+        };
+        let width = self.width;
+        let active = Some(self.active);
+        self.pending_serialization = cx.background_spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        serialization_key,
+                        serde_json::to_string(&SerializedUndoTreePanel { width, active })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    fn set_active_editor(
+        &mut self,
+        editor: Option<Entity<Editor>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.active_editor = editor.map(|editor| ActiveEditor {
+            editor: editor.downgrade(),
+            _subscription: cx.subscribe_in(
+                &editor,
+                window,
+                |this, _, event: &EditorEvent, window, cx| {
+                    if let EditorEvent::BufferEdited = event {
+                        // Wait for the transaction of the edit to end, as it adds the new state
+                        // to the tree.
+                        this.update_task = cx.spawn_in(window, async move |this, cx| {
+                            cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+                            this.update(cx, |this, cx| this.update_entries(cx)).ok();
+                        });
+                    }
+                },
+            ),
+        });
+        self.update_entries(cx);
+    }
+
+    fn active_buffer(&self, cx: &App) -> Option<Entity<Buffer>> {
+        let editor = self.active_editor.as_ref()?.editor.upgrade()?;
+        editor.read(cx).buffer().read(cx).as_singleton()
+    }
+
+    fn update_entries(&mut self, cx: &mut Context<Self>) {
+        self.entries.clear();
+        self.current_state = None;
+        if let Some(buffer) = self.active_buffer(cx) {
+            let buffer = buffer.read(cx);
+            let nodes = buffer.undo_tree();
+            let mut children = HashMap::<Option<TransactionId>, Vec<usize>>::default();
+            for (ix, node) in nodes.iter().enumerate() {
+                children.entry(node.parent).or_default().push(ix);
+            }
+
+            self.entries.push(UndoTreeEntry {
+                state: None,
+                number: 0,
+                depth: 0,
+                edited_at: None,
+            });
+            let mut stack = Vec::new();
+            push_children(&mut stack, children.get(&None), 0);
+            while let Some((ix, depth)) = stack.pop() {
+                let node = &nodes[ix];
+                self.entries.push(UndoTreeEntry {
+                    state: Some(node.transaction_id),
+                    number: ix + 1,
+                    depth,
+                    edited_at: Some(node.edited_at),
+                });
+                push_children(&mut stack, children.get(&Some(node.transaction_id)), depth);
+            }
+            self.current_state = buffer.undo_tree_state();
+        }
+
+        let current_index = self
+            .entries
+            .iter()
+            .position(|entry| entry.state == self.current_state);
+        self.select_index(current_index.unwrap_or(0), cx);
+        cx.notify();
+    }
+
+    fn select_index(&mut self, index: usize, cx: &mut Context<Self>) {
+        if index < self.entries.len() {
+            self.selected_index = index;
+            self.scroll_handle
+                .scroll_to_item(index, ScrollStrategy::Center);
+            cx.notify();
+        }
+    }
+
+    fn select_next(&mut self, _: &SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_index(self.selected_index + 1, cx);
+    }
+
+    fn select_previous(&mut self, _: &SelectPrevious, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_index(self.selected_index.saturating_sub(1), cx);
+    }
+
+    fn select_first(&mut self, _: &SelectFirst, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_index(0, cx);
+    }
+
+    fn select_last(&mut self, _: &SelectLast, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_index(self.entries.len().saturating_sub(1), cx);
+    }
+
+    fn confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        self.go_to_entry(self.selected_index, window, cx);
+    }
+
+    fn go_to_entry(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(entry) = self.entries.get(index) else {
+            return;
+        };
+        let state = entry.state;
+        let Some(editor) = self
+            .active_editor
+            .as_ref()
+            .and_then(|active_editor| active_editor.editor.upgrade())
+        else {
+            return;
+        };
+        editor.update(cx, |editor, cx| {
+            editor.go_to_undo_tree_state(state, window, cx);
+        });
+        self.update_entries(cx);
+    }
+
+    fn dispatch_context(&self) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("UndoTreePanel");
+        dispatch_context.add("menu");
+        dispatch_context
+    }
+
+    fn render_entry(&self, index: usize, cx: &mut Context<Self>) -> impl IntoElement {
+        let entry = &self.entries[index];
+        let indent_size = UndoTreePanelSettings::get_global(cx).indent_size;
+        let label = match entry.state {
+            Some(_) => format!("Change {}", entry.number),
+            None => "Original".to_string(),
+        };
+        let is_current = entry.state == self.current_state;
+
+        ListItem::new(index)
+            .indent_level(entry.depth)
+            .indent_step_size(px(indent_size))
+            .toggle_state(self.selected_index == index)
+            .child(h_flex().gap_2().child(Label::new(label)).when_some(
+                entry.edited_at,
+                |this, edited_at| {
+                    this.child(
+                        Label::new(time_since(edited_at))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                },
+            ))
+            .when(is_current, |this| {
+                this.end_slot(
+                    Icon::new(IconName::Check)
+                        .size(IconSize::Small)
+                        .color(Color::Accent),
+                )
+            })
+            .on_click(cx.listener(move |this, _, window, cx| {
+                this.go_to_entry(index, window, cx);
+            }))
+    }
+}
+
+/// Pushes the children of a state to the DFS stack, so that the branches created after the
+/// first child are listed, indented, right after their parent.
+fn push_children(stack: &mut Vec<(usize, usize)>, children: Option<&Vec<usize>>, depth: usize) {
+    let Some((first_child, branches)) = children.and_then(|children| children.split_first()) else {
+        return;
+    };
+    stack.push((*first_child, depth));
+    stack.extend(branches.iter().rev().map(|child| (*child, depth + 1)));
+}
+
+fn time_since(instant: Instant) -> String {
+    let seconds = instant.elapsed().as_secs();
+    match seconds {
+        0..60 => format!("{seconds}s ago"),
+        60..3600 => format!("{}m ago", seconds / 60),
+        3600..86400 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
+}
+
+impl Panel for UndoTreePanel {
+    fn persistent_name() -> &'static str {
+        "Undo Tree Panel"
+    }
+
+    fn position(&self, _: &Window, cx: &App) -> DockPosition {
+        match UndoTreePanelSettings::get_global(cx).dock {
+            UndoTreePanelDockPosition::Left => DockPosition::Left,
+            UndoTreePanelDockPosition::Right => DockPosition::Right,
+        }
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, _: &mut Window, cx: &mut Context<Self>) {
+        settings::update_settings_file::<UndoTreePanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings, _| {
+                let dock = match position {
+                    DockPosition::Left | DockPosition::Bottom => UndoTreePanelDockPosition::Left,
+                    DockPosition::Right => UndoTreePanelDockPosition::Right,
+                };
+                settings.dock = Some(dock);
+            },
+        );
+    }
+
+    fn size(&self, _: &Window, cx: &App) -> Pixels {
+        self.width
+            .unwrap_or_else(|| UndoTreePanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, window: &mut Window, cx: &mut Context<Self>) {
+        self.width = size;
+        cx.notify();
+        cx.defer_in(window, |this, _, cx| {
+            this.serialize(cx);
+        });
+    }
+
+    fn icon(&self, _: &Window, cx: &App) -> Option<IconName> {
+        UndoTreePanelSettings::get_global(cx)
+            .button
+            .then_some(IconName::HistoryRerun)
+    }
+
+    fn icon_tooltip(&self, _window: &Window, _: &App) -> Option<&'static str> {
+        Some("Undo Tree")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn starts_open(&self, _window: &Window, _: &App) -> bool {
+        self.active
+    }
+
+    fn set_active(&mut self, active: bool, _: &mut Window, cx: &mut Context<Self>) {
+        if self.active != active {
+            self.active = active;
+            self.serialize(cx);
+        }
+    }
+
+    fn activation_priority(&self) -> u32 {
+        7
+    }
+}
+
+impl Focusable for UndoTreePanel {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for UndoTreePanel {}
+
+impl Render for UndoTreePanel {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .id("undo-tree-panel")
+            .size_full()
+            .overflow_hidden()
+            .key_context(self.dispatch_context())
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::confirm))
+            .track_focus(&self.focus_handle)
+            .map(|this| {
+                if self.entries.is_empty() {
+                    this.child(
+                        v_flex()
+                            .flex_1()
+                            .p_4()
+                            .justify_center()
+                            .items_center()
+                            .child(
+                                Label::new("Open a file to show its undo tree").color(Color::Muted),
+                            ),
+                    )
+                } else {
+                    this.child(
+                        uniform_list(
+                            "undo-tree-entries",
+                            self.entries.len(),
+                            cx.processor(|this, range: std::ops::Range<usize>, _, cx| {
+                                range.map(|index| this.render_entry(index, cx)).collect()
+                            }),
+                        )
+                        .size_full()
+                        .with_sizing_behavior(ListSizingBehavior::Infer)
+                        .track_scroll(self.scroll_handle.clone()),
+                    )
+                }
+            })
+    }
+}
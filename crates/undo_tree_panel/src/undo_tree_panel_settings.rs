@@ -0,0 +1,53 @@
+use gpui::Pixels;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsKey, SettingsSources, SettingsUi};
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UndoTreePanelDockPosition {
+    Left,
+    Right,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct UndoTreePanelSettings {
+    pub button: bool,
+    pub default_width: Pixels,
+    pub dock: UndoTreePanelDockPosition,
+    pub indent_size: f32,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug, SettingsUi, SettingsKey)]
+#[settings_key(key = "undo_tree_panel")]
+pub struct UndoTreePanelSettingsContent {
+    /// Whether to show the undo tree panel button in the status bar.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// Customize default width (in pixels) taken by undo tree panel
+    ///
+    /// Default: 300
+    pub default_width: Option<f32>,
+    /// The position of undo tree panel
+    ///
+    /// Default: right
+    pub dock: Option<UndoTreePanelDockPosition>,
+    /// Amount of indentation (in pixels) for branches of the undo tree.
+    ///
+    /// Default: 16
+    pub indent_size: Option<f32>,
+}
+
+impl Settings for UndoTreePanelSettings {
+    type FileContent = UndoTreePanelSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut gpui::App,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+
+    fn import_from_vscode(_: &settings::VsCodeSettings, _: &mut Self::FileContent) {}
+}
@@ -0,0 +1,245 @@
+use editor::Editor;
+use gpui::{Action, App, Context, Window};
+use itertools::Itertools;
+use settings::Settings;
+
+use crate::{
+    Vim, VimSettings,
+    state::{Mode, VimGlobals},
+};
+
+/// Defines an abbreviation, or shows the defined ones when no abbreviation is given.
+#[derive(Clone, Debug, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+pub(crate) struct Abbreviate {
+    pub(crate) insert: bool,
+    pub(crate) command_line: bool,
+    pub(crate) abbreviation: Option<(String, String)>,
+}
+
+/// Removes an abbreviation.
+#[derive(Clone, Debug, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+pub(crate) struct Unabbreviate {
+    pub(crate) insert: bool,
+    pub(crate) command_line: bool,
+    pub(crate) abbreviation: String,
+}
+
+impl Abbreviate {
+    pub(crate) fn new(insert: bool, command_line: bool) -> Self {
+        Self {
+            insert,
+            command_line,
+            abbreviation: None,
+        }
+    }
+
+    /// Parses the `{lhs} {rhs}` arguments of `:abbreviate` and its variants.
+    pub(crate) fn with_args(action: Box<dyn Action>, args: String) -> Option<Box<dyn Action>> {
+        let action = action.as_any().downcast_ref::<Self>()?;
+        let (lhs, rhs) = args.split_once(char::is_whitespace)?;
+        let rhs = rhs.trim();
+        if rhs.is_empty() {
+            return None;
+        }
+        Some(
+            Self {
+                abbreviation: Some((lhs.to_owned(), rhs.to_owned())),
+                ..action.clone()
+            }
+            .boxed_clone(),
+        )
+    }
+}
+
+pub(crate) fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
+    Vim::action(editor, cx, |vim, action: &Abbreviate, _, cx| {
+        let Some((lhs, rhs)) = &action.abbreviation else {
+            vim.status_label = Some(list_abbreviations(action, cx).into());
+            cx.notify();
+            return;
+        };
+        let globals = Vim::globals(cx);
+        if action.insert {
+            globals
+                .insert_abbreviations
+                .insert(lhs.clone(), rhs.clone());
+        }
+        if action.command_line {
+            globals
+                .command_line_abbreviations
+                .insert(lhs.clone(), rhs.clone());
+        }
+    });
+
+    Vim::action(editor, cx, |_, action: &Unabbreviate, _, cx| {
+        let globals = Vim::globals(cx);
+        if action.insert {
+            globals.insert_abbreviations.remove(&action.abbreviation);
+        }
+        if action.command_line {
+            globals
+                .command_line_abbreviations
+                .remove(&action.abbreviation);
+        }
+    });
+}
+
+fn list_abbreviations(action: &Abbreviate, cx: &App) -> String {
+    let globals = cx.global::<VimGlobals>();
+    let mut abbreviations = Vec::new();
+    if action.insert {
+        let settings_abbreviations = &VimSettings::get_global(cx).abbreviations;
+        abbreviations.extend(
+            settings_abbreviations
+                .iter()
+                .filter(|(lhs, _)| !globals.insert_abbreviations.contains_key(*lhs))
+                .chain(&globals.insert_abbreviations)
+                .map(|(lhs, rhs)| format!("i {lhs} {rhs}")),
+        );
+    }
+    if action.command_line {
+        abbreviations.extend(
+            globals
+                .command_line_abbreviations
+                .iter()
+                .map(|(lhs, rhs)| format!("c {lhs} {rhs}")),
+        );
+    }
+    abbreviations.sort();
+    abbreviations.join(", ")
+}
+
+fn insert_abbreviation<'a>(word: &str, cx: &'a App) -> Option<&'a String> {
+    cx.global::<VimGlobals>()
+        .insert_abbreviations
+        .get(word)
+        .or_else(|| VimSettings::get_global(cx).abbreviations.get(word))
+}
+
+/// Expands the command-line abbreviations among the words of an ex command, returning `None`
+/// when there is nothing to expand.
+pub(crate) fn expand_command_line_abbreviations(input: &str, cx: &App) -> Option<String> {
+    let abbreviations = &cx.try_global::<VimGlobals>()?.command_line_abbreviations;
+    if !input
+        .split(' ')
+        .any(|word| abbreviations.contains_key(word))
+    {
+        return None;
+    }
+    Some(
+        input
+            .split(' ')
+            .map(|word| abbreviations.get(word).map_or(word, String::as_str))
+            .join(" "),
+    )
+}
+
+impl Vim {
+    /// Expands the insert mode abbreviations that end right before the typed text, when it is a
+    /// non-keyword character.
+    pub(crate) fn expand_abbreviation(
+        &mut self,
+        text: &str,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.mode != Mode::Insert {
+            return;
+        }
+        let mut chars = text.chars();
+        let (Some(typed), None) = (chars.next(), chars.next()) else {
+            return;
+        };
+        if cx.global::<VimGlobals>().insert_abbreviations.is_empty()
+            && VimSettings::get_global(cx).abbreviations.is_empty()
+        {
+            return;
+        }
+
+        self.update_editor(cx, |_, editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let mut edits = Vec::new();
+            for selection in editor.selections.all::<usize>(cx) {
+                if !selection.is_empty() {
+                    continue;
+                }
+                let Some(typed_start) = selection.head().checked_sub(typed.len_utf8()) else {
+                    continue;
+                };
+                let classifier = snapshot.char_classifier_at(typed_start);
+                if classifier.is_word(typed) || snapshot.chars_at(typed_start).next() != Some(typed)
+                {
+                    continue;
+                }
+                let mut word = snapshot
+                    .reversed_chars_at(typed_start)
+                    .take_while(|c| classifier.is_word(*c))
+                    .collect::<Vec<_>>();
+                word.reverse();
+                let word = word.into_iter().collect::<String>();
+                if let Some(expansion) = insert_abbreviation(&word, cx) {
+                    edits.push((typed_start - word.len()..typed_start, expansion.clone()));
+                }
+            }
+            if !edits.is_empty() {
+                editor.edit(edits, cx);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use collections::HashMap;
+    use settings::SettingsStore;
+
+    use crate::{VimSettings, state::Mode, test::VimTestContext};
+
+    #[gpui::test]
+    async fn test_abbreviations_from_settings(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<VimSettings>(cx, |s| {
+                let mut abbreviations = HashMap::default();
+                abbreviations.insert("teh".into(), "the".into());
+                s.abbreviations = Some(abbreviations);
+            });
+        });
+
+        cx.set_state("ˇ", Mode::Normal);
+        cx.simulate_keystrokes("i t e h space t e h s space t e h . escape");
+        cx.assert_state("the tehs theˇ.", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_iabbrev(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇ", Mode::Normal);
+        cx.simulate_keystrokes(": i a b b r e v space f n space f u n c t i o n enter");
+        cx.simulate_keystrokes("i f n space f n escape");
+        cx.assert_state("function fˇn", Mode::Normal);
+
+        cx.set_state("ˇa\nˇb", Mode::Normal);
+        cx.simulate_keystrokes("shift-a space f n ( escape");
+        cx.assert_state("a functionˇ(\nb functionˇ(", Mode::Normal);
+
+        cx.simulate_keystrokes(": i u n a b b r e v space f n enter");
+        cx.set_state("ˇ", Mode::Normal);
+        cx.simulate_keystrokes("i f n space escape");
+        cx.assert_state("fnˇ ", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_cabbrev(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa\nb\nc", Mode::Normal);
+        cx.simulate_keystrokes(": c a b b r e v space L a s t space $ enter");
+        cx.simulate_keystrokes(": L a s t enter");
+        cx.assert_state("a\nb\nˇc", Mode::Normal);
+    }
+}
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use collections::{HashMap, HashSet};
 use command_palette_hooks::CommandInterceptResult;
 use editor::{
@@ -6,15 +6,20 @@ use editor::{
     actions::{SortLinesCaseInsensitive, SortLinesCaseSensitive},
     display_map::ToDisplayPoint,
 };
-use gpui::{Action, App, AppContext as _, Context, Global, Keystroke, Window, actions};
+use futures::FutureExt as _;
+use gpui::{
+    Action, App, AppContext as _, AsyncWindowContext, Context, Entity, Focusable, Global,
+    Keystroke, Task, Window, actions,
+};
 use itertools::Itertools;
-use language::Point;
+use language::{Buffer, Point};
 use multi_buffer::MultiBufferRow;
 use project::ProjectPath;
 use regex::Regex;
 use schemars::JsonSchema;
-use search::{BufferSearchBar, SearchOptions};
+use search::{BufferSearchBar, ProjectSearchView, SearchOptions};
 use serde::Deserialize;
+use settings::Settings as _;
 use std::{
     io::Write,
     iter::Peekable,
@@ -23,24 +28,25 @@ use std::{
     process::Stdio,
     str::Chars,
     sync::{Arc, OnceLock},
-    time::Instant,
+    time::{Duration, Instant},
 };
 use task::{HideStrategy, RevealStrategy, SpawnInTerminal, TaskId};
 use ui::ActiveTheme;
 use util::ResultExt;
-use workspace::{Item, SaveIntent, notifications::NotifyResultExt};
+use workspace::{Item, SaveIntent, Workspace, notifications::NotifyResultExt};
 use workspace::{SplitDirection, notifications::DetachAndPromptErr};
 use zed_actions::{OpenDocs, RevealTarget};
 
 use crate::{
-    ToggleMarksView, ToggleRegistersView, Vim,
+    ToggleMarksView, ToggleRegistersView, Vim, VimAddon, VimSettings,
+    abbreviation::{Abbreviate, Unabbreviate, expand_command_line_abbreviations},
     motion::{EndOfDocument, Motion, MotionKind, StartOfDocument},
     normal::{
         JoinLines,
         search::{FindCommand, ReplaceCommand, Replacement},
     },
     object::Object,
-    state::{Mark, Mode},
+    state::{Mark, Mode, VimGlobals},
     visual::VisualDeleteLine,
 };
 
@@ -217,6 +223,53 @@ struct VimNorm {
     pub command: String,
 }
 
+/// Goes to an older or newer state of the undo tree, even if it is on another branch.
+#[derive(Clone, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+struct UndoTreeJump {
+    older: bool,
+    offset: UndoTreeOffset,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum UndoTreeOffset {
+    /// A number of changes, counted in the order they were made.
+    Changes(usize),
+    /// An amount of time before or after the last edit of the current state.
+    Time(Duration),
+}
+
+impl UndoTreeJump {
+    /// Parses the `{N}`, `{N}s`, `{N}m`, `{N}h` or `{N}d` argument of `:earlier` and `:later`.
+    fn with_args(action: Box<dyn Action>, args: String) -> Option<Box<dyn Action>> {
+        let action = action.as_any().downcast_ref::<Self>()?;
+        let unit_start = args
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(args.len());
+        let count = args[..unit_start].parse::<u64>().ok()?;
+        let offset = match &args[unit_start..] {
+            "" => UndoTreeOffset::Changes(count as usize),
+            unit => {
+                let unit_seconds = match unit {
+                    "s" => 1,
+                    "m" => 60,
+                    "h" => 60 * 60,
+                    "d" => 24 * 60 * 60,
+                    _ => return None,
+                };
+                UndoTreeOffset::Time(Duration::from_secs(count.checked_mul(unit_seconds)?))
+            }
+        };
+        Some(
+            Self {
+                offset,
+                ..action.clone()
+            }
+            .boxed_clone(),
+        )
+    }
+}
+
 #[derive(Debug)]
 struct WrappedAction(Box<dyn Action>);
 
@@ -455,6 +508,16 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
         });
     });
 
+    Vim::action(editor, cx, |vim, action: &UndoTreeJump, window, cx| {
+        vim.go_to_undo_tree_state(window, cx, |buffer| match action.offset {
+            UndoTreeOffset::Changes(count) if action.older => {
+                buffer.undo_tree_state_by_changes(-(count as isize))
+            }
+            UndoTreeOffset::Changes(count) => buffer.undo_tree_state_by_changes(count as isize),
+            UndoTreeOffset::Time(offset) => buffer.undo_tree_state_by_time(offset, action.older),
+        });
+    });
+
     Vim::action(editor, cx, |vim, action: &VimNorm, window, cx| {
         let keystrokes = action
             .command
@@ -494,7 +557,7 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
         });
         let had_range = action.range.is_some();
 
-        cx.spawn_in(window, async move |vim, cx| {
+        let task = cx.spawn_in(window, async move |vim, cx| {
             task.await;
             vim.update_in(cx, |vim, window, cx| {
                 vim.update_editor(cx, |_, editor, cx| {
@@ -524,8 +587,8 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
                 });
             })
             .ok();
-        })
-        .detach();
+        });
+        vim.track_ex_command_task(task, cx);
     });
 
     Vim::action(editor, cx, |vim, _: &CountCommand, window, cx| {
@@ -652,6 +715,38 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
 
     Vim::action(editor, cx, |vim, action: &ShellExec, window, cx| {
         action.run(vim, window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, action: &RunInEach, window, cx| {
+        action.run(vim, window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, action: &RunUserCommand, window, cx| {
+        action.run(vim, window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, action: &DefineCommand, window, cx| {
+        action.run(vim, window, cx)
+    });
+
+    Vim::action(editor, cx, |_, action: &DeleteCommand, _, cx| {
+        Vim::globals(cx).user_commands.remove(&action.name);
+    });
+
+    Vim::action(editor, cx, |vim, action: &VimArgs, window, cx| {
+        let Some(first_file) = action.files.first() else {
+            vim.status_label = Some(Vim::globals(cx).arg_list.join(" ").into());
+            cx.notify();
+            return;
+        };
+        window.dispatch_action(
+            VimEdit {
+                filename: first_file.clone(),
+            }
+            .boxed_clone(),
+            cx,
+        );
+        Vim::globals(cx).arg_list = action.files.clone();
     })
 }
 
@@ -1170,6 +1265,14 @@ fn generate_commands(_: &App) -> Vec<VimCommand> {
         VimCommand::new(("br", "ewind"), workspace::ActivateItem(0)),
         VimCommand::new(("bl", "ast"), workspace::ActivateLastItem),
         VimCommand::str(("buffers", ""), "tab_switcher::ToggleAll"),
+        VimCommand::new(("ar", "gs"), VimArgs { files: Vec::new() }).args(|_, args| {
+            Some(
+                VimArgs {
+                    files: args.split_whitespace().map(ToOwned::to_owned).collect(),
+                }
+                .boxed_clone(),
+            )
+        }),
         VimCommand::str(("ls", ""), "tab_switcher::ToggleAll"),
         VimCommand::new(("new", ""), workspace::NewFileSplitHorizontal),
         VimCommand::new(("vne", "w"), workspace::NewFileSplitVertical),
@@ -1232,6 +1335,22 @@ fn generate_commands(_: &App) -> Vec<VimCommand> {
             editor::actions::GoToPreviousDiagnostic::default(),
         )
         .range(wrap_count),
+        VimCommand::new(
+            ("ea", "rlier"),
+            UndoTreeJump {
+                older: true,
+                offset: UndoTreeOffset::Changes(1),
+            },
+        )
+        .args(UndoTreeJump::with_args),
+        VimCommand::new(
+            ("lat", "er"),
+            UndoTreeJump {
+                older: false,
+                offset: UndoTreeOffset::Changes(1),
+            },
+        )
+        .args(UndoTreeJump::with_args),
         VimCommand::new(("j", "oin"), JoinLines).range(select_range),
         VimCommand::new(("fo", "ld"), editor::actions::FoldSelectedRanges).range(act_on_range),
         VimCommand::new(("foldo", "pen"), editor::actions::UnfoldLines)
@@ -1283,6 +1402,48 @@ fn generate_commands(_: &App) -> Vec<VimCommand> {
         VimCommand::new(("cpp", "link"), editor::actions::CopyPermalinkToLine).range(act_on_range),
         VimCommand::str(("opt", "ions"), "zed::OpenDefaultSettings"),
         VimCommand::str(("map", ""), "vim::OpenDefaultKeymap"),
+        VimCommand::new(("ab", "breviate"), Abbreviate::new(true, true))
+            .args(Abbreviate::with_args),
+        VimCommand::new(("ia", "bbrev"), Abbreviate::new(true, false)).args(Abbreviate::with_args),
+        VimCommand::new(("ca", "bbrev"), Abbreviate::new(false, true)).args(Abbreviate::with_args),
+        VimCommand::new(("una", "bbreviate"), ArgumentRequired).args(|_, args| {
+            Some(
+                Unabbreviate {
+                    insert: true,
+                    command_line: true,
+                    abbreviation: args,
+                }
+                .boxed_clone(),
+            )
+        }),
+        VimCommand::new(("iuna", "bbrev"), ArgumentRequired).args(|_, args| {
+            Some(
+                Unabbreviate {
+                    insert: true,
+                    command_line: false,
+                    abbreviation: args,
+                }
+                .boxed_clone(),
+            )
+        }),
+        VimCommand::new(("cuna", "bbrev"), ArgumentRequired).args(|_, args| {
+            Some(
+                Unabbreviate {
+                    insert: false,
+                    command_line: true,
+                    abbreviation: args,
+                }
+                .boxed_clone(),
+            )
+        }),
+        VimCommand::new(("com", "mand"), DefineCommand::default())
+            .bang(DefineCommand {
+                overwrite: true,
+                ..Default::default()
+            })
+            .args(DefineCommand::with_args),
+        VimCommand::new(("delc", "ommand"), ArgumentRequired)
+            .args(|_, name| Some(DeleteCommand { name }.boxed_clone())),
         VimCommand::new(("h", "elp"), OpenDocs),
     ]
 }
@@ -1338,11 +1499,19 @@ pub fn command_interceptor(mut input: &str, cx: &App) -> Vec<CommandInterceptRes
     while input.starts_with(':') {
         input = &input[1..];
     }
+    let expanded_input = expand_command_line_abbreviations(input, cx);
+    let input = expanded_input.as_deref().unwrap_or(input);
 
     let (range, query) = VimCommand::parse_range(input);
     let range_prefix = input[0..(input.len() - query.len())].to_string();
     let query = query.as_str().trim();
 
+    if range.is_none()
+        && let Some(result) = user_command_result(query, cx)
+    {
+        return vec![result];
+    }
+
     let action = if range.is_some() && query.is_empty() {
         Some(
             GoToLine {
@@ -1368,6 +1537,8 @@ pub fn command_interceptor(mut input: &str, cx: &App) -> Vec<CommandInterceptRes
             }
         }
         return commands;
+    } else if let Some(run_in_each) = RunInEach::parse(query, cx) {
+        Some(run_in_each.boxed_clone())
     } else if query.starts_with('s') {
         let mut substitute = "substitute".chars().peekable();
         let mut query = query.chars().peekable();
@@ -1423,6 +1594,7 @@ pub fn command_interceptor(mut input: &str, cx: &App) -> Vec<CommandInterceptRes
         }];
     }
 
+    let mut results = Vec::new();
     for command in commands(cx).iter() {
         if let Some(action) = command.parse(query, &range, cx) {
             let mut string = ":".to_owned() + &range_prefix + command.prefix + command.suffix;
@@ -1431,14 +1603,18 @@ pub fn command_interceptor(mut input: &str, cx: &App) -> Vec<CommandInterceptRes
             }
             let positions = generate_positions(&string, &(range_prefix + query));
 
-            return vec![CommandInterceptResult {
+            results.push(CommandInterceptResult {
                 action,
                 string,
                 positions,
-            }];
+            });
+            break;
         }
     }
-    Vec::default()
+    if range.is_none() {
+        results.extend(user_command_completions(query, cx));
+    }
+    results
 }
 
 fn generate_positions(string: &str, query: &str) -> Vec<usize> {
@@ -1595,7 +1771,7 @@ impl OnMatchingLines {
             });
         };
 
-        vim.update_editor(cx, |_, editor, cx| {
+        let task = vim.update_editor(cx, |_, editor, cx| {
             let snapshot = editor.snapshot(window, cx);
             let mut row = range.start.0;
 
@@ -1658,8 +1834,445 @@ impl OnMatchingLines {
                     })
                     .ok();
             })
-            .detach();
         });
+        if let Some(task) = task {
+            vim.track_ex_command_task(task, cx);
+        }
+    }
+}
+
+/// Where `:bufdo`, `:windo`, `:cdo`, `:cfdo` and `:argdo` run their command.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BatchTargets {
+    /// Every buffer that is open in the workspace.
+    Buffers,
+    /// The active editor of every pane.
+    Windows,
+    /// Every match of the project search.
+    SearchMatches,
+    /// Every file with matches of the project search.
+    SearchFiles,
+    /// Every file of the argument list.
+    ArgList,
+}
+
+/// Runs a command in each buffer, pane, project search result or file of the argument list.
+#[derive(Clone, Debug, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+struct RunInEach {
+    targets: BatchTargets,
+    action: WrappedAction,
+}
+
+/// Sets the argument list and opens its first file, or shows the argument list.
+#[derive(Clone, Debug, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+struct VimArgs {
+    files: Vec<String>,
+}
+
+enum BatchTarget {
+    Editor(Entity<Editor>),
+    Buffer(Entity<Buffer>, text::Anchor),
+    Path(ProjectPath),
+}
+
+impl RunInEach {
+    fn parse(query: &str, cx: &App) -> Option<Self> {
+        const COMMANDS: [(&str, &str, BatchTargets); 5] = [
+            ("bufd", "o", BatchTargets::Buffers),
+            ("windo", "", BatchTargets::Windows),
+            ("cdo", "", BatchTargets::SearchMatches),
+            ("cfd", "o", BatchTargets::SearchFiles),
+            ("argdo", "", BatchTargets::ArgList),
+        ];
+        let (name, command) = query.split_once(' ')?;
+        let targets = COMMANDS.iter().find_map(|(prefix, suffix, targets)| {
+            let rest = name.strip_prefix(prefix)?;
+            suffix.starts_with(rest).then_some(*targets)
+        })?;
+        let action = command_interceptor(command.trim(), cx)
+            .first()?
+            .action
+            .boxed_clone();
+        Some(Self {
+            targets,
+            action: WrappedAction(action),
+        })
+    }
+
+    fn targets(&self, workspace: &Workspace, cx: &App) -> Result<Vec<BatchTarget>> {
+        let targets = match self.targets {
+            BatchTargets::Buffers => {
+                let mut buffer_ids = HashSet::default();
+                workspace
+                    .items_of_type::<Editor>(cx)
+                    .filter(|editor| {
+                        editor
+                            .read(cx)
+                            .buffer()
+                            .read(cx)
+                            .as_singleton()
+                            .is_some_and(|buffer| buffer_ids.insert(buffer.entity_id()))
+                    })
+                    .map(BatchTarget::Editor)
+                    .collect()
+            }
+            BatchTargets::Windows => workspace
+                .panes()
+                .iter()
+                .filter_map(|pane| pane.read(cx).active_item()?.downcast::<Editor>())
+                .map(BatchTarget::Editor)
+                .collect(),
+            BatchTargets::SearchMatches | BatchTargets::SearchFiles => {
+                let search_view = workspace
+                    .active_item_as::<ProjectSearchView>(cx)
+                    .or_else(|| workspace.items_of_type::<ProjectSearchView>(cx).next())
+                    .context("No project search results")?;
+                let search_view = search_view.read(cx);
+                let results_buffer = search_view.results_editor().read(cx).buffer().read(cx);
+                let mut buffer_ids = HashSet::default();
+                search_view
+                    .get_matches(cx)
+                    .into_iter()
+                    .filter_map(|range| {
+                        let buffer_id = range.start.buffer_id?;
+                        if self.targets == BatchTargets::SearchFiles
+                            && !buffer_ids.insert(buffer_id)
+                        {
+                            return None;
+                        }
+                        let buffer = results_buffer.buffer(buffer_id)?;
+                        Some(BatchTarget::Buffer(buffer, range.start.text_anchor))
+                    })
+                    .collect()
+            }
+            BatchTargets::ArgList => {
+                let Some(worktree) = workspace.project().read(cx).visible_worktrees(cx).next()
+                else {
+                    return Ok(Vec::new());
+                };
+                let worktree_id = worktree.read(cx).id();
+                cx.global::<VimGlobals>()
+                    .arg_list
+                    .iter()
+                    .map(|file| {
+                        BatchTarget::Path(ProjectPath {
+                            worktree_id,
+                            path: Arc::from(Path::new(file)),
+                        })
+                    })
+                    .collect()
+            }
+        };
+        Ok(targets)
+    }
+
+    pub fn run(&self, vim: &mut Vim, window: &mut Window, cx: &mut Context<Vim>) {
+        let Some(workspace) = vim.workspace(window) else {
+            return;
+        };
+        let Some(targets) = workspace.update(cx, |workspace, cx| {
+            self.targets(workspace, cx).notify_err(workspace, cx)
+        }) else {
+            return;
+        };
+
+        let action = self.action.clone();
+        let workspace = workspace.downgrade();
+        cx.spawn_in(window, async move |_, cx| {
+            // Commands that run in the same editor one after another, like `:cdo` does for the
+            // matches of a file, are grouped in one transaction so they are undone together.
+            let mut transaction_editor: Option<Entity<Editor>> = None;
+            for target in targets {
+                let editor = match target {
+                    BatchTarget::Editor(editor) => {
+                        let Ok(_) = workspace.update_in(cx, |workspace, window, cx| {
+                            workspace.activate_item(&editor, true, true, window, cx)
+                        }) else {
+                            break;
+                        };
+                        editor
+                    }
+                    BatchTarget::Buffer(buffer, position) => {
+                        let Ok(editor) = workspace.update_in(cx, |workspace, window, cx| {
+                            let pane = workspace.active_pane().clone();
+                            let editor = workspace.open_project_item::<Editor>(
+                                pane,
+                                buffer.clone(),
+                                true,
+                                true,
+                                window,
+                                cx,
+                            );
+                            let point = buffer.read(cx).summary_for_anchor::<Point>(&position);
+                            editor.update(cx, |editor, cx| {
+                                editor.change_selections(Default::default(), window, cx, |s| {
+                                    s.select_ranges([point..point])
+                                });
+                            });
+                            editor
+                        }) else {
+                            break;
+                        };
+                        editor
+                    }
+                    BatchTarget::Path(project_path) => {
+                        let Ok(open_task) = workspace.update_in(cx, |workspace, window, cx| {
+                            workspace.open_path(project_path, None, true, window, cx)
+                        }) else {
+                            break;
+                        };
+                        match open_task.await {
+                            Ok(item) => {
+                                let Some(editor) = item.downcast::<Editor>() else {
+                                    continue;
+                                };
+                                editor
+                            }
+                            Err(e) => {
+                                log::error!("Failed to open an argument list file: {e:#}");
+                                continue;
+                            }
+                        }
+                    }
+                };
+                let Ok(Some(target_vim)) = editor.read_with(cx, |editor, _| {
+                    editor.addon::<VimAddon>().map(|addon| addon.entity.clone())
+                }) else {
+                    continue;
+                };
+
+                if transaction_editor.as_ref() != Some(&editor) {
+                    if let Some(previous_editor) = transaction_editor.take() {
+                        previous_editor
+                            .update(cx, |editor, cx| {
+                                editor.end_transaction_at(Instant::now(), cx);
+                            })
+                            .ok();
+                    }
+                    editor
+                        .update_in(cx, |editor, window, cx| {
+                            editor.start_transaction_at(Instant::now(), window, cx);
+                        })
+                        .ok();
+                    transaction_editor = Some(editor);
+                }
+
+                dispatch_ex_command(&*action, &target_vim, cx).await;
+            }
+            if let Some(editor) = transaction_editor {
+                editor
+                    .update(cx, |editor, cx| {
+                        editor.end_transaction_at(Instant::now(), cx);
+                    })
+                    .ok();
+            }
+        })
+        .detach();
+    }
+}
+
+/// Dispatches an ex command to the editor of the given vim, and waits for the work that it leaves
+/// running in the background (like `:s` does), or for the command it dispatches in turn.
+async fn dispatch_ex_command(action: &dyn Action, vim: &Entity<Vim>, cx: &mut AsyncWindowContext) {
+    vim.update_in(cx, |vim, window, cx| {
+        let Some(editor) = vim.editor() else {
+            return;
+        };
+        let focus_handle = editor.read(cx).focus_handle(cx);
+        focus_handle.dispatch_action(action, window, cx);
+    })
+    .ok();
+    while let Ok(Some(task)) = vim.read_with(cx, |vim, _| vim.ex_command_task.clone()) {
+        if task.peek().is_some() {
+            break;
+        }
+        task.await;
+    }
+}
+
+/// Runs the steps of a user-defined ex command one after another.
+#[derive(Clone, Debug, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+struct RunUserCommand {
+    name: String,
+    args: String,
+    steps: Vec<String>,
+}
+
+/// Defines an ex command, or shows the user-defined ones when no name is given.
+#[derive(Clone, Debug, Default, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+struct DefineCommand {
+    name: String,
+    steps: Vec<String>,
+    overwrite: bool,
+}
+
+/// Removes an ex command defined with `:command`.
+#[derive(Clone, Debug, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+struct DeleteCommand {
+    name: String,
+}
+
+/// Returns the steps of the user-defined command with the given name, preferring the commands
+/// defined with `:command` over the ones from the settings.
+fn user_command_steps<'a>(name: &str, cx: &'a App) -> Option<&'a Vec<String>> {
+    cx.try_global::<VimGlobals>()
+        .and_then(|globals| globals.user_commands.get(name))
+        .or_else(|| VimSettings::get_global(cx).commands.get(name))
+}
+
+fn user_command_names(cx: &App) -> Vec<&String> {
+    cx.try_global::<VimGlobals>()
+        .into_iter()
+        .flat_map(|globals| globals.user_commands.keys())
+        .chain(VimSettings::get_global(cx).commands.keys())
+        .sorted()
+        .dedup()
+        .collect()
+}
+
+fn user_command_result(query: &str, cx: &App) -> Option<CommandInterceptResult> {
+    let (name, args) = query.split_once(' ').unwrap_or((query, ""));
+    let steps = user_command_steps(name, cx)?;
+    let string = format!(":{query}");
+    Some(CommandInterceptResult {
+        positions: generate_positions(&string, query),
+        action: RunUserCommand {
+            name: name.to_owned(),
+            args: args.trim().to_owned(),
+            steps: steps.clone(),
+        }
+        .boxed_clone(),
+        string,
+    })
+}
+
+fn user_command_completions(query: &str, cx: &App) -> Vec<CommandInterceptResult> {
+    if query.is_empty() || query.contains(' ') {
+        return Vec::new();
+    }
+    user_command_names(cx)
+        .into_iter()
+        .filter(|name| name.starts_with(query))
+        .filter_map(|name| user_command_result(name, cx))
+        .collect()
+}
+
+impl DefineCommand {
+    /// Parses the `{name} {replacement}` arguments of `:command`, where the replacement is made
+    /// of steps separated by `|`.
+    fn with_args(action: Box<dyn Action>, args: String) -> Option<Box<dyn Action>> {
+        let action = action.as_any().downcast_ref::<Self>()?;
+        let (name, replacement) = args.split_once(char::is_whitespace)?;
+
+        let mut steps = vec![String::new()];
+        let mut chars = replacement.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' if chars.peek() == Some(&'|') => {
+                    chars.next();
+                    steps.last_mut()?.push('|');
+                }
+                '|' => steps.push(String::new()),
+                c => steps.last_mut()?.push(c),
+            }
+        }
+        let steps = steps
+            .iter()
+            .map(|step| step.trim())
+            .filter(|step| !step.is_empty())
+            .map(ToOwned::to_owned)
+            .collect::<Vec<_>>();
+        if steps.is_empty() {
+            return None;
+        }
+
+        Some(
+            Self {
+                name: name.to_owned(),
+                steps,
+                overwrite: action.overwrite,
+            }
+            .boxed_clone(),
+        )
+    }
+
+    fn run(&self, vim: &mut Vim, window: &mut Window, cx: &mut Context<Vim>) {
+        if self.name.is_empty() {
+            let commands = user_command_names(cx)
+                .into_iter()
+                .filter_map(|name| {
+                    let steps = user_command_steps(name, cx)?;
+                    Some(format!("{name} {}", steps.join(" | ")))
+                })
+                .join(", ");
+            vim.status_label = Some(commands.into());
+            cx.notify();
+            return;
+        }
+
+        let result = if !self.name.starts_with(|c: char| c.is_ascii_uppercase())
+            || !self.name.chars().all(|c| c.is_ascii_alphanumeric())
+        {
+            Err(anyhow::anyhow!(
+                "User-defined command names must start with an uppercase letter: {}",
+                self.name
+            ))
+        } else if !self.overwrite && user_command_steps(&self.name, cx).is_some() {
+            Err(anyhow::anyhow!(
+                "Command already exists, add ! to replace it: {}",
+                self.name
+            ))
+        } else {
+            Vim::globals(cx)
+                .user_commands
+                .insert(self.name.clone(), self.steps.clone());
+            Ok(())
+        };
+        if let Some(workspace) = vim.workspace(window) {
+            workspace.update(cx, |workspace, cx| {
+                result.notify_err(workspace, cx);
+            });
+        }
+    }
+}
+
+impl RunUserCommand {
+    pub fn run(&self, vim: &mut Vim, window: &mut Window, cx: &mut Context<Vim>) {
+        let Some(workspace) = vim.workspace(window) else {
+            return;
+        };
+        let actions = self
+            .steps
+            .iter()
+            .map(|step| {
+                let step = step.replace("<args>", &self.args);
+                let step = step.trim().trim_start_matches(':');
+                cx.build_action(step, None)
+                    .ok()
+                    .or_else(|| {
+                        let result = command_interceptor(step, cx).into_iter().next()?;
+                        Some(result.action)
+                    })
+                    .with_context(|| format!("Unknown step of command {}: {step}", self.name))
+            })
+            .collect::<Result<Vec<_>>>();
+        let Some(actions) = workspace.update(cx, |workspace, cx| actions.notify_err(workspace, cx))
+        else {
+            return;
+        };
+
+        let vim = cx.entity();
+        cx.spawn_in(window, async move |_, cx| {
+            for action in actions {
+                dispatch_ex_command(&*action, &vim, cx).await;
+            }
+        })
+        .detach();
     }
 }
 
@@ -1673,6 +2286,14 @@ pub struct ShellExec {
 }
 
 impl Vim {
+    /// Keeps track of the work that an ex command leaves running, so that the commands that run
+    /// it in many buffers, like `:bufdo`, can wait for it before moving on to the next buffer.
+    pub(crate) fn track_ex_command_task(&mut self, task: Task<()>, cx: &mut Context<Self>) {
+        let task = task.shared();
+        self.ex_command_task = Some(task.clone());
+        cx.spawn(async move |_, _| task.await).detach();
+    }
+
     pub fn cancel_running_command(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         if self.running_command.take().is_some() {
             self.update_editor(cx, |_, editor, cx| {
@@ -1865,6 +2486,7 @@ impl ShellExec {
                     show_summary: false,
                     show_command: false,
                     show_rerun: false,
+                    problem_matcher: Vec::new(),
                 };
 
                 let task_status = workspace.spawn_in_terminal(spawn_in_terminal, window, cx);
@@ -2014,13 +2636,16 @@ mod test {
     use std::path::Path;
 
     use crate::{
-        VimAddon,
+        VimAddon, VimSettings,
+        command::command_interceptor,
         state::Mode,
         test::{NeovimBackedTestContext, VimTestContext},
     };
+    use collections::HashMap;
     use editor::Editor;
-    use gpui::{Context, TestAppContext};
+    use gpui::{App, Context, TestAppContext};
     use indoc::indoc;
+    use settings::SettingsStore;
     use util::path;
     use workspace::Workspace;
 
@@ -2234,6 +2859,143 @@ mod test {
         assert_eq!(file_path, Path::new(expected_path));
     }
 
+    fn open_buffer_text(workspace: &Workspace, expected_path: &str, cx: &App) -> Option<String> {
+        workspace.items_of_type::<Editor>(cx).find_map(|editor| {
+            let buffer = editor.read(cx).buffer().read(cx).as_singleton()?;
+            let file = buffer.read(cx).file()?;
+            (file.as_local()?.abs_path(cx) == Path::new(expected_path))
+                .then(|| buffer.read(cx).text())
+        })
+    }
+
+    #[gpui::test]
+    async fn test_command_bufdo(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        let fs = cx.workspace(|workspace, _, cx| workspace.project().read(cx).fs().clone());
+        fs.as_fake()
+            .insert_file(path!("/root/dir/file_2.rs"), b"foo\nbar foo\n".to_vec())
+            .await;
+
+        cx.set_state("ˇfoo foo\n", Mode::Normal);
+        cx.simulate_keystrokes(": tabedit space dir/file_2.rs");
+        cx.simulate_keystrokes("enter");
+        cx.simulate_keystrokes(": bufdo space % s / foo / baz / g");
+        cx.simulate_keystrokes("enter");
+
+        cx.workspace(|workspace, _, cx| {
+            assert_eq!(
+                open_buffer_text(workspace, path!("/root/dir/file.rs"), cx).as_deref(),
+                Some("baz baz\n")
+            );
+            assert_eq!(
+                open_buffer_text(workspace, path!("/root/dir/file_2.rs"), cx).as_deref(),
+                Some("baz\nbar baz\n")
+            );
+            assert_active_item(
+                workspace,
+                path!("/root/dir/file_2.rs"),
+                "baz\nbar baz\n",
+                cx,
+            );
+        });
+
+        // The changes are undone one buffer at a time.
+        cx.simulate_keystrokes("u");
+        cx.workspace(|workspace, _, cx| {
+            assert_active_item(
+                workspace,
+                path!("/root/dir/file_2.rs"),
+                "foo\nbar foo\n",
+                cx,
+            );
+            assert_eq!(
+                open_buffer_text(workspace, path!("/root/dir/file.rs"), cx).as_deref(),
+                Some("baz baz\n")
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_command_windo(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa\n", Mode::Normal);
+        cx.simulate_keystrokes(": v s enter");
+        cx.workspace(|workspace, _, _| assert_eq!(workspace.panes().len(), 2));
+        cx.simulate_keystrokes(": windo space normal space A ;");
+        cx.simulate_keystrokes("enter");
+
+        // Both panes show the same buffer, so it is changed once per pane.
+        cx.workspace(|workspace, _, cx| {
+            assert_active_item(workspace, path!("/root/dir/file.rs"), "a;;\n", cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_command_argdo(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        let fs = cx.workspace(|workspace, _, cx| workspace.project().read(cx).fs().clone());
+        fs.as_fake()
+            .insert_file(path!("/root/dir/file_2.rs"), b"two\n".to_vec())
+            .await;
+        fs.as_fake()
+            .insert_file(path!("/root/dir/file_3.rs"), b"three\n".to_vec())
+            .await;
+
+        cx.simulate_keystrokes(": args space dir/file_2.rs space dir/file_3.rs");
+        cx.simulate_keystrokes("enter");
+        cx.workspace(|workspace, _, cx| {
+            assert_active_item(workspace, path!("/root/dir/file_2.rs"), "two\n", cx);
+        });
+
+        cx.simulate_keystrokes(": argdo space s / $ / ; /");
+        cx.simulate_keystrokes("enter");
+        cx.workspace(|workspace, _, cx| {
+            assert_eq!(
+                open_buffer_text(workspace, path!("/root/dir/file_2.rs"), cx).as_deref(),
+                Some("two;\n")
+            );
+            assert_active_item(workspace, path!("/root/dir/file_3.rs"), "three;\n", cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_user_commands(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<VimSettings>(cx, |s| {
+                let mut commands = HashMap::default();
+                commands.insert(
+                    "Twice".into(),
+                    vec!["normal A<args>".into(), ":normal A<args>".into()],
+                );
+                s.commands = Some(commands);
+            });
+        });
+
+        cx.set_state("ˇa", Mode::Normal);
+        cx.simulate_keystrokes(": T w i c e space ; enter");
+        cx.assert_state("a;ˇ;", Mode::Normal);
+
+        cx.update(|_, cx| {
+            let results = command_interceptor("Tw", cx);
+            assert_eq!(
+                results.first().map(|result| result.string.as_str()),
+                Some(":Twice")
+            );
+        });
+
+        cx.set_state("ˇa\nb\nc", Mode::Normal);
+        cx.simulate_keystrokes(": command space L a s t space $ space | space normal space x");
+        cx.simulate_keystrokes("enter");
+        cx.simulate_keystrokes(": L a s t enter");
+        cx.assert_state("a\nb\nˇ", Mode::Normal);
+
+        cx.simulate_keystrokes(": delcommand space L a s t enter");
+        cx.update(|_, cx| assert!(command_interceptor("Last", cx).is_empty()));
+    }
+
     #[gpui::test]
     async fn test_command_gf(cx: &mut TestAppContext) {
         let mut cx = VimTestContext::new(cx, true).await;
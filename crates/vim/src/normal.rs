@@ -29,7 +29,7 @@ use editor::{Anchor, SelectionEffects};
 use editor::{Bias, ToPoint};
 use editor::{display_map::ToDisplayPoint, movement};
 use gpui::{Context, Window, actions};
-use language::{Point, SelectionGoal};
+use language::{Buffer, Point, SelectionGoal, TransactionId};
 use log::error;
 use multi_buffer::MultiBufferRow;
 
@@ -94,6 +94,10 @@ actions!(
         Redo,
         /// Undoes all changes to the most recently changed line.
         UndoLastLine,
+        /// Goes to the previous state of the undo tree, even if it is on another branch.
+        UndoTreeOlder,
+        /// Goes to the next state of the undo tree, even if it is on another branch.
+        UndoTreeNewer,
     ]
 );
 
@@ -213,6 +217,20 @@ pub(crate) fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
             }
         });
     });
+    Vim::action(editor, cx, |vim, _: &UndoTreeOlder, window, cx| {
+        let times = Vim::take_count(cx).unwrap_or(1) as isize;
+        Vim::take_forced_motion(cx);
+        vim.go_to_undo_tree_state(window, cx, |buffer| {
+            buffer.undo_tree_state_by_changes(-times)
+        });
+    });
+    Vim::action(editor, cx, |vim, _: &UndoTreeNewer, window, cx| {
+        let times = Vim::take_count(cx).unwrap_or(1) as isize;
+        Vim::take_forced_motion(cx);
+        vim.go_to_undo_tree_state(window, cx, |buffer| {
+            buffer.undo_tree_state_by_changes(times)
+        });
+    });
     Vim::action(editor, cx, |vim, _: &UndoLastLine, window, cx| {
         Vim::take_forced_motion(cx);
         vim.update_editor(cx, |vim, editor, cx| {
@@ -336,6 +354,22 @@ pub(crate) fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
 }
 
 impl Vim {
+    /// Moves the buffer to the state of its undo tree picked by `find_state`.
+    pub(crate) fn go_to_undo_tree_state(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        find_state: impl FnOnce(&Buffer) -> Option<TransactionId>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+                return;
+            };
+            let target = find_state(buffer.read(cx));
+            editor.go_to_undo_tree_state(target, window, cx);
+        });
+    }
+
     pub fn normal_motion(
         &mut self,
         motion: Motion,
@@ -2087,6 +2121,27 @@ mod test {
         cx.shared_state().await.assert_matches();
     }
 
+    #[gpui::test]
+    async fn test_undo_tree(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone two", Mode::Normal);
+        cx.simulate_keystrokes("x x u w x");
+        cx.assert_state("ne ˇwo", Mode::Normal);
+
+        cx.simulate_keystrokes("g -");
+        cx.assert_state("ˇe two", Mode::Normal);
+        cx.simulate_keystrokes("g -");
+        cx.assert_state("ˇne two", Mode::Normal);
+        cx.simulate_keystrokes("2 g +");
+        cx.assert_state("ne ˇwo", Mode::Normal);
+
+        cx.simulate_keystrokes(": e a r l i e r space 2 enter");
+        cx.assert_state("ne ˇtwo", Mode::Normal);
+        cx.simulate_keystrokes(": l a t e r enter");
+        cx.assert_state("ˇe two", Mode::Normal);
+    }
+
     #[gpui::test]
     async fn test_undo_last_line_multicursor(cx: &mut gpui::TestAppContext) {
         let mut cx = VimTestContext::new(cx, true).await;
@@ -6,7 +6,7 @@ use search::{BufferSearchBar, SearchOptions, buffer_search};
 use serde::Deserialize;
 use settings::Settings;
 use std::{iter::Peekable, str::Chars};
-use util::serde::default_true;
+use util::{ResultExt as _, serde::default_true};
 use workspace::{notifications::NotifyResultExt, searchable::Direction};
 
 use crate::{
@@ -524,7 +524,7 @@ impl Vim {
         }
         let Some(search) = search else { return };
         let search_bar = search_bar.downgrade();
-        cx.spawn_in(window, async move |vim, cx| {
+        let task = cx.spawn_in(window, async move |vim, cx| {
             search.await?;
             search_bar.update_in(cx, |search_bar, window, cx| {
                 if replacement.flag_c {
@@ -554,8 +554,11 @@ impl Vim {
                 options.set(SearchOptions::ONE_MATCH_PER_LINE, false);
                 search_bar.set_search_options(options, cx);
             })
-        })
-        .detach_and_log_err(cx);
+        });
+        let task = cx.spawn(async move |_, _| {
+            task.await.log_err();
+        });
+        self.track_ex_command_task(task, cx);
     }
 }
 
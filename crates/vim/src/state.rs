@@ -235,6 +235,15 @@ pub struct VimGlobals {
     pub focused_vim: Option<WeakEntity<Vim>>,
 
     pub marks: HashMap<EntityId, Entity<MarksState>>,
+
+    /// The files of the argument list, set with `:args`.
+    pub arg_list: Vec<String>,
+    /// The insert mode abbreviations defined with `:iabbrev`, on top of the `abbreviations` setting.
+    pub insert_abbreviations: HashMap<String, String>,
+    /// The command-line abbreviations defined with `:cabbrev`.
+    pub command_line_abbreviations: HashMap<String, String>,
+    /// The ex commands defined with `:command`, on top of the `commands` setting.
+    pub user_commands: HashMap<String, Vec<String>>,
 }
 
 pub struct MarksState {
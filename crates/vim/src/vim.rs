@@ -3,6 +3,7 @@
 #[cfg(test)]
 mod test;
 
+mod abbreviation;
 mod change_list;
 mod command;
 mod digraph;
@@ -26,6 +27,7 @@ use editor::{
     ToPoint,
     movement::{self, FindRange},
 };
+use futures::future::Shared;
 use gpui::{
     Action, App, AppContext, Axis, Context, Entity, EventEmitter, KeyContext, KeystrokeEvent,
     Render, Subscription, Task, WeakEntity, Window, actions,
@@ -400,6 +402,8 @@ pub(crate) struct Vim {
 
     last_command: Option<String>,
     running_command: Option<Task<()>>,
+    /// The work that the last ex command left running, if any.
+    ex_command_task: Option<Shared<Task<()>>>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -449,6 +453,7 @@ impl Vim {
 
             last_command: None,
             running_command: None,
+            ex_command_task: None,
 
             editor: editor.downgrade(),
             _subscriptions: vec![
@@ -809,6 +814,7 @@ impl Vim {
             visual::register(editor, cx);
             change_list::register(editor, cx);
             digraph::register(editor, cx);
+            abbreviation::register(editor, cx);
 
             cx.defer_in(window, |vim, window, cx| {
                 vim.focused(false, window, cx);
@@ -928,7 +934,12 @@ impl Vim {
             EditorEvent::InputHandled {
                 text,
                 utf16_range_to_replace: range_to_replace,
-            } => Vim::globals(cx).observe_insertion(text, range_to_replace.clone()),
+            } => {
+                Vim::globals(cx).observe_insertion(text, range_to_replace.clone());
+                if range_to_replace.is_none() {
+                    self.expand_abbreviation(text, window, cx);
+                }
+            }
             EditorEvent::TransactionBegun { transaction_id } => {
                 self.transaction_begun(*transaction_id, window, cx)
             }
@@ -1833,6 +1844,8 @@ struct VimSettings {
     pub use_system_clipboard: UseSystemClipboard,
     pub use_smartcase_find: bool,
     pub custom_digraphs: HashMap<String, Arc<str>>,
+    pub abbreviations: HashMap<String, String>,
+    pub commands: HashMap<String, Vec<String>>,
     pub highlight_on_yank_duration: u64,
     pub cursor_shape: CursorShapeSettings,
 }
@@ -1845,6 +1858,8 @@ struct VimSettingsContent {
     pub use_system_clipboard: Option<UseSystemClipboard>,
     pub use_smartcase_find: Option<bool>,
     pub custom_digraphs: Option<HashMap<String, Arc<str>>>,
+    pub abbreviations: Option<HashMap<String, String>>,
+    pub commands: Option<HashMap<String, Vec<String>>>,
     pub highlight_on_yank_duration: Option<u64>,
     pub cursor_shape: Option<CursorShapeSettings>,
 }
@@ -1897,6 +1912,8 @@ impl Settings for VimSettings {
                 .use_smartcase_find
                 .ok_or_else(Self::missing_default)?,
             custom_digraphs: settings.custom_digraphs.ok_or_else(Self::missing_default)?,
+            abbreviations: settings.abbreviations.ok_or_else(Self::missing_default)?,
+            commands: settings.commands.ok_or_else(Self::missing_default)?,
             highlight_on_yank_duration: settings
                 .highlight_on_yank_duration
                 .ok_or_else(Self::missing_default)?,
@@ -107,11 +107,9 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
     Vim::action(editor, cx, |vim, _: &SelectLargerSyntaxNode, window, cx| {
         let count = Vim::take_count(cx).unwrap_or(1);
         Vim::take_forced_motion(cx);
-        for _ in 0..count {
-            vim.update_editor(cx, |_, editor, cx| {
-                editor.select_larger_syntax_node(&Default::default(), window, cx);
-            });
-        }
+        vim.update_editor(cx, |_, editor, cx| {
+            editor.select_larger_syntax_node_by(count, window, cx);
+        });
     });
 
     Vim::action(editor, cx, |vim, _: &SelectNextSyntaxNode, window, cx| {
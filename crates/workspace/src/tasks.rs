@@ -1,11 +1,22 @@
-use std::process::ExitStatus;
+use std::{cell::RefCell, process::ExitStatus, rc::Rc, sync::Arc};
 
-use anyhow::Result;
-use gpui::{AppContext, Context, Entity, Task};
+use anyhow::{Context as _, Result, anyhow, bail};
+use collections::HashMap;
+use futures::{
+    FutureExt as _,
+    future::{LocalBoxFuture, Shared, join_all},
+};
+use gpui::{AppContext, AsyncWindowContext, Context, Entity, Task, WeakEntity};
 use language::Buffer;
-use project::{TaskSourceKind, WorktreeId};
+use project::{
+    ResolvedTaskChain, TaskSourceKind, WorktreeId,
+    task_store::{TaskStore, TaskStoreEvent},
+};
 use remote::ConnectionState;
-use task::{DebugScenario, ResolvedTask, SpawnInTerminal, TaskContext, TaskTemplate};
+use task::{
+    DebugScenario, DependsOrder, ResolvedTask, RevealStrategy, SpawnInTerminal, TaskContext,
+    TaskId, TaskTemplate,
+};
 use ui::Window;
 
 use crate::Workspace;
@@ -54,7 +65,66 @@ impl Workspace {
         window: &mut Window,
         cx: &mut Context<Workspace>,
     ) {
+        if resolved_task.has_unanswered_inputs() {
+            let resolve_inputs =
+                self.resolve_task_inputs(task_source_kind.clone(), resolved_task, window, cx);
+            let task = cx.spawn_in(window, async move |workspace, cx| {
+                let Some(resolved_task) = resolve_inputs.await else {
+                    return;
+                };
+                workspace
+                    .update_in(cx, |workspace, window, cx| {
+                        workspace.schedule_resolved_task(
+                            task_source_kind,
+                            resolved_task,
+                            omit_history,
+                            window,
+                            cx,
+                        )
+                    })
+                    .ok();
+            });
+            self.scheduled_tasks.push(task);
+            return;
+        }
+
         let spawn_in_terminal = resolved_task.resolved.clone();
+        let task_chain = if resolved_task.original_task().depends_on.is_empty() {
+            None
+        } else {
+            let task_chain = self
+                .project
+                .read(cx)
+                .task_store()
+                .read(cx)
+                .task_inventory()
+                .context("Cannot resolve task dependencies without a task inventory")
+                .and_then(|inventory| {
+                    inventory
+                        .read(cx)
+                        .resolve_task_chain(task_source_kind.clone(), resolved_task.clone())
+                });
+            match task_chain {
+                Ok(task_chain) => Some(task_chain),
+                Err(e) => {
+                    log::error!("Failed to resolve task dependencies: {e:#}");
+                    self.show_error(&e, cx);
+                    return;
+                }
+            }
+        };
+        if resolved_task.original_task().watch.is_some() {
+            let task_store = self.project.read(cx).task_store().clone();
+            if !task_store.read(cx).is_task_watched(&resolved_task.id) {
+                let watch_result = task_store.update(cx, |task_store, cx| {
+                    task_store.watch_task(task_source_kind.clone(), resolved_task.clone(), cx)
+                });
+                if let Err(e) = watch_result {
+                    log::error!("Failed to watch task files: {e:#}");
+                    self.show_error(&e, cx);
+                }
+            }
+        }
         if !omit_history {
             if let Some(debugger_provider) = self.debugger_provider.as_ref() {
                 debugger_provider.task_scheduled(cx);
@@ -71,7 +141,23 @@ impl Workspace {
             });
         }
 
-        if let Some(terminal_provider) = self.terminal_provider.as_ref() {
+        if let Some(task_chain) = task_chain {
+            let task = cx.spawn_in(window, async move |workspace, cx| {
+                let mut task_chain = task_chain;
+                let mut resolved_tasks = HashMap::default();
+                if resolve_chain_inputs(&workspace, &mut task_chain, &mut resolved_tasks, cx)
+                    .await
+                    .is_none()
+                {
+                    return;
+                }
+                let runs = Rc::default();
+                if let Err(e) = run_task_chain(workspace, task_chain, runs, cx.clone()).await {
+                    log::error!("Task chain stopped: {e:#}");
+                }
+            });
+            self.scheduled_tasks.push(task);
+        } else if let Some(terminal_provider) = self.terminal_provider.as_ref() {
             let task_status = terminal_provider.spawn(spawn_in_terminal, window, cx);
             let task = cx.background_spawn(async move {
                 match task_status.await {
@@ -90,6 +176,52 @@ impl Workspace {
         }
     }
 
+    /// Asks for the values of the task's inputs that were not entered yet, and resolves the task
+    /// again with them. Tasks with inputs are not run when nothing can ask for their values.
+    fn resolve_task_inputs(
+        &mut self,
+        task_source_kind: TaskSourceKind,
+        task: ResolvedTask,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Option<ResolvedTask>> {
+        if let Some(task_input_provider) = self.task_input_provider.as_ref() {
+            task_input_provider.resolve_inputs(task_source_kind, task, window, cx)
+        } else {
+            let e = anyhow!(
+                "Cannot ask for the inputs of task `{}`",
+                task.resolved_label
+            );
+            log::error!("{e}");
+            self.show_error(&e, cx);
+            Task::ready(None)
+        }
+    }
+
+    pub(crate) fn on_task_store_event(
+        &mut self,
+        _: &Entity<TaskStore>,
+        event: &TaskStoreEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            TaskStoreEvent::WatchedTaskChanged {
+                task_source_kind,
+                task,
+            } => {
+                let mut task = task.clone();
+                // Rerun in the same terminal, replacing the previous run if it is still going.
+                task.resolved.allow_concurrent_runs = true;
+                task.resolved.use_new_terminal = false;
+                if task.resolved.reveal == RevealStrategy::Always {
+                    task.resolved.reveal = RevealStrategy::NoFocus;
+                }
+                self.schedule_resolved_task(task_source_kind.clone(), task, true, window, cx);
+            }
+        }
+    }
+
     pub fn start_debug_session(
         &mut self,
         scenario: DebugScenario,
@@ -124,3 +256,110 @@ impl Workspace {
         }
     }
 }
+
+/// Asks for the inputs of the tasks in the chain before any of them runs, one task at a time, as
+/// each of them is entered in a modal. A task that several tasks of the chain depend on is asked
+/// for once. Returns `None` if any of the inputs got dismissed.
+fn resolve_chain_inputs<'a>(
+    workspace: &'a WeakEntity<Workspace>,
+    task_chain: &'a mut ResolvedTaskChain,
+    resolved_tasks: &'a mut HashMap<TaskId, ResolvedTask>,
+    cx: &'a mut AsyncWindowContext,
+) -> LocalBoxFuture<'a, Option<()>> {
+    async move {
+        for dependency in &mut task_chain.dependencies {
+            resolve_chain_inputs(workspace, dependency, resolved_tasks, cx).await?;
+        }
+        if !task_chain.task.has_unanswered_inputs() {
+            return Some(());
+        }
+        let task_id = task_chain.task.id.clone();
+        let resolved_task = match resolved_tasks.get(&task_id) {
+            Some(resolved_task) => resolved_task.clone(),
+            None => {
+                let resolve_inputs = workspace
+                    .update_in(cx, |workspace, window, cx| {
+                        workspace.resolve_task_inputs(
+                            task_chain.task_source_kind.clone(),
+                            task_chain.task.clone(),
+                            window,
+                            cx,
+                        )
+                    })
+                    .ok()?;
+                let resolved_task = resolve_inputs.await?;
+                resolved_tasks.insert(task_id, resolved_task.clone());
+                resolved_task
+            }
+        };
+        task_chain.task = resolved_task;
+        Some(())
+    }
+    .boxed_local()
+}
+
+/// The run of a task in a task chain, awaited by every task that depends on it.
+type SharedTaskRun = Shared<LocalBoxFuture<'static, Result<(), Arc<anyhow::Error>>>>;
+
+/// Runs the dependencies of the task first, and the task itself only if all of them succeeded.
+/// A task that several tasks of the chain depend on is run once.
+fn run_task_chain(
+    workspace: WeakEntity<Workspace>,
+    task_chain: ResolvedTaskChain,
+    runs: Rc<RefCell<HashMap<TaskId, SharedTaskRun>>>,
+    cx: AsyncWindowContext,
+) -> LocalBoxFuture<'static, Result<()>> {
+    let run = runs
+        .borrow_mut()
+        .entry(task_chain.task.id.clone())
+        .or_insert_with(|| {
+            run_task_with_dependencies(workspace, task_chain, runs.clone(), cx)
+                .map(|result| result.map_err(Arc::new))
+                .boxed_local()
+                .shared()
+        })
+        .clone();
+    async move { run.await.map_err(|e| anyhow!("{e:#}")) }.boxed_local()
+}
+
+fn run_task_with_dependencies(
+    workspace: WeakEntity<Workspace>,
+    task_chain: ResolvedTaskChain,
+    runs: Rc<RefCell<HashMap<TaskId, SharedTaskRun>>>,
+    mut cx: AsyncWindowContext,
+) -> LocalBoxFuture<'static, Result<()>> {
+    async move {
+        let depends_order = task_chain.depends_order();
+        let dependencies = task_chain.dependencies.into_iter().map(|dependency| {
+            run_task_chain(workspace.clone(), dependency, runs.clone(), cx.clone())
+        });
+        match depends_order {
+            DependsOrder::Sequence => {
+                for dependency in dependencies {
+                    dependency.await?;
+                }
+            }
+            DependsOrder::Parallel => {
+                for result in join_all(dependencies).await {
+                    result?;
+                }
+            }
+        }
+
+        let task = task_chain.task;
+        if !task.original_task().has_command() {
+            return Ok(());
+        }
+        let label = task.resolved_label.clone();
+        let task_status = workspace.update_in(&mut cx, |workspace, window, cx| {
+            workspace.spawn_in_terminal(task.resolved, window, cx)
+        })?;
+        match task_status.await {
+            Some(Ok(status)) if status.success() => Ok(()),
+            Some(Ok(status)) => bail!("Task `{label}` failed, code: {:?}", status.code()),
+            Some(Err(e)) => Err(e.context(format!("spawning task `{label}`"))),
+            None => bail!("Task `{label}` got cancelled"),
+        }
+    }
+    .boxed_local()
+}
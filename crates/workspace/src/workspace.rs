@@ -71,8 +71,8 @@ pub use persistence::{
 };
 use postage::stream::Stream;
 use project::{
-    DirectoryLister, Project, ProjectEntryId, ProjectPath, ResolvedPath, Worktree, WorktreeId,
-    WorktreeSettings,
+    DirectoryLister, Project, ProjectEntryId, ProjectPath, ResolvedPath, TaskSourceKind, Worktree,
+    WorktreeId, WorktreeSettings,
     debugger::{breakpoint_store::BreakpointStoreEvent, session::ThreadStatus},
     toolchain_store::ToolchainStoreEvent,
 };
@@ -102,7 +102,7 @@ use std::{
     sync::{Arc, LazyLock, Weak, atomic::AtomicUsize},
     time::Duration,
 };
-use task::{DebugScenario, SpawnInTerminal, TaskContext};
+use task::{DebugScenario, ResolvedTask, SpawnInTerminal, TaskContext};
 use theme::{ActiveTheme, SystemAppearance, ThemeSettings};
 pub use toolbar::{Toolbar, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView};
 pub use ui;
@@ -145,6 +145,19 @@ pub trait TerminalProvider {
     ) -> Task<Option<Result<ExitStatus>>>;
 }
 
+pub trait TaskInputProvider {
+    /// Asks for the values of the inputs the task declares but that were not entered yet,
+    /// and resolves the task again with them.
+    /// Returns `None` if any of the inputs got dismissed, or if the task cannot be resolved.
+    fn resolve_inputs(
+        &self,
+        task_source_kind: TaskSourceKind,
+        task: ResolvedTask,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Task<Option<ResolvedTask>>;
+}
+
 pub trait DebuggerProvider {
     // `active_buffer` is used to resolve build task's name against language-specific tasks.
     fn start_session(
@@ -1144,6 +1157,7 @@ pub struct Workspace {
     on_prompt_for_new_path: Option<PromptForNewPath>,
     on_prompt_for_open_path: Option<PromptForOpenPath>,
     terminal_provider: Option<Box<dyn TerminalProvider>>,
+    task_input_provider: Option<Box<dyn TaskInputProvider>>,
     debugger_provider: Option<Arc<dyn DebuggerProvider>>,
     serializable_items_tx: UnboundedSender<Box<dyn SerializableItemHandle>>,
     _items_serializer: Task<Result<()>>,
@@ -1436,6 +1450,11 @@ impl Workspace {
                     store.workspaces.remove(&window_handle.clone());
                 })
             }),
+            cx.subscribe_in(
+                &project.read(cx).task_store().clone(),
+                window,
+                Self::on_task_store_event,
+            ),
         ];
 
         cx.defer_in(window, |this, window, cx| {
@@ -1487,6 +1506,7 @@ impl Workspace {
             on_prompt_for_new_path: None,
             on_prompt_for_open_path: None,
             terminal_provider: None,
+            task_input_provider: None,
             debugger_provider: None,
             serializable_items_tx,
             _items_serializer,
@@ -2060,6 +2080,10 @@ impl Workspace {
         self.terminal_provider = Some(Box::new(provider));
     }
 
+    pub fn set_task_input_provider(&mut self, provider: impl TaskInputProvider + 'static) {
+        self.task_input_provider = Some(Box::new(provider));
+    }
+
     pub fn set_debugger_provider(&mut self, provider: impl DebuggerProvider + 'static) {
         self.debugger_provider = Some(Arc::new(provider));
     }
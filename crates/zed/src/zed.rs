@@ -15,6 +15,7 @@ pub use app_menus::*;
 use assets::Assets;
 use audio::{AudioSettings, REPLAY_DURATION};
 use breadcrumbs::Breadcrumbs;
+use call_hierarchy_panel::CallHierarchyPanel;
 use client::zed_urls;
 use collections::VecDeque;
 use debugger_ui::debugger_panel::DebugPanel;
@@ -70,6 +71,7 @@ use std::{
 use terminal_view::terminal_panel::{self, TerminalPanel};
 use theme::{ActiveTheme, ThemeSettings};
 use ui::{PopoverMenuHandle, prelude::*};
+use undo_tree_panel::UndoTreePanel;
 use util::markdown::MarkdownString;
 use util::{ResultExt, asset_str};
 use uuid::Uuid;
@@ -552,6 +554,8 @@ fn initialize_panels(
     cx.spawn_in(window, async move |workspace_handle, cx| {
         let project_panel = ProjectPanel::load(workspace_handle.clone(), cx.clone());
         let outline_panel = OutlinePanel::load(workspace_handle.clone(), cx.clone());
+        let call_hierarchy_panel = CallHierarchyPanel::load(workspace_handle.clone(), cx.clone());
+        let undo_tree_panel = UndoTreePanel::load(workspace_handle.clone(), cx.clone());
         let terminal_panel = TerminalPanel::load(workspace_handle.clone(), cx.clone());
         let git_panel = GitPanel::load(workspace_handle.clone(), cx.clone());
         let channels_panel =
@@ -565,6 +569,8 @@ fn initialize_panels(
         let (
             project_panel,
             outline_panel,
+            call_hierarchy_panel,
+            undo_tree_panel,
             terminal_panel,
             git_panel,
             channels_panel,
@@ -573,6 +579,8 @@ fn initialize_panels(
         ) = futures::try_join!(
             project_panel,
             outline_panel,
+            call_hierarchy_panel,
+            undo_tree_panel,
             git_panel,
             terminal_panel,
             channels_panel,
@@ -583,6 +591,8 @@ fn initialize_panels(
         workspace_handle.update_in(cx, |workspace, window, cx| {
             workspace.add_panel(project_panel, window, cx);
             workspace.add_panel(outline_panel, window, cx);
+            workspace.add_panel(call_hierarchy_panel, window, cx);
+            workspace.add_panel(undo_tree_panel, window, cx);
             workspace.add_panel(terminal_panel, window, cx);
             workspace.add_panel(git_panel, window, cx);
             workspace.add_panel(channels_panel, window, cx);
@@ -4450,6 +4460,7 @@ mod tests {
                 "auto_update",
                 "branches",
                 "buffer_search",
+                "call_hierarchy_panel",
                 "channel_modal",
                 "cli",
                 "client",
@@ -4509,6 +4520,7 @@ mod tests {
                 "theme_selector",
                 "toast",
                 "toolchain",
+                "undo_tree_panel",
                 "variable_list",
                 "vim",
                 "window",
@@ -4649,6 +4661,8 @@ mod tests {
             git_ui::init(cx);
             project_panel::init(cx);
             outline_panel::init(cx);
+            call_hierarchy_panel::init(cx);
+            undo_tree_panel::init(cx);
             terminal_view::init(cx);
             copilot::copilot_chat::init(
                 app_state.fs.clone(),
@@ -182,7 +182,17 @@ pub mod git {
             #[action(deprecated_aliases = ["branches::OpenRecent"])]
             Branch,
             /// Opens the git stash selector.
-            ViewStash
+            ViewStash,
+            /// Opens the commit history of the active repository.
+            ViewHistory,
+            /// Opens the commit history of the active file.
+            FileHistory,
+            /// Opens the commit history of the selected lines of the active file.
+            SelectionHistory,
+            /// Opens the git branch selector to merge a branch into the current one.
+            MergeBranch,
+            /// Opens the git worktree selector, to open or create linked worktrees.
+            Worktrees
         ]
     );
 }
@@ -194,7 +204,29 @@ pub mod jj {
         jj,
         [
             /// Opens the Jujutsu bookmark list.
-            BookmarkList
+            BookmarkList,
+            /// Opens the Jujutsu change log.
+            Log,
+            /// Creates a new empty change on top of the selected change.
+            NewChange,
+            /// Edits the description of the selected change.
+            DescribeChange,
+            /// Squashes the selected change into its parent.
+            SquashChange,
+            /// Squashes the changes to some paths of the selected change into its parent.
+            SquashChangePaths,
+            /// Makes the selected change the working-copy change.
+            EditChange,
+            /// Abandons the selected change.
+            AbandonChange,
+            /// Creates a bookmark pointing at the selected change.
+            CreateBookmark,
+            /// Points a bookmark at the selected change, creating it if needed.
+            SetBookmark,
+            /// Moves an existing bookmark to the selected change.
+            MoveBookmark,
+            /// Undoes the last Jujutsu operation.
+            Undo
         ]
     );
 }
@@ -448,6 +480,14 @@ pub struct Rerun {
     pub task_id: Option<String>,
 }
 
+actions!(
+    task,
+    [
+        /// Stops rerunning the tasks with `watch` patterns when their files change.
+        StopWatching
+    ]
+);
+
 pub mod outline {
     use std::sync::OnceLock;
 